@@ -0,0 +1,118 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Small TTL cache of serialized API responses, keyed by endpoint + params.
+/// Cleared whenever the change feed reports stored data so fresh data shows up immediately.
+pub struct ResponseCache {
+    entries: Mutex<HashMap<String, (Instant, Value)>>,
+    ttl: Duration,
+}
+
+impl ResponseCache {
+    /// A zero TTL disables caching
+    pub fn new(ttl: Duration) -> Self {
+        ResponseCache {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<Value> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some((stored_at, value)) if stored_at.elapsed() < self.ttl => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert<T: Serialize>(&self, key: String, value: &T) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        if let Ok(value) = serde_json::to_value(value) {
+            let mut entries = self.entries.lock().unwrap();
+            // Drop expired entries so one-off dates don't accumulate forever
+            entries.retain(|_, (stored_at, _)| stored_at.elapsed() < self.ttl);
+            entries.insert(key, (Instant::now(), value));
+        }
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+/// Keys an upstream lookup recently found nothing for, so repeating them doesn't reach the
+/// upstream again within `ttl`. Holds at most `capacity` keys; while full, unseen keys count as
+/// missing too, which caps the lookups callers can cause per `ttl`.
+pub struct MissCache {
+    entries: Mutex<HashMap<String, Instant>>,
+    ttl: Duration,
+    capacity: usize,
+}
+
+impl MissCache {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        MissCache {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+            capacity,
+        }
+    }
+
+    /// Whether to skip the lookup of `key`
+    pub fn is_missing(&self, key: &str) -> bool {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, stored_at| stored_at.elapsed() < self.ttl);
+        entries.contains_key(key) || entries.len() >= self.capacity
+    }
+
+    pub fn insert(&self, key: String) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() < self.capacity {
+            entries.insert(key, Instant::now());
+        }
+    }
+}
+
+/// Upstream calls each client may cause per `window`, for public endpoints that call an upstream
+/// on the caller's behalf. Tracks at most `capacity` clients; while full, new clients get none.
+pub struct ClientQuota {
+    used: Mutex<HashMap<String, (Instant, u32)>>,
+    limit: u32,
+    window: Duration,
+    capacity: usize,
+}
+
+impl ClientQuota {
+    pub fn new(limit: u32, window: Duration, capacity: usize) -> Self {
+        ClientQuota {
+            used: Mutex::new(HashMap::new()),
+            limit,
+            window,
+            capacity,
+        }
+    }
+
+    /// Take one call from `client`'s quota; false once it is used up for the current window
+    pub fn try_take(&self, client: &str) -> bool {
+        let mut used = self.used.lock().unwrap();
+        used.retain(|_, (started_at, _)| started_at.elapsed() < self.window);
+        if !used.contains_key(client) && used.len() >= self.capacity {
+            return false;
+        }
+        let (_, calls) = used.entry(client.to_string()).or_insert((Instant::now(), 0));
+        if *calls >= self.limit {
+            return false;
+        }
+        *calls += 1;
+        true
+    }
+}
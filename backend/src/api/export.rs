@@ -0,0 +1,136 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{error, warn};
+
+use crate::api::handlers::AppState;
+use crate::models::TrendingRepo;
+
+/// Chunks buffered ahead of a slow client; each chunk holds one day of rows
+const EXPORT_BUFFER_CHUNKS: usize = 4;
+
+const CSV_HEADER: &str = "date,rank,repo_id,repo_name,primary_language,stars,forks,pull_requests,pushes,\
+total_score,computed_score,korean_summary\n";
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    pub from: String,
+    pub to: String,
+    /// "json" (default) or "csv"
+    pub format: Option<String>,
+}
+
+// GET /api/export/trends
+// Rows are read and written one day at a time, so memory stays flat however long the range is.
+pub async fn export_trends(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ExportQuery>,
+) -> Response {
+    let csv = match query.format.as_deref().unwrap_or("json") {
+        "json" => false,
+        "csv" => true,
+        other => {
+            return (StatusCode::BAD_REQUEST, format!("Unknown export format '{}'", other)).into_response();
+        }
+    };
+
+    let dates = match state.db.get_trending_dates_between(&query.from, &query.to) {
+        Ok(dates) => dates,
+        Err(e) => {
+            error!("Failed to list export dates: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    };
+
+    let (tx, rx) = mpsc::channel::<Result<Bytes, std::io::Error>>(EXPORT_BUFFER_CHUNKS);
+    tokio::spawn(async move {
+        let mut first = true;
+        let opening = if csv { CSV_HEADER } else { "[" };
+        if tx.send(Ok(Bytes::from_static(opening.as_bytes()))).await.is_err() {
+            return;
+        }
+
+        for date in dates {
+            let repos = match state.db.get_trending_repos(&date) {
+                Ok(repos) => repos,
+                Err(e) => {
+                    warn!("Export aborted at {}: {}", date, e);
+                    let _ = tx.send(Err(std::io::Error::other(e.to_string()))).await;
+                    return;
+                }
+            };
+
+            let mut chunk = String::new();
+            for repo in &repos {
+                if csv {
+                    chunk.push_str(&csv_row(repo));
+                } else {
+                    if !first {
+                        chunk.push(',');
+                    }
+                    chunk.push_str(&serde_json::to_string(repo).unwrap_or_default());
+                }
+                first = false;
+            }
+
+            // Stop reading once the client has gone away
+            if tx.send(Ok(Bytes::from(chunk))).await.is_err() {
+                return;
+            }
+        }
+
+        if !csv {
+            let _ = tx.send(Ok(Bytes::from_static(b"]"))).await;
+        }
+    });
+
+    let (content_type, extension) = if csv { ("text/csv; charset=utf-8", "csv") } else { ("application/json", "json") };
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"trends_{}_{}.{}\"", query.from, query.to, extension),
+            ),
+        ],
+        Body::from_stream(ReceiverStream::new(rx)),
+    )
+        .into_response()
+}
+
+fn csv_row(repo: &TrendingRepo) -> String {
+    let fields = [
+        repo.date.clone(),
+        repo.rank.map(|v| v.to_string()).unwrap_or_default(),
+        repo.repo_id.to_string(),
+        repo.repo_name.clone(),
+        repo.primary_language.clone().unwrap_or_default(),
+        repo.stars.map(|v| v.to_string()).unwrap_or_default(),
+        repo.forks.map(|v| v.to_string()).unwrap_or_default(),
+        repo.pull_requests.map(|v| v.to_string()).unwrap_or_default(),
+        repo.pushes.map(|v| v.to_string()).unwrap_or_default(),
+        repo.total_score.map(|v| v.to_string()).unwrap_or_default(),
+        repo.computed_score.map(|v| v.to_string()).unwrap_or_default(),
+        repo.korean_summary.clone().unwrap_or_default(),
+    ];
+
+    let mut row = fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(",");
+    row.push('\n');
+    row
+}
+
+/// Quote a field when it contains a delimiter, quote or line break
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
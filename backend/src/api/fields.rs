@@ -0,0 +1,30 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// Fields requested through `fields=repo_name,stars,...`; `None` keeps every field
+pub fn parse_fields(fields: Option<&str>) -> Option<Vec<String>> {
+    let fields: Vec<String> = fields?
+        .split(',')
+        .map(|f| f.trim().to_string())
+        .filter(|f| !f.is_empty())
+        .collect();
+    (!fields.is_empty()).then_some(fields)
+}
+
+/// Serialize each item, keeping only the requested top-level fields.
+/// Unknown field names are ignored so clients can share one selection across endpoints.
+pub fn select_fields<T: Serialize>(items: Vec<T>, fields: Option<&[String]>) -> Vec<Value> {
+    items
+        .into_iter()
+        .map(|item| {
+            let value = serde_json::to_value(item).unwrap_or(Value::Null);
+            match (value, fields) {
+                (Value::Object(mut object), Some(fields)) => {
+                    object.retain(|key, _| fields.iter().any(|f| f == key));
+                    Value::Object(object)
+                }
+                (value, _) => value,
+            }
+        })
+        .collect()
+}
@@ -1,150 +1,2882 @@
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
-    response::{IntoResponse, sse::{Event, KeepAlive, Sse}},
+    extract::{ConnectInfo, Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{Html, IntoResponse, Response, sse::{Event, KeepAlive, Sse}},
     Json,
 };
+use chrono::Datelike;
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
-use tracing::{info, error};
-use tokio_stream::wrappers::BroadcastStream;
+use std::sync::Arc;
+use tracing::{info, error, warn};
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
 use tokio_stream::StreamExt;
 use futures::stream::Stream;
 
-use crate::db::Database;
-use crate::config::Config;
-use crate::models::{TrendingRepoResponse, LanguageTrend};
-use crate::services::DataCollector;
+use crate::api::{ClientQuota, MissCache, ResponseCache};
+use crate::api::routes::{client_ip, has_admin_token};
+use crate::api::export::csv_field;
+use crate::api::fields::{parse_fields, select_fields};
+use crate::db::{ArchiveFilter, Database};
+use crate::config::{Config, SummaryStyle, UsagePricing};
+use crate::models::{TrendingRepo, TrendingRepoResponse, LanguageTrend, DigestSubscription, CreateSubscriptionRequest, BatchTrendsRequest, CreateWebhookRequest, Webhook, DeliverySchedule, CreateJobRequest, Job, RenderedReadme, EvaluationPair, ModelSummary, SummaryFeedbackRequest, SummaryFeedbackTally, RequeueSummaryRequest, ShareLink, SharedBrief, ArchiveYear, ArchiveMonth, DailyActivity, DailyUsage, CollectionTrendsResponse, ArchiveCursor, DeleteDayPreview, DeleteDayResult, RunCutoff, AdhocQueryRequest, AdhocQueryResult, LanguageMover, GitHubRepoInfo, RepoProfile};
+use crate::services::{DataCollector, Providers};
+use crate::telemetry::{self, SseSubscriber};
+use crate::services::changes::ChangeFeed;
+use crate::services::jobs::{self, Enqueued, JobQueue};
+use crate::services::{adhoc, calendar, digest, forecast, profile, readme, run_events, scoring, selfcheck, sources, tags, trends, tts, webhooks};
+use crate::services::webhooks::WebhookDispatcher;
+use crate::services::digest::DigestSender;
+use crate::services::errors::ServiceError;
+use crate::services::github::OTHER_LANGUAGE;
+use crate::services::collector::SUMMARY_PENDING;
+use crate::services::sitemap::Sitemap;
+use crate::services::templates::NotificationTemplates;
 
 pub struct AppState {
     pub db: Database,
     pub config: Config,
     pub progress_tx: tokio::sync::broadcast::Sender<crate::models::CollectionStatus>,
-    pub is_collecting: Arc<AtomicBool>,
+    /// Background work (collections, backfills, housekeeping), run one job at a time
+    pub jobs: JobQueue,
+    pub cache: Arc<ResponseCache>,
+    pub http_client: reqwest::Client,
+    /// Upstreams collections read from
+    pub providers: Providers,
+    pub sitemap: Sitemap,
+    /// Everything collections store; the response cache and webhooks follow it
+    pub changes: ChangeFeed,
+    /// Repo names GitHub recently didn't know, looked up by `/api/repos/by-name`
+    pub unknown_repo_names: MissCache,
+    /// GitHub lookups of unknown repo names each client may cause
+    pub repo_name_lookups: ClientQuota,
 }
 
+impl AppState {
+    /// State of one workspace, with the background consumers of its progress and change feeds
+    pub fn new(
+        db: Database,
+        config: Config,
+        cache: Arc<ResponseCache>,
+        http_client: reqwest::Client,
+        providers: Providers,
+    ) -> Arc<Self> {
+        let (tx, _rx) = tokio::sync::broadcast::channel(config.progress_channel_capacity);
+        if !config.read_only {
+            run_events::spawn_recorder(&tx, db.clone());
+        }
+        let jobs = JobQueue::new(db.clone());
+        let changes = ChangeFeed::new();
+        changes.spawn_cache_invalidation(cache.clone());
+        changes.spawn_webhook_delivery(WebhookDispatcher::new(&config, db.clone(), http_client.clone()));
+        let sitemap = Sitemap::new(&config, db.clone());
+        changes.spawn_sitemap_regeneration(sitemap.clone());
+        Arc::new(AppState {
+            db,
+            config,
+            progress_tx: tx,
+            jobs,
+            cache,
+            http_client,
+            providers,
+            sitemap,
+            changes,
+            unknown_repo_names: MissCache::new(UNKNOWN_REPO_NAME_TTL, UNKNOWN_REPO_NAME_CAPACITY),
+            repo_name_lookups: ClientQuota::new(REPO_NAME_LOOKUPS_PER_CLIENT, UNKNOWN_REPO_NAME_TTL, UNKNOWN_REPO_NAME_CAPACITY),
+        })
+    }
+}
+
+/// How long a name GitHub didn't know is answered without asking again
+const UNKNOWN_REPO_NAME_TTL: std::time::Duration = std::time::Duration::from_secs(3600);
+/// Most unknown names remembered, and so GitHub lookups by name per `UNKNOWN_REPO_NAME_TTL`
+const UNKNOWN_REPO_NAME_CAPACITY: usize = 1000;
+/// GitHub lookups of unknown repo names one client may cause per `UNKNOWN_REPO_NAME_TTL`
+const REPO_NAME_LOOKUPS_PER_CLIENT: u32 = 20;
+
 #[derive(Debug, Deserialize)]
 pub struct DateQuery {
     pub date: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct PageQuery {
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    /// Page size (default 50, max 200)
+    pub limit: Option<usize>,
+    pub fields: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    pub fields: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LanguagesQuery {
+    pub date: Option<String>,
+    /// Drop the "Other" bucket of sub-threshold languages, e.g. for charts
+    #[serde(default)]
+    pub exclude_other: bool,
+    /// Daily only: "unweighted", "score" (OSS Insight total_score) or "stars" (star delta)
+    pub weighting: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TrendsQuery {
+    pub date: Option<String>,
+    /// "upstream" (OSS Insight total_score, default) or "computed"
+    pub sort: Option<String>,
+    /// "latest" serves the newest collected date when the requested one is empty
+    pub fallback: Option<String>,
+    /// Only repos rated for this audience (library, app, tool, course, research)
+    pub audience: Option<String>,
+    /// Only repos of this difficulty (beginner, intermediate, advanced)
+    pub difficulty: Option<String>,
+    /// Only repos in this summary category (ai-ml, web, devtools, ...)
+    pub category: Option<String>,
+    /// Only repos owned by this user or organization
+    pub org: Option<String>,
+    /// Only repos in this lifecycle state (new, recurring, resurfaced)
+    pub lifecycle: Option<String>,
+    /// Only repos tagged with this topic (compared in normalized form, e.g. "Web Framework" = "web-framework")
+    pub tag: Option<String>,
+    /// Comma-separated repo fields to return, e.g. "repo_name,stars,korean_summary"
+    pub fields: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CategoryTrendsQuery {
+    /// Last day of the window (default: latest collected)
+    pub date: Option<String>,
+    /// Window length (default 30, max 365)
+    pub days: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TagCloudQuery {
+    /// Last day of the window (default: latest collected)
+    pub date: Option<String>,
+    /// "day", "week" (default) or "month"
+    pub period: Option<String>,
+    /// Number of tags (default 50, max 500)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ActivityQuery {
+    /// Calendar year (default: current)
+    pub year: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ArchiveQuery {
+    /// Only this calendar year (default: every collected day)
+    pub year: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrgsQuery {
+    pub date: Option<String>,
+    /// Number of owners (default 20, max 100)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FieldsQuery {
+    pub fields: Option<String>,
+}
+
+/// Response header carrying the date whose data was actually served
+pub const SERVED_DATE_HEADER: &str = "x-served-date";
+
+#[derive(Debug, Serialize)]
+pub struct LatestTrendsResponse {
+    pub date: String,
+    pub repos: Vec<serde_json::Value>,
+}
+
+/// Everything the home page shows, in one response
+#[derive(Debug, Serialize)]
+pub struct DashboardResponse {
+    pub date: String,
+    pub top_repos: Vec<TrendingRepoResponse>,
+    pub top_languages: Vec<LanguageTrend>,
+    /// Languages whose share moved most since `previous_date`
+    pub language_movers: Vec<LanguageMover>,
+    /// Collected day before `date` the movers are measured against; `None` on the first day
+    pub previous_date: Option<String>,
+    pub freshness: DashboardFreshness,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DashboardFreshness {
+    /// Newest collected day, which may be later than the dashboard's date
+    pub latest_date: Option<String>,
+    /// When the dashboard's date was last written by a collection
+    pub collected_at: Option<String>,
+    /// Minutes since `collected_at`
+    pub age_minutes: Option<i64>,
+    /// A collection or other background job is running right now
+    pub collecting: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForecastQuery {
+    pub date: Option<String>,
+    pub days: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TopReposQuery {
+    pub date: Option<String>,
+    /// "day", "week" (default) or "month"
+    pub period: Option<String>,
+    /// "score" (default) or "stars"
+    pub sort: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CooccurrenceQuery {
+    pub date: Option<String>,
+    /// Days ending at `date` to include (default 30, max 365)
+    pub days: Option<i64>,
+    /// Languages in the matrix (default 15, max 50)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LookbackQuery {
+    pub date: Option<String>,
+    /// How far back to look, e.g. "30d" or "1y"
+    pub offset: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OverlapQuery {
+    pub date: Option<String>,
+    /// Least number of shared contributors for a pair to be listed (default 1)
+    pub min_shared: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteDayQuery {
+    pub date: String,
+    /// `confirm_token` returned by a previous call without it
+    pub confirm: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecomputeTrendsQuery {
+    /// First day to rebuild; every collected day after it is rebuilt too
+    pub from: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LimitQuery {
+    /// Number of entries, newest first (default 100, max 1000)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JobsQuery {
+    /// Only jobs with this status ("queued", "running", "done", "failed", "cancelled")
+    pub status: Option<String>,
+    /// Number of jobs, newest first (default 100, max 1000)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShareQuery {
+    /// "html" (default, the `digest.html` template) or "json"
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeedbackReportQuery {
+    /// Only summaries of this day
+    pub date: Option<String>,
+    /// Number of summaries (default 50, max 500)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunEventsQuery {
+    /// Only events after this event id, for paging or polling a running collection
+    pub after: Option<i64>,
+    /// Number of events, oldest first (default 1000, max 10000)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    /// Only entries of this actor ("admin" or "anonymous")
+    pub actor: Option<String>,
+    /// Number of entries, newest first (default 100, max 1000)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UsageQuery {
+    /// Number of days to report, newest first (default 30)
+    pub days: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsageResponse {
+    pub pricing: UsagePricing,
+    pub days: Vec<DailyUsage>,
+}
+
+/// Shields.io endpoint badge (https://shields.io/badges/endpoint-badge)
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShieldsBadge {
+    pub schema_version: u8,
+    pub label: String,
+    pub message: String,
+    pub color: &'static str,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_error: bool,
+    pub cache_seconds: u64,
+}
+
+/// How long shields.io and browsers may reuse a badge
+const BADGE_CACHE_SECS: u64 = 300;
+
+impl ShieldsBadge {
+    fn new(label: impl Into<String>, message: impl Into<String>, color: &'static str) -> Self {
+        ShieldsBadge {
+            schema_version: 1,
+            label: label.into(),
+            message: message.into(),
+            color,
+            is_error: false,
+            cache_seconds: BADGE_CACHE_SECS,
+        }
+    }
+
+    /// Shown in place of the badge; still a 200, since shields.io renders other statuses as
+    /// "inaccessible" without the message
+    fn error(label: impl Into<String>, message: impl Into<String>) -> Self {
+        ShieldsBadge {
+            is_error: true,
+            ..ShieldsBadge::new(label, message, "lightgrey")
+        }
+    }
+}
+
+impl IntoResponse for ShieldsBadge {
+    fn into_response(self) -> Response {
+        let cache_control = format!("public, max-age={}", self.cache_seconds);
+        ([(header::CACHE_CONTROL, cache_control)], Json(self)).into_response()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LifecycleResponse {
+    pub date: String,
+    /// Repos per state, including "dropped" for those that trended the day before but not on `date`
+    pub counts: std::collections::BTreeMap<&'static str, usize>,
+    pub dropped: Vec<TrendingRepoResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LookbackResponse {
+    pub date: String,
+    pub past_date: String,
+    pub offset: String,
+    pub today: Vec<TrendingRepoResponse>,
+    pub past: Vec<TrendingRepoResponse>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
     pub error: Option<String>,
+    pub meta: ResponseMeta,
+}
+
+/// Context of a response, so clients need not infer it from the URL they called
+#[derive(Debug, Clone, Serialize)]
+pub struct ResponseMeta {
+    /// Date whose data was served
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    pub generated_at: String,
+    /// Number of items in `data`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+    /// Cursor for the next page of paginated endpoints; absent on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    /// When the served date's data was last written by a collection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_freshness: Option<String>,
+    /// Set when `MAX_REPOS_PER_RUN` left candidates of the served date out of the brief, or when
+    /// upstream values failed to parse
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cutoff: Option<RunCutoff>,
+    /// Set when the served date's data may be incomplete: `collecting` while a run is filling it
+    /// in, `errors` when its last run failed or left repos out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial: Option<&'static str>,
+}
+
+impl ResponseMeta {
+    pub fn now() -> Self {
+        ResponseMeta {
+            date: None,
+            generated_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            total: None,
+            next_cursor: None,
+            data_freshness: None,
+            cutoff: None,
+            partial: None,
+        }
+    }
+}
+
+// Values of `ResponseMeta::partial`
+pub const PARTIAL_COLLECTING: &str = "collecting";
+pub const PARTIAL_ERRORS: &str = "errors";
+
+/// Whether `date`'s data may be incomplete, see `ResponseMeta::partial`
+fn partial_day(state: &AppState, date: &str) -> Option<&'static str> {
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let collecting = state.jobs.is_busy()
+        && state
+            .db
+            .get_jobs(Some(jobs::JOB_RUNNING), 10)
+            .unwrap_or_default()
+            .iter()
+            .filter(|job| [jobs::JOB_COLLECT, jobs::JOB_BACKFILL, jobs::JOB_SUMMARIZE_MISSING].contains(&job.kind.as_str()))
+            .any(|job| job.date.as_deref().unwrap_or(&today) == date);
+    if collecting {
+        return Some(PARTIAL_COLLECTING);
+    }
+    match state.db.last_collection_failed(date) {
+        Ok(true) => Some(PARTIAL_ERRORS),
+        Ok(false) => None,
+        Err(e) => {
+            warn!("Failed to check the last collection of {}: {}", date, e);
+            None
+        }
+    }
+}
+
+/// Meta for data of one date, with its freshness looked up from storage
+fn dated_meta(state: &AppState, date: &str, total: Option<usize>) -> ResponseMeta {
+    ResponseMeta {
+        date: Some(date.to_string()),
+        total,
+        data_freshness: state.db.get_collected_at(date).ok().flatten(),
+        cutoff: state.db.get_run_cutoff(date).ok().flatten().filter(|c| c.trimmed() > 0 || !c.parse_failures.is_empty() || !c.circuit_breaks.is_empty()),
+        partial: partial_day(state, date),
+        ..ResponseMeta::now()
+    }
 }
 
 #[derive(Debug, Serialize)]
 pub struct CollectResponse {
     pub message: String,
     pub collected_count: usize,
+    /// Matches `run_id` of this run's progress events
+    pub run_id: Option<String>,
+    /// Date the run collects into
+    pub date: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CollectQuery {
+    /// Past date to re-collect or fill in (default today)
+    pub date: Option<String>,
+    /// "full" (default) re-runs the collection; "languages" only recomputes the date's
+    /// language trends from stored repo languages
+    pub mode: Option<String>,
 }
 
 // GET /api/trends
 pub async fn get_trends(
     State(state): State<Arc<AppState>>,
-    Query(query): Query<DateQuery>,
+    Query(query): Query<TrendsQuery>,
 ) -> impl IntoResponse {
     let date = query.date.unwrap_or_else(|| {
         chrono::Utc::now().format("%Y-%m-%d").to_string()
     });
 
-    match state.db.get_trending_repos(&date) {
-        Ok(repos) => {
-            let mut response_repos: Vec<TrendingRepoResponse> = Vec::new();
-            
-            for (rank, repo) in repos.into_iter().enumerate() {
-                // Get languages for this repo
-                let languages = state.db
-                    .get_repo_languages(&date, repo.repo_id)
-                    .unwrap_or_default()
-                    .into_iter()
-                    .map(|l| crate::models::LanguageInfo {
-                        language: l.language,
-                        percentage: l.percentage,
-                    })
-                    .collect();
-
-                response_repos.push(TrendingRepoResponse {
-                    rank: rank + 1,
-                    repo_id: repo.repo_id,
-                    repo_name: repo.repo_name.clone(),
-                    github_url: format!("https://github.com/{}", repo.repo_name),
-                    primary_language: repo.primary_language,
-                    languages,
-                    description: repo.description,
-                    korean_summary: repo.korean_summary,
-                    stars: repo.stars,
-                    forks: repo.forks,
-                    total_score: repo.total_score,
+    // With fallback=latest, serve the newest date with data on or before the requested one
+    let date = if query.fallback.as_deref() == Some("latest") {
+        state.db.get_latest_date_on_or_before(&date).ok().flatten().unwrap_or(date)
+    } else {
+        date
+    };
+
+    let fields = parse_fields(query.fields.as_deref());
+    let tag = query.tag.as_deref().map(tags::normalize_tag);
+    let cache_key = format!(
+        "trends:{}:{}:{}:{}:{}:{}:{}:{}:{}",
+        date,
+        query.sort.as_deref().unwrap_or_default(),
+        query.audience.as_deref().unwrap_or_default(),
+        query.difficulty.as_deref().unwrap_or_default(),
+        query.category.as_deref().unwrap_or_default(),
+        query.org.as_deref().unwrap_or_default(),
+        query.lifecycle.as_deref().unwrap_or_default(),
+        tag.as_deref().unwrap_or_default(),
+        fields.as_deref().map(|f| f.join(",")).unwrap_or_default(),
+    );
+    // A response cached before a run started would hide that the day is being collected
+    let cached = if state.jobs.is_busy() { None } else { state.cache.get(&cache_key) };
+    let mut response = match cached {
+        Some(cached) => Json(cached).into_response(),
+        None => {
+            let result = state.db.get_trending_repos(&date).map(|mut repos| {
+                if query.sort.as_deref() == Some("computed") {
+                    repos.sort_by(|a, b| {
+                        b.computed_score
+                            .unwrap_or(f64::MIN)
+                            .total_cmp(&a.computed_score.unwrap_or(f64::MIN))
+                    });
+                }
+                // Filter after ranking so repos keep their rank in the full brief
+                let mut responses = build_repo_responses(&state.db, &date, repos);
+                responses.retain(|r| {
+                    matches_filter(r.audience.as_deref(), query.audience.as_deref())
+                        && matches_filter(r.difficulty.as_deref(), query.difficulty.as_deref())
+                        && matches_filter(r.category.as_deref(), query.category.as_deref())
+                        && matches_filter(r.owner.as_deref(), query.org.as_deref())
+                        && matches_filter(r.lifecycle.as_deref(), query.lifecycle.as_deref())
+                        && tag.as_ref().is_none_or(|tag| r.tags.contains(tag))
                 });
+                select_fields(responses, fields.as_deref())
+            });
+            let meta = dated_meta(&state, &date, result.as_ref().ok().map(Vec::len));
+            cached_response(&state, cache_key, result, meta, "trending repos")
+        }
+    };
+
+    if let Ok(value) = HeaderValue::from_str(&date) {
+        response.headers_mut().insert(SERVED_DATE_HEADER, value);
+    }
+    response
+}
+
+/// Most dates one batch request may cover
+const MAX_BATCH_DATES: usize = 92;
+
+// POST /api/trends/batch
+pub async fn get_trends_batch(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<FieldsQuery>,
+    Json(req): Json<BatchTrendsRequest>,
+) -> Response {
+    let fields = parse_fields(query.fields.as_deref());
+    let dates = match batch_dates(&req) {
+        Ok(dates) => dates,
+        Err(message) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()> {
+                    success: false,
+                    data: None,
+                    error: Some(message),
+                    meta: ResponseMeta::now(),
+                }),
+            ).into_response();
+        }
+    };
+    let limit = req.limit.unwrap_or(10).clamp(1, 100);
+
+    let mut trends = std::collections::BTreeMap::new();
+    for date in dates {
+        match state.db.get_trending_repos(&date) {
+            Ok(mut repos) => {
+                repos.truncate(limit);
+                let responses = build_repo_responses(&state.db, &date, repos);
+                trends.insert(date, select_fields(responses, fields.as_deref()));
+            }
+            Err(e) => {
+                error!("Failed to get trending repos for {}: {}", date, e);
+                return Json(ApiResponse::<()> {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }).into_response();
+            }
+        }
+    }
+
+    Json(ApiResponse {
+        success: true,
+        meta: ResponseMeta {
+            total: Some(trends.len()),
+            ..ResponseMeta::now()
+        },
+        data: Some(trends),
+        error: None,
+    }).into_response()
+}
+
+/// Validated, de-duplicated dates of a batch request
+fn batch_dates(req: &BatchTrendsRequest) -> Result<Vec<String>, String> {
+    let parse = |d: &str| {
+        chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").map_err(|_| format!("Invalid date '{}'", d))
+    };
+
+    let mut dates = match (&req.dates, &req.from, &req.to) {
+        (Some(dates), None, None) => dates.iter().map(|d| parse(d)).collect::<Result<Vec<_>, _>>()?,
+        (None, Some(from), Some(to)) => {
+            let (from, to) = (parse(from)?, parse(to)?);
+            if from > to {
+                return Err("`from` must not be after `to`".to_string());
             }
+            from.iter_days().take_while(|d| *d <= to).take(MAX_BATCH_DATES + 1).collect()
+        }
+        _ => return Err("Provide either `dates` or both `from` and `to`".to_string()),
+    };
+    dates.sort();
+    dates.dedup();
+
+    if dates.is_empty() || dates.len() > MAX_BATCH_DATES {
+        return Err(format!("Request between 1 and {} dates", MAX_BATCH_DATES));
+    }
+    Ok(dates.iter().map(|d| d.format("%Y-%m-%d").to_string()).collect())
+}
+
+// GET /api/trends/latest
+pub async fn get_latest_trends(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<FieldsQuery>,
+) -> impl IntoResponse {
+    let fields = parse_fields(query.fields.as_deref());
+    let date = match state.db.get_latest_date() {
+        Ok(Some(date)) => date,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<LatestTrendsResponse> {
+                    success: false,
+                    data: None,
+                    error: Some("No data has been collected yet".to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            ).into_response();
+        }
+        Err(e) => {
+            error!("Failed to get latest date: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<LatestTrendsResponse> {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            ).into_response();
+        }
+    };
+
+    let result = state.db.get_trending_repos(&date).map(|repos| LatestTrendsResponse {
+        repos: select_fields(build_repo_responses(&state.db, &date, repos), fields.as_deref()),
+        date: date.clone(),
+    });
+    let cache_key = format!(
+        "trends:latest:{}:{}",
+        date,
+        fields.as_deref().map(|f| f.join(",")).unwrap_or_default(),
+    );
+    let meta = dated_meta(&state, &date, result.as_ref().ok().map(|r| r.repos.len()));
+    cached_response(&state, cache_key, result, meta, "latest trends")
+}
+
+/// Repos, languages and movers the dashboard shows
+const DASHBOARD_LIMIT: usize = 5;
 
+// GET /api/dashboard
+// The home page in one request: the day's top repos and languages, the languages that moved most
+// since the previous collected day, and how fresh the data is. Without a date, the latest
+// collected day. Not cached, since the freshness is live.
+pub async fn get_dashboard(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DateQuery>,
+) -> Response {
+    let dashboard_error = |status: StatusCode, message: String| {
+        (
+            status,
+            Json(ApiResponse::<DashboardResponse> {
+                success: false,
+                data: None,
+                error: Some(message),
+                meta: ResponseMeta::now(),
+            }),
+        ).into_response()
+    };
+
+    let latest_date = match state.db.get_latest_date() {
+        Ok(latest_date) => latest_date,
+        Err(e) => {
+            error!("Failed to get latest date: {}", e);
+            return dashboard_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+        }
+    };
+    let date = match query.date.or_else(|| latest_date.clone()) {
+        Some(date) => match chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d") {
+            Ok(day) => day,
+            Err(_) => return dashboard_error(StatusCode::BAD_REQUEST, format!("Invalid date '{}'", date)),
+        },
+        None => return dashboard_error(StatusCode::NOT_FOUND, "No data has been collected yet".to_string()),
+    };
+    let previous_day = (date - chrono::Duration::days(1)).format("%Y-%m-%d").to_string();
+    let date = date.format("%Y-%m-%d").to_string();
+
+    let result = build_dashboard(&state, &date, &previous_day, latest_date);
+
+    match result {
+        Ok(dashboard) => {
+            let meta = dated_meta(&state, &date, Some(dashboard.top_repos.len()));
             Json(ApiResponse {
                 success: true,
-                data: Some(response_repos),
+                data: Some(dashboard),
                 error: None,
-            })
+                meta,
+            }).into_response()
         }
         Err(e) => {
-            error!("Failed to get trending repos: {}", e);
-            Json(ApiResponse {
+            error!("Failed to build the dashboard of {}: {}", date, e);
+            dashboard_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        }
+    }
+}
+
+/// Top repos and languages of `date`, movers against the last collected day on or before
+/// `previous_day`, and freshness
+fn build_dashboard(
+    state: &AppState,
+    date: &str,
+    previous_day: &str,
+    latest_date: Option<String>,
+) -> anyhow::Result<DashboardResponse> {
+    let mut repos = state.db.get_trending_repos(date)?;
+    repos.truncate(DASHBOARD_LIMIT);
+    let languages = without_other(state.db.get_daily_language_trends(date)?, true);
+    let previous_date = state.db.get_latest_date_on_or_before(previous_day)?;
+    let previous_languages = match &previous_date {
+        Some(previous_date) => without_other(state.db.get_daily_language_trends(previous_date)?, true),
+        None => Vec::new(),
+    };
+    let collected_at = state.db.get_collected_at(date)?;
+
+    Ok(DashboardResponse {
+        top_repos: build_repo_responses(&state.db, date, repos),
+        language_movers: if previous_date.is_some() {
+            trends::language_movers(&languages, &previous_languages, DASHBOARD_LIMIT)
+        } else {
+            Vec::new()
+        },
+        top_languages: languages.into_iter().take(DASHBOARD_LIMIT).collect(),
+        previous_date,
+        freshness: DashboardFreshness {
+            latest_date,
+            age_minutes: collected_at
+                .as_deref()
+                .and_then(|at| chrono::NaiveDateTime::parse_from_str(at, "%Y-%m-%dT%H:%M:%S").ok())
+                .map(|at| (chrono::Utc::now().naive_utc() - at).num_minutes()),
+            collected_at,
+            collecting: state.jobs.is_busy(),
+        },
+        date: date.to_string(),
+    })
+}
+
+/// Wrap a query result in `ApiResponse`, caching it under `key` on success.
+/// Nothing is cached mid-collection, since summaries are still landing.
+fn cached_response<T: Serialize>(
+    state: &AppState,
+    key: String,
+    result: anyhow::Result<T>,
+    meta: ResponseMeta,
+    what: &str,
+) -> Response {
+    match result {
+        Ok(data) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(data),
+                error: None,
+                meta,
+            };
+            if !state.jobs.is_busy() {
+                state.cache.insert(key, &response);
+            }
+            Json(response).into_response()
+        }
+        Err(e) => {
+            error!("Failed to get {}: {}", what, e);
+            Json(ApiResponse::<T> {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+                meta: ResponseMeta::now(),
+            }).into_response()
+        }
+    }
+}
+
+fn matches_filter(value: Option<&str>, wanted: Option<&str>) -> bool {
+    wanted.is_none_or(|w| value.is_some_and(|v| v.eq_ignore_ascii_case(w)))
+}
+
+/// Parse a stored JSON string array, treating missing or malformed values as empty
+fn json_list(value: Option<&str>) -> Vec<String> {
+    value
+        .and_then(|v| serde_json::from_str(v).ok())
+        .unwrap_or_default()
+}
+
+fn without_other(mut trends: Vec<LanguageTrend>, exclude: bool) -> Vec<LanguageTrend> {
+    if exclude {
+        trends.retain(|t| t.language != OTHER_LANGUAGE);
+    }
+    trends
+}
+
+/// Attach languages and derived fields to stored repos. Each keeps the rank stored at collection
+/// time; only rows stored before ranks were are ranked by their position in `repos`.
+fn build_repo_responses(db: &Database, date: &str, repos: Vec<TrendingRepo>) -> Vec<TrendingRepoResponse> {
+    let mut response_repos: Vec<TrendingRepoResponse> = Vec::new();
+
+    for (position, repo) in repos.into_iter().enumerate() {
+        let rank = repo.rank.and_then(|rank| usize::try_from(rank).ok()).unwrap_or(position + 1);
+        // Get languages for this repo
+        let languages = db
+            .get_repo_languages(date, repo.repo_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|l| crate::models::LanguageInfo {
+                language: l.language,
+                percentage: l.percentage,
+            })
+            .collect();
+
+        response_repos.push(TrendingRepoResponse {
+            rank,
+            repo_id: repo.repo_id,
+            repo_name: repo.repo_name.clone(),
+            owner: repo.owner,
+            total_stars: repo.total_stars,
+            lifecycle: repo.lifecycle,
+            github_url: format!("https://github.com/{}", repo.repo_name),
+            primary_language: repo.primary_language,
+            languages,
+            description: repo.description,
+            description_language: repo.description_language,
+            description_ko: repo.description_ko,
+            korean_summary: repo.korean_summary,
+            summary_status: repo.summary_status,
+            readme_updated: repo.readme_updated,
+            readme_change_note: repo.readme_change_note,
+            features: json_list(repo.summary_features.as_deref()),
+            tech_stack: json_list(repo.summary_tech_stack.as_deref()),
+            tags: json_list(repo.summary_tags.as_deref()),
+            category: repo.category,
+            audience: repo.audience,
+            difficulty: repo.difficulty,
+            homepage: repo.homepage,
+            doc_links: json_list(repo.doc_links.as_deref()),
+            quality_flag: repo.quality_flag,
+            policy_flagged: repo.policy_flags.is_some(),
+            policy_flags: json_list(repo.policy_flags.as_deref()),
+            summary_model: repo.summary_model,
+            stars: repo.stars,
+            forks: repo.forks,
+            total_score: repo.total_score,
+            computed_score: repo.computed_score,
+            issues: db.get_repo_issues(date, repo.repo_id).unwrap_or_default(),
+            sources: db.get_repo_sources(date, repo.repo_id).unwrap_or_default(),
+        });
+    }
+
+    response_repos
+}
+
+// GET /api/repos/:id/history
+pub async fn get_repo_history(
+    State(state): State<Arc<AppState>>,
+    Path(repo_id): Path<i64>,
+    Query(query): Query<PageQuery>,
+) -> Response {
+    archive_page(&state, ArchiveFilter::Repo(repo_id), query.cursor.as_deref(), query.limit, query.fields.as_deref())
+}
+
+// GET /api/repos/by-name/:owner/:repo
+// A repo's profile over the whole archive, by full name
+pub async fn get_repo_profile(
+    State(state): State<Arc<AppState>>,
+    Path((owner, repo)): Path<(String, String)>,
+    peer: Option<ConnectInfo<std::net::SocketAddr>>,
+    headers: HeaderMap,
+) -> Response {
+    let repo_name = format!("{}/{}", owner, repo);
+    let cache_key = format!("repos:profile:{}", repo_name.to_lowercase());
+    if let Some(cached) = state.cache.get(&cache_key) {
+        return Json(cached).into_response();
+    }
+
+    let client = client_ip(&state.config.trusted_proxies, &headers, peer.map(|ci| ci.0.ip()))
+        .map(|ip| ip.to_string())
+        .unwrap_or_default();
+    match load_repo_profile(&state, &repo_name, &client).await {
+        Ok(RepoLookup::Found(profile)) => cached_response(&state, cache_key, Ok(profile), ResponseMeta::now(), "repo profile"),
+        Ok(RepoLookup::Unknown) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<RepoProfile> {
+                success: false,
+                data: None,
+                error: Some(format!("{} never trended", repo_name)),
+                meta: ResponseMeta::now(),
+            }),
+        ).into_response(),
+        Ok(RepoLookup::OverQuota) => (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(ApiResponse::<RepoProfile> {
+                success: false,
+                data: None,
+                error: Some(format!("{} is not in the archive, and this client looked up too many such names on GitHub; try again later", repo_name)),
+                meta: ResponseMeta::now(),
+            }),
+        ).into_response(),
+        Err(e) => match e.downcast_ref::<ServiceError>() {
+            // GitHub couldn't say whether the name was renamed, which isn't "not found"
+            Some(service) => {
+                warn!("Failed to look up {} on GitHub: {}", repo_name, service);
+                let status = match service {
+                    ServiceError::RateLimited(_) | ServiceError::CircuitOpen(_) | ServiceError::Timeout(_) => {
+                        StatusCode::SERVICE_UNAVAILABLE
+                    }
+                    _ => StatusCode::BAD_GATEWAY,
+                };
+                (
+                    status,
+                    Json(ApiResponse::<RepoProfile> {
+                        success: false,
+                        data: None,
+                        error: Some(format!("GitHub lookup of {} failed: {}", repo_name, service)),
+                        meta: ResponseMeta::now(),
+                    }),
+                ).into_response()
+            }
+            None => cached_response::<RepoProfile>(&state, cache_key, Err(e), ResponseMeta::now(), "repo profile"),
+        },
+    }
+}
+
+/// Profile of the repo `repo_name` names; `None` when it never trended. A name the repo had
+/// before a rename resolves through the archive, one it got after its last appearance through
+/// GitHub, whose misses are remembered for a while.
+enum RepoLookup {
+    Found(RepoProfile),
+    Unknown,
+    /// Not in the archive, and `client` may not ask GitHub about more names for now
+    OverQuota,
+}
+
+async fn load_repo_profile(state: &AppState, repo_name: &str, client: &str) -> anyhow::Result<RepoLookup> {
+    let repo_id = match state.db.resolve_repo_name(repo_name)? {
+        Some(repo_id) => repo_id,
+        None => {
+            let key = repo_name.to_lowercase();
+            if !is_github_repo_name(repo_name) || state.unknown_repo_names.is_missing(&key) {
+                return Ok(RepoLookup::Unknown);
+            }
+            if !state.repo_name_lookups.try_take(client) {
+                return Ok(RepoLookup::OverQuota);
+            }
+            match state.providers.metadata.get_repo_info(repo_name).await? {
+                Some(GitHubRepoInfo { id: Some(repo_id), .. }) => repo_id,
+                _ => {
+                    state.unknown_repo_names.insert(key);
+                    return Ok(RepoLookup::Unknown);
+                }
+            }
+        }
+    };
+    let appearances = state.db.get_repo_appearances(repo_id)?;
+    let others = state.db.get_latest_repo_tags(repo_id)?;
+    Ok(profile::repo_profile(&appearances, &others).map_or(RepoLookup::Unknown, RepoLookup::Found))
+}
+
+/// Whether `name` could be a GitHub `owner/repo` at all: owners are up to 39 letters, digits and
+/// hyphens, repos up to 100 letters, digits, '.', '-' and '_'
+fn is_github_repo_name(name: &str) -> bool {
+    let Some((owner, repo)) = name.split_once('/') else {
+        return false;
+    };
+    let owner_ok = (1..=39).contains(&owner.len())
+        && !owner.starts_with('-')
+        && owner.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    let repo_ok = (1..=100).contains(&repo.len())
+        && repo != "."
+        && repo != ".."
+        && repo.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+    owner_ok && repo_ok
+}
+
+// GET /api/search
+pub async fn search_repos(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SearchQuery>,
+) -> Response {
+    let q = query.q.trim();
+    if q.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<Vec<TrendingRepo>> {
+                success: false,
+                data: None,
+                error: Some("Search query must not be empty".to_string()),
+                meta: ResponseMeta::now(),
+            }),
+        ).into_response();
+    }
+    archive_page(&state, ArchiveFilter::Search(q), query.cursor.as_deref(), query.limit, query.fields.as_deref())
+}
+
+// GET /api/tags/cloud
+// Tag frequencies over the day, week or month ending at `date`, most frequent first
+pub async fn get_tag_cloud(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<TagCloudQuery>,
+) -> Response {
+    let days = match query.period.as_deref().unwrap_or("week") {
+        "day" => 1,
+        "week" => 7,
+        "month" => 30,
+        other => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()> {
+                    success: false,
+                    data: None,
+                    error: Some(format!("Unknown period '{}' (expected day, week or month)", other)),
+                    meta: ResponseMeta::now(),
+                }),
+            ).into_response();
+        }
+    };
+    let end = query
+        .date
+        .and_then(|d| chrono::NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok())
+        .or_else(|| {
+            let latest = state.db.get_latest_date().ok().flatten()?;
+            chrono::NaiveDate::parse_from_str(&latest, "%Y-%m-%d").ok()
+        })
+        .unwrap_or_else(|| chrono::Utc::now().date_naive());
+    let fmt = |d: chrono::NaiveDate| d.format("%Y-%m-%d").to_string();
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+
+    let cache_key = format!("tags:cloud:{}:{}:{}", fmt(end), days, limit);
+    if let Some(cached) = state.cache.get(&cache_key) {
+        return Json(cached).into_response();
+    }
+
+    let result = state
+        .db
+        .get_repo_tags(&fmt(end - chrono::Duration::days(days - 1)), &fmt(end))
+        .map(|rows| tags::tag_cloud(&rows, limit));
+    let meta = dated_meta(&state, &fmt(end), result.as_ref().ok().map(Vec::len));
+    cached_response(&state, cache_key, result, meta, "tag cloud")
+}
+
+// GET /api/categories/trends
+// Share of each day's trending repos per summary category over the `days` ending at `date`
+pub async fn get_category_trends(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<CategoryTrendsQuery>,
+) -> impl IntoResponse {
+    let end = query
+        .date
+        .and_then(|d| chrono::NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok())
+        .or_else(|| {
+            let latest = state.db.get_latest_date().ok().flatten()?;
+            chrono::NaiveDate::parse_from_str(&latest, "%Y-%m-%d").ok()
+        })
+        .unwrap_or_else(|| chrono::Utc::now().date_naive());
+    let fmt = |d: chrono::NaiveDate| d.format("%Y-%m-%d").to_string();
+    let days = query.days.unwrap_or(30).clamp(1, 365);
+
+    let cache_key = format!("categories:trends:{}:{}", fmt(end), days);
+    if let Some(cached) = state.cache.get(&cache_key) {
+        return Json(cached).into_response();
+    }
+
+    let result = state
+        .db
+        .get_category_counts(&fmt(end - chrono::Duration::days(days - 1)), &fmt(end))
+        .map(|counts| trends::category_shares(&counts));
+    let meta = dated_meta(&state, &fmt(end), result.as_ref().ok().map(Vec::len));
+    cached_response(&state, cache_key, result, meta, "category trends")
+}
+
+// GET /api/activity
+pub async fn get_activity(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ActivityQuery>,
+) -> impl IntoResponse {
+    let year = query.year.unwrap_or_else(|| chrono::Utc::now().year());
+
+    let cache_key = format!("activity:{}", year);
+    if let Some(cached) = state.cache.get(&cache_key) {
+        return Json(cached).into_response();
+    }
+
+    let result = state.db.get_daily_activity(Some(year));
+    let meta = ResponseMeta {
+        total: result.as_ref().ok().map(Vec::len),
+        ..ResponseMeta::now()
+    };
+    cached_response(&state, cache_key, result, meta, "activity calendar")
+}
+
+// GET /api/archive
+pub async fn get_archive(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ArchiveQuery>,
+) -> impl IntoResponse {
+    let cache_key = format!("archive:{}", query.year.map(|y| y.to_string()).unwrap_or_default());
+    if let Some(cached) = state.cache.get(&cache_key) {
+        return Json(cached).into_response();
+    }
+
+    let result = state.db.get_daily_activity(query.year).map(group_archive);
+    let meta = ResponseMeta {
+        total: result.as_ref().ok().map(|years| years.iter().map(|y| y.days).sum()),
+        ..ResponseMeta::now()
+    };
+    cached_response(&state, cache_key, result, meta, "archive")
+}
+
+/// Nest collected days (oldest first) into years and months, newest first
+fn group_archive(days: Vec<DailyActivity>) -> Vec<ArchiveYear> {
+    let mut years: Vec<ArchiveYear> = Vec::new();
+
+    for day in days.into_iter().rev() {
+        let Ok(date) = chrono::NaiveDate::parse_from_str(&day.date, "%Y-%m-%d") else {
+            continue;
+        };
+        if years.last().map(|y| y.year) != Some(date.year()) {
+            years.push(ArchiveYear { year: date.year(), days: 0, months: vec![] });
+        }
+        let year = years.last_mut().expect("pushed above");
+        if year.months.last().map(|m| m.month) != Some(date.month()) {
+            year.months.push(ArchiveMonth { month: date.month(), repos: 0, days: vec![] });
+        }
+        let month = year.months.last_mut().expect("pushed above");
+
+        year.days += 1;
+        month.repos += day.repos;
+        month.days.push(day);
+    }
+
+    years
+}
+
+// GET /api/orgs
+pub async fn get_orgs(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<OrgsQuery>,
+) -> impl IntoResponse {
+    let date = query.date.unwrap_or_else(|| {
+        chrono::Utc::now().format("%Y-%m-%d").to_string()
+    });
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+
+    match state.db.get_org_trends(&date, limit) {
+        Ok(orgs) => Json(ApiResponse {
+            success: true,
+            meta: dated_meta(&state, &date, Some(orgs.len())),
+            data: Some(orgs),
+            error: None,
+        }),
+        Err(e) => {
+            error!("Failed to get org trends: {}", e);
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+                meta: ResponseMeta::now(),
+            })
+        }
+    }
+}
+
+// GET /api/contributors/overlap
+pub async fn get_contributor_overlap(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<OverlapQuery>,
+) -> impl IntoResponse {
+    let date = query.date.unwrap_or_else(|| {
+        chrono::Utc::now().format("%Y-%m-%d").to_string()
+    });
+
+    match state.db.get_contributor_overlap(&date, query.min_shared.unwrap_or(1).max(1)) {
+        Ok(pairs) => Json(ApiResponse {
+            success: true,
+            meta: dated_meta(&state, &date, Some(pairs.len())),
+            data: Some(pairs),
+            error: None,
+        }),
+        Err(e) => {
+            error!("Failed to get contributor overlap: {}", e);
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+                meta: ResponseMeta::now(),
+            })
+        }
+    }
+}
+
+// GET /api/contributors/:login/repos
+pub async fn get_contributor_repos(
+    State(state): State<Arc<AppState>>,
+    Path(login): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Response {
+    archive_page(&state, ArchiveFilter::Contributor(&login), query.cursor.as_deref(), query.limit, query.fields.as_deref())
+}
+
+/// One keyset page of the archive; `next_cursor` is set only when more rows follow
+fn archive_page(
+    state: &AppState,
+    filter: ArchiveFilter<'_>,
+    cursor: Option<&str>,
+    limit: Option<usize>,
+    fields: Option<&str>,
+) -> Response {
+    let cursor = match cursor.map(ArchiveCursor::parse) {
+        Some(None) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<Vec<TrendingRepo>> {
+                    success: false,
+                    data: None,
+                    error: Some("Invalid cursor".to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            ).into_response();
+        }
+        Some(cursor) => cursor,
+        None => None,
+    };
+    let limit = limit.unwrap_or(50).clamp(1, 200);
+
+    // Fetch one extra row to learn whether another page exists
+    match state.db.get_repo_archive(&filter, cursor.as_ref(), limit + 1) {
+        Ok(mut repos) => {
+            let next_cursor = if repos.len() > limit {
+                repos.truncate(limit);
+                repos.last().map(|r| ArchiveCursor { date: r.date.clone(), repo_id: r.repo_id }.encode())
+            } else {
+                None
+            };
+
+            Json(ApiResponse {
+                success: true,
+                meta: ResponseMeta {
+                    total: Some(repos.len()),
+                    next_cursor,
+                    ..ResponseMeta::now()
+                },
+                data: Some(select_fields(repos, parse_fields(fields).as_deref())),
+                error: None,
+            }).into_response()
+        }
+        Err(e) => {
+            error!("Failed to page repo archive: {}", e);
+            Json(ApiResponse::<Vec<TrendingRepo>> {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+                meta: ResponseMeta::now(),
+            }).into_response()
+        }
+    }
+}
+
+// GET /api/collections/:id/trends
+pub async fn get_collection_trends(
+    State(state): State<Arc<AppState>>,
+    Path(collection_id): Path<i64>,
+    Query(query): Query<DateQuery>,
+) -> impl IntoResponse {
+    let date = query.date.unwrap_or_else(|| {
+        chrono::Utc::now().format("%Y-%m-%d").to_string()
+    });
+
+    match state.db.get_collection_repos(collection_id, &date) {
+        Ok(repos) => Json(ApiResponse {
+            success: true,
+            meta: ResponseMeta {
+                date: Some(date.clone()),
+                total: Some(repos.len()),
+                ..ResponseMeta::now()
+            },
+            data: Some(CollectionTrendsResponse {
+                collection_id,
+                collection_name: repos.first().map(|r| r.collection_name.clone()),
+                date,
+                repos,
+            }),
+            error: None,
+        }),
+        Err(e) => {
+            error!("Failed to get trends of collection {}: {}", collection_id, e);
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+                meta: ResponseMeta::now(),
+            })
+        }
+    }
+}
+
+// GET /api/repos/:id/readme
+pub async fn get_repo_readme(
+    State(state): State<Arc<AppState>>,
+    Path(repo_id): Path<i64>,
+    Query(query): Query<DateQuery>,
+) -> impl IntoResponse {
+    let date = query.date.unwrap_or_else(|| {
+        chrono::Utc::now().format("%Y-%m-%d").to_string()
+    });
+
+    match state.db.get_readme(&date, repo_id) {
+        Ok(Some(markdown)) => (
+            StatusCode::OK,
+            Json(ApiResponse {
+                success: true,
+                meta: ResponseMeta {
+                    date: Some(date.clone()),
+                    ..ResponseMeta::now()
+                },
+                data: Some(RenderedReadme {
+                    repo_id,
+                    date,
+                    html: readme::render_html(&markdown),
+                }),
+                error: None,
+            }),
+        ),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(format!("No README stored for repo {} on {}", repo_id, date)),
+                meta: ResponseMeta::now(),
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to get README for repo {}: {}", repo_id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            )
+        }
+    }
+}
+
+// POST /api/share
+pub async fn create_share_link(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DateQuery>,
+) -> impl IntoResponse {
+    let date = query.date.unwrap_or_else(|| {
+        chrono::Utc::now().format("%Y-%m-%d").to_string()
+    });
+
+    let token = match state.db.get_collected_at(&date) {
+        Ok(Some(_)) => {
+            let candidate = uuid::Uuid::new_v4().simple().to_string()[..12].to_string();
+            state.db.get_or_create_share_token(&date, &candidate)
+        }
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<ShareLink> {
+                    success: false,
+                    data: None,
+                    error: Some(format!("No brief collected for {}", date)),
+                    meta: ResponseMeta::now(),
+                }),
+            );
+        }
+        Err(e) => Err(e),
+    };
+
+    match token {
+        Ok(token) => {
+            // Nested workspaces serve their share links under their own prefix
+            let prefix = match state.config.workspace.as_str() {
+                "default" => String::new(),
+                workspace => format!("/w/{}", workspace),
+            };
+            (
+                StatusCode::CREATED,
+                Json(ApiResponse {
+                    success: true,
+                    data: Some(ShareLink {
+                        path: format!("{}/share/{}", prefix, token),
+                        token,
+                        date: date.clone(),
+                    }),
+                    error: None,
+                    meta: ResponseMeta {
+                        date: Some(date),
+                        ..ResponseMeta::now()
+                    },
+                }),
+            )
+        }
+        Err(e) => {
+            error!("Failed to create a share link for {}: {}", date, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            )
+        }
+    }
+}
+
+// GET /api/badge/top-language
+// Without a date, the latest collected day, so an embedded badge stays live
+pub async fn get_top_language_badge(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DateQuery>,
+) -> Response {
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let date = match query.date {
+        Some(date) => Some(date),
+        None => match state.db.get_latest_date() {
+            Ok(date) => date,
+            Err(e) => {
+                error!("Failed to find the latest date for a badge: {}", e);
+                return (StatusCode::INTERNAL_SERVER_ERROR, ShieldsBadge::error("#1 language", "unavailable")).into_response();
+            }
+        },
+    };
+    let Some(date) = date else {
+        return ShieldsBadge::error("#1 language", "no data yet").into_response();
+    };
+    let label = if date == today { "Today's #1 language".to_string() } else { format!("#1 language on {}", date) };
+
+    match state.db.get_daily_language_trends(&date) {
+        Ok(trends) => match trends.first() {
+            Some(top) => ShieldsBadge::new(label, format!("{} ({:.1}%)", top.language, top.normalized_percentage), "blue")
+                .into_response(),
+            None => ShieldsBadge::error(label, "no data").into_response(),
+        },
+        Err(e) => {
+            error!("Failed to get language trends for a badge: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, ShieldsBadge::error(label, "unavailable")).into_response()
+        }
+    }
+}
+
+// GET /api/badge/repo/:id
+// The repo's latest appearance in the brief
+pub async fn get_repo_badge(
+    State(state): State<Arc<AppState>>,
+    Path(repo_id): Path<i64>,
+) -> Response {
+    const LABEL: &str = "GitHub trending";
+
+    match state.db.get_repo_archive(&ArchiveFilter::Repo(repo_id), None, 1) {
+        Ok(repos) => match repos.first() {
+            Some(repo) => {
+                let message = match repo.rank {
+                    Some(rank) => format!("#{} on {}", rank, repo.date),
+                    None => repo.date.clone(),
+                };
+                let color = if repo.rank.is_some_and(|rank| rank <= 10) { "brightgreen" } else { "green" };
+                ShieldsBadge::new(LABEL, message, color).into_response()
+            }
+            None => ShieldsBadge::error(LABEL, "not trending").into_response(),
+        },
+        Err(e) => {
+            error!("Failed to get repo {} for a badge: {}", repo_id, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, ShieldsBadge::error(LABEL, "unavailable")).into_response()
+        }
+    }
+}
+
+// GET /api/brief/audio
+// The recorded brief of a day (default the latest collected); 404 until one is recorded
+pub async fn get_brief_audio(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DateQuery>,
+) -> Response {
+    let Some(tts_config) = &state.config.tts else {
+        return (StatusCode::NOT_FOUND, "Text-to-speech is not configured").into_response();
+    };
+    let date = match query.date {
+        // Validated before it becomes part of a file path
+        Some(date) if chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d").is_err() => {
+            return (StatusCode::BAD_REQUEST, format!("Invalid date '{}'", date)).into_response();
+        }
+        Some(date) => date,
+        None => match state.db.get_latest_date() {
+            Ok(Some(date)) => date,
+            Ok(None) => return (StatusCode::NOT_FOUND, "No data yet").into_response(),
+            Err(e) => {
+                error!("Failed to find the latest date for the brief audio: {}", e);
+                return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+            }
+        },
+    };
+
+    match tokio::fs::read(tts::audio_path(&tts_config.audio_dir, &date)).await {
+        Ok(audio) => (
+            [
+                (header::CONTENT_TYPE, "audio/mpeg"),
+                (header::CACHE_CONTROL, "public, max-age=3600"),
+            ],
+            audio,
+        ).into_response(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            (StatusCode::NOT_FOUND, format!("No recording of {}", date)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to read the brief audio of {}: {}", date, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
+
+/// Days of collection runs `calendar.ics` covers
+const CALENDAR_DAYS: i64 = 90;
+
+// GET /calendar.ics
+pub async fn get_calendar(State(state): State<Arc<AppState>>) -> Response {
+    match state.db.get_finished_collections(CALENDAR_DAYS) {
+        Ok(jobs) => (
+            [
+                (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+                (header::CACHE_CONTROL, "public, max-age=600"),
+            ],
+            calendar::render(&state.config.workspace, state.config.site_url.as_deref(), &jobs),
+        ).into_response(),
+        Err(e) => {
+            error!("Failed to build the calendar: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
+
+// GET /sitemap.xml
+pub async fn get_sitemap(State(state): State<Arc<AppState>>) -> Response {
+    match state.sitemap.get() {
+        Ok(Some(xml)) => (
+            [
+                (header::CONTENT_TYPE, "application/xml; charset=utf-8"),
+                (header::CACHE_CONTROL, "public, max-age=3600"),
+            ],
+            xml,
+        ).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, "No sitemap without SITE_URL").into_response(),
+        Err(e) => {
+            error!("Failed to build the sitemap: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
+
+// GET /share/:token
+// Public and read-only, so it keeps working if the API later requires keys. Past days rarely
+// change, so they may be cached for a day; today's brief is still being written.
+pub async fn get_shared_brief(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+    Query(query): Query<ShareQuery>,
+) -> Response {
+    let date = match state.db.get_share_link_date(&token) {
+        Ok(Some(date)) => date,
+        Ok(None) => return (StatusCode::NOT_FOUND, "Unknown share link").into_response(),
+        Err(e) => {
+            error!("Failed to resolve share link {}: {}", token, e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    };
+    if !matches!(state.db.get_collected_at(&date), Ok(Some(_))) {
+        return (StatusCode::NOT_FOUND, format!("The brief of {} is no longer available", date)).into_response();
+    }
+
+    let rendered = if query.format.as_deref() == Some("json") {
+        let languages = state.db.get_daily_language_trends(&date);
+        state.db.get_trending_repos(&date).and_then(|repos| {
+            let brief = SharedBrief {
+                repos: build_repo_responses(&state.db, &date, repos),
+                languages: languages?,
+                date: date.clone(),
+            };
+            Ok(Json(ApiResponse {
+                success: true,
+                data: Some(brief),
+                error: None,
+                meta: ResponseMeta {
+                    date: Some(date.clone()),
+                    ..ResponseMeta::now()
+                },
+            }).into_response())
+        })
+    } else {
+        NotificationTemplates::load(state.config.notification_templates_dir.as_deref())
+            .and_then(|templates| templates.render("digest.html", digest::digest_context(&state.db, &date, false, usize::MAX)?))
+            .map(|html| Html(html).into_response())
+    };
+
+    match rendered {
+        Ok(mut response) => {
+            let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+            let cache_control = if date < today { "public, max-age=86400" } else { "public, max-age=300" };
+            response.headers_mut().insert(header::CACHE_CONTROL, HeaderValue::from_static(cache_control));
+            response
+        }
+        Err(e) => {
+            error!("Failed to render the shared brief of {}: {}", date, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
+
+/// Longest accepted feedback comment, in characters
+const MAX_FEEDBACK_COMMENT_CHARS: usize = 1000;
+
+// POST /api/repos/:id/summary/feedback
+pub async fn submit_summary_feedback(
+    State(state): State<Arc<AppState>>,
+    Path(repo_id): Path<i64>,
+    peer: Option<ConnectInfo<std::net::SocketAddr>>,
+    headers: HeaderMap,
+    Json(req): Json<SummaryFeedbackRequest>,
+) -> impl IntoResponse {
+    let date = req.date.unwrap_or_else(|| {
+        chrono::Utc::now().format("%Y-%m-%d").to_string()
+    });
+    let comment = req.comment.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    let vote = match req.vote.as_str() {
+        "up" => Some(1),
+        "down" => Some(-1),
+        _ => None,
+    };
+
+    let invalid = if vote.is_none() {
+        Some(format!("Invalid vote '{}' (expected up or down)", req.vote))
+    } else if chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d").is_err() {
+        Some(format!("Invalid date '{}'", date))
+    } else if comment.is_some_and(|c| c.chars().count() > MAX_FEEDBACK_COMMENT_CHARS) {
+        Some(format!("Comment must be at most {} characters", MAX_FEEDBACK_COMMENT_CHARS))
+    } else {
+        None
+    };
+    if let Some(message) = invalid {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<SummaryFeedbackTally> {
+                success: false,
+                data: None,
+                error: Some(message),
+                meta: ResponseMeta::now(),
+            }),
+        );
+    }
+
+    // Each reader counts once, so a single client can't vote a summary into regeneration
+    let voter = client_ip(&state.config.trusted_proxies, &headers, peer.map(|ci| ci.0.ip())).map(|ip| ip.to_string());
+    let (upvotes, downvotes) = match state.db.save_summary_feedback(&date, repo_id, vote.unwrap_or_default(), comment, voter.as_deref()) {
+        Ok(Some(tally)) => tally,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(format!("No summary for repo {} on {}", repo_id, date)),
+                    meta: ResponseMeta::now(),
+                }),
+            );
+        }
+        Err(e) => {
+            error!("Failed to save summary feedback for repo {}: {}", repo_id, e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            );
+        }
+    };
+
+    let threshold = state.config.feedback_requeue_downvotes;
+    let requeued = threshold > 0 && downvotes - upvotes >= threshold && match requeue_summary(&state, &date, repo_id, None) {
+        Ok(job) => {
+            info!("Summary of repo {} on {} downvoted {} times, regenerating in job {}", repo_id, date, downvotes, job.id);
+            true
+        }
+        Err(e) => {
+            error!("Failed to requeue the summary of repo {} on {}: {}", repo_id, date, e);
+            false
+        }
+    };
+
+    (
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: Some(SummaryFeedbackTally {
+                date,
+                repo_id,
+                upvotes,
+                downvotes,
+                requeued,
+            }),
+            error: None,
+            meta: ResponseMeta::now(),
+        }),
+    )
+}
+
+/// Drop a repo's summary and queue a `summarize_missing` job for its date to write a new one
+/// in `style`, or the configured style when `None`
+fn requeue_summary(state: &AppState, date: &str, repo_id: i64, style: Option<&SummaryStyle>) -> anyhow::Result<Job> {
+    let style = style.map(serde_json::to_string).transpose()?;
+    state.db.update_summary_style(date, repo_id, style.as_deref())?;
+    state.db.update_repo_summary(date, repo_id, None, None, None, SUMMARY_PENDING)?;
+    state.cache.clear();
+    match state.jobs.enqueue(jobs::JOB_SUMMARIZE_MISSING, Some(date), None)? {
+        Enqueued::New(job) | Enqueued::Existing(job) => Ok(job),
+    }
+}
+
+// GET /api/trends/lifecycle
+pub async fn get_trends_lifecycle(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DateQuery>,
+) -> impl IntoResponse {
+    let date = query
+        .date
+        .and_then(|d| chrono::NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok())
+        .unwrap_or_else(|| chrono::Utc::now().date_naive());
+    let previous_date = (date - chrono::Duration::days(1)).format("%Y-%m-%d").to_string();
+    let date = date.format("%Y-%m-%d").to_string();
+
+    match (state.db.get_trending_repos(&date), state.db.get_trending_repos(&previous_date)) {
+        (Ok(today), Ok(previous)) => {
+            let mut counts: std::collections::BTreeMap<&'static str, usize> = [
+                scoring::LIFECYCLE_NEW,
+                scoring::LIFECYCLE_RECURRING,
+                scoring::LIFECYCLE_RESURFACED,
+            ]
+            .into_iter()
+            .map(|lifecycle| (lifecycle, today.iter().filter(|r| r.lifecycle.as_deref() == Some(lifecycle)).count()))
+            .collect();
+
+            let present: std::collections::HashSet<i64> = today.iter().map(|r| r.repo_id).collect();
+            let dropped: Vec<TrendingRepo> = previous.into_iter().filter(|r| !present.contains(&r.repo_id)).collect();
+            counts.insert(scoring::LIFECYCLE_DROPPED, dropped.len());
+
+            (
+                StatusCode::OK,
+                Json(ApiResponse {
+                    success: true,
+                    meta: dated_meta(&state, &date, Some(today.len())),
+                    data: Some(LifecycleResponse {
+                        dropped: build_repo_responses(&state.db, &previous_date, dropped),
+                        date,
+                        counts,
+                    }),
+                    error: None,
+                }),
+            )
+        }
+        (Err(e), _) | (_, Err(e)) => {
+            error!("Failed to get repo lifecycle: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            )
+        }
+    }
+}
+
+// GET /api/trends/lookback
+pub async fn get_trends_lookback(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<LookbackQuery>,
+) -> impl IntoResponse {
+    let date = query
+        .date
+        .and_then(|d| chrono::NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok())
+        .unwrap_or_else(|| chrono::Utc::now().date_naive());
+    let offset = query.offset.unwrap_or_else(|| "30d".to_string());
+
+    let Some(past) = subtract_offset(date, &offset) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<LookbackResponse> {
+                success: false,
+                data: None,
+                error: Some(format!("Invalid offset '{}', expected e.g. 30d, 2w, 6m or 1y", offset)),
+                meta: ResponseMeta::now(),
+            }),
+        );
+    };
+
+    let date = date.format("%Y-%m-%d").to_string();
+    let past_date = past.format("%Y-%m-%d").to_string();
+
+    match (state.db.get_trending_repos(&date), state.db.get_trending_repos(&past_date)) {
+        (Ok(today), Ok(then)) => (
+            StatusCode::OK,
+            Json(ApiResponse {
+                success: true,
+                meta: dated_meta(&state, &date, None),
+                data: Some(LookbackResponse {
+                    today: build_repo_responses(&state.db, &date, today),
+                    past: build_repo_responses(&state.db, &past_date, then),
+                    date,
+                    past_date,
+                    offset,
+                }),
+                error: None,
+            }),
+        ),
+        (Err(e), _) | (_, Err(e)) => {
+            error!("Failed to get lookback trends: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            )
+        }
+    }
+}
+
+/// Parse offsets like "30d", "2w", "6m", "1y" and step back from `date`
+fn subtract_offset(date: chrono::NaiveDate, offset: &str) -> Option<chrono::NaiveDate> {
+    let offset = offset.trim();
+    let unit = offset.chars().last()?;
+    let amount: u32 = offset[..offset.len() - unit.len_utf8()].parse().ok()?;
+
+    match unit {
+        'd' => date.checked_sub_days(chrono::Days::new(amount as u64)),
+        'w' => date.checked_sub_days(chrono::Days::new(amount as u64 * 7)),
+        'm' => date.checked_sub_months(chrono::Months::new(amount)),
+        'y' => date.checked_sub_months(chrono::Months::new(amount.checked_mul(12)?)),
+        _ => None,
+    }
+}
+
+// GET /api/developers
+pub async fn get_developers(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DateQuery>,
+) -> impl IntoResponse {
+    let date = query.date.unwrap_or_else(|| {
+        chrono::Utc::now().format("%Y-%m-%d").to_string()
+    });
+
+    match state.db.get_trending_developers(&date) {
+        Ok(devs) => {
+            let response: Vec<crate::models::TrendingDeveloperResponse> = devs
+                .into_iter()
+                .map(|d| crate::models::TrendingDeveloperResponse {
+                    rank: d.rank,
+                    github_url: format!("https://github.com/{}", d.login),
+                    avatar_url: format!("https://github.com/{}.png", d.login),
+                    login: d.login,
+                    followers: d.followers,
+                    notable_repos: d
+                        .notable_repos
+                        .map(|r| r.split(',').map(|s| s.to_string()).collect())
+                        .unwrap_or_default(),
+                })
+                .collect();
+
+            Json(ApiResponse {
+                success: true,
+                meta: dated_meta(&state, &date, Some(response.len())),
+                data: Some(response),
+                error: None,
+            })
+        }
+        Err(e) => {
+            error!("Failed to get trending developers: {}", e);
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+                meta: ResponseMeta::now(),
+            })
+        }
+    }
+}
+
+// GET /api/languages/daily
+pub async fn get_daily_languages(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<LanguagesQuery>,
+) -> impl IntoResponse {
+    let date = query.date.unwrap_or_else(|| {
+        chrono::Utc::now().format("%Y-%m-%d").to_string()
+    });
+
+    let weighting = query.weighting.as_deref().unwrap_or(state.config.language_trend_weighting.as_str());
+    let cache_key = format!("languages:daily:{}:{}:{}", date, query.exclude_other, weighting);
+    if let Some(cached) = state.cache.get(&cache_key) {
+        return Json(cached).into_response();
+    }
+
+    // Stored trends are unweighted; other modes are computed from the repo languages
+    let result = if weighting == "unweighted" {
+        state.db.get_daily_language_trends(&date)
+    } else {
+        trends::stored_language_trends(
+            &state.db,
+            &state.config.language_rules,
+            &date,
+            weighting,
+            state.config.language_threshold,
+            None,
+        )
+    }
+    .map(|trends| without_other(trends, query.exclude_other));
+    let meta = dated_meta(&state, &date, result.as_ref().ok().map(Vec::len));
+    cached_response(&state, cache_key, result, meta, "daily language trends")
+}
+
+// GET /api/languages/weekly
+pub async fn get_weekly_languages(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<LanguagesQuery>,
+) -> impl IntoResponse {
+    let date = query.date.unwrap_or_else(|| {
+        chrono::Utc::now().format("%Y-%m-%d").to_string()
+    });
+
+    let cache_key = format!("languages:weekly:{}:{}", date, query.exclude_other);
+    if let Some(cached) = state.cache.get(&cache_key) {
+        return Json(cached).into_response();
+    }
+
+    let result = state.db
+        .get_weekly_language_trends(&date)
+        .map(|trends| without_other(trends, query.exclude_other));
+    let meta = dated_meta(&state, &date, result.as_ref().ok().map(Vec::len));
+    cached_response(&state, cache_key, result, meta, "weekly language trends")
+}
+
+// GET /api/languages/changes
+pub async fn get_language_changes(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DateQuery>,
+) -> impl IntoResponse {
+    let end = query
+        .date
+        .and_then(|d| chrono::NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok())
+        .unwrap_or_else(|| chrono::Utc::now().date_naive());
+    let fmt = |d: chrono::NaiveDate| d.format("%Y-%m-%d").to_string();
+
+    let cache_key = format!("languages:changes:{}", fmt(end));
+    if let Some(cached) = state.cache.get(&cache_key) {
+        return Json(cached).into_response();
+    }
+
+    let this_week = state.db.get_language_trends_range(&fmt(end - chrono::Duration::days(6)), &fmt(end));
+    let last_week = state.db.get_language_trends_range(
+        &fmt(end - chrono::Duration::days(13)),
+        &fmt(end - chrono::Duration::days(7)),
+    );
+
+    let result = this_week.and_then(|current| {
+        last_week.map(|previous| trends::compare_periods(&current, &previous))
+    });
+    let meta = dated_meta(&state, &fmt(end), result.as_ref().ok().map(Vec::len));
+    cached_response(&state, cache_key, result, meta, "language changes")
+}
+
+// GET /api/languages/cooccurrence
+pub async fn get_language_cooccurrence(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<CooccurrenceQuery>,
+) -> impl IntoResponse {
+    let end = query
+        .date
+        .and_then(|d| chrono::NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok())
+        .unwrap_or_else(|| chrono::Utc::now().date_naive());
+    let days = query.days.unwrap_or(30).clamp(1, 365);
+    let limit = query.limit.unwrap_or(15).clamp(1, 50);
+    let fmt = |d: chrono::NaiveDate| d.format("%Y-%m-%d").to_string();
+
+    let cache_key = format!("languages:cooccurrence:{}:{}:{}", fmt(end), days, limit);
+    if let Some(cached) = state.cache.get(&cache_key) {
+        return Json(cached).into_response();
+    }
+
+    let result = state.db
+        .get_repo_languages_between(&fmt(end - chrono::Duration::days(days - 1)), &fmt(end))
+        .map(|rows| trends::language_cooccurrence(&rows, &state.config.language_rules, limit));
+    let meta = dated_meta(&state, &fmt(end), result.as_ref().ok().map(|c| c.languages.len()));
+    cached_response(&state, cache_key, result, meta, "language co-occurrence")
+}
+
+// GET /api/languages/:language/top
+pub async fn get_language_top_repos(
+    State(state): State<Arc<AppState>>,
+    Path(language): Path<String>,
+    Query(query): Query<TopReposQuery>,
+) -> impl IntoResponse {
+    let date = query.date.unwrap_or_else(|| {
+        chrono::Utc::now().format("%Y-%m-%d").to_string()
+    });
+    let days = match query.period.as_deref().unwrap_or("week") {
+        "day" => 1,
+        "month" => 30,
+        _ => 7,
+    };
+    let by_stars = query.sort.as_deref() == Some("stars");
+    let limit = query.limit.unwrap_or(10).clamp(1, 100);
+
+    match state.db.get_language_top_repos(&language, &date, days, by_stars, limit) {
+        Ok(repos) => Json(ApiResponse {
+            success: true,
+            meta: ResponseMeta {
+                date: Some(date.clone()),
+                total: Some(repos.len()),
+                ..ResponseMeta::now()
+            },
+            data: Some(repos),
+            error: None,
+        }),
+        Err(e) => {
+            error!("Failed to get top repos for {}: {}", language, e);
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+                meta: ResponseMeta::now(),
+            })
+        }
+    }
+}
+
+// GET /api/languages/:language/forecast
+pub async fn get_language_forecast(
+    State(state): State<Arc<AppState>>,
+    Path(language): Path<String>,
+    Query(query): Query<ForecastQuery>,
+) -> impl IntoResponse {
+    let date = query.date.unwrap_or_else(|| {
+        chrono::Utc::now().format("%Y-%m-%d").to_string()
+    });
+    let days = query.days.unwrap_or(7).clamp(1, 30);
+
+    let history = match state.db.get_language_history(&language, &date, forecast::HISTORY_DAYS) {
+        Ok(history) => history,
+        Err(e) => {
+            error!("Failed to get language history for {}: {}", language, e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<crate::models::LanguageForecast> {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            );
+        }
+    };
+
+    match forecast::forecast_language(&language, &history, days) {
+        Some(result) => (
+            StatusCode::OK,
+            Json(ApiResponse {
+                success: true,
+                data: Some(result),
+                error: None,
+                meta: ResponseMeta::now(),
+            }),
+        ),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(format!("Not enough history to forecast {}", language)),
+                meta: ResponseMeta::now(),
+            }),
+        ),
+    }
+}
+
+// GET /api/scoring
+pub async fn get_scoring_formula(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    Json(ApiResponse {
+        success: true,
+        data: Some(serde_json::json!({
+            "formula": "star_delta*ln(1+stars) + fork_delta*ln(1+forks) + pull_requests*ln(1+prs) \
+                        + pushes*ln(1+pushes) + recency/(1+days_since_first_seen) + streak*ln(1+streak_days)",
+            "weights": state.config.score_weights,
+        })),
+        error: None,
+        meta: ResponseMeta::now(),
+    })
+}
+
+// GET /api/alerts
+pub async fn get_alerts(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DateQuery>,
+) -> impl IntoResponse {
+    match state.db.get_language_alerts(query.date.as_deref()) {
+        Ok(alerts) => Json(ApiResponse {
+            success: true,
+            meta: ResponseMeta {
+                date: query.date.clone(),
+                total: Some(alerts.len()),
+                ..ResponseMeta::now()
+            },
+            data: Some(alerts),
+            error: None,
+        }),
+        Err(e) => {
+            error!("Failed to get language alerts: {}", e);
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+                meta: ResponseMeta::now(),
+            })
+        }
+    }
+}
+
+// GET /api/alerts/milestones
+pub async fn get_milestone_alerts(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DateQuery>,
+) -> impl IntoResponse {
+    match state.db.get_star_milestones(query.date.as_deref()) {
+        Ok(milestones) => Json(ApiResponse {
+            success: true,
+            meta: ResponseMeta {
+                date: query.date.clone(),
+                total: Some(milestones.len()),
+                ..ResponseMeta::now()
+            },
+            data: Some(milestones),
+            error: None,
+        }),
+        Err(e) => {
+            error!("Failed to get star milestones: {}", e);
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+                meta: ResponseMeta::now(),
+            })
+        }
+    }
+}
+
+// GET /api/admin/audit
+pub async fn get_audit_log(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AuditQuery>,
+) -> impl IntoResponse {
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+
+    match state.db.get_audit_log(query.actor.as_deref(), limit) {
+        Ok(entries) => Json(ApiResponse {
+            success: true,
+            meta: ResponseMeta {
+                total: Some(entries.len()),
+                ..ResponseMeta::now()
+            },
+            data: Some(entries),
+            error: None,
+        }),
+        Err(e) => {
+            error!("Failed to get audit log: {}", e);
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+                meta: ResponseMeta::now(),
+            })
+        }
+    }
+}
+
+// GET /api/admin/selfcheck
+pub async fn get_selfcheck(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let report = selfcheck::run(&state.config, &state.db, &state.http_client).await;
+    let status = if report.ok { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+
+    (
+        status,
+        Json(ApiResponse {
+            success: report.ok,
+            data: Some(report),
+            error: None,
+            meta: ResponseMeta::now(),
+        }),
+    )
+}
+
+// POST /api/jobs
+pub async fn create_job(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateJobRequest>,
+) -> impl IntoResponse {
+    let mut invalid = jobs::validate(&req.kind, req.date.as_deref()).err();
+    if req.kind == jobs::JOB_EXPORT && state.config.archive.is_none() {
+        invalid = Some("export jobs need an archive (ARCHIVE_BUCKET)".to_string());
+    }
+    if let Some(message) = invalid {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<Job> {
+                success: false,
+                data: None,
+                error: Some(message),
+                meta: ResponseMeta::now(),
+            }),
+        );
+    }
+
+    match state.jobs.enqueue(&req.kind, req.date.as_deref(), req.priority) {
+        Ok(Enqueued::New(job)) => (
+            StatusCode::ACCEPTED,
+            Json(ApiResponse {
+                success: true,
+                data: Some(job),
+                error: None,
+                meta: ResponseMeta::now(),
+            }),
+        ),
+        Ok(Enqueued::Existing(job)) => (
+            StatusCode::CONFLICT,
+            Json(ApiResponse {
+                success: false,
+                error: Some(format!("Job {} already {}", job.id, job.status)),
+                data: Some(job),
+                meta: ResponseMeta::now(),
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to queue {} job: {}", req.kind, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            )
+        }
+    }
+}
+
+// GET /api/jobs
+pub async fn list_jobs(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<JobsQuery>,
+) -> impl IntoResponse {
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+
+    match state.db.get_jobs(query.status.as_deref(), limit) {
+        Ok(jobs) => Json(ApiResponse {
+            success: true,
+            meta: ResponseMeta {
+                total: Some(jobs.len()),
+                ..ResponseMeta::now()
+            },
+            data: Some(jobs),
+            error: None,
+        }),
+        Err(e) => {
+            error!("Failed to list jobs: {}", e);
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+                meta: ResponseMeta::now(),
+            })
+        }
+    }
+}
+
+// GET /api/jobs/:id
+pub async fn get_job(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    match state.db.get_job(id) {
+        Ok(Some(job)) => (
+            StatusCode::OK,
+            Json(ApiResponse {
+                success: true,
+                data: Some(job),
+                error: None,
+                meta: ResponseMeta::now(),
+            }),
+        ),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(format!("Job {} not found", id)),
+                meta: ResponseMeta::now(),
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to get job {}: {}", id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            )
+        }
+    }
+}
+
+// DELETE /api/jobs/:id
+// Only queued jobs can be cancelled; a running job finishes
+pub async fn cancel_job(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let result = state.db.cancel_job(id).and_then(|cancelled| {
+        if cancelled {
+            Ok(None)
+        } else {
+            state.db.get_job(id).map(|job| Some(job.map(|j| j.status)))
+        }
+    });
+
+    let (status, error) = match result {
+        Ok(None) => (StatusCode::OK, None),
+        Ok(Some(None)) => (StatusCode::NOT_FOUND, Some(format!("Job {} not found", id))),
+        Ok(Some(Some(job_status))) => (StatusCode::CONFLICT, Some(format!("Job {} is {}", id, job_status))),
+        Err(e) => {
+            error!("Failed to cancel job {}: {}", id, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Some(e.to_string()))
+        }
+    };
+
+    (
+        status,
+        Json(ApiResponse::<()> {
+            success: error.is_none(),
+            data: None,
+            error,
+            meta: ResponseMeta::now(),
+        }),
+    )
+}
+
+// GET /api/admin/quality-decisions
+pub async fn get_quality_decisions(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DateQuery>,
+) -> impl IntoResponse {
+    let date = query.date.unwrap_or_else(|| {
+        chrono::Utc::now().format("%Y-%m-%d").to_string()
+    });
+
+    match state.db.get_quality_decisions(&date) {
+        Ok(decisions) => Json(ApiResponse {
+            success: true,
+            meta: dated_meta(&state, &date, Some(decisions.len())),
+            data: Some(decisions),
+            error: None,
+        }),
+        Err(e) => {
+            error!("Failed to get quality decisions: {}", e);
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+                meta: ResponseMeta::now(),
+            })
+        }
+    }
+}
+
+// GET /api/admin/sources/compare
+pub async fn compare_sources(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DateQuery>,
+) -> impl IntoResponse {
+    let date = query.date.unwrap_or_else(|| {
+        chrono::Utc::now().format("%Y-%m-%d").to_string()
+    });
+
+    match state.db.get_source_repos(&date) {
+        Ok(rows) => {
+            let comparison = sources::compare_sources(&date, rows);
+            Json(ApiResponse {
+                success: true,
+                meta: dated_meta(&state, &date, Some(comparison.sources.len())),
+                data: Some(comparison),
+                error: None,
+            })
+        }
+        Err(e) => {
+            error!("Failed to compare sources: {}", e);
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+                meta: ResponseMeta::now(),
+            })
+        }
+    }
+}
+
+// GET /api/admin/evaluations
+pub async fn get_summary_evaluations(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DateQuery>,
+) -> impl IntoResponse {
+    let date = query.date.unwrap_or_else(|| {
+        chrono::Utc::now().format("%Y-%m-%d").to_string()
+    });
+
+    match state.db.get_summary_evaluations(&date) {
+        Ok(rows) => {
+            let pairs = rows
+                .chunk_by(|a, b| a.repo_id == b.repo_id)
+                .map(|group| EvaluationPair {
+                    repo_id: group[0].repo_id,
+                    repo_name: group[0].repo_name.clone(),
+                    summaries: group
+                        .iter()
+                        .map(|row| ModelSummary {
+                            model: row.model.clone(),
+                            one_liner: row.one_liner.clone(),
+                            features: row.features.clone(),
+                            tech_stack: row.tech_stack.clone(),
+                        })
+                        .collect(),
+                })
+                .collect::<Vec<_>>();
+
+            Json(ApiResponse {
+                success: true,
+                meta: ResponseMeta {
+                    date: Some(date.clone()),
+                    total: Some(pairs.len()),
+                    ..ResponseMeta::now()
+                },
+                data: Some(pairs),
+                error: None,
+            })
+        }
+        Err(e) => {
+            error!("Failed to get summary evaluations: {}", e);
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+                meta: ResponseMeta::now(),
+            })
+        }
+    }
+}
+
+// GET /api/admin/summary-feedback
+pub async fn get_summary_feedback(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<FeedbackReportQuery>,
+) -> impl IntoResponse {
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+
+    match state.db.get_summary_feedback_report(query.date.as_deref(), limit) {
+        Ok(entries) => Json(ApiResponse {
+            success: true,
+            meta: ResponseMeta {
+                date: query.date,
+                total: Some(entries.len()),
+                ..ResponseMeta::now()
+            },
+            data: Some(entries),
+            error: None,
+        }),
+        Err(e) => {
+            error!("Failed to get summary feedback: {}", e);
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+                meta: ResponseMeta::now(),
+            })
+        }
+    }
+}
+
+// POST /api/admin/summary-feedback/requeue
+pub async fn requeue_summary_feedback(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<RequeueSummaryRequest>,
+) -> impl IntoResponse {
+    if let Err(message) = jobs::validate(jobs::JOB_SUMMARIZE_MISSING, Some(&req.date)) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<Job> {
+                success: false,
+                data: None,
+                error: Some(message),
+                meta: ResponseMeta::now(),
+            }),
+        );
+    }
+    let style = match req.style.as_ref().map(|changes| state.config.summary_style.with_override(changes)).transpose() {
+        Ok(style) => style,
+        Err(message) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<Job> {
+                    success: false,
+                    data: None,
+                    error: Some(message),
+                    meta: ResponseMeta::now(),
+                }),
+            );
+        }
+    };
+
+    match requeue_summary(&state, &req.date, req.repo_id, style.as_ref()) {
+        Ok(job) => (
+            StatusCode::ACCEPTED,
+            Json(ApiResponse {
+                success: true,
+                data: Some(job),
+                error: None,
+                meta: ResponseMeta::now(),
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to requeue the summary of repo {} on {}: {}", req.repo_id, req.date, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            )
+        }
+    }
+}
+
+// DELETE /api/admin/trends
+// Without `confirm`, nothing is deleted: the response lists the rows at stake and the token to
+// confirm with. The token changes whenever that day's data does, so it can't be reused blindly.
+pub async fn delete_day(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DeleteDayQuery>,
+) -> Response {
+    if chrono::NaiveDate::parse_from_str(&query.date, "%Y-%m-%d").is_err() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()> {
+                success: false,
+                data: None,
+                error: Some(format!("Invalid date '{}'", query.date)),
+                meta: ResponseMeta::now(),
+            }),
+        ).into_response();
+    }
+
+    let rows = match state.db.count_day_rows(&query.date) {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!("Failed to count rows of {}: {}", query.date, e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()> {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            ).into_response();
+        }
+    };
+    let token = delete_confirm_token(&query.date, &rows, state.db.get_collected_at(&query.date).ok().flatten());
+
+    if query.confirm.as_deref() != Some(token.as_str()) {
+        return (
+            StatusCode::CONFLICT,
+            Json(ApiResponse {
+                success: false,
+                data: Some(DeleteDayPreview {
+                    date: query.date.clone(),
+                    rows: rows.into_iter().collect(),
+                    confirm_token: token,
+                }),
+                error: Some("Repeat the request with confirm=<confirm_token> to delete".to_string()),
+                meta: ResponseMeta::now(),
+            }),
+        ).into_response();
+    }
+
+    match state.db.delete_day(&query.date) {
+        Ok(deleted_rows) => {
+            info!("Deleted {} rows collected on {}", deleted_rows, query.date);
+            state.cache.clear();
+            Json(ApiResponse {
+                success: true,
+                data: Some(DeleteDayResult {
+                    date: query.date.clone(),
+                    deleted_rows,
+                }),
+                error: None,
+                meta: ResponseMeta {
+                    date: Some(query.date),
+                    ..ResponseMeta::now()
+                },
+            }).into_response()
+        }
+        Err(e) => {
+            error!("Failed to delete {}: {}", query.date, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()> {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            ).into_response()
+        }
+    }
+}
+
+fn delete_confirm_token(date: &str, rows: &[(String, i64)], collected_at: Option<String>) -> String {
+    use sha2::{Digest, Sha256};
+
+    let fingerprint = format!("{}:{:?}:{}", date, rows, collected_at.unwrap_or_default());
+    format!("{:x}", Sha256::digest(fingerprint.as_bytes()))[..16].to_string()
+}
+
+// POST /api/admin/query
+// Ad-hoc read-only SQL against the database, as JSON or CSV. Rows past the limit are dropped
+// (`truncated`) and the query is cancelled after ADMIN_QUERY_TIMEOUT_SECS.
+pub async fn run_admin_query(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<AdhocQueryRequest>,
+) -> Response {
+    let query_error = |status: StatusCode, message: String| {
+        (
+            status,
+            Json(ApiResponse::<()> {
                 success: false,
                 data: None,
-                error: Some(e.to_string()),
-            })
+                error: Some(message),
+                meta: ResponseMeta::now(),
+            }),
+        ).into_response()
+    };
+
+    let csv = match request.format.as_deref().unwrap_or("json") {
+        "json" => false,
+        "csv" => true,
+        other => return query_error(StatusCode::BAD_REQUEST, format!("Unknown format '{}'", other)),
+    };
+    let tables = match state.db.table_names() {
+        Ok(tables) => tables,
+        Err(e) => return query_error(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to list tables: {}", e)),
+    };
+    let sql = match adhoc::check_read_only(&request.sql, &tables) {
+        Ok(sql) => sql.to_string(),
+        Err(message) => return query_error(StatusCode::BAD_REQUEST, message),
+    };
+    let max_rows = request.max_rows.unwrap_or(usize::MAX).clamp(1, state.config.admin_query_max_rows);
+    let timeout = std::time::Duration::from_secs(state.config.admin_query_timeout_secs);
+
+    // DuckDB blocks until the query finishes or is interrupted, so keep it off the async workers
+    let db = state.db.clone();
+    let result = match tokio::task::spawn_blocking(move || db.run_adhoc_query(&sql, max_rows, timeout)).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => {
+            info!("Ad-hoc query failed: {}", e);
+            // The analyst's SQL is at fault, not the server
+            return query_error(StatusCode::BAD_REQUEST, e.to_string());
         }
+        Err(e) => return query_error(StatusCode::INTERNAL_SERVER_ERROR, format!("Ad-hoc query panicked: {}", e)),
+    };
+    info!("Ad-hoc query returned {} rows in {} ms", result.rows.len(), result.elapsed_ms);
+
+    if csv {
+        (
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (header::HeaderName::from_static("x-truncated"), result.truncated.to_string()),
+            ],
+            adhoc_csv(&result),
+        ).into_response()
+    } else {
+        Json(ApiResponse {
+            success: true,
+            data: Some(result),
+            error: None,
+            meta: ResponseMeta::now(),
+        }).into_response()
     }
 }
 
-// GET /api/languages/daily
-pub async fn get_daily_languages(
+fn adhoc_csv(result: &AdhocQueryResult) -> String {
+    let mut csv = result.columns.iter().map(|c| csv_field(c)).collect::<Vec<_>>().join(",");
+    csv.push('\n');
+    for row in &result.rows {
+        let fields = row.iter().map(|value| match value {
+            serde_json::Value::Null => String::new(),
+            serde_json::Value::String(text) => csv_field(text),
+            other => csv_field(&other.to_string()),
+        });
+        csv.push_str(&fields.collect::<Vec<_>>().join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+// POST /api/admin/recompute-trends
+// Queue a `recompute_trends` job rebuilding stored language trends from `from` on, e.g. after
+// LANGUAGE_THRESHOLD or LANGUAGE_TREND_EXCLUDE/REMAP changed
+pub async fn recompute_trends(
     State(state): State<Arc<AppState>>,
-    Query(query): Query<DateQuery>,
+    Query(query): Query<RecomputeTrendsQuery>,
 ) -> impl IntoResponse {
-    let date = query.date.unwrap_or_else(|| {
-        chrono::Utc::now().format("%Y-%m-%d").to_string()
-    });
+    if let Err(message) = jobs::validate(jobs::JOB_RECOMPUTE_TRENDS, Some(&query.from)) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<Job> {
+                success: false,
+                data: None,
+                error: Some(message),
+                meta: ResponseMeta::now(),
+            }),
+        );
+    }
 
-    match state.db.get_daily_language_trends(&date) {
-        Ok(trends) => Json(ApiResponse {
-            success: true,
-            data: Some(trends),
-            error: None,
-        }),
-        Err(e) => {
-            error!("Failed to get daily language trends: {}", e);
+    match state.jobs.enqueue(jobs::JOB_RECOMPUTE_TRENDS, Some(&query.from), None) {
+        Ok(Enqueued::New(job)) => (
+            StatusCode::ACCEPTED,
+            Json(ApiResponse {
+                success: true,
+                data: Some(job),
+                error: None,
+                meta: ResponseMeta::now(),
+            }),
+        ),
+        Ok(Enqueued::Existing(job)) => (
+            StatusCode::CONFLICT,
             Json(ApiResponse {
                 success: false,
-                data: None,
-                error: Some(e.to_string()),
-            })
+                error: Some(format!("Job {} already {}", job.id, job.status)),
+                data: Some(job),
+                meta: ResponseMeta::now(),
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to queue trend recomputation: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            )
         }
     }
 }
 
-// GET /api/languages/weekly
-pub async fn get_weekly_languages(
+// GET /api/admin/usage
+pub async fn get_usage(
     State(state): State<Arc<AppState>>,
-    Query(query): Query<DateQuery>,
+    Query(query): Query<UsageQuery>,
 ) -> impl IntoResponse {
-    let date = query.date.unwrap_or_else(|| {
-        chrono::Utc::now().format("%Y-%m-%d").to_string()
-    });
+    let days = query.days.unwrap_or(30).clamp(1, 365);
+    let pricing = &state.config.usage_pricing;
 
-    match state.db.get_weekly_language_trends(&date) {
-        Ok(trends) => Json(ApiResponse {
-            success: true,
-            data: Some(trends),
-            error: None,
-        }),
+    match state.db.get_daily_usage(days) {
+        Ok(mut usage) => {
+            for day in &mut usage {
+                day.estimated_cost_usd = pricing.estimate(day.prompt_tokens, day.completion_tokens, day.github_requests);
+            }
+
+            Json(ApiResponse {
+                success: true,
+                data: Some(UsageResponse {
+                    pricing: pricing.clone(),
+                    days: usage,
+                }),
+                error: None,
+                meta: ResponseMeta::now(),
+            })
+        }
         Err(e) => {
-            error!("Failed to get weekly language trends: {}", e);
+            error!("Failed to get API usage: {}", e);
             Json(ApiResponse {
                 success: false,
                 data: None,
                 error: Some(e.to_string()),
+                meta: ResponseMeta::now(),
             })
         }
     }
@@ -153,35 +2885,73 @@ pub async fn get_weekly_languages(
 // POST /api/collect
 pub async fn trigger_collect(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<CollectQuery>,
 ) -> impl IntoResponse {
-    // Check if already running
-    if state.is_collecting.load(Ordering::SeqCst) {
+    // Backfills and language-only runs are admin operations; anyone may only collect today
+    if (query.date.is_some() || query.mode.is_some()) && !has_admin_token(&state, &headers) {
         return (
-            StatusCode::CONFLICT,
+            StatusCode::UNAUTHORIZED,
             Json(ApiResponse::<CollectResponse> {
                 success: false,
                 data: None,
-                error: Some("Collection already in progress".to_string()),
+                error: Some("`date` and `mode` require the admin token".to_string()),
+                meta: ResponseMeta::now(),
             }),
         ).into_response();
     }
 
-    info!("Manual data collection triggered (async)");
-    state.is_collecting.store(true, Ordering::SeqCst);
-    
-    let is_collecting = state.is_collecting.clone();
-    let tx = state.progress_tx.clone();
+    let today = chrono::Utc::now().date_naive();
+    let date = match query.date.as_deref().map(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d")) {
+        None => today,
+        Some(Ok(date)) if date <= today => date,
+        Some(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<CollectResponse> {
+                    success: false,
+                    data: None,
+                    error: Some("`date` must be a YYYY-MM-DD date no later than today".to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            ).into_response();
+        }
+    };
+    let date = date.format("%Y-%m-%d").to_string();
+
+    if query.mode.as_deref() == Some("languages") {
+        return backfill_language_trends(&state, date);
+    }
 
-    // Spawn background task
-    tokio::spawn(async move {
-        let collector = DataCollector::new(&state.config, state.db.clone());
-        match collector.collect(Some(tx)).await {
-            Ok(count) => info!("Background collection complete: {} repos", count),
-            Err(e) => error!("Background collection failed: {}", e),
+    info!("Manual data collection triggered for {} (async)", date);
+    let past = date != today.format("%Y-%m-%d").to_string();
+    let run_id = match start_collection(&state, past.then(|| date.clone())) {
+        Ok(Some(run_id)) => run_id,
+        // Already queued or running: another trigger or the daily job got there first
+        Ok(None) => {
+            return (
+                StatusCode::CONFLICT,
+                Json(ApiResponse::<CollectResponse> {
+                    success: false,
+                    data: None,
+                    error: Some("Collection already in progress".to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            ).into_response();
         }
-        // Reset flag
-        is_collecting.store(false, Ordering::SeqCst);
-    });
+        Err(e) => {
+            error!("Failed to queue collection: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<CollectResponse> {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            ).into_response();
+        }
+    };
 
     // Return immediate response with 202 Accepted
     (
@@ -189,34 +2959,620 @@ pub async fn trigger_collect(
         Json(ApiResponse {
             success: true,
             data: Some(CollectResponse {
-                message: "Data collection started in background. Connect to /api/collect/progress for updates.".to_string(),
+                message: if past {
+                    format!(
+                        "Collection for {} queued. Upstream sources only expose current data, so repos \
+                         stored for that day keep their metrics and rank, and only missing repos are \
+                         filled in from today's snapshot; developers and collections are skipped.",
+                        date
+                    )
+                } else {
+                    "Data collection queued. Connect to /api/collect/progress for updates.".to_string()
+                },
                 collected_count: 0,
+                run_id: Some(run_id),
+                date,
             }),
             error: None,
+            meta: ResponseMeta::now(),
         }),
     ).into_response()
 }
 
+/// Recompute a date's language trends from its stored repo languages, e.g. after changing
+/// the trend rules or filling in a missed day
+fn backfill_language_trends(state: &Arc<AppState>, date: String) -> Response {
+    let collector = DataCollector::new(&state.config, state.db.clone(), &state.providers, state.changes.clone());
+    match collector.backfill_language_trends(&date) {
+        Ok(trends) => {
+            info!("Recomputed {} language trends for {}", trends.len(), date);
+            Json(ApiResponse {
+                success: true,
+                meta: ResponseMeta {
+                    date: Some(date),
+                    total: Some(trends.len()),
+                    ..ResponseMeta::now()
+                },
+                data: Some(trends),
+                error: None,
+            }).into_response()
+        }
+        Err(e) => {
+            error!("Failed to recompute language trends for {}: {}", date, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()> {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            ).into_response()
+        }
+    }
+}
+
+/// Queue a collection unless the same one is already queued or running; returns the run id
+/// its progress events will carry. `date` collects into a past date instead of today.
+/// Progress is broadcast to SSE subscribers; stored data goes out on the change feed.
+pub fn start_collection(state: &Arc<AppState>, date: Option<String>) -> anyhow::Result<Option<String>> {
+    let kind = if date.is_some() { jobs::JOB_BACKFILL } else { jobs::JOB_COLLECT };
+    match state.jobs.enqueue(kind, date.as_deref(), None)? {
+        Enqueued::New(job) => Ok(job.run_id),
+        Enqueued::Existing(_) => Ok(None),
+    }
+}
+
+// GET /api/collect/runs/:run_id
+pub async fn get_run_report(
+    State(state): State<Arc<AppState>>,
+    Path(run_id): Path<String>,
+) -> impl IntoResponse {
+    match state.db.get_run_report(&run_id) {
+        Ok(Some(report)) => (
+            StatusCode::OK,
+            Json(ApiResponse {
+                success: true,
+                meta: ResponseMeta {
+                    date: Some(report.date.clone()),
+                    ..ResponseMeta::now()
+                },
+                data: Some(report),
+                error: None,
+            }),
+        ),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(format!("No report for run {} (unknown or still running)", run_id)),
+                meta: ResponseMeta::now(),
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to get the report of run {}: {}", run_id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            )
+        }
+    }
+}
+
+// GET /api/collect/runs/:run_id/events
+pub async fn get_run_events(
+    State(state): State<Arc<AppState>>,
+    Path(run_id): Path<String>,
+    Query(query): Query<RunEventsQuery>,
+) -> impl IntoResponse {
+    let limit = query.limit.unwrap_or(1000).clamp(1, 10000);
+
+    match state.db.get_collection_events(&run_id, query.after, limit) {
+        Ok(events) if events.is_empty() && query.after.is_none() => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(format!("No events recorded for run {}", run_id)),
+                meta: ResponseMeta::now(),
+            }),
+        ),
+        Ok(events) => (
+            StatusCode::OK,
+            Json(ApiResponse {
+                success: true,
+                meta: ResponseMeta {
+                    total: Some(events.len()),
+                    ..ResponseMeta::now()
+                },
+                data: Some(events),
+                error: None,
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to get events of run {}: {}", run_id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            )
+        }
+    }
+}
+
+/// SSE event type of the notice sent when a subscriber lagged behind the progress channel
+pub const PROGRESS_EVENTS_MISSED: &str = "events_missed";
+
 // GET /api/collect/progress
 pub async fn sse_progress(
     State(state): State<Arc<AppState>>,
 ) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
     let rx = state.progress_tx.subscribe();
-    
-    let stream = BroadcastStream::new(rx).map(|msg| {
+    let subscriber = SseSubscriber::new(&state.config.workspace);
+
+    let stream = BroadcastStream::new(rx).map(move |msg| {
+        // Lives as long as the stream, so the gauge drops when the client disconnects
+        let _subscriber = &subscriber;
         match msg {
             Ok(status) => {
                 let json = serde_json::to_string(&status).unwrap_or_default();
                 Ok(Event::default().data(json))
             }
-            // If channel lag or other error, we can send a comment or ignore
-            Err(_) => Ok(Event::default().comment("keep-alive")),
+            // A subscriber too slow for the channel skips events; tell it how many, so it can
+            // resync from the API instead of silently showing stale progress
+            Err(BroadcastStreamRecvError::Lagged(missed)) => Ok(Event::default()
+                .event(PROGRESS_EVENTS_MISSED)
+                .data(serde_json::json!({ "missed": missed }).to_string())),
         }
     });
 
     Sse::new(stream).keep_alive(KeepAlive::default())
 }
 
+#[derive(Deserialize)]
+pub struct LogStreamQuery {
+    /// Least severe level sent: error, warn, info (default), debug or trace
+    pub level: Option<String>,
+}
+
+// GET /api/admin/logs/stream
+// The buffered tail first, then live lines; only what passes RUST_LOG is ever captured
+pub async fn stream_logs(Query(query): Query<LogStreamQuery>) -> Response {
+    let min_level = match query.level.as_deref().unwrap_or("info").parse::<tracing::Level>() {
+        Ok(level) => level,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()> {
+                    success: false,
+                    data: None,
+                    error: Some(format!(
+                        "Unknown level '{}' (expected error, warn, info, debug or trace)",
+                        query.level.unwrap_or_default()
+                    )),
+                    meta: ResponseMeta::now(),
+                }),
+            ).into_response();
+        }
+    };
+
+    let (backlog, rx) = telemetry::log_tail().subscribe();
+    let stream = tokio_stream::iter(backlog.into_iter().map(Ok))
+        .chain(BroadcastStream::new(rx))
+        .filter_map(move |line| match line {
+            Ok(line) if line.at_least(min_level) => {
+                Some(Ok::<_, axum::Error>(Event::default().data(serde_json::to_string(&line).unwrap_or_default())))
+            }
+            Ok(_) => None,
+            Err(BroadcastStreamRecvError::Lagged(missed)) => Some(Ok(Event::default()
+                .event(PROGRESS_EVENTS_MISSED)
+                .data(serde_json::json!({ "missed": missed }).to_string()))),
+        });
+
+    Sse::new(stream).keep_alive(KeepAlive::default()).into_response()
+}
+
+// POST /api/subscriptions
+/// Store the subscription unconfirmed and email its address a confirmation link; digests only go
+/// out once that link is opened.
+pub async fn create_subscription(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateSubscriptionRequest>,
+) -> Response {
+    let sub = DigestSubscription {
+        id: 0,
+        email: req.email.trim().to_string(),
+        frequency: req.frequency.unwrap_or_else(|| "daily".to_string()),
+        delivery_time: req.delivery_time.unwrap_or_else(|| "09:00".to_string()),
+        timezone: req.timezone.unwrap_or_else(|| "UTC".to_string()),
+        weekday: req.weekday,
+        last_sent_date: None,
+        confirmed: false,
+        unsubscribe_token: webhooks::generate_secret(),
+    };
+
+    if let Err(message) = validate_subscription(&sub) {
+        return subscription_error(StatusCode::BAD_REQUEST, message);
+    }
+
+    let sender = match DigestSender::new(&state.config, state.db.clone()) {
+        Ok(Some(sender)) if sender.can_confirm() => sender,
+        Ok(_) => {
+            return subscription_error(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Email digests need SMTP_HOST, SMTP_FROM and SITE_URL".to_string(),
+            );
+        }
+        Err(e) => {
+            error!("Failed to set up email digests: {}", e);
+            return subscription_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+        }
+    };
+
+    let confirm_token = webhooks::generate_secret();
+    let id = match state.db.create_subscription(&sub, &confirm_token) {
+        Ok(id) => id,
+        Err(e) => {
+            error!("Failed to create subscription: {}", e);
+            return subscription_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+        }
+    };
+
+    if let Err(e) = sender.send_confirmation(&sub, &confirm_token).await {
+        warn!("Failed to send confirmation for subscription {}: {}", id, e);
+        // Without the email the subscription could never be confirmed
+        if let Err(e) = state.db.unsubscribe(&sub.unsubscribe_token) {
+            error!("Failed to drop unconfirmable subscription {}: {}", id, e);
+        }
+        return subscription_error(StatusCode::BAD_GATEWAY, "Failed to send the confirmation email".to_string());
+    }
+
+    info!("Created {} digest subscription {}, awaiting confirmation", sub.frequency, id);
+    (
+        StatusCode::ACCEPTED,
+        Json(ApiResponse {
+            success: true,
+            data: Some(DigestSubscription { id, ..sub }),
+            error: None,
+            meta: ResponseMeta::now(),
+        }),
+    ).into_response()
+}
+
+fn subscription_error(status: StatusCode, message: String) -> Response {
+    (
+        status,
+        Json(ApiResponse::<()> {
+            success: false,
+            data: None,
+            error: Some(message),
+            meta: ResponseMeta::now(),
+        }),
+    ).into_response()
+}
+
+fn validate_subscription(sub: &DigestSubscription) -> Result<(), String> {
+    if sub.email.parse::<lettre::Address>().is_err() {
+        return Err(format!("Invalid email address: {}", sub.email));
+    }
+    digest::validate_schedule(&sub.frequency, &sub.delivery_time, &sub.timezone, sub.weekday)
+}
+
+/// Outcome of a subscription change made through one of its tokens
+fn subscription_change(result: anyhow::Result<bool>, action: &str) -> Response {
+    match result {
+        Ok(true) => (
+            StatusCode::OK,
+            Json(ApiResponse::<()> {
+                success: true,
+                data: None,
+                error: None,
+                meta: ResponseMeta::now(),
+            }),
+        ).into_response(),
+        Ok(false) => subscription_error(StatusCode::NOT_FOUND, "Unknown or expired subscription link".to_string()),
+        Err(e) => {
+            error!("Failed to {} subscription: {}", action, e);
+            subscription_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        }
+    }
+}
+
+// GET /api/subscriptions/confirm/:token
+pub async fn confirm_subscription(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> Response {
+    subscription_change(state.db.confirm_subscription(&token), "confirm")
+}
+
+// GET /api/subscriptions/unsubscribe/:token
+/// The unsubscribe link of every digest email
+pub async fn unsubscribe(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> Response {
+    subscription_change(state.db.unsubscribe(&token), "remove")
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UnsubscribeQuery {
+    /// Token of the subscription's unsubscribe link
+    pub token: String,
+}
+
+// DELETE /api/subscriptions/:id?token=...
+pub async fn delete_subscription(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Query(query): Query<UnsubscribeQuery>,
+) -> Response {
+    subscription_change(state.db.delete_subscription(id, &query.token), "remove")
+}
+
+// POST /api/webhooks
+pub async fn create_webhook(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateWebhookRequest>,
+) -> impl IntoResponse {
+    let url = req.url.trim().to_string();
+    let format = req.format.unwrap_or_else(|| webhooks::FORMAT_JSON.to_string());
+    if let Err(message) = webhooks::validate(&url, &req.events, &format, req.schedule.as_ref()) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<Webhook> {
+                success: false,
+                data: None,
+                error: Some(message),
+                meta: ResponseMeta::now(),
+            }),
+        );
+    }
+
+    let secret = req.secret.filter(|s| !s.is_empty()).unwrap_or_else(webhooks::generate_secret);
+    match state.db.create_webhook(&url, &secret, &req.events, &format, req.schedule.as_ref()) {
+        Ok(id) => {
+            info!("Created webhook {} for {}", id, url);
+            (
+                StatusCode::CREATED,
+                Json(ApiResponse {
+                    success: true,
+                    data: Some(Webhook {
+                        id,
+                        url,
+                        events: req.events,
+                        format,
+                        secret: Some(secret),
+                        schedule: req.schedule,
+                        created_at: None,
+                    }),
+                    error: None,
+                    meta: ResponseMeta::now(),
+                }),
+            )
+        }
+        Err(e) => {
+            error!("Failed to create webhook: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            )
+        }
+    }
+}
+
+// GET /api/webhooks
+pub async fn list_webhooks(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match state.db.get_webhooks() {
+        Ok(hooks) => {
+            // Secrets are only shown once, at creation
+            let hooks: Vec<Webhook> = hooks.into_iter().map(|h| Webhook { secret: None, ..h }).collect();
+            Json(ApiResponse {
+                success: true,
+                meta: ResponseMeta {
+                    total: Some(hooks.len()),
+                    ..ResponseMeta::now()
+                },
+                data: Some(hooks),
+                error: None,
+            })
+        }
+        Err(e) => {
+            error!("Failed to list webhooks: {}", e);
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+                meta: ResponseMeta::now(),
+            })
+        }
+    }
+}
+
+// DELETE /api/webhooks/:id
+pub async fn delete_webhook(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    match state.db.delete_webhook(id) {
+        Ok(true) => (
+            StatusCode::OK,
+            Json(ApiResponse::<()> {
+                success: true,
+                data: None,
+                error: None,
+                meta: ResponseMeta::now(),
+            }),
+        ),
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()> {
+                success: false,
+                data: None,
+                error: Some(format!("Webhook {} not found", id)),
+                meta: ResponseMeta::now(),
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to delete webhook {}: {}", id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()> {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            )
+        }
+    }
+}
+
+// PUT /api/webhooks/:id/schedule
+pub async fn set_webhook_schedule(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(schedule): Json<DeliverySchedule>,
+) -> impl IntoResponse {
+    if let Err(message) = webhooks::validate_schedule(&schedule) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<DeliverySchedule> {
+                success: false,
+                data: None,
+                error: Some(message),
+                meta: ResponseMeta::now(),
+            }),
+        );
+    }
+    update_webhook_schedule(&state, id, Some(schedule))
+}
+
+// DELETE /api/webhooks/:id/schedule
+pub async fn clear_webhook_schedule(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    update_webhook_schedule(&state, id, None)
+}
+
+fn update_webhook_schedule(
+    state: &AppState,
+    id: i64,
+    schedule: Option<DeliverySchedule>,
+) -> (StatusCode, Json<ApiResponse<DeliverySchedule>>) {
+    match state.db.set_webhook_schedule(id, schedule.as_ref()) {
+        Ok(true) => {
+            info!("Webhook {} schedule {}", id, if schedule.is_some() { "set" } else { "cleared" });
+            (
+                StatusCode::OK,
+                Json(ApiResponse {
+                    success: true,
+                    data: schedule,
+                    error: None,
+                    meta: ResponseMeta::now(),
+                }),
+            )
+        }
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(format!("Webhook {} not found", id)),
+                meta: ResponseMeta::now(),
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to update schedule of webhook {}: {}", id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            )
+        }
+    }
+}
+
+// GET /api/webhooks/:id/deliveries
+pub async fn get_webhook_deliveries(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Query(query): Query<LimitQuery>,
+) -> impl IntoResponse {
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+
+    let result = state.db.webhook_exists(id).and_then(|exists| {
+        exists.then(|| state.db.get_webhook_deliveries(id, limit)).transpose()
+    });
+    match result {
+        Ok(Some(deliveries)) => (
+            StatusCode::OK,
+            Json(ApiResponse {
+                success: true,
+                meta: ResponseMeta {
+                    total: Some(deliveries.len()),
+                    ..ResponseMeta::now()
+                },
+                data: Some(deliveries),
+                error: None,
+            }),
+        ),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(format!("Webhook {} not found", id)),
+                meta: ResponseMeta::now(),
+            }),
+        ),
+        Err(e) => {
+            error!("Failed to get deliveries of webhook {}: {}", id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e.to_string()),
+                    meta: ResponseMeta::now(),
+                }),
+            )
+        }
+    }
+}
+
+// POST /mcp
+pub async fn mcp_endpoint(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<serde_json::Value>,
+) -> impl IntoResponse {
+    match crate::mcp::McpServer::new(state.db.clone()).handle(request) {
+        Some(response) => (StatusCode::OK, Json(response)).into_response(),
+        // Notifications get no body
+        None => StatusCode::ACCEPTED.into_response(),
+    }
+}
+
 // GET /health
 pub async fn health_check() -> impl IntoResponse {
     Json(serde_json::json!({
@@ -1,3 +1,9 @@
+pub mod cache;
+pub mod export;
+pub mod fields;
 pub mod handlers;
+pub mod routes;
 
+pub use cache::{ClientQuota, MissCache, ResponseCache};
 pub use handlers::*;
+pub use routes::{router, rewrite_workspace_header};
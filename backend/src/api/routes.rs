@@ -0,0 +1,333 @@
+use axum::{
+    extract::{ConnectInfo, DefaultBodyLimit, MatchedPath, Request, State},
+    http::{header, HeaderMap, Method, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post, put},
+    Router,
+};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use metrics::{counter, histogram};
+use std::time::{Duration, Instant};
+use tower_http::timeout::TimeoutLayer;
+use tracing::warn;
+
+use crate::api::export::export_trends;
+use crate::api::handlers::*;
+use crate::config::is_valid_workspace_name;
+use crate::telemetry;
+
+pub const WORKSPACE_HEADER: &str = "x-workspace";
+
+/// POST routes that only read, left out of the audit log
+const READ_ONLY_POSTS: &[&str] = &["/api/trends/batch", "/mcp"];
+
+/// All `/api` routes for a single workspace's state.
+/// Routes are grouped by how long they may take; streaming routes get no timeout.
+pub fn router(state: Arc<AppState>) -> Router {
+    let request_timeout = Duration::from_secs(state.config.request_timeout_secs);
+    let analytics_timeout = Duration::from_secs(state.config.analytics_timeout_secs);
+
+    let requests = Router::new()
+        .route("/api/trends", get(get_trends))
+        .route("/api/trends/latest", get(get_latest_trends))
+        .route("/api/dashboard", get(get_dashboard))
+        .route("/api/trends/batch", post(get_trends_batch))
+        .route("/api/developers", get(get_developers))
+        .route("/api/repos/:id/readme", get(get_repo_readme))
+        .route("/api/repos/:id/history", get(get_repo_history))
+        .route("/api/repos/by-name/:owner/:repo", get(get_repo_profile))
+        .route("/api/repos/:id/summary/feedback", post(submit_summary_feedback))
+        .route("/api/search", get(search_repos))
+        .route("/api/badge/top-language", get(get_top_language_badge))
+        .route("/api/badge/repo/:id", get(get_repo_badge))
+        .route("/api/brief/audio", get(get_brief_audio))
+        .route("/api/contributors/overlap", get(get_contributor_overlap))
+        .route("/api/orgs", get(get_orgs))
+        .route("/api/tags/cloud", get(get_tag_cloud))
+        .route("/api/categories/trends", get(get_category_trends))
+        .route("/api/activity", get(get_activity))
+        .route("/api/archive", get(get_archive))
+        .route("/api/contributors/:login/repos", get(get_contributor_repos))
+        .route("/api/collections/:id/trends", get(get_collection_trends))
+        .route("/api/languages/daily", get(get_daily_languages))
+        .route("/api/languages/weekly", get(get_weekly_languages))
+        .route("/api/scoring", get(get_scoring_formula))
+        .route("/api/alerts", get(get_alerts))
+        .route("/api/alerts/milestones", get(get_milestone_alerts))
+        .route("/api/collect", post(trigger_collect))
+        .route("/api/collect/runs/:run_id", get(get_run_report))
+        .route("/api/collect/runs/:run_id/events", get(get_run_events))
+        .route("/api/subscriptions", post(create_subscription))
+        .route("/api/share", post(create_share_link))
+        .route("/share/:token", get(get_shared_brief))
+        .route("/sitemap.xml", get(get_sitemap))
+        .route("/calendar.ics", get(get_calendar))
+        .route("/api/subscriptions/:id", delete(delete_subscription))
+        .route("/api/subscriptions/confirm/:token", get(confirm_subscription))
+        .route("/api/subscriptions/unsubscribe/:token", get(unsubscribe))
+        .route_layer(TimeoutLayer::new(request_timeout));
+
+    let analytics = Router::new()
+        .route("/api/trends/lookback", get(get_trends_lookback))
+        .route("/api/trends/lifecycle", get(get_trends_lifecycle))
+        .route("/api/languages/changes", get(get_language_changes))
+        .route("/api/languages/cooccurrence", get(get_language_cooccurrence))
+        .route("/api/languages/:language/top", get(get_language_top_repos))
+        .route("/api/languages/:language/forecast", get(get_language_forecast))
+        .route("/mcp", post(mcp_endpoint))
+        .route_layer(TimeoutLayer::new(analytics_timeout));
+
+    let admin = Router::new()
+        .route("/api/admin/evaluations", get(get_summary_evaluations))
+        .route("/api/admin/summary-feedback", get(get_summary_feedback))
+        .route("/api/admin/summary-feedback/requeue", post(requeue_summary_feedback))
+        .route("/api/admin/usage", get(get_usage))
+        .route("/api/admin/audit", get(get_audit_log))
+        .route("/api/admin/sources/compare", get(compare_sources))
+        .route("/api/admin/quality-decisions", get(get_quality_decisions))
+        .route("/api/admin/selfcheck", get(get_selfcheck))
+        .route("/api/admin/trends", delete(delete_day))
+        .route("/api/admin/recompute-trends", post(recompute_trends))
+        .route("/api/webhooks", get(list_webhooks).post(create_webhook))
+        .route("/api/webhooks/:id", delete(delete_webhook))
+        .route("/api/webhooks/:id/schedule", put(set_webhook_schedule).delete(clear_webhook_schedule))
+        .route("/api/webhooks/:id/deliveries", get(get_webhook_deliveries))
+        .route("/api/jobs", get(list_jobs).post(create_job))
+        .route("/api/jobs/:id", get(get_job).delete(cancel_job))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_admin_token))
+        .route_layer(TimeoutLayer::new(request_timeout));
+
+    // Ad-hoc SQL enforces ADMIN_QUERY_TIMEOUT_SECS itself, interrupting DuckDB. Only mounted
+    // with an admin token, so a misconfigured gate can't expose it.
+    let admin_query = if state.config.admin_token.is_some() {
+        Router::new()
+            .route("/api/admin/query", post(run_admin_query))
+            .route_layer(middleware::from_fn_with_state(state.clone(), require_admin_token))
+    } else {
+        Router::new()
+    };
+
+    let admin_streaming = Router::new()
+        .route("/api/admin/logs/stream", get(stream_logs))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_admin_token));
+
+    let streaming = Router::new()
+        .route("/api/collect/progress", get(sse_progress))
+        .route("/api/export/trends", get(export_trends));
+
+    requests
+        .merge(analytics)
+        .merge(admin)
+        .merge(admin_query)
+        .merge(admin_streaming)
+        .merge(streaming)
+        .layer(middleware::from_fn_with_state(state.clone(), audit_mutations))
+        .layer(middleware::from_fn_with_state(state.clone(), record_metrics))
+        .layer(middleware::from_fn_with_state(state.clone(), reject_on_read_only))
+        .layer(DefaultBodyLimit::max(state.config.max_body_bytes))
+        .with_state(state)
+}
+
+/// Whether the request carries the configured admin token
+pub(crate) fn has_admin_token(state: &AppState, headers: &HeaderMap) -> bool {
+    state.config.admin_token.as_ref().is_some_and(|token| {
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .is_some_and(|v| constant_time_eq(v.as_bytes(), token.as_bytes()))
+    })
+}
+
+/// Compare secrets without returning early on the first differing byte
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Whether a request only reads data
+fn is_read_request(method: &Method, path: &str) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) || READ_ONLY_POSTS.contains(&path)
+}
+
+/// On a read-only replica refuse everything that writes (collection, subscriptions, including
+/// their confirm and unsubscribe links) and the admin routes, before the audit log would try to
+/// record it.
+async fn reject_on_read_only(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Response {
+    let path = req.uri().path();
+    let refused = ["/api/admin", "/api/webhooks", "/api/jobs", "/api/subscriptions"]
+        .iter()
+        .any(|prefix| path.starts_with(prefix));
+    if state.config.read_only && (refused || !is_read_request(req.method(), path)) {
+        return (StatusCode::FORBIDDEN, "This server is a read-only replica").into_response();
+    }
+
+    next.run(req).await
+}
+
+/// Record every mutating request with its caller, parameters and outcome in `audit_log`.
+async fn audit_mutations(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Response {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    if is_read_request(&method, &path) {
+        return next.run(req).await;
+    }
+
+    let query = req.uri().query().map(str::to_string);
+    let actor = if has_admin_token(&state, req.headers()) { "admin" } else { "anonymous" };
+    let peer = req.extensions().get::<ConnectInfo<SocketAddr>>().map(|ci| ci.0.ip());
+    let client_ip = client_ip(&state.config.trusted_proxies, req.headers(), peer).map(|ip| ip.to_string());
+
+    let response = next.run(req).await;
+
+    if let Err(e) = state.db.record_audit(
+        actor,
+        client_ip.as_deref(),
+        method.as_str(),
+        &path,
+        query.as_deref(),
+        response.status().as_u16(),
+    ) {
+        warn!("Failed to record audit entry for {} {}: {}", method, path, e);
+    }
+
+    response
+}
+
+/// The caller's IP: the peer address, or the client a trusted reverse proxy reports in
+/// X-Forwarded-For. Over the Unix socket there is no peer address and only the local proxy can
+/// connect, so its header is believed as is.
+pub(crate) fn client_ip(trusted_proxies: &[IpAddr], headers: &HeaderMap, peer: Option<IpAddr>) -> Option<IpAddr> {
+    if peer.is_some_and(|ip| !trusted_proxies.contains(&ip)) {
+        return peer;
+    }
+
+    // Proxies append the address they saw, so the last entry not added by a trusted proxy is the
+    // client; anything to its left came from the client and could be forged
+    let forwarded = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter_map(|v| v.trim().parse::<IpAddr>().ok())
+        .collect::<Vec<_>>();
+    forwarded
+        .iter()
+        .rev()
+        .find(|ip| !trusted_proxies.contains(ip))
+        .or(forwarded.first())
+        .copied()
+        .or(peer)
+}
+
+/// Count and time every request by its route pattern, so ids in paths don't split the series
+async fn record_metrics(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| "unmatched".to_string(), |p| p.as_str().to_string());
+
+    let response = next.run(req).await;
+
+    let labels = [
+        ("workspace", state.config.workspace.clone()),
+        ("method", method),
+        ("route", route),
+        ("status", response.status().as_u16().to_string()),
+    ];
+    counter!(telemetry::HTTP_REQUESTS, &labels).increment(1);
+    histogram!(telemetry::HTTP_REQUEST_DURATION, &labels).record(started.elapsed().as_secs_f64());
+
+    response
+}
+
+/// Reject admin requests without `Authorization: Bearer <ADMIN_TOKEN>`.
+/// Without a configured token the admin routes don't exist at all.
+async fn require_admin_token(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Response {
+    if state.config.admin_token.is_none() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    if !has_admin_token(&state, req.headers()) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    next.run(req).await
+}
+
+/// Route requests carrying an `X-Workspace` header to that workspace's `/w/{name}` tree.
+/// Must wrap the whole router since URI rewrites inside `Router::layer` happen after routing.
+pub async fn rewrite_workspace_header(mut req: Request, next: Next) -> Response {
+    let workspace = req
+        .headers()
+        .get(WORKSPACE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_string())
+        .filter(|v| v != "default" && is_valid_workspace_name(v));
+
+    if let Some(workspace) = workspace {
+        if !req.uri().path().starts_with("/w/") {
+            let path_and_query = req
+                .uri()
+                .path_and_query()
+                .map(|pq| pq.as_str())
+                .unwrap_or("/");
+            if let Ok(uri) = format!("/w/{}{}", workspace, path_and_query).parse() {
+                *req.uri_mut() = uri;
+            }
+        }
+    }
+
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    fn request(peer: Option<&str>, forwarded: Option<&str>) -> Request {
+        let mut builder = axum::http::Request::builder().uri("/api/collect");
+        if let Some(forwarded) = forwarded {
+            builder = builder.header("x-forwarded-for", forwarded);
+        }
+        let mut req = builder.body(Body::empty()).unwrap();
+        if let Some(peer) = peer {
+            req.extensions_mut().insert(ConnectInfo(SocketAddr::new(peer.parse().unwrap(), 40000)));
+        }
+        req
+    }
+
+    fn client_ip_of(trusted_proxies: &[IpAddr], req: &Request) -> Option<IpAddr> {
+        let peer = req.extensions().get::<ConnectInfo<SocketAddr>>().map(|ci| ci.0.ip());
+        client_ip(trusted_proxies, req.headers(), peer)
+    }
+
+    fn ip(value: &str) -> Option<IpAddr> {
+        Some(value.parse().unwrap())
+    }
+
+    #[test]
+    fn forwarded_for_from_untrusted_peer_is_ignored() {
+        let req = request(Some("203.0.113.7"), Some("10.0.0.1"));
+        assert_eq!(client_ip_of(&[], &req), ip("203.0.113.7"));
+    }
+
+    #[test]
+    fn trusted_proxy_reports_the_client_it_saw() {
+        let trusted = [ip("10.0.0.2").unwrap()];
+        let req = request(Some("10.0.0.2"), Some("1.2.3.4, 198.51.100.9"));
+        assert_eq!(client_ip_of(&trusted, &req), ip("198.51.100.9"));
+
+        let req = request(Some("10.0.0.2"), None);
+        assert_eq!(client_ip_of(&trusted, &req), ip("10.0.0.2"));
+    }
+
+    #[test]
+    fn unix_socket_proxy_is_believed() {
+        let req = request(None, Some("198.51.100.9"));
+        assert_eq!(client_ip_of(&[], &req), ip("198.51.100.9"));
+    }
+}
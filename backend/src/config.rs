@@ -1,38 +1,813 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use dotenvy::dotenv;
+use serde::{Deserialize, Serialize};
 use std::env;
+use std::net::IpAddr;
+
+use crate::models::SummaryStyleOverride;
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub oss_insight_base_url: String,
     pub github_api_url: String,
-    pub github_token: Option<String>,
+    /// Host serving raw repository files (READMEs)
+    pub github_raw_url: String,
+    /// Personal access tokens rotated through as each nears its rate limit
+    pub github_tokens: Vec<String>,
+    /// Switch to the next token once one has this many requests left
+    pub github_token_min_remaining: i64,
+    /// Takes precedence over `github_tokens` when set
+    pub github_app: Option<GitHubAppConfig>,
+    /// Sent with every outbound request
+    pub user_agent: String,
+    /// Public origin of the frontend (e.g. `https://brief.example.com`), used for `sitemap.xml`
+    pub site_url: Option<String>,
     pub deepseek_base_url: String,
     pub deepseek_api_key: String,
     pub deepseek_model: String,
+    /// Stream summary completions, reporting one-liners as they are written and stopping ones
+    /// that run past the length budget
+    pub llm_stream: bool,
     pub language_threshold: f64,
     pub database_path: String,
     pub server_host: String,
     pub server_port: u16,
+    /// Serve HTTPS directly instead of behind a TLS-terminating proxy
+    pub tls: Option<TlsConfig>,
+    /// Listen on this Unix socket instead of `server_host:server_port`
+    pub unix_socket_path: Option<String>,
+    /// File mode of the Unix socket, so the proxy's user can connect
+    pub unix_socket_mode: u32,
+    /// Reverse proxies whose X-Forwarded-For is believed for the client IP
+    pub trusted_proxies: Vec<IpAddr>,
+    pub smtp_host: Option<String>,
+    pub smtp_port: u16,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub smtp_from: Option<String>,
+    /// Name of the workspace this config belongs to ("default" for the global dataset)
+    pub workspace: String,
+    /// Only collect repos whose primary language is in this list (empty = all)
+    pub language_filter: Vec<String>,
+    /// Most repos a run keeps, highest OSS Insight score first (0 = all)
+    pub max_repos_per_run: usize,
+    /// Merge the configured collections into the trending candidates instead of storing them apart
+    pub merge_sources: bool,
+    /// `(source, weight)` pairs scaling each source's share of a merged candidate's score
+    pub source_weights: Vec<(String, f64)>,
+    /// OSS Insight collection ids collected daily alongside the generic trends
+    pub collections: Vec<i64>,
+    /// Overrides the built-in README summary system prompt
+    pub summary_prompt: Option<String>,
+    /// Length and tone of the summaries, applied with or without `summary_prompt`
+    pub summary_style: SummaryStyle,
+    /// Cheaper and stronger models summaries are routed to by README size
+    pub summary_routing: SummaryRouting,
+    /// Days a summary stays fresh: a repo trending again with the same README reuses a fresh
+    /// summary, and re-runs of a day only regenerate the stale ones (0 = fresh for its day only)
+    pub summary_max_age_days: i64,
+    /// Additional workspaces loaded from `WORKSPACES_FILE`
+    pub workspaces: Vec<WorkspaceConfig>,
+    pub score_weights: ScoreWeights,
+    /// Standard deviations from the trailing mean before a language share is flagged
+    pub anomaly_stddev_threshold: f64,
+    /// Minimum days of history before a language is checked for anomalies
+    pub anomaly_min_days: i64,
+    /// Total star counts that raise an alert when a repo passes them between appearances
+    pub star_milestones: Vec<i64>,
+    /// Delivery attempts per webhook event before giving up
+    pub webhook_max_attempts: u32,
+    /// First retry delay of a webhook delivery, doubled per attempt
+    pub webhook_retry_backoff_ms: u64,
+    /// Directory whose files override the built-in notification templates
+    pub notification_templates_dir: Option<String>,
+    /// TTL of cached read responses in seconds (0 disables the cache)
+    pub cache_ttl_secs: u64,
+    /// Progress events buffered per SSE subscriber before the slowest ones miss events
+    pub progress_channel_capacity: usize,
+    /// Recent log lines `/api/admin/logs/stream` replays to a new client
+    pub log_tail_lines: usize,
+    /// Consecutive failures of a GitHub or LLM host that open its circuit (0 = never)
+    pub circuit_breaker_failures: u32,
+    /// Seconds an open circuit refuses calls before letting one through
+    pub circuit_breaker_cooldown_secs: u64,
+    /// Total timeout of outbound HTTP requests in seconds
+    pub http_timeout_secs: u64,
+    pub http_connect_timeout_secs: u64,
+    pub http_pool_max_idle_per_host: usize,
+    /// Proxy URL for all outbound requests
+    pub outbound_proxy: Option<String>,
+    /// Proxy for plain-HTTP requests (`HTTP_PROXY`)
+    pub http_proxy: Option<String>,
+    /// Proxy for HTTPS requests (`HTTPS_PROXY`)
+    pub https_proxy: Option<String>,
+    /// Comma-separated hosts/CIDRs that bypass the proxy (`NO_PROXY`)
+    pub no_proxy: Option<String>,
+    pub proxy_username: Option<String>,
+    pub proxy_password: Option<String>,
+    /// Timeout for ordinary API requests in seconds
+    pub request_timeout_secs: u64,
+    /// Timeout for aggregate/analytics endpoints in seconds
+    pub analytics_timeout_secs: u64,
+    /// Maximum accepted request body size in bytes
+    pub max_body_bytes: usize,
+    /// Net downvotes (down minus up) that drop a summary and queue it for regeneration (0 = never)
+    pub feedback_requeue_downvotes: i64,
+    /// Second model summarizing a sample of repos for side-by-side comparison
+    pub eval_model: Option<String>,
+    /// Fraction of repos (0.0-1.0) also summarized by `eval_model`
+    pub eval_fraction: f64,
+    /// Bearer token required by `/api/admin` routes (unset = admin routes disabled)
+    pub admin_token: Option<String>,
+    /// Most rows `/api/admin/query` returns
+    pub admin_query_max_rows: usize,
+    /// Seconds after which `/api/admin/query` interrupts DuckDB
+    pub admin_query_timeout_secs: u64,
+    pub usage_pricing: UsagePricing,
+    /// Ask the LLM for a one-line note when a README changed since the repo last trended
+    pub readme_change_notes: bool,
+    /// Ask the LLM to translate descriptions written in neither Korean nor English
+    pub translate_descriptions: bool,
+    /// Languages dropped or merged when computing language trends
+    pub language_rules: LanguageRules,
+    /// Heuristics flagging or dropping low-value trending repos
+    pub quality_rules: QualityRules,
+    /// Default weighting of daily language shares: "unweighted", "score" or "stars"
+    pub language_trend_weighting: String,
+    /// Collector stage fetching repo languages
+    pub metadata_stage: StagePolicy,
+    /// Collector stage fetching READMEs
+    pub readme_stage: StagePolicy,
+    /// Collector stage calling the LLM
+    pub summary_stage: StagePolicy,
+    /// Object storage each run's exports are published to (unset = no archive)
+    pub archive: Option<ArchiveConfig>,
+    /// Database calls holding the connection at least this long are logged (0 = off)
+    pub slow_query_ms: u64,
+    /// Replica mode: database opened read-only, no scheduler, writes and admin routes refused
+    pub read_only: bool,
+    /// Record upstream responses to, or replay them from, fixture files (unset = live upstreams)
+    pub fixtures: Option<FixtureConfig>,
+    /// Speech API the daily brief is read aloud with (unset = no audio)
+    pub tts: Option<TtsConfig>,
+    /// Policy screening of descriptions and READMEs (unset = none)
+    pub moderation: Option<ModerationConfig>,
+}
+
+// Values of `FixtureConfig::mode`
+pub const FIXTURE_MODE_RECORD: &str = "record";
+pub const FIXTURE_MODE_REPLAY: &str = "replay";
+
+/// Fixture files standing in for OSS Insight, GitHub and the LLM (see `services::fixtures`)
+#[derive(Debug, Clone)]
+pub struct FixtureConfig {
+    /// "record" (call upstreams and save their responses) or "replay" (serve saved responses only)
+    pub mode: String,
+    pub dir: String,
+    /// Subdirectory of `dir` holding one recording; record defaults to today's date,
+    /// replay to the newest recording
+    pub snapshot: Option<String>,
+}
+
+impl FixtureConfig {
+    /// Enabled by `FIXTURE_MODE`
+    fn from_env() -> Result<Option<Self>> {
+        let Some(mode) = env::var("FIXTURE_MODE").ok().filter(|s| !s.is_empty()) else {
+            return Ok(None);
+        };
+        if mode != FIXTURE_MODE_RECORD && mode != FIXTURE_MODE_REPLAY {
+            anyhow::bail!("FIXTURE_MODE must be '{}' or '{}'", FIXTURE_MODE_RECORD, FIXTURE_MODE_REPLAY);
+        }
+
+        let snapshot = env::var("FIXTURE_SNAPSHOT").ok().filter(|s| !s.is_empty());
+        if let Some(snapshot) = &snapshot {
+            // Used as a directory name
+            if !snapshot.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+                anyhow::bail!("Invalid FIXTURE_SNAPSHOT '{}'", snapshot);
+            }
+        }
+
+        Ok(Some(FixtureConfig {
+            mode,
+            dir: env::var("FIXTURE_DIR").unwrap_or_else(|_| "./fixtures".to_string()),
+            snapshot,
+        }))
+    }
+}
+
+// Values of `TtsConfig::provider`
+pub const TTS_PROVIDER_OPENAI: &str = "openai";
+pub const TTS_PROVIDER_ELEVENLABS: &str = "elevenlabs";
+
+/// Text-to-speech API the daily brief is read aloud with (see `services::tts`)
+#[derive(Clone)]
+pub struct TtsConfig {
+    /// "openai" (`POST /audio/speech`, also served by compatible APIs) or "elevenlabs"
+    pub provider: String,
+    pub base_url: String,
+    pub api_key: String,
+    pub model: String,
+    /// Voice name (OpenAI) or voice id (ElevenLabs)
+    pub voice: String,
+    /// Directory the MP3 of each day is written to, as `<date>.mp3`
+    pub audio_dir: String,
+    /// Longest script sent to the API; longer briefs lose their last repos
+    pub max_chars: usize,
+}
+
+impl std::fmt::Debug for TtsConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TtsConfig")
+            .field("provider", &self.provider)
+            .field("base_url", &self.base_url)
+            .field("model", &self.model)
+            .field("voice", &self.voice)
+            .field("audio_dir", &self.audio_dir)
+            .field("max_chars", &self.max_chars)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TtsConfig {
+    /// Enabled by `TTS_PROVIDER`
+    fn from_env() -> Result<Option<Self>> {
+        let Some(provider) = env::var("TTS_PROVIDER").ok().filter(|s| !s.is_empty()) else {
+            return Ok(None);
+        };
+        let (base_url, model, voice) = match provider.as_str() {
+            TTS_PROVIDER_OPENAI => ("https://api.openai.com/v1", "tts-1", "alloy"),
+            TTS_PROVIDER_ELEVENLABS => ("https://api.elevenlabs.io/v1", "eleven_multilingual_v2", ""),
+            _ => anyhow::bail!("TTS_PROVIDER must be '{}' or '{}'", TTS_PROVIDER_OPENAI, TTS_PROVIDER_ELEVENLABS),
+        };
+        let Some(api_key) = env::var("TTS_API_KEY").ok().filter(|s| !s.is_empty()) else {
+            anyhow::bail!("TTS_PROVIDER is set but TTS_API_KEY is not");
+        };
+        let voice = env::var("TTS_VOICE").ok().filter(|s| !s.is_empty()).unwrap_or_else(|| voice.to_string());
+        if voice.is_empty() {
+            anyhow::bail!("TTS_VOICE (a voice id) is required for {}", provider);
+        }
+
+        Ok(Some(TtsConfig {
+            base_url: env::var("TTS_BASE_URL")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| base_url.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            provider,
+            api_key,
+            model: env::var("TTS_MODEL").ok().filter(|s| !s.is_empty()).unwrap_or_else(|| model.to_string()),
+            voice,
+            audio_dir: env::var("TTS_AUDIO_DIR").unwrap_or_else(|_| "./data/audio".to_string()),
+            max_chars: env_parse("TTS_MAX_CHARS", 4000usize).max(1),
+        }))
+    }
+}
+
+// Values of `MODERATION`
+pub const MODERATION_KEYWORDS: &str = "keywords";
+pub const MODERATION_API: &str = "api";
+
+/// Policy screening run before summaries are stored (see `services::moderation`)
+#[derive(Clone)]
+pub struct ModerationConfig {
+    /// Case-insensitive words or phrases that violate policy, checked in either mode
+    pub keywords: Vec<String>,
+    /// OpenAI-compatible `/moderations` API; `None` in keyword mode
+    pub api: Option<ModerationApiConfig>,
+}
+
+#[derive(Clone)]
+pub struct ModerationApiConfig {
+    pub base_url: String,
+    pub api_key: String,
+    pub model: String,
+    /// Provider categories that count as violations; empty = every category the API flags
+    pub categories: Vec<String>,
+}
+
+impl std::fmt::Debug for ModerationConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModerationConfig")
+            .field("keywords", &self.keywords.len())
+            .field("api_base_url", &self.api.as_ref().map(|api| &api.base_url))
+            .field("api_model", &self.api.as_ref().map(|api| &api.model))
+            .finish_non_exhaustive()
+    }
+}
+
+impl ModerationConfig {
+    /// Enabled by `MODERATION`
+    fn from_env() -> Result<Option<Self>> {
+        let Some(mode) = env::var("MODERATION").ok().filter(|s| !s.is_empty()) else {
+            return Ok(None);
+        };
+        let keywords = parse_list(&env::var("MODERATION_KEYWORDS").unwrap_or_default().to_lowercase());
+
+        let api = match mode.as_str() {
+            MODERATION_KEYWORDS => {
+                if keywords.is_empty() {
+                    anyhow::bail!("MODERATION={} needs MODERATION_KEYWORDS", MODERATION_KEYWORDS);
+                }
+                None
+            }
+            MODERATION_API => {
+                let Some(api_key) = env::var("MODERATION_API_KEY").ok().filter(|s| !s.is_empty()) else {
+                    anyhow::bail!("MODERATION={} needs MODERATION_API_KEY", MODERATION_API);
+                };
+                Some(ModerationApiConfig {
+                    base_url: env::var("MODERATION_BASE_URL")
+                        .unwrap_or_else(|_| "https://api.openai.com/v1".to_string())
+                        .trim_end_matches('/')
+                        .to_string(),
+                    api_key,
+                    model: env::var("MODERATION_MODEL").unwrap_or_else(|_| "omni-moderation-latest".to_string()),
+                    categories: parse_list(&env::var("MODERATION_CATEGORIES").unwrap_or_default()),
+                })
+            }
+            _ => anyhow::bail!("MODERATION must be '{}' or '{}'", MODERATION_KEYWORDS, MODERATION_API),
+        };
+
+        Ok(Some(ModerationConfig { keywords, api }))
+    }
+}
+
+// Values of `ArchiveConfig::layout`
+pub const ARCHIVE_LAYOUT_DATE: &str = "date";
+pub const ARCHIVE_LAYOUT_HIVE: &str = "hive";
+
+/// S3-compatible bucket (AWS, GCS interoperability, MinIO, R2) daily exports are uploaded to
+#[derive(Clone)]
+pub struct ArchiveConfig {
+    pub bucket: String,
+    /// Key prefix; workspaces archive under `<prefix>/<workspace>`
+    pub prefix: String,
+    /// "date" (`<prefix>/2024-01-15/`) or "hive" (`<prefix>/year=2024/month=01/day=15/`)
+    pub layout: String,
+    /// Custom endpoint for non-AWS stores, e.g. `https://storage.googleapis.com`
+    pub endpoint: Option<String>,
+    pub region: String,
+    /// Fall back to `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY`
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<String>,
+    /// Delete archived objects older than this many days (0 = keep forever)
+    pub retention_days: u32,
+    /// Serve days missing from the local database from the archive (see `Database::attach_remote_archive`)
+    pub query_remote: bool,
+}
+
+impl std::fmt::Debug for ArchiveConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ArchiveConfig")
+            .field("bucket", &self.bucket)
+            .field("prefix", &self.prefix)
+            .field("layout", &self.layout)
+            .field("endpoint", &self.endpoint)
+            .field("region", &self.region)
+            .field("retention_days", &self.retention_days)
+            .field("query_remote", &self.query_remote)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ArchiveConfig {
+    /// Enabled by `ARCHIVE_BUCKET`
+    fn from_env() -> Result<Option<Self>> {
+        let Some(bucket) = env::var("ARCHIVE_BUCKET").ok().filter(|s| !s.is_empty()) else {
+            return Ok(None);
+        };
+
+        let layout = env::var("ARCHIVE_LAYOUT").unwrap_or_else(|_| ARCHIVE_LAYOUT_DATE.to_string());
+        if layout != ARCHIVE_LAYOUT_DATE && layout != ARCHIVE_LAYOUT_HIVE {
+            anyhow::bail!("ARCHIVE_LAYOUT must be '{}' or '{}'", ARCHIVE_LAYOUT_DATE, ARCHIVE_LAYOUT_HIVE);
+        }
+
+        Ok(Some(ArchiveConfig {
+            bucket,
+            prefix: env::var("ARCHIVE_PREFIX")
+                .unwrap_or_else(|_| "daily-git-brief".to_string())
+                .trim_matches('/')
+                .to_string(),
+            layout,
+            endpoint: env::var("ARCHIVE_ENDPOINT").ok().filter(|s| !s.is_empty()),
+            region: env::var("ARCHIVE_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+            access_key_id: env_either("ARCHIVE_ACCESS_KEY_ID", "AWS_ACCESS_KEY_ID"),
+            secret_access_key: env_either("ARCHIVE_SECRET_ACCESS_KEY", "AWS_SECRET_ACCESS_KEY"),
+            retention_days: env_parse("ARCHIVE_RETENTION_DAYS", 0),
+            query_remote: env_parse("ARCHIVE_QUERY_REMOTE", false),
+        }))
+    }
+
+    /// Key prefix of one day's objects
+    pub fn day_prefix(&self, date: &str) -> String {
+        if self.layout == ARCHIVE_LAYOUT_HIVE {
+            let mut parts = date.splitn(3, '-');
+            let (year, month, day) = (
+                parts.next().unwrap_or_default(),
+                parts.next().unwrap_or_default(),
+                parts.next().unwrap_or_default(),
+            );
+            format!("{}/year={}/month={}/day={}", self.prefix, year, month, day)
+        } else {
+            format!("{}/{}", self.prefix, date)
+        }
+    }
+
+    /// Date of an archived object from its key or URL, the inverse of `day_prefix`
+    pub fn date_of(&self, key: &str) -> Option<String> {
+        let segments: Vec<&str> = key.rsplit('/').skip(1).take(3).collect();
+        let date = if self.layout == ARCHIVE_LAYOUT_HIVE {
+            let value = |name: &str| segments.iter().find_map(|s| s.strip_prefix(name));
+            format!("{}-{}-{}", value("year=")?, value("month=")?, value("day=")?)
+        } else {
+            segments.first()?.to_string()
+        };
+        chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d").ok().map(|_| date)
+    }
+}
+
+/// PEM certificate chain and private key of the built-in HTTPS listener
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub cert_path: String,
+    pub key_path: String,
+    /// How often the files are checked for renewed certificates
+    pub reload_secs: u64,
+}
+
+impl TlsConfig {
+    /// TLS_CERT_PATH and TLS_KEY_PATH are required together
+    fn from_env() -> Result<Option<Self>> {
+        let cert_path = env::var("TLS_CERT_PATH").ok().filter(|s| !s.is_empty());
+        let key_path = env::var("TLS_KEY_PATH").ok().filter(|s| !s.is_empty());
+
+        match (cert_path, key_path) {
+            (Some(cert_path), Some(key_path)) => Ok(Some(TlsConfig {
+                cert_path,
+                key_path,
+                reload_secs: env_parse("TLS_RELOAD_SECS", 60).max(1),
+            })),
+            (None, None) => Ok(None),
+            _ => anyhow::bail!("TLS needs both TLS_CERT_PATH and TLS_KEY_PATH"),
+        }
+    }
+}
+
+/// GitHub App credentials used to mint installation tokens
+#[derive(Clone)]
+pub struct GitHubAppConfig {
+    pub app_id: String,
+    pub installation_id: String,
+    /// PEM-encoded RSA private key
+    pub private_key: String,
+}
+
+impl std::fmt::Debug for GitHubAppConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GitHubAppConfig")
+            .field("app_id", &self.app_id)
+            .field("installation_id", &self.installation_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl GitHubAppConfig {
+    /// All of GITHUB_APP_ID, GITHUB_APP_INSTALLATION_ID and a private key are required together
+    fn from_env() -> Result<Option<Self>> {
+        let app_id = env::var("GITHUB_APP_ID").ok().filter(|s| !s.is_empty());
+        let installation_id = env::var("GITHUB_APP_INSTALLATION_ID").ok().filter(|s| !s.is_empty());
+        let private_key = match env::var("GITHUB_APP_PRIVATE_KEY_PATH").ok().filter(|s| !s.is_empty()) {
+            Some(path) => Some(
+                std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read GitHub App private key {}", path))?,
+            ),
+            // Inline keys usually arrive with escaped newlines from .env files
+            None => env::var("GITHUB_APP_PRIVATE_KEY")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .map(|key| key.replace("\\n", "\n")),
+        };
+
+        match (app_id, installation_id, private_key) {
+            (Some(app_id), Some(installation_id), Some(private_key)) => Ok(Some(GitHubAppConfig {
+                app_id,
+                installation_id,
+                private_key,
+            })),
+            (None, None, None) => Ok(None),
+            _ => anyhow::bail!(
+                "GitHub App auth needs GITHUB_APP_ID, GITHUB_APP_INSTALLATION_ID and GITHUB_APP_PRIVATE_KEY(_PATH)"
+            ),
+        }
+    }
+}
+
+/// Weights for our own repo ranking (see `services::scoring`)
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoreWeights {
+    pub star_delta: f64,
+    pub fork_delta: f64,
+    pub pull_requests: f64,
+    pub pushes: f64,
+    pub recency: f64,
+    pub streak: f64,
+}
+
+impl ScoreWeights {
+    fn from_env() -> Self {
+        ScoreWeights {
+            star_delta: env_f64("SCORE_WEIGHT_STAR_DELTA", 1.0),
+            fork_delta: env_f64("SCORE_WEIGHT_FORK_DELTA", 0.5),
+            pull_requests: env_f64("SCORE_WEIGHT_PULL_REQUESTS", 0.3),
+            pushes: env_f64("SCORE_WEIGHT_PUSHES", 0.2),
+            recency: env_f64("SCORE_WEIGHT_RECENCY", 1.0),
+            streak: env_f64("SCORE_WEIGHT_STREAK", 0.5),
+        }
+    }
+}
+
+/// Prices (USD) used to estimate the cost of each day's collection
+#[derive(Debug, Clone, Serialize)]
+pub struct UsagePricing {
+    pub prompt_per_million_tokens: f64,
+    pub completion_per_million_tokens: f64,
+    pub github_per_request: f64,
+}
+
+impl UsagePricing {
+    fn from_env() -> Self {
+        UsagePricing {
+            prompt_per_million_tokens: env_f64("PRICE_PROMPT_PER_MILLION_TOKENS", 0.27),
+            completion_per_million_tokens: env_f64("PRICE_COMPLETION_PER_MILLION_TOKENS", 1.10),
+            github_per_request: env_f64("PRICE_GITHUB_PER_REQUEST", 0.0),
+        }
+    }
+
+    pub fn estimate(&self, prompt_tokens: i64, completion_tokens: i64, github_requests: i64) -> f64 {
+        (prompt_tokens as f64 * self.prompt_per_million_tokens
+            + completion_tokens as f64 * self.completion_per_million_tokens)
+            / 1_000_000.0
+            + github_requests as f64 * self.github_per_request
+    }
+}
+
+/// Exclusions and remaps applied when computing language trends; raw repo languages are kept as-is
+#[derive(Debug, Clone)]
+pub struct LanguageRules {
+    /// Languages left out of trends (markup, notebooks, generated code)
+    pub exclude: Vec<String>,
+    /// `(from, to)` pairs, e.g. Jupyter Notebook counted as Python
+    pub remap: Vec<(String, String)>,
+}
+
+impl LanguageRules {
+    /// `LANGUAGE_TREND_EXCLUDE` is a comma list, `LANGUAGE_TREND_REMAP` a comma list of `from=to`
+    fn from_env() -> Self {
+        let exclude = env::var("LANGUAGE_TREND_EXCLUDE").unwrap_or_else(|_| "HTML,CSS".to_string());
+        let remap = env::var("LANGUAGE_TREND_REMAP").unwrap_or_else(|_| "Jupyter Notebook=Python".to_string());
+        LanguageRules {
+            exclude: parse_list(&exclude),
+            remap: parse_list(&remap)
+                .iter()
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(from, to)| (from.trim().to_string(), to.trim().to_string()))
+                .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+                .collect(),
+        }
+    }
+
+    /// The language a raw language counts as in trends, or `None` when excluded
+    pub fn trend_language<'s>(&'s self, language: &'s str) -> Option<&'s str> {
+        let language = self
+            .remap
+            .iter()
+            .find(|(from, _)| from.eq_ignore_ascii_case(language))
+            .map_or(language, |(_, to)| to.as_str());
+        if self.exclude.iter().any(|e| e.eq_ignore_ascii_case(language)) {
+            None
+        } else {
+            Some(language)
+        }
+    }
+}
+
+// Values of `QualityRules::mode`
+pub const QUALITY_OFF: &str = "off";
+/// Keep the repo, marking it
+pub const QUALITY_FLAG: &str = "flag";
+/// Hide the repo from the brief and skip its summary
+pub const QUALITY_DROP: &str = "drop";
+
+/// Filter stage of the collector's summary phase (see `services::quality`)
+#[derive(Debug, Clone)]
+pub struct QualityRules {
+    pub mode: String,
+    /// Share of a README's non-empty lines carrying a link above which it counts as a link list
+    pub max_link_density: f64,
+    /// READMEs with fewer links are never judged by their link density
+    pub min_links: usize,
+    /// Case-insensitive substrings of repo names and descriptions marking likely spam
+    pub patterns: Vec<String>,
+    /// Ask the LLM to confirm repos the heuristics caught; a "not low quality" verdict keeps them
+    pub llm_judge: bool,
+}
+
+impl QualityRules {
+    fn from_env() -> Result<Self> {
+        let mode = env::var("QUALITY_FILTER").unwrap_or_else(|_| QUALITY_OFF.to_string()).to_lowercase();
+        if ![QUALITY_OFF, QUALITY_FLAG, QUALITY_DROP].contains(&mode.as_str()) {
+            anyhow::bail!("QUALITY_FILTER must be '{}', '{}' or '{}'", QUALITY_OFF, QUALITY_FLAG, QUALITY_DROP);
+        }
+        let patterns = env::var("QUALITY_PATTERNS")
+            .unwrap_or_else(|_| "crack,keygen,free-download,aimbot,star-for-star,follow-for-follow".to_string());
+        Ok(QualityRules {
+            mode,
+            max_link_density: env_parse("QUALITY_MAX_LINK_DENSITY", 0.6),
+            min_links: env_parse("QUALITY_MIN_LINKS", 30),
+            patterns: parse_list(&patterns.to_lowercase()),
+            llm_judge: env_parse("QUALITY_LLM_JUDGE", false),
+        })
+    }
+}
+
+/// How the README summaries read, spelled out to the LLM with every summary request. Requeued
+/// summaries may override it per repo, so it is stored with them as JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SummaryStyle {
+    /// Most characters of the one-liner (`None` = no limit)
+    pub max_chars: Option<usize>,
+    /// Most sentences of the one-liner (`None` = no limit)
+    pub max_sentences: Option<usize>,
+    /// How the summary reads, e.g. "concise and informative" or "friendly, for beginners"
+    pub tone: String,
+    /// Whether the summary lists the tech stack; without it `tech_stack` stays empty
+    pub include_tech_stack: bool,
+}
+
+/// Models READMEs are summarized with by their estimated size (see `services::routing`);
+/// READMEs between the thresholds, or with no model set for their size, use `DEEPSEEK_MODEL`
+#[derive(Debug, Clone)]
+pub struct SummaryRouting {
+    /// Model for READMEs of at most `small_max_tokens` estimated tokens
+    pub small_model: Option<String>,
+    pub small_max_tokens: usize,
+    /// Model for READMEs of at least `large_min_tokens` estimated tokens
+    pub large_model: Option<String>,
+    pub large_min_tokens: usize,
+}
+
+impl SummaryRouting {
+    fn from_env() -> Result<Self> {
+        let routing = SummaryRouting {
+            small_model: env::var("SUMMARY_SMALL_MODEL").ok().filter(|s| !s.is_empty()),
+            small_max_tokens: env_parse("SUMMARY_SMALL_MAX_TOKENS", 500),
+            large_model: env::var("SUMMARY_LARGE_MODEL").ok().filter(|s| !s.is_empty()),
+            large_min_tokens: env_parse("SUMMARY_LARGE_MIN_TOKENS", 1800),
+        };
+        if routing.small_model.is_some() && routing.large_model.is_some() && routing.small_max_tokens >= routing.large_min_tokens {
+            anyhow::bail!("SUMMARY_SMALL_MAX_TOKENS must be below SUMMARY_LARGE_MIN_TOKENS");
+        }
+        Ok(routing)
+    }
+}
+
+/// Longest accepted `SummaryStyle::tone`
+pub const MAX_SUMMARY_TONE_CHARS: usize = 100;
+
+impl SummaryStyle {
+    fn from_env() -> Result<Self> {
+        let tone = env::var("SUMMARY_TONE")
+            .ok()
+            .map(|tone| tone.trim().to_string())
+            .filter(|tone| !tone.is_empty())
+            .unwrap_or_else(|| "concise and informative".to_string());
+        if tone.chars().count() > MAX_SUMMARY_TONE_CHARS {
+            anyhow::bail!("SUMMARY_TONE must be at most {} characters", MAX_SUMMARY_TONE_CHARS);
+        }
+        Ok(SummaryStyle {
+            // 0 lifts the limit
+            max_chars: Some(env_parse("SUMMARY_MAX_CHARS", 200usize)).filter(|n| *n > 0),
+            max_sentences: Some(env_parse("SUMMARY_MAX_SENTENCES", 0usize)).filter(|n| *n > 0),
+            tone,
+            include_tech_stack: env_parse("SUMMARY_INCLUDE_TECH_STACK", true),
+        })
+    }
+
+    /// This style with the fields `changes` sets replaced; `Err` describes an invalid tone
+    pub fn with_override(&self, changes: &SummaryStyleOverride) -> std::result::Result<Self, String> {
+        let mut style = self.clone();
+        if let Some(max_chars) = changes.max_chars {
+            style.max_chars = Some(max_chars).filter(|n| *n > 0);
+        }
+        if let Some(max_sentences) = changes.max_sentences {
+            style.max_sentences = Some(max_sentences).filter(|n| *n > 0);
+        }
+        if let Some(tone) = &changes.tone {
+            let tone = tone.trim();
+            if tone.is_empty() || tone.chars().count() > MAX_SUMMARY_TONE_CHARS {
+                return Err(format!("tone must be 1 to {} characters", MAX_SUMMARY_TONE_CHARS));
+            }
+            style.tone = tone.to_string();
+        }
+        if let Some(include_tech_stack) = changes.include_tech_stack {
+            style.include_tech_stack = include_tech_stack;
+        }
+        Ok(style)
+    }
+}
+
+/// Concurrency and retry policy of one collector pipeline stage (see `services::pipeline`)
+#[derive(Debug, Clone, Copy)]
+pub struct StagePolicy {
+    pub concurrency: usize,
+    /// Extra attempts after a failed call
+    pub retries: u32,
+    /// Delay before the first retry, doubled on each further attempt
+    pub retry_backoff_ms: u64,
+}
+
+impl StagePolicy {
+    /// Reads `{prefix}_CONCURRENCY`, `{prefix}_RETRIES` and `{prefix}_RETRY_BACKOFF_MS`
+    fn from_env(prefix: &str, concurrency: usize, retries: u32) -> Self {
+        StagePolicy {
+            concurrency: env_parse(&format!("{}_CONCURRENCY", prefix), concurrency).max(1),
+            retries: env_parse(&format!("{}_RETRIES", prefix), retries),
+            retry_backoff_ms: env_parse(&format!("{}_RETRY_BACKOFF_MS", prefix), 500),
+        }
+    }
+}
+
+/// Per-workspace overrides; anything left unset falls back to the global config
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkspaceConfig {
+    pub name: String,
+    pub database_path: Option<String>,
+    pub oss_insight_base_url: Option<String>,
+    pub language_threshold: Option<f64>,
+    #[serde(default)]
+    pub language_filter: Vec<String>,
+    #[serde(default)]
+    pub collections: Vec<i64>,
+    pub summary_prompt: Option<String>,
 }
 
 impl Config {
     pub fn from_env() -> Result<Self> {
         dotenv().ok();
 
-        let github_token = env::var("GITHUB_TOKEN").ok().filter(|s| !s.is_empty());
+        // GITHUB_TOKEN stays the primary token; GITHUB_TOKENS adds more to rotate through
+        let mut github_tokens: Vec<String> = env::var("GITHUB_TOKEN").ok().filter(|s| !s.is_empty()).into_iter().collect();
+        for token in parse_list(&env::var("GITHUB_TOKENS").unwrap_or_default()) {
+            if !github_tokens.contains(&token) {
+                github_tokens.push(token);
+            }
+        }
+
+        let tls = TlsConfig::from_env()?;
+        let unix_socket_path = env::var("UNIX_SOCKET_PATH").ok().filter(|s| !s.is_empty());
+        if tls.is_some() && unix_socket_path.is_some() {
+            anyhow::bail!("UNIX_SOCKET_PATH cannot be combined with TLS; terminate TLS in the proxy in front of the socket");
+        }
+        let unix_socket_mode = match env::var("UNIX_SOCKET_MODE").ok().filter(|s| !s.is_empty()) {
+            Some(mode) => u32::from_str_radix(&mode, 8)
+                .ok()
+                .filter(|m| *m <= 0o777)
+                .with_context(|| format!("UNIX_SOCKET_MODE must be an octal file mode, got '{}'", mode))?,
+            None => 0o660,
+        };
+        let trusted_proxies = parse_list(&env::var("TRUSTED_PROXIES").unwrap_or_default())
+            .iter()
+            .map(|ip| ip.parse().with_context(|| format!("TRUSTED_PROXIES must list IP addresses, got '{}'", ip)))
+            .collect::<Result<Vec<IpAddr>>>()?;
+
+        let workspaces = match env::var("WORKSPACES_FILE").ok().filter(|s| !s.is_empty()) {
+            Some(path) => load_workspaces(&path)?,
+            None => vec![],
+        };
 
         Ok(Config {
             oss_insight_base_url: env::var("OSS_INSIGHT_BASE_URL")
                 .unwrap_or_else(|_| "https://api.ossinsight.io".to_string()),
             github_api_url: "https://api.github.com".to_string(),
-            github_token,
+            github_raw_url: "https://raw.githubusercontent.com".to_string(),
+            github_tokens,
+            github_token_min_remaining: env_parse("GITHUB_TOKEN_MIN_REMAINING", 100),
+            github_app: GitHubAppConfig::from_env()?,
+            user_agent: env::var("USER_AGENT")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| "Daily-Git-Brief".to_string()),
+            site_url: env::var("SITE_URL")
+                .ok()
+                .map(|url| url.trim_end_matches('/').to_string())
+                .filter(|url| !url.is_empty()),
             deepseek_base_url: env::var("DEEPSEEK_BASE_URL")
                 .unwrap_or_else(|_| "https://api.deepseek.com".to_string()),
             deepseek_api_key: env::var("DEEPSEEK_API_KEY")
                 .expect("DEEPSEEK_API_KEY must be set"),
             deepseek_model: env::var("DEEPSEEK_MODEL")
                 .unwrap_or_else(|_| "deepseek-chat".to_string()),
+            llm_stream: env_parse("LLM_STREAM", false),
             language_threshold: env::var("LANGUAGE_THRESHOLD")
                 .unwrap_or_else(|_| "0.2".to_string())
                 .parse()
@@ -45,6 +820,176 @@ impl Config {
                 .unwrap_or_else(|_| "8080".to_string())
                 .parse()
                 .unwrap_or(8080),
+            tls,
+            unix_socket_path,
+            unix_socket_mode,
+            trusted_proxies,
+            smtp_host: env::var("SMTP_HOST").ok().filter(|s| !s.is_empty()),
+            smtp_port: env::var("SMTP_PORT")
+                .unwrap_or_else(|_| "587".to_string())
+                .parse()
+                .unwrap_or(587),
+            smtp_username: env::var("SMTP_USERNAME").ok().filter(|s| !s.is_empty()),
+            smtp_password: env::var("SMTP_PASSWORD").ok().filter(|s| !s.is_empty()),
+            smtp_from: env::var("SMTP_FROM").ok().filter(|s| !s.is_empty()),
+            workspace: "default".to_string(),
+            language_filter: parse_list(&env::var("LANGUAGE_FILTER").unwrap_or_default()),
+            max_repos_per_run: env_parse("MAX_REPOS_PER_RUN", 0usize),
+            merge_sources: env_parse("MERGE_SOURCES", false),
+            source_weights: parse_list(&env::var("SOURCE_WEIGHTS").unwrap_or_default())
+                .iter()
+                .filter_map(|pair| pair.split_once('='))
+                .filter_map(|(source, weight)| Some((source.trim().to_string(), weight.trim().parse().ok()?)))
+                .collect(),
+            collections: parse_list(&env::var("OSS_INSIGHT_COLLECTIONS").unwrap_or_default())
+                .iter()
+                .filter_map(|id| id.parse().ok())
+                .collect(),
+            summary_prompt: env::var("SUMMARY_PROMPT").ok().filter(|s| !s.is_empty()),
+            summary_style: SummaryStyle::from_env()?,
+            summary_routing: SummaryRouting::from_env()?,
+            summary_max_age_days: env_parse("SUMMARY_MAX_AGE_DAYS", 14i64).max(0),
+            workspaces,
+            score_weights: ScoreWeights::from_env(),
+            anomaly_stddev_threshold: env_f64("ANOMALY_STDDEV_THRESHOLD", 3.0),
+            anomaly_min_days: env::var("ANOMALY_MIN_DAYS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(7),
+            star_milestones: parse_list(&env::var("STAR_MILESTONES").unwrap_or_else(|_| "10000,50000,100000".to_string()))
+                .iter()
+                .filter_map(|m| m.parse().ok())
+                .filter(|m| *m > 0)
+                .collect(),
+            webhook_max_attempts: env_parse("WEBHOOK_MAX_ATTEMPTS", 5u32).max(1),
+            webhook_retry_backoff_ms: env_parse("WEBHOOK_RETRY_BACKOFF_MS", 1000),
+            notification_templates_dir: env::var("NOTIFICATION_TEMPLATES_DIR").ok().filter(|s| !s.is_empty()),
+            progress_channel_capacity: env_parse("PROGRESS_CHANNEL_CAPACITY", 256).max(1),
+            log_tail_lines: env_parse("LOG_TAIL_LINES", 500),
+            cache_ttl_secs: env::var("CACHE_TTL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(300),
+            circuit_breaker_failures: env_parse("CIRCUIT_BREAKER_FAILURES", 5),
+            circuit_breaker_cooldown_secs: env_parse("CIRCUIT_BREAKER_COOLDOWN_SECS", 60),
+            http_timeout_secs: env_parse("HTTP_TIMEOUT_SECS", 60),
+            http_connect_timeout_secs: env_parse("HTTP_CONNECT_TIMEOUT_SECS", 10),
+            http_pool_max_idle_per_host: env_parse("HTTP_POOL_MAX_IDLE_PER_HOST", 8),
+            outbound_proxy: env::var("OUTBOUND_PROXY").ok().filter(|s| !s.is_empty()),
+            http_proxy: env_either("HTTP_PROXY", "http_proxy"),
+            https_proxy: env_either("HTTPS_PROXY", "https_proxy"),
+            no_proxy: env_either("NO_PROXY", "no_proxy"),
+            proxy_username: env::var("PROXY_USERNAME").ok().filter(|s| !s.is_empty()),
+            proxy_password: env::var("PROXY_PASSWORD").ok().filter(|s| !s.is_empty()),
+            request_timeout_secs: env_parse("REQUEST_TIMEOUT_SECS", 15),
+            analytics_timeout_secs: env_parse("ANALYTICS_TIMEOUT_SECS", 60),
+            max_body_bytes: env_parse("MAX_BODY_BYTES", 64 * 1024),
+            feedback_requeue_downvotes: env_parse("FEEDBACK_REQUEUE_DOWNVOTES", 0i64).max(0),
+            eval_model: env::var("EVAL_MODEL").ok().filter(|s| !s.is_empty()),
+            eval_fraction: env_f64("EVAL_FRACTION", 0.0).clamp(0.0, 1.0),
+            admin_token: env::var("ADMIN_TOKEN").ok().filter(|s| !s.is_empty()),
+            admin_query_max_rows: env_parse("ADMIN_QUERY_MAX_ROWS", 10_000usize).max(1),
+            admin_query_timeout_secs: env_parse("ADMIN_QUERY_TIMEOUT_SECS", 30u64).max(1),
+            usage_pricing: UsagePricing::from_env(),
+            readme_change_notes: env_parse("README_CHANGE_NOTES", false),
+            translate_descriptions: env_parse("TRANSLATE_DESCRIPTIONS", false),
+            language_rules: LanguageRules::from_env(),
+            quality_rules: QualityRules::from_env()?,
+            language_trend_weighting: env::var("LANGUAGE_TREND_WEIGHTING").unwrap_or_else(|_| "unweighted".to_string()),
+            metadata_stage: StagePolicy::from_env("COLLECTOR_METADATA", 4, 2),
+            readme_stage: StagePolicy::from_env("COLLECTOR_README", 4, 2),
+            summary_stage: StagePolicy::from_env("COLLECTOR_SUMMARY", 2, 1),
+            archive: ArchiveConfig::from_env()?,
+            slow_query_ms: env_parse("SLOW_QUERY_MS", 500),
+            read_only: env_parse("READ_ONLY", false),
+            fixtures: FixtureConfig::from_env()?,
+            tts: TtsConfig::from_env()?,
+            moderation: ModerationConfig::from_env()?,
         })
     }
+
+    /// Build the effective config for a workspace by layering its overrides on this one
+    pub fn for_workspace(&self, ws: &WorkspaceConfig) -> Config {
+        let mut config = self.clone();
+        config.workspace = ws.name.clone();
+        config.database_path = ws.database_path.clone().unwrap_or_else(|| {
+            let dir = std::path::Path::new(&self.database_path)
+                .parent()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|| ".".to_string());
+            format!("{}/{}.duckdb", dir, ws.name)
+        });
+        if let Some(url) = &ws.oss_insight_base_url {
+            config.oss_insight_base_url = url.clone();
+        }
+        if let Some(threshold) = ws.language_threshold {
+            config.language_threshold = threshold;
+        }
+        if !ws.language_filter.is_empty() {
+            config.language_filter = ws.language_filter.clone();
+        }
+        if !ws.collections.is_empty() {
+            config.collections = ws.collections.clone();
+        }
+        if ws.summary_prompt.is_some() {
+            config.summary_prompt = ws.summary_prompt.clone();
+        }
+        if let Some(archive) = &mut config.archive {
+            archive.prefix = format!("{}/{}", archive.prefix, ws.name);
+        }
+        if let Some(tts) = &mut config.tts {
+            tts.audio_dir = format!("{}/{}", tts.audio_dir, ws.name);
+        }
+        config.workspaces = vec![];
+        config
+    }
+}
+
+fn env_f64(key: &str, default: f64) -> f64 {
+    env_parse(key, default)
+}
+
+fn env_parse<T: std::str::FromStr>(key: &str, default: T) -> T {
+    env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+/// The first of two variables that is set; proxy variables are conventionally accepted in either case
+fn env_either(upper: &str, lower: &str) -> Option<String> {
+    env::var(upper)
+        .or_else(|_| env::var(lower))
+        .ok()
+        .filter(|s| !s.is_empty())
+}
+
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn load_workspaces(path: &str) -> Result<Vec<WorkspaceConfig>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read workspaces file {}", path))?;
+    let workspaces: Vec<WorkspaceConfig> = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid workspaces file {}", path))?;
+
+    for ws in &workspaces {
+        if !is_valid_workspace_name(&ws.name) || ws.name == "default" {
+            anyhow::bail!("Invalid workspace name '{}'", ws.name);
+        }
+    }
+
+    Ok(workspaces)
+}
+
+/// Workspace names end up in URL paths and file names
+pub fn is_valid_workspace_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
 }
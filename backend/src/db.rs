@@ -1,12 +1,121 @@
 use anyhow::Result;
-use duckdb::{Connection, params};
+use duckdb::{Connection, OptionalExt, params, params_from_iter};
+use duckdb::types::{TimeUnit, Value};
+use metrics::counter;
+use std::collections::BTreeSet;
+use std::ops::{Deref, DerefMut};
 use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tracing::warn;
 
-use crate::models::{TrendingRepo, RepoLanguage, LanguageTrend};
+use crate::models::{TrendingRepo, RepoLanguage, LanguageTrend, DigestSubscription, LanguageShareStats, LanguageAlert, TrendingDeveloper, LanguageTopRepo, RepoIssue, StructuredSummary, EvaluationSummary, DailyUsage, CollectionRepo, ArchiveCursor, ContributorOverlap, DailyActivity, OrgTrend, StarMilestone, AuditEntry, Webhook, WebhookDelivery, DeliverySchedule, Job, CollectionStatus, CollectionEvent, RepoProgress, SummaryFeedbackEntry, RunCutoff, RepoSource, AdhocQueryResult, QualityDecision, QualityVerdict, CollectionReport};
+use crate::config::ArchiveConfig;
+use crate::services::jobs::{JOB_BACKFILL, JOB_CANCELLED, JOB_COLLECT, JOB_DONE, JOB_FAILED, JOB_QUEUED, JOB_RUNNING};
+use crate::services::search;
+use crate::services::usage::{UsageCounts, PROVIDER_GITHUB, PROVIDER_LLM};
+use crate::telemetry;
+
+/// Which stored repos an archive page spans
+pub enum ArchiveFilter<'a> {
+    /// Every appearance of one repo
+    Repo(i64),
+    /// Case-insensitive match on name, description or summary
+    Search(&'a str),
+    /// Repos a contributor appeared on
+    Contributor(&'a str),
+}
+
+/// Tables holding a day's collected data; `api_usage` is kept as a record of what was spent
+const DAY_TABLES: &[&str] = &[
+    "trending_repos",
+    "repo_languages",
+    "repo_contributors",
+    "daily_language_trends",
+    "language_alerts",
+    "star_milestones",
+    "trending_developers",
+    "collection_repos",
+    "repo_issues",
+    "repo_readmes",
+    "summary_evaluations",
+    "summary_feedback",
+    "collection_runs",
+    "run_reports",
+    "repo_sources",
+    "quality_decisions",
+];
+
+/// READMEs are small and written once per day, so favor ratio over speed
+const README_ZSTD_LEVEL: i32 = 9;
+
+/// Column list matching `job_from_row`
+const JOB_COLUMNS: &str = "id, kind, date, priority, status, run_id, result, error, CAST(created_at AS VARCHAR), \
+    CAST(started_at AS VARCHAR), CAST(finished_at AS VARCHAR)";
+
+/// Column list matching `trending_repo_from_row`
+const TRENDING_REPO_COLUMNS: &str = "CAST(date AS VARCHAR), repo_id, repo_name, primary_language, description, \
+    korean_summary, stars, forks, pull_requests, pushes, total_score, contributor_logins, collection_names, \
+    computed_score, summary_status, run_id, readme_updated, readme_change_note, summary_features, \
+    summary_tech_stack, audience, difficulty, rank, owner, total_stars, lifecycle, description_language, \
+    description_ko, summary_tags, category, homepage, doc_links, quality_flag, policy_flags, summary_model";
 
 pub struct Database {
     conn: Arc<Mutex<Connection>>,
+    remote: Option<Arc<RemoteArchive>>,
+    /// Methods holding the connection longer than this are logged (unset = never)
+    slow_query_threshold: Option<Duration>,
+}
+
+/// The locked connection of one `Database` method call. Dropping it times the call; a slow one is
+/// logged with its parameters, which are only rendered in that case, and counted.
+struct TimedConnection<'a, F: FnOnce() -> String> {
+    conn: MutexGuard<'a, Connection>,
+    method: &'static str,
+    params: Option<F>,
+    started: Instant,
+    threshold: Option<Duration>,
+}
+
+impl<F: FnOnce() -> String> Deref for TimedConnection<'_, F> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        &self.conn
+    }
+}
+
+impl<F: FnOnce() -> String> DerefMut for TimedConnection<'_, F> {
+    fn deref_mut(&mut self) -> &mut Connection {
+        &mut self.conn
+    }
+}
+
+impl<F: FnOnce() -> String> Drop for TimedConnection<'_, F> {
+    fn drop(&mut self) {
+        let elapsed = self.started.elapsed();
+        if self.threshold.is_some_and(|threshold| elapsed >= threshold) {
+            let params = self.params.take().map(|params| params()).unwrap_or_default();
+            warn!("Slow query: {}({}) took {} ms", self.method, params, elapsed.as_millis());
+            counter!(telemetry::DB_SLOW_QUERIES, "method" => self.method).increment(1);
+        }
+    }
+}
+
+/// Parquet archive published by `services::archive`, read through httpfs for days missing locally
+struct RemoteArchive {
+    config: ArchiveConfig,
+    /// Archived days, listed once when the archive is attached
+    dates: BTreeSet<String>,
+}
+
+impl RemoteArchive {
+    /// `read_parquet` argument for one archived table of `date`, if that day was archived
+    fn table(&self, date: &str, table: &str) -> Option<String> {
+        self.dates.contains(date).then(|| {
+            sql_literal(&format!("s3://{}/{}/{}.parquet", self.config.bucket, self.config.day_prefix(date), table))
+        })
+    }
 }
 
 impl Database {
@@ -19,13 +128,85 @@ impl Database {
         let conn = Connection::open(db_path)?;
         let db = Database {
             conn: Arc::new(Mutex::new(conn)),
+            remote: None,
+            slow_query_threshold: None,
         };
         db.init_schema()?;
         Ok(db)
     }
 
+    /// Open an existing database without write access; the schema is left as the writer made it
+    pub fn open_read_only(db_path: &str) -> Result<Self> {
+        let config = duckdb::Config::default().access_mode(duckdb::AccessMode::ReadOnly)?;
+        let conn = Connection::open_with_flags(db_path, config)?;
+        Ok(Database {
+            conn: Arc::new(Mutex::new(conn)),
+            remote: None,
+            slow_query_threshold: None,
+        })
+    }
+
+    /// Log methods holding the connection for at least `ms` milliseconds (0 disables)
+    pub fn with_slow_query_log(mut self, ms: u64) -> Self {
+        self.slow_query_threshold = (ms > 0).then(|| Duration::from_millis(ms));
+        self
+    }
+
+    /// Lock the connection for `method`; `params` describes the call in the slow-query log
+    fn lock<F: FnOnce() -> String>(&self, method: &'static str, params: F) -> TimedConnection<'_, F> {
+        TimedConnection {
+            conn: self.conn.lock().unwrap(),
+            method,
+            params: Some(params),
+            started: Instant::now(),
+            threshold: self.slow_query_threshold,
+        }
+    }
+
+    /// Serve days missing from this database from the Parquet archive in `archive`, read on demand
+    /// through DuckDB's httpfs extension. Returns the number of archived days found.
+    pub fn attach_remote_archive(&mut self, archive: &ArchiveConfig) -> Result<usize> {
+        let conn = self.lock("attach_remote_archive", String::new);
+        conn.execute_batch("INSTALL httpfs; LOAD httpfs;")?;
+
+        let mut secret = vec!["TYPE s3".to_string(), format!("REGION {}", sql_literal(&archive.region))];
+        if let Some(endpoint) = &archive.endpoint {
+            let (host, use_ssl) = match endpoint.strip_prefix("http://") {
+                Some(host) => (host, false),
+                None => (endpoint.strip_prefix("https://").unwrap_or(endpoint), true),
+            };
+            secret.push(format!("ENDPOINT {}", sql_literal(host.trim_end_matches('/'))));
+            secret.push(format!("USE_SSL {}", use_ssl));
+            secret.push("URL_STYLE 'path'".to_string());
+        }
+        if let (Some(key_id), Some(key)) = (&archive.access_key_id, &archive.secret_access_key) {
+            secret.push(format!("KEY_ID {}", sql_literal(key_id)));
+            secret.push(format!("SECRET {}", sql_literal(key)));
+        }
+        // Temporary: lives as long as this connection, never written to the database file
+        conn.execute_batch(&format!("CREATE OR REPLACE SECRET archive ({})", secret.join(", ")))?;
+
+        let pattern = format!("s3://{}/{}/trending_repos.parquet", archive.bucket, archive.day_prefix("*-*-*"));
+        let mut stmt = conn.prepare(&format!("SELECT file FROM glob({})", sql_literal(&pattern)))?;
+        let dates: BTreeSet<String> = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?
+            .iter()
+            .filter_map(|file| archive.date_of(file))
+            .collect();
+        drop(stmt);
+        drop(conn);
+
+        let days = dates.len();
+        self.remote = Some(Arc::new(RemoteArchive {
+            config: archive.clone(),
+            dates,
+        }));
+        Ok(days)
+    }
+
     fn init_schema(&self) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock("init_schema", String::new);
         
         conn.execute_batch(r#"
             CREATE TABLE IF NOT EXISTS trending_repos (
@@ -61,34 +242,383 @@ impl Database {
                 PRIMARY KEY (date, language)
             );
 
+            CREATE SEQUENCE IF NOT EXISTS digest_subscriptions_seq;
+
+            CREATE TABLE IF NOT EXISTS digest_subscriptions (
+                id BIGINT PRIMARY KEY DEFAULT nextval('digest_subscriptions_seq'),
+                email VARCHAR NOT NULL,
+                frequency VARCHAR NOT NULL,
+                delivery_time VARCHAR NOT NULL,
+                timezone VARCHAR NOT NULL,
+                weekday INTEGER,
+                last_sent_date VARCHAR,
+                created_at TIMESTAMP DEFAULT current_timestamp
+            );
+
+            CREATE SEQUENCE IF NOT EXISTS webhooks_seq;
+
+            CREATE TABLE IF NOT EXISTS webhooks (
+                id BIGINT PRIMARY KEY DEFAULT nextval('webhooks_seq'),
+                url VARCHAR NOT NULL,
+                secret VARCHAR NOT NULL,
+                events VARCHAR,
+                created_at TIMESTAMP DEFAULT current_timestamp
+            );
+
+            CREATE SEQUENCE IF NOT EXISTS webhook_deliveries_seq;
+
+            CREATE TABLE IF NOT EXISTS webhook_deliveries (
+                id BIGINT PRIMARY KEY DEFAULT nextval('webhook_deliveries_seq'),
+                webhook_id BIGINT NOT NULL,
+                delivery_id VARCHAR NOT NULL,
+                event VARCHAR NOT NULL,
+                attempt INTEGER NOT NULL,
+                success BOOLEAN NOT NULL,
+                status_code INTEGER,
+                error VARCHAR,
+                duration_ms BIGINT NOT NULL,
+                created_at TIMESTAMP DEFAULT current_timestamp
+            );
+
+            CREATE SEQUENCE IF NOT EXISTS jobs_seq;
+
+            CREATE TABLE IF NOT EXISTS jobs (
+                id BIGINT PRIMARY KEY DEFAULT nextval('jobs_seq'),
+                kind VARCHAR NOT NULL,
+                date VARCHAR,
+                priority INTEGER NOT NULL,
+                status VARCHAR NOT NULL,
+                run_id VARCHAR,
+                result VARCHAR,
+                error VARCHAR,
+                created_at TIMESTAMP DEFAULT current_timestamp,
+                started_at TIMESTAMP,
+                finished_at TIMESTAMP
+            );
+
+            CREATE SEQUENCE IF NOT EXISTS collection_events_seq;
+
+            CREATE TABLE IF NOT EXISTS collection_events (
+                id BIGINT PRIMARY KEY DEFAULT nextval('collection_events_seq'),
+                run_id VARCHAR NOT NULL,
+                is_running BOOLEAN NOT NULL,
+                phase VARCHAR NOT NULL,
+                message VARCHAR NOT NULL,
+                current_count BIGINT NOT NULL,
+                total_count BIGINT NOT NULL,
+                repo_event VARCHAR,
+                repo_id BIGINT,
+                repo_name VARCHAR,
+                repo_error VARCHAR,
+                created_at TIMESTAMP DEFAULT current_timestamp
+            );
+
+            CREATE SEQUENCE IF NOT EXISTS audit_log_seq;
+
+            CREATE TABLE IF NOT EXISTS audit_log (
+                id BIGINT PRIMARY KEY DEFAULT nextval('audit_log_seq'),
+                created_at TIMESTAMP DEFAULT current_timestamp,
+                actor VARCHAR NOT NULL,
+                client_ip VARCHAR,
+                method VARCHAR NOT NULL,
+                path VARCHAR NOT NULL,
+                query VARCHAR,
+                status INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS language_alerts (
+                date DATE NOT NULL,
+                language VARCHAR NOT NULL,
+                share DOUBLE NOT NULL,
+                trailing_mean DOUBLE NOT NULL,
+                trailing_stddev DOUBLE NOT NULL,
+                z_score DOUBLE NOT NULL,
+                PRIMARY KEY (date, language)
+            );
+
+            CREATE TABLE IF NOT EXISTS star_milestones (
+                date DATE NOT NULL,
+                repo_id BIGINT NOT NULL,
+                repo_name VARCHAR NOT NULL,
+                milestone BIGINT NOT NULL,
+                previous_stars BIGINT NOT NULL,
+                total_stars BIGINT NOT NULL,
+                previous_date DATE NOT NULL,
+                PRIMARY KEY (date, repo_id, milestone)
+            );
+
+            CREATE TABLE IF NOT EXISTS trending_developers (
+                date DATE NOT NULL,
+                login VARCHAR NOT NULL,
+                rank INTEGER NOT NULL,
+                followers BIGINT,
+                notable_repos VARCHAR,
+                PRIMARY KEY (date, login)
+            );
+
+            CREATE TABLE IF NOT EXISTS collection_repos (
+                date DATE NOT NULL,
+                collection_id BIGINT NOT NULL,
+                collection_name VARCHAR NOT NULL,
+                repo_id BIGINT NOT NULL,
+                repo_name VARCHAR NOT NULL,
+                rank INTEGER NOT NULL,
+                stars_growth BIGINT,
+                total_stars BIGINT,
+                run_id VARCHAR,
+                PRIMARY KEY (date, collection_id, repo_id)
+            );
+
+            CREATE TABLE IF NOT EXISTS repo_contributors (
+                date DATE NOT NULL,
+                repo_id BIGINT NOT NULL,
+                login VARCHAR NOT NULL,
+                PRIMARY KEY (date, repo_id, login)
+            );
+
+            CREATE TABLE IF NOT EXISTS repo_issues (
+                date DATE NOT NULL,
+                repo_id BIGINT NOT NULL,
+                stage VARCHAR NOT NULL,
+                code VARCHAR NOT NULL,
+                message VARCHAR,
+                PRIMARY KEY (date, repo_id, stage)
+            );
+
+            -- README text as fetched (already truncated), zstd-compressed
+            CREATE TABLE IF NOT EXISTS repo_readmes (
+                date DATE NOT NULL,
+                repo_id BIGINT NOT NULL,
+                content BLOB NOT NULL,
+                PRIMARY KEY (date, repo_id)
+            );
+
+            CREATE TABLE IF NOT EXISTS summary_evaluations (
+                date DATE NOT NULL,
+                repo_id BIGINT NOT NULL,
+                model VARCHAR NOT NULL,
+                one_liner VARCHAR NOT NULL,
+                features VARCHAR,
+                tech_stack VARCHAR,
+                PRIMARY KEY (date, repo_id, model)
+            );
+
+            CREATE SEQUENCE IF NOT EXISTS summary_feedback_seq;
+
+            -- Reader votes on a summary; `summary` is the one-liner rated, so votes on a
+            -- regenerated summary start from zero
+            CREATE TABLE IF NOT EXISTS summary_feedback (
+                id BIGINT PRIMARY KEY DEFAULT nextval('summary_feedback_seq'),
+                date DATE NOT NULL,
+                repo_id BIGINT NOT NULL,
+                summary VARCHAR NOT NULL,
+                vote INTEGER NOT NULL,
+                comment VARCHAR,
+                created_at TIMESTAMP DEFAULT current_timestamp
+            );
+
+            -- Public links to a day's brief; they outlive the day's data, which may be deleted
+            CREATE TABLE IF NOT EXISTS share_links (
+                token VARCHAR PRIMARY KEY,
+                date DATE NOT NULL,
+                created_at TIMESTAMP DEFAULT current_timestamp
+            );
+
+            -- Sources a merged candidate came from and what each added to its score
+            CREATE TABLE IF NOT EXISTS repo_sources (
+                date DATE NOT NULL,
+                repo_id BIGINT NOT NULL,
+                source VARCHAR NOT NULL,
+                source_rank INTEGER NOT NULL,
+                source_score DOUBLE,
+                weight DOUBLE NOT NULL,
+                contribution DOUBLE NOT NULL,
+                PRIMARY KEY (date, repo_id, source)
+            );
+
+            -- Quality filter decisions on the repos its heuristics caught, kept for audit
+            CREATE TABLE IF NOT EXISTS quality_decisions (
+                date DATE NOT NULL,
+                repo_id BIGINT NOT NULL,
+                repo_name VARCHAR NOT NULL,
+                decision VARCHAR NOT NULL,
+                reasons VARCHAR NOT NULL,
+                llm_low_quality BOOLEAN,
+                llm_reason VARCHAR,
+                run_id VARCHAR,
+                created_at TIMESTAMP DEFAULT current_timestamp
+            );
+
+            -- How many OSS Insight candidates each run saw and kept under MAX_REPOS_PER_RUN
+            CREATE TABLE IF NOT EXISTS collection_runs (
+                run_id VARCHAR PRIMARY KEY,
+                date DATE NOT NULL,
+                candidates INTEGER NOT NULL,
+                kept INTEGER NOT NULL,
+                max_repos INTEGER,
+                min_score DOUBLE,
+                created_at TIMESTAMP DEFAULT current_timestamp
+            );
+
+            -- What each collection or summary retry run did with every repo, as JSON
+            CREATE TABLE IF NOT EXISTS run_reports (
+                run_id VARCHAR PRIMARY KEY,
+                date DATE NOT NULL,
+                kind VARCHAR NOT NULL,
+                report VARCHAR NOT NULL,
+                created_at TIMESTAMP DEFAULT current_timestamp
+            );
+
+            CREATE TABLE IF NOT EXISTS api_usage (
+                date DATE NOT NULL,
+                provider VARCHAR NOT NULL,
+                model VARCHAR NOT NULL,
+                requests BIGINT NOT NULL DEFAULT 0,
+                prompt_tokens BIGINT NOT NULL DEFAULT 0,
+                completion_tokens BIGINT NOT NULL DEFAULT 0,
+                PRIMARY KEY (date, provider, model)
+            );
+
             CREATE INDEX IF NOT EXISTS idx_trending_date ON trending_repos(date);
             CREATE INDEX IF NOT EXISTS idx_languages_date ON repo_languages(date);
             CREATE INDEX IF NOT EXISTS idx_trends_date ON daily_language_trends(date);
         "#)?;
 
+        // Columns added after the initial schema; existing databases pick them up here
+        conn.execute_batch(r#"
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS computed_score DOUBLE;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS summary_status VARCHAR;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS run_id VARCHAR;
+            ALTER TABLE repo_languages ADD COLUMN IF NOT EXISTS run_id VARCHAR;
+            ALTER TABLE daily_language_trends ADD COLUMN IF NOT EXISTS run_id VARCHAR;
+            ALTER TABLE trending_developers ADD COLUMN IF NOT EXISTS run_id VARCHAR;
+            ALTER TABLE repo_issues ADD COLUMN IF NOT EXISTS run_id VARCHAR;
+            ALTER TABLE repo_readmes ADD COLUMN IF NOT EXISTS content_hash VARCHAR;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS readme_updated BOOLEAN;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS readme_change_note VARCHAR;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS summary_features VARCHAR;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS summary_tech_stack VARCHAR;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS audience VARCHAR;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS difficulty VARCHAR;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS collected_at TIMESTAMP;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS rank INTEGER;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS owner VARCHAR;
+            UPDATE trending_repos SET owner = split_part(repo_name, '/', 1) WHERE owner IS NULL;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS total_stars BIGINT;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS lifecycle VARCHAR;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS description_language VARCHAR;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS description_ko VARCHAR;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS search_text VARCHAR;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS summary_tags VARCHAR;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS category VARCHAR;
+            ALTER TABLE collection_runs ADD COLUMN IF NOT EXISTS parse_failures VARCHAR;
+            ALTER TABLE collection_runs ADD COLUMN IF NOT EXISTS circuit_breaks VARCHAR;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS homepage VARCHAR;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS doc_links VARCHAR;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS quality_flag VARCHAR;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS policy_flags VARCHAR;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS summary_style VARCHAR;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS summary_model VARCHAR;
+            ALTER TABLE trending_repos ADD COLUMN IF NOT EXISTS summarized_on DATE;
+            ALTER TABLE webhooks ADD COLUMN IF NOT EXISTS format VARCHAR DEFAULT 'json';
+            ALTER TABLE webhooks ADD COLUMN IF NOT EXISTS frequency VARCHAR;
+            ALTER TABLE webhooks ADD COLUMN IF NOT EXISTS delivery_time VARCHAR;
+            ALTER TABLE webhooks ADD COLUMN IF NOT EXISTS timezone VARCHAR;
+            ALTER TABLE webhooks ADD COLUMN IF NOT EXISTS weekday INTEGER;
+            ALTER TABLE webhooks ADD COLUMN IF NOT EXISTS top_n INTEGER;
+            ALTER TABLE webhooks ADD COLUMN IF NOT EXISTS last_sent_date VARCHAR;
+            ALTER TABLE digest_subscriptions ADD COLUMN IF NOT EXISTS unsubscribe_token VARCHAR;
+            ALTER TABLE digest_subscriptions ADD COLUMN IF NOT EXISTS confirm_token VARCHAR;
+            ALTER TABLE digest_subscriptions ADD COLUMN IF NOT EXISTS confirmed_at TIMESTAMP;
+            -- One vote per reader IP and summary; votes without a known IP count as one reader
+            ALTER TABLE summary_feedback ADD COLUMN IF NOT EXISTS client_ip VARCHAR;
+            -- Subscriptions from before double opt-in stay active and get an unsubscribe link
+            UPDATE digest_subscriptions SET confirmed_at = created_at WHERE unsubscribe_token IS NULL;
+            UPDATE digest_subscriptions SET unsubscribe_token = replace(CAST(uuid() AS VARCHAR), '-', '')
+            WHERE unsubscribe_token IS NULL;
+            UPDATE trending_repos SET lifecycle = CASE
+                    WHEN NOT EXISTS (SELECT 1 FROM trending_repos p
+                                     WHERE p.repo_id = trending_repos.repo_id AND p.date < trending_repos.date)
+                        THEN 'new'
+                    WHEN EXISTS (SELECT 1 FROM trending_repos p
+                                 WHERE p.repo_id = trending_repos.repo_id
+                                   AND p.date = CAST(trending_repos.date - INTERVAL 1 DAY AS DATE))
+                        THEN 'recurring'
+                    ELSE 'resurfaced'
+                END
+            WHERE lifecycle IS NULL;
+
+            -- Days stored before ranks were tracked get the upstream score order
+            UPDATE trending_repos SET rank = ranked.position
+            FROM (
+                SELECT date, repo_id,
+                       row_number() OVER (PARTITION BY date ORDER BY total_score DESC NULLS LAST, repo_id) AS position
+                FROM trending_repos
+            ) ranked
+            WHERE trending_repos.date = ranked.date
+              AND trending_repos.repo_id = ranked.repo_id
+              AND trending_repos.rank IS NULL;
+
+            -- Split stored contributor lists the first time the normalized table is used
+            INSERT INTO repo_contributors (date, repo_id, login)
+            SELECT DISTINCT date, repo_id, trim(login)
+            FROM (
+                SELECT date, repo_id, unnest(string_split(contributor_logins, ',')) AS login
+                FROM trending_repos
+                WHERE contributor_logins IS NOT NULL
+            )
+            WHERE trim(login) <> ''
+              AND NOT EXISTS (SELECT 1 FROM repo_contributors);
+        "#)?;
+
+        // Rows stored before search normalization get their search text once
+        refresh_search_text(&conn, "search_text IS NULL", vec![])?;
+
         Ok(())
     }
 
     pub fn save_trending_repo(&self, repo: &TrendingRepo) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock("save_trending_repo", || format!("date={:?} repo_id={}", repo.date, repo.repo_id));
         
         conn.execute(
             r#"INSERT INTO trending_repos 
                (date, repo_id, repo_name, primary_language, description, korean_summary, 
-                stars, forks, pull_requests, pushes, total_score, contributor_logins, collection_names)
-               VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                stars, forks, pull_requests, pushes, total_score, contributor_logins, collection_names,
+                computed_score, summary_status, run_id, rank, owner, total_stars, lifecycle, description_language,
+                homepage, collected_at)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, current_timestamp)
                ON CONFLICT (date, repo_id) DO UPDATE SET
                    repo_name = excluded.repo_name,
                    primary_language = excluded.primary_language,
+                   -- A translation stays valid for as long as the description it was made from
+                   description_ko = CASE
+                       WHEN excluded.description IS NOT DISTINCT FROM trending_repos.description
+                           THEN trending_repos.description_ko
+                   END,
                    description = excluded.description,
-                   korean_summary = excluded.korean_summary,
+                   description_language = excluded.description_language,
+                   -- Metadata refreshes must not wipe a summary stored earlier
+                   korean_summary = COALESCE(excluded.korean_summary, trending_repos.korean_summary),
+                   summary_status = CASE
+                       WHEN excluded.korean_summary IS NULL AND trending_repos.korean_summary IS NOT NULL
+                           THEN trending_repos.summary_status
+                       ELSE excluded.summary_status
+                   END,
                    stars = excluded.stars,
                    forks = excluded.forks,
                    pull_requests = excluded.pull_requests,
                    pushes = excluded.pushes,
                    total_score = excluded.total_score,
                    contributor_logins = excluded.contributor_logins,
-                   collection_names = excluded.collection_names"#,
+                   collection_names = excluded.collection_names,
+                   computed_score = excluded.computed_score,
+                   run_id = excluded.run_id,
+                   rank = excluded.rank,
+                   owner = excluded.owner,
+                   total_stars = COALESCE(excluded.total_stars, trending_repos.total_stars),
+                   lifecycle = excluded.lifecycle,
+                   homepage = COALESCE(excluded.homepage, trending_repos.homepage),
+                   collected_at = excluded.collected_at"#,
             params![
                 repo.date,
                 repo.repo_id,
@@ -103,76 +633,370 @@ impl Database {
                 repo.total_score,
                 repo.contributor_logins,
                 repo.collection_names,
+                repo.computed_score,
+                repo.summary_status,
+                repo.run_id,
+                repo.rank,
+                repo.owner,
+                repo.total_stars,
+                repo.lifecycle,
+                repo.description_language,
+                repo.homepage,
             ],
         )?;
+        refresh_search_text(&conn, SEARCH_ROW, search_row(&repo.date, repo.repo_id))?;
 
         Ok(())
     }
 
     pub fn save_repo_language(&self, lang: &RepoLanguage) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock("save_repo_language", || format!("date={:?} repo_id={} language={:?}", lang.date, lang.repo_id, lang.language));
         
         conn.execute(
-            r#"INSERT INTO repo_languages (date, repo_id, language, percentage)
-               VALUES (?, ?, ?, ?)
+            r#"INSERT INTO repo_languages (date, repo_id, language, percentage, run_id)
+               VALUES (?, ?, ?, ?, ?)
                ON CONFLICT (date, repo_id, language) DO UPDATE SET
-                   percentage = excluded.percentage"#,
-            params![lang.date, lang.repo_id, lang.language, lang.percentage],
+                   percentage = excluded.percentage,
+                   run_id = excluded.run_id"#,
+            params![lang.date, lang.repo_id, lang.language, lang.percentage, lang.run_id],
         )?;
 
         Ok(())
     }
 
     pub fn save_language_trend(&self, trend: &LanguageTrend) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock("save_language_trend", || format!("date={:?} language={:?}", trend.date, trend.language));
         
         conn.execute(
-            r#"INSERT INTO daily_language_trends (date, language, normalized_percentage, repo_count)
-               VALUES (?, ?, ?, ?)
+            r#"INSERT INTO daily_language_trends (date, language, normalized_percentage, repo_count, run_id)
+               VALUES (?, ?, ?, ?, ?)
                ON CONFLICT (date, language) DO UPDATE SET
                    normalized_percentage = excluded.normalized_percentage,
-                   repo_count = excluded.repo_count"#,
-            params![trend.date, trend.language, trend.normalized_percentage, trend.repo_count],
+                   repo_count = excluded.repo_count,
+                   run_id = excluded.run_id"#,
+            params![trend.date, trend.language, trend.normalized_percentage, trend.repo_count, trend.run_id],
         )?;
 
         Ok(())
     }
 
     pub fn get_trending_repos(&self, date: &str) -> Result<Vec<TrendingRepo>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock("get_trending_repos", || format!("date={:?}", date));
         
+        let mut stmt = conn.prepare(&format!(
+            r#"SELECT {} FROM trending_repos
+               WHERE date = ? AND quality_flag IS DISTINCT FROM 'dropped'
+               ORDER BY rank NULLS LAST, total_score DESC, repo_id"#,
+            TRENDING_REPO_COLUMNS
+        ))?;
+
+        let repos = stmt.query_map(params![date], trending_repo_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if let Some(file) = self.remote_table(&repos, date, "trending_repos") {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {} FROM read_parquet({}) ORDER BY rank NULLS LAST, total_score DESC, repo_id",
+                TRENDING_REPO_COLUMNS, file
+            ))?;
+            return Ok(stmt.query_map([], trending_repo_from_row)?.collect::<Result<Vec<_>, _>>()?);
+        }
+
+        Ok(repos)
+    }
+
+    /// The archived copy of `table` for `date` when nothing was found locally
+    fn remote_table<T>(&self, local: &[T], date: &str, table: &str) -> Option<String> {
+        if !local.is_empty() {
+            return None;
+        }
+        self.remote.as_ref()?.table(date, table)
+    }
+
+    /// Archived days not collected into this database
+    fn remote_dates(&self) -> impl Iterator<Item = &String> {
+        self.remote.iter().flat_map(|remote| remote.dates.iter())
+    }
+
+    /// Swap a date's language trends for `trends` in one transaction, so languages that
+    /// dropped out of a recomputation don't linger
+    pub fn replace_language_trends(&self, date: &str, trends: &[LanguageTrend]) -> Result<()> {
+        let mut conn = self.lock("replace_language_trends", || format!("date={:?} trends={}", date, trends.len()));
+        let tx = conn.transaction()?;
+
+        tx.execute("DELETE FROM daily_language_trends WHERE date = ?", params![date])?;
+        for trend in trends {
+            tx.execute(
+                r#"INSERT INTO daily_language_trends (date, language, normalized_percentage, repo_count, run_id)
+                   VALUES (?, ?, ?, ?, ?)"#,
+                params![trend.date, trend.language, trend.normalized_percentage, trend.repo_count, trend.run_id],
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Replace a repo's contributors for `date` with the given logins
+    pub fn save_repo_contributors(&self, date: &str, repo_id: i64, logins: &[&str]) -> Result<()> {
+        let mut conn = self.lock("save_repo_contributors", || format!("date={:?} repo_id={} logins={}", date, repo_id, logins.len()));
+        let tx = conn.transaction()?;
+
+        tx.execute("DELETE FROM repo_contributors WHERE date = ? AND repo_id = ?", params![date, repo_id])?;
+        for login in logins {
+            tx.execute(
+                "INSERT INTO repo_contributors (date, repo_id, login) VALUES (?, ?, ?) ON CONFLICT DO NOTHING",
+                params![date, repo_id, login],
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Replace a repo's merge provenance for `date`
+    pub fn save_repo_sources(&self, date: &str, repo_id: i64, sources: &[RepoSource]) -> Result<()> {
+        let mut conn = self.lock("save_repo_sources", || format!("date={:?} repo_id={} sources={}", date, repo_id, sources.len()));
+        let tx = conn.transaction()?;
+
+        tx.execute("DELETE FROM repo_sources WHERE date = ? AND repo_id = ?", params![date, repo_id])?;
+        for source in sources {
+            tx.execute(
+                r#"INSERT INTO repo_sources (date, repo_id, source, source_rank, source_score, weight, contribution)
+                   VALUES (?, ?, ?, ?, ?, ?, ?)"#,
+                params![date, repo_id, source.source, source.rank, source.score, source.weight, source.contribution],
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    pub fn save_quality_decision(&self, decision: &QualityDecision) -> Result<()> {
+        let reasons = serde_json::to_string(&decision.reasons)?;
+        let conn = self.lock("save_quality_decision", || format!("date={:?} repo_id={}", decision.date, decision.repo_id));
+
+        conn.execute(
+            r#"INSERT INTO quality_decisions (date, repo_id, repo_name, decision, reasons, llm_low_quality, llm_reason, run_id)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?)"#,
+            params![
+                decision.date,
+                decision.repo_id,
+                decision.repo_name,
+                decision.decision,
+                reasons,
+                decision.llm_verdict.as_ref().map(|v| v.low_quality),
+                decision.llm_verdict.as_ref().and_then(|v| v.reason.clone()),
+                decision.run_id,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Quality filter decisions recorded for `date`, newest first
+    pub fn get_quality_decisions(&self, date: &str) -> Result<Vec<QualityDecision>> {
+        let conn = self.lock("get_quality_decisions", || format!("date={:?}", date));
+
         let mut stmt = conn.prepare(
-            r#"SELECT CAST(date AS VARCHAR), repo_id, repo_name, primary_language, description, korean_summary,
-                      stars, forks, pull_requests, pushes, total_score, contributor_logins, collection_names
-               FROM trending_repos WHERE date = ? ORDER BY total_score DESC"#
+            r#"SELECT CAST(date AS VARCHAR), repo_id, repo_name, decision, reasons, llm_low_quality, llm_reason,
+                      run_id, CAST(created_at AS VARCHAR)
+               FROM quality_decisions
+               WHERE date = ?
+               ORDER BY created_at DESC, repo_id"#
         )?;
 
-        let repos = stmt.query_map(params![date], |row| {
-            Ok(TrendingRepo {
+        let decisions = stmt.query_map(params![date], |row| {
+            let reasons: String = row.get(4)?;
+            let llm_low_quality: Option<bool> = row.get(5)?;
+            let llm_reason: Option<String> = row.get(6)?;
+            Ok(QualityDecision {
                 date: row.get(0)?,
                 repo_id: row.get(1)?,
                 repo_name: row.get(2)?,
-                primary_language: row.get(3)?,
-                description: row.get(4)?,
-                korean_summary: row.get(5)?,
-                stars: row.get(6)?,
-                forks: row.get(7)?,
-                pull_requests: row.get(8)?,
-                pushes: row.get(9)?,
-                total_score: row.get(10)?,
-                contributor_logins: row.get(11)?,
-                collection_names: row.get(12)?,
+                decision: row.get(3)?,
+                reasons: serde_json::from_str(&reasons).unwrap_or_default(),
+                llm_verdict: llm_low_quality.map(|low_quality| QualityVerdict { low_quality, reason: llm_reason }),
+                run_id: row.get(7)?,
+                created_at: row.get(8)?,
             })
         })?.collect::<Result<Vec<_>, _>>()?;
 
-        Ok(repos)
+        Ok(decisions)
+    }
+
+    /// Set the summary style a requeued repo is summarized with (JSON `SummaryStyle`); `None`
+    /// leaves it to the configured style
+    pub fn update_summary_style(&self, date: &str, repo_id: i64, style: Option<&str>) -> Result<()> {
+        let conn = self.lock("update_summary_style", || format!("date={:?} repo_id={} style={:?}", date, repo_id, style));
+
+        conn.execute(
+            "UPDATE trending_repos SET summary_style = ? WHERE date = ? AND repo_id = ?",
+            params![style, date, repo_id],
+        )?;
+
+        Ok(())
+    }
+
+    /// Store the policy violations moderation found in a repo; an empty list clears earlier ones
+    pub fn update_policy_flags(&self, date: &str, repo_id: i64, violations: &[String]) -> Result<()> {
+        let flags = (!violations.is_empty()).then(|| serde_json::to_string(violations)).transpose()?;
+        let conn = self.lock("update_policy_flags", || format!("date={:?} repo_id={} violations={}", date, repo_id, violations.len()));
+
+        conn.execute(
+            "UPDATE trending_repos SET policy_flags = ? WHERE date = ? AND repo_id = ?",
+            params![flags, date, repo_id],
+        )?;
+
+        Ok(())
+    }
+
+    /// Mark a repo "flagged" or "dropped" by the quality filter, or clear the mark
+    pub fn update_quality_flag(&self, date: &str, repo_id: i64, flag: Option<&str>) -> Result<()> {
+        let conn = self.lock("update_quality_flag", || format!("date={:?} repo_id={} flag={:?}", date, repo_id, flag));
+
+        conn.execute(
+            "UPDATE trending_repos SET quality_flag = ? WHERE date = ? AND repo_id = ?",
+            params![flag, date, repo_id],
+        )?;
+
+        Ok(())
+    }
+
+    /// Merge provenance of a repo on `date`, largest contribution first
+    pub fn get_repo_sources(&self, date: &str, repo_id: i64) -> Result<Vec<RepoSource>> {
+        let conn = self.lock("get_repo_sources", || format!("date={:?} repo_id={}", date, repo_id));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT source, source_rank, source_score, weight, contribution FROM repo_sources
+               WHERE date = ? AND repo_id = ? ORDER BY contribution DESC, source"#
+        )?;
+
+        let sources = stmt.query_map(params![date, repo_id], |row| {
+            Ok(RepoSource {
+                source: row.get(0)?,
+                rank: row.get(1)?,
+                score: row.get(2)?,
+                weight: row.get(3)?,
+                contribution: row.get(4)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(sources)
+    }
+
+    /// `(source, repo_name)` of every repo a trend source produced on `date`: "oss_insight" for the
+    /// trending list and "collection:<name>" per configured collection
+    pub fn get_source_repos(&self, date: &str) -> Result<Vec<(String, String)>> {
+        let conn = self.lock("get_source_repos", || format!("date={:?}", date));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT 'oss_insight' AS source, repo_name FROM trending_repos WHERE date = ?
+               UNION
+               SELECT 'collection:' || collection_name, repo_name FROM collection_repos WHERE date = ?
+               ORDER BY source, repo_name"#
+        )?;
+
+        let rows = stmt.query_map(params![date, date], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Per-day repo and summary counts of a year, with each day's leading language
+    /// Per-day collection stats, oldest first; every collected day when `year` is unset
+    pub fn get_daily_activity(&self, year: Option<i32>) -> Result<Vec<DailyActivity>> {
+        let conn = self.lock("get_daily_activity", || format!("year={:?}", year));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT CAST(r.date AS VARCHAR), COUNT(*), COUNT(r.korean_summary), l.language
+               FROM trending_repos r
+               LEFT JOIN (
+                   SELECT date, arg_max(language, normalized_percentage) AS language
+                   FROM daily_language_trends
+                   GROUP BY date
+               ) l ON l.date = r.date
+               WHERE CAST(? AS INTEGER) IS NULL OR year(r.date) = ?
+               GROUP BY r.date, l.language
+               ORDER BY r.date"#
+        )?;
+
+        let days = stmt.query_map(params![year, year], |row| {
+            Ok(DailyActivity {
+                date: row.get(0)?,
+                repos: row.get(1)?,
+                summaries: row.get(2)?,
+                dominant_language: row.get(3)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(days)
+    }
+
+    /// Owners of a day's trending repos ranked by repo count, then by stars
+    pub fn get_org_trends(&self, date: &str, limit: usize) -> Result<Vec<OrgTrend>> {
+        let conn = self.lock("get_org_trends", || format!("date={:?} limit={}", date, limit));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT owner, COUNT(*) AS repo_count, CAST(COALESCE(SUM(stars), 0) AS BIGINT) AS total_stars,
+                      string_agg(repo_name, ',' ORDER BY rank NULLS LAST, repo_name)
+               FROM trending_repos
+               WHERE date = ? AND owner IS NOT NULL
+               GROUP BY owner
+               ORDER BY repo_count DESC, total_stars DESC, owner
+               LIMIT ?"#
+        )?;
+
+        let orgs = stmt.query_map(params![date, limit as i64], |row| {
+            let repos: String = row.get(3)?;
+            Ok(OrgTrend {
+                owner: row.get(0)?,
+                repo_count: row.get(1)?,
+                total_stars: row.get(2)?,
+                repos: repos.split(',').map(str::to_string).collect(),
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(orgs)
+    }
+
+    /// Pairs of a day's trending repos sharing at least `min_shared` contributors, most shared first
+    pub fn get_contributor_overlap(&self, date: &str, min_shared: i64) -> Result<Vec<ContributorOverlap>> {
+        let conn = self.lock("get_contributor_overlap", || format!("date={:?} min_shared={}", date, min_shared));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT a.repo_id, ra.repo_name, b.repo_id, rb.repo_name,
+                      COUNT(*) AS shared, string_agg(a.login, ',' ORDER BY a.login)
+               FROM repo_contributors a
+               JOIN repo_contributors b ON b.date = a.date AND b.login = a.login AND b.repo_id > a.repo_id
+               JOIN trending_repos ra ON ra.date = a.date AND ra.repo_id = a.repo_id
+               JOIN trending_repos rb ON rb.date = b.date AND rb.repo_id = b.repo_id
+               WHERE a.date = ?
+               GROUP BY a.repo_id, ra.repo_name, b.repo_id, rb.repo_name
+               HAVING COUNT(*) >= ?
+               ORDER BY shared DESC, ra.repo_name, rb.repo_name"#
+        )?;
+
+        let pairs = stmt.query_map(params![date, min_shared], |row| {
+            let logins: String = row.get(5)?;
+            Ok(ContributorOverlap {
+                repo_id: row.get(0)?,
+                repo_name: row.get(1)?,
+                other_repo_id: row.get(2)?,
+                other_repo_name: row.get(3)?,
+                shared_count: row.get(4)?,
+                shared_logins: logins.split(',').map(str::to_string).collect(),
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(pairs)
     }
 
     pub fn get_repo_languages(&self, date: &str, repo_id: i64) -> Result<Vec<RepoLanguage>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock("get_repo_languages", || format!("date={:?} repo_id={}", date, repo_id));
         
         let mut stmt = conn.prepare(
-            r#"SELECT CAST(date AS VARCHAR), repo_id, language, percentage
+            r#"SELECT CAST(date AS VARCHAR), repo_id, language, percentage, run_id
                FROM repo_languages WHERE date = ? AND repo_id = ? ORDER BY percentage DESC"#
         )?;
 
@@ -182,6 +1006,7 @@ impl Database {
                 repo_id: row.get(1)?,
                 language: row.get(2)?,
                 percentage: row.get(3)?,
+                run_id: row.get(4)?,
             })
         })?.collect::<Result<Vec<_>, _>>()?;
 
@@ -189,53 +1014,49 @@ impl Database {
     }
 
     pub fn get_daily_language_trends(&self, date: &str) -> Result<Vec<LanguageTrend>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock("get_daily_language_trends", || format!("date={:?}", date));
         
         let mut stmt = conn.prepare(
-            r#"SELECT CAST(date AS VARCHAR), language, normalized_percentage, repo_count
+            r#"SELECT CAST(date AS VARCHAR), language, normalized_percentage, repo_count, run_id
                FROM daily_language_trends WHERE date = ? ORDER BY normalized_percentage DESC"#
         )?;
 
-        let trends = stmt.query_map(params![date], |row| {
-            Ok(LanguageTrend {
-                date: row.get(0)?,
-                language: row.get(1)?,
-                normalized_percentage: row.get(2)?,
-                repo_count: row.get(3)?,
-            })
-        })?.collect::<Result<Vec<_>, _>>()?;
+        let trends = stmt.query_map(params![date], language_trend_from_row)?.collect::<Result<Vec<_>, _>>()?;
+
+        if let Some(file) = self.remote_table(&trends, date, "daily_language_trends") {
+            let mut stmt = conn.prepare(&format!(
+                r#"SELECT CAST(date AS VARCHAR), language, normalized_percentage, repo_count, run_id
+                   FROM read_parquet({}) ORDER BY normalized_percentage DESC"#,
+                file
+            ))?;
+            return Ok(stmt.query_map([], language_trend_from_row)?.collect::<Result<Vec<_>, _>>()?);
+        }
 
         Ok(trends)
     }
 
     pub fn get_weekly_language_trends(&self, end_date: &str) -> Result<Vec<LanguageTrend>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock("get_weekly_language_trends", || format!("end_date={:?}", end_date));
         
         let mut stmt = conn.prepare(
             r#"SELECT ? as date, language, 
                       AVG(normalized_percentage) as normalized_percentage,
-                      SUM(repo_count) as repo_count
+                      SUM(repo_count) as repo_count,
+                      NULL as run_id
                FROM daily_language_trends 
                WHERE date >= DATE(?, '-7 days') AND date <= ?
                GROUP BY language
                ORDER BY normalized_percentage DESC"#
         )?;
 
-        let trends = stmt.query_map(params![end_date, end_date, end_date], |row| {
-            Ok(LanguageTrend {
-                date: row.get(0)?,
-                language: row.get(1)?,
-                normalized_percentage: row.get(2)?,
-                repo_count: row.get(3)?,
-            })
-        })?.collect::<Result<Vec<_>, _>>()?;
+        let trends = stmt.query_map(params![end_date, end_date, end_date], language_trend_from_row)?.collect::<Result<Vec<_>, _>>()?;
 
         Ok(trends)
     }
 
     /// Check if a repo already exists for the given date with a Korean summary
     pub fn has_repo_with_summary(&self, date: &str, repo_id: i64) -> bool {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.lock("has_repo_with_summary", || format!("date={:?} repo_id={}", date, repo_id));
         
         let result: Result<Option<i32>, _> = conn.query_row(
             r#"SELECT 1 FROM trending_repos 
@@ -247,27 +1068,2048 @@ impl Database {
         result.is_ok()
     }
 
-    /// Get set of repo IDs that already have summaries for the given date
-    pub fn get_existing_repo_ids(&self, date: &str) -> Result<std::collections::HashSet<i64>> {
-        let conn = self.conn.lock().unwrap();
+    /// Get set of repo IDs with a summary for the given date written on or after `fresh_since`
+    pub fn get_fresh_summary_ids(&self, date: &str, fresh_since: &str) -> Result<std::collections::HashSet<i64>> {
+        let conn = self.lock("get_fresh_summary_ids", || format!("date={:?} fresh_since={:?}", date, fresh_since));
         
         let mut stmt = conn.prepare(
             r#"SELECT repo_id FROM trending_repos 
-               WHERE date = ? AND korean_summary IS NOT NULL"#
+               WHERE date = ? AND korean_summary IS NOT NULL
+                 AND COALESCE(summarized_on, date) >= CAST(? AS DATE)"#
         )?;
 
-        let ids = stmt.query_map(params![date], |row| row.get(0))?
+        let ids = stmt.query_map(params![date, fresh_since], |row| row.get(0))?
             .filter_map(|r| r.ok())
             .collect();
 
         Ok(ids)
     }
-}
 
-impl Clone for Database {
-    fn clone(&self) -> Self {
-        Database {
-            conn: Arc::clone(&self.conn),
-        }
+    /// Rank of every repo stored for `date`, by id (`None` for rows stored before ranks were)
+    pub fn get_stored_ranks(&self, date: &str) -> Result<std::collections::HashMap<i64, Option<i32>>> {
+        let conn = self.lock("get_stored_ranks", || format!("date={:?}", date));
+
+        let mut stmt = conn.prepare("SELECT repo_id, rank FROM trending_repos WHERE date = ?")?;
+        let ranks = stmt.query_map(params![date], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+
+        Ok(ranks)
     }
+
+    /// Store an unconfirmed subscription, activated by `confirm_subscription(confirm_token)`
+    pub fn create_subscription(&self, sub: &DigestSubscription, confirm_token: &str) -> Result<i64> {
+        let conn = self.lock("create_subscription", || format!("frequency={:?}", sub.frequency));
+
+        let id = conn.query_row(
+            r#"INSERT INTO digest_subscriptions
+                   (email, frequency, delivery_time, timezone, weekday, unsubscribe_token, confirm_token)
+               VALUES (?, ?, ?, ?, ?, ?, ?)
+               RETURNING id"#,
+            params![
+                sub.email,
+                sub.frequency,
+                sub.delivery_time,
+                sub.timezone,
+                sub.weekday,
+                sub.unsubscribe_token,
+                confirm_token,
+            ],
+            |row| row.get(0),
+        )?;
+
+        Ok(id)
+    }
+
+    /// Activate the subscription sent `confirm_token`; returns false for an unknown or used token
+    pub fn confirm_subscription(&self, confirm_token: &str) -> Result<bool> {
+        let conn = self.lock("confirm_subscription", String::new);
+
+        let affected = conn.execute(
+            r#"UPDATE digest_subscriptions SET confirmed_at = current_timestamp, confirm_token = NULL
+               WHERE confirm_token = ?"#,
+            params![confirm_token],
+        )?;
+
+        Ok(affected > 0)
+    }
+
+    /// Returns true if subscription `id` existed with `unsubscribe_token` and was removed
+    pub fn delete_subscription(&self, id: i64, unsubscribe_token: &str) -> Result<bool> {
+        let conn = self.lock("delete_subscription", || format!("id={}", id));
+
+        let affected = conn.execute(
+            "DELETE FROM digest_subscriptions WHERE id = ? AND unsubscribe_token = ?",
+            params![id, unsubscribe_token],
+        )?;
+
+        Ok(affected > 0)
+    }
+
+    /// Remove the subscription with `unsubscribe_token`; returns true if there was one
+    pub fn unsubscribe(&self, unsubscribe_token: &str) -> Result<bool> {
+        let conn = self.lock("unsubscribe", String::new);
+
+        let affected = conn.execute(
+            "DELETE FROM digest_subscriptions WHERE unsubscribe_token = ?",
+            params![unsubscribe_token],
+        )?;
+
+        Ok(affected > 0)
+    }
+
+    /// Confirmed subscriptions, the only ones digests go to
+    pub fn get_subscriptions(&self) -> Result<Vec<DigestSubscription>> {
+        let conn = self.lock("get_subscriptions", String::new);
+
+        let mut stmt = conn.prepare(
+            r#"SELECT id, email, frequency, delivery_time, timezone, weekday, last_sent_date, unsubscribe_token
+               FROM digest_subscriptions WHERE confirmed_at IS NOT NULL ORDER BY id"#
+        )?;
+
+        let subs = stmt.query_map([], |row| {
+            Ok(DigestSubscription {
+                id: row.get(0)?,
+                email: row.get(1)?,
+                frequency: row.get(2)?,
+                delivery_time: row.get(3)?,
+                timezone: row.get(4)?,
+                weekday: row.get(5)?,
+                last_sent_date: row.get(6)?,
+                confirmed: true,
+                unsubscribe_token: row.get(7)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(subs)
+    }
+
+    pub fn create_webhook(
+        &self,
+        url: &str,
+        secret: &str,
+        events: &[String],
+        format: &str,
+        schedule: Option<&DeliverySchedule>,
+    ) -> Result<i64> {
+        let conn = self.lock("create_webhook", || format!("events={:?} format={:?}", events, format));
+
+        let events = (!events.is_empty()).then(|| events.join(","));
+        let id = conn.query_row(
+            r#"INSERT INTO webhooks (url, secret, events, format, frequency, delivery_time, timezone, weekday, top_n)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+               RETURNING id"#,
+            params![
+                url,
+                secret,
+                events,
+                format,
+                schedule.map(|s| &s.frequency),
+                schedule.map(|s| &s.delivery_time),
+                schedule.map(|s| &s.timezone),
+                schedule.and_then(|s| s.weekday),
+                schedule.map(|s| s.top_n),
+            ],
+            |row| row.get(0),
+        )?;
+
+        Ok(id)
+    }
+
+    /// Replace a webhook's schedule (`None` = back to per-event posts); returns false if it doesn't exist
+    pub fn set_webhook_schedule(&self, id: i64, schedule: Option<&DeliverySchedule>) -> Result<bool> {
+        let conn = self.lock("set_webhook_schedule", || format!("id={} schedule={:?}", id, schedule));
+
+        let affected = conn.execute(
+            r#"UPDATE webhooks
+               SET frequency = ?, delivery_time = ?, timezone = ?, weekday = ?, top_n = ?, last_sent_date = NULL
+               WHERE id = ?"#,
+            params![
+                schedule.map(|s| &s.frequency),
+                schedule.map(|s| &s.delivery_time),
+                schedule.map(|s| &s.timezone),
+                schedule.and_then(|s| s.weekday),
+                schedule.map(|s| s.top_n),
+                id,
+            ],
+        )?;
+
+        Ok(affected > 0)
+    }
+
+    /// Record the hook-local date a scheduled digest was posted on
+    pub fn mark_webhook_sent(&self, id: i64, local_date: &str) -> Result<()> {
+        let conn = self.lock("mark_webhook_sent", || format!("id={} local_date={:?}", id, local_date));
+
+        conn.execute(
+            "UPDATE webhooks SET last_sent_date = ? WHERE id = ?",
+            params![local_date, id],
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns true if a webhook was removed; its delivery log goes with it
+    pub fn delete_webhook(&self, id: i64) -> Result<bool> {
+        let mut conn = self.lock("delete_webhook", || format!("id={}", id));
+        let tx = conn.transaction()?;
+
+        tx.execute("DELETE FROM webhook_deliveries WHERE webhook_id = ?", params![id])?;
+        let affected = tx.execute("DELETE FROM webhooks WHERE id = ?", params![id])?;
+        tx.commit()?;
+
+        Ok(affected > 0)
+    }
+
+    /// Every webhook with its secret, for signing deliveries
+    pub fn get_webhooks(&self) -> Result<Vec<Webhook>> {
+        let conn = self.lock("get_webhooks", String::new);
+
+        let mut stmt = conn.prepare(
+            r#"SELECT id, url, secret, events, CAST(created_at AS VARCHAR), COALESCE(format, 'json'),
+                      frequency, delivery_time, timezone, weekday, top_n, last_sent_date
+               FROM webhooks ORDER BY id"#
+        )?;
+
+        let hooks = stmt.query_map([], |row| {
+            let events: Option<String> = row.get(3)?;
+            let frequency: Option<String> = row.get(6)?;
+            let schedule = match frequency {
+                Some(frequency) => Some(DeliverySchedule {
+                    frequency,
+                    delivery_time: row.get::<_, Option<String>>(7)?.unwrap_or_else(|| "09:00".to_string()),
+                    timezone: row.get::<_, Option<String>>(8)?.unwrap_or_else(|| "UTC".to_string()),
+                    weekday: row.get(9)?,
+                    top_n: row.get::<_, Option<i64>>(10)?.unwrap_or(10),
+                    last_sent_date: row.get(11)?,
+                }),
+                None => None,
+            };
+            Ok(Webhook {
+                id: row.get(0)?,
+                url: row.get(1)?,
+                secret: row.get(2)?,
+                events: events
+                    .map(|e| e.split(',').map(str::to_string).collect())
+                    .unwrap_or_default(),
+                created_at: row.get(4)?,
+                format: row.get(5)?,
+                schedule,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(hooks)
+    }
+
+    pub fn webhook_exists(&self, id: i64) -> Result<bool> {
+        let conn = self.lock("webhook_exists", || format!("id={}", id));
+
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM webhooks WHERE id = ?", params![id], |row| row.get(0))?;
+
+        Ok(count > 0)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn record_webhook_delivery(
+        &self,
+        webhook_id: i64,
+        delivery_id: &str,
+        event: &str,
+        attempt: u32,
+        success: bool,
+        status_code: Option<u16>,
+        error: Option<&str>,
+        duration_ms: i64,
+    ) -> Result<()> {
+        let conn = self.lock("record_webhook_delivery", || format!("webhook_id={} event={:?} attempt={}", webhook_id, event, attempt));
+
+        conn.execute(
+            r#"INSERT INTO webhook_deliveries
+                (webhook_id, delivery_id, event, attempt, success, status_code, error, duration_ms)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?)"#,
+            params![
+                webhook_id,
+                delivery_id,
+                event,
+                attempt as i32,
+                success,
+                status_code.map(i32::from),
+                error,
+                duration_ms,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// A webhook's delivery attempts, newest first
+    pub fn get_webhook_deliveries(&self, webhook_id: i64, limit: usize) -> Result<Vec<WebhookDelivery>> {
+        let conn = self.lock("get_webhook_deliveries", || format!("webhook_id={} limit={}", webhook_id, limit));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT id, delivery_id, event, attempt, success, status_code, error, duration_ms,
+                      CAST(created_at AS VARCHAR)
+               FROM webhook_deliveries
+               WHERE webhook_id = ?
+               ORDER BY id DESC
+               LIMIT ?"#
+        )?;
+
+        let deliveries = stmt.query_map(params![webhook_id, limit as i64], |row| {
+            Ok(WebhookDelivery {
+                id: row.get(0)?,
+                delivery_id: row.get(1)?,
+                event: row.get(2)?,
+                attempt: row.get(3)?,
+                success: row.get(4)?,
+                status_code: row.get(5)?,
+                error: row.get(6)?,
+                duration_ms: row.get(7)?,
+                created_at: row.get(8)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(deliveries)
+    }
+
+    /// Record the subscriber-local date a digest was delivered on
+    pub fn mark_subscription_sent(&self, id: i64, local_date: &str) -> Result<()> {
+        let conn = self.lock("mark_subscription_sent", || format!("id={} local_date={:?}", id, local_date));
+
+        conn.execute(
+            "UPDATE digest_subscriptions SET last_sent_date = ? WHERE id = ?",
+            params![local_date, id],
+        )?;
+
+        Ok(())
+    }
+
+    /// Most recent date that has any trending repos stored
+    pub fn get_latest_date(&self) -> Result<Option<String>> {
+        let conn = self.lock("get_latest_date", String::new);
+
+        let date: Option<String> = conn.query_row(
+            "SELECT CAST(MAX(date) AS VARCHAR) FROM trending_repos",
+            [],
+            |row| row.get(0),
+        )?;
+
+        Ok(date.into_iter().chain(self.remote_dates().cloned()).max())
+    }
+
+    /// One page of stored repos across all dates, newest first. Keyset pagination on
+    /// `(date, repo_id)` keeps pages stable and cheap as the archive grows.
+    pub fn get_repo_archive(
+        &self,
+        filter: &ArchiveFilter<'_>,
+        after: Option<&ArchiveCursor>,
+        limit: usize,
+    ) -> Result<Vec<TrendingRepo>> {
+        let conn = self.lock("get_repo_archive", || format!("limit={}", limit));
+
+        let (mut condition, mut values) = match filter {
+            ArchiveFilter::Repo(repo_id) => ("repo_id = ?".to_string(), vec![Value::BigInt(*repo_id)]),
+            ArchiveFilter::Search(query) => search_condition(query),
+            ArchiveFilter::Contributor(login) => (
+                r#"EXISTS (SELECT 1 FROM repo_contributors c
+                           WHERE c.date = trending_repos.date AND c.repo_id = trending_repos.repo_id AND c.login = ?)"#
+                    .to_string(),
+                vec![Value::Text(login.to_string())],
+            ),
+        };
+        if let Some(cursor) = after {
+            condition.push_str(" AND (date < CAST(? AS DATE) OR (date = CAST(? AS DATE) AND repo_id > ?))");
+            values.push(Value::Text(cursor.date.clone()));
+            values.push(Value::Text(cursor.date.clone()));
+            values.push(Value::BigInt(cursor.repo_id));
+        }
+        values.push(Value::BigInt(limit as i64));
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM trending_repos WHERE {} ORDER BY date DESC, repo_id LIMIT ?",
+            TRENDING_REPO_COLUMNS, condition
+        ))?;
+
+        let repos = stmt.query_map(params_from_iter(values), trending_repo_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(repos)
+    }
+
+    /// When rows of `date` were last written, as an ISO 8601 timestamp
+    pub fn get_collected_at(&self, date: &str) -> Result<Option<String>> {
+        let conn = self.lock("get_collected_at", || format!("date={:?}", date));
+
+        let collected_at = conn.query_row(
+            r#"SELECT strftime(MAX(collected_at), '%Y-%m-%dT%H:%M:%S')
+               FROM trending_repos WHERE date = ?"#,
+            params![date],
+            |row| row.get(0),
+        )?;
+
+        Ok(collected_at)
+    }
+
+    /// Rows stored for `date` in each per-day table
+    pub fn count_day_rows(&self, date: &str) -> Result<Vec<(String, i64)>> {
+        let conn = self.lock("count_day_rows", || format!("date={:?}", date));
+
+        DAY_TABLES
+            .iter()
+            .map(|table| {
+                let count: i64 = conn.query_row(
+                    &format!("SELECT COUNT(*) FROM {} WHERE date = ?", table),
+                    params![date],
+                    |row| row.get(0),
+                )?;
+                Ok((table.to_string(), count))
+            })
+            .collect()
+    }
+
+    /// Write `date`'s rows of a day table to a Parquet file at `path`
+    pub fn export_day_parquet(&self, table: &str, date: &str, path: &Path) -> Result<()> {
+        anyhow::ensure!(DAY_TABLES.contains(&table), "Not a day table: {}", table);
+        // COPY takes no parameters, so the date is parsed before it is spliced in
+        let date = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")?;
+        let conn = self.lock("export_day_parquet", || format!("table={:?} date={:?} path={:?}", table, date, path));
+
+        conn.execute_batch(&format!(
+            "COPY (SELECT * FROM {} WHERE date = DATE '{}') TO {} (FORMAT parquet, COMPRESSION zstd)",
+            table,
+            date,
+            sql_literal(&path.display().to_string()),
+        ))?;
+
+        Ok(())
+    }
+
+    /// Remove everything collected for `date` in one transaction, returning the rows deleted
+    pub fn delete_day(&self, date: &str) -> Result<usize> {
+        let mut conn = self.lock("delete_day", || format!("date={:?}", date));
+        let tx = conn.transaction()?;
+
+        let mut deleted = 0;
+        for table in DAY_TABLES {
+            deleted += tx.execute(&format!("DELETE FROM {} WHERE date = ?", table), params![date])?;
+        }
+        tx.commit()?;
+
+        Ok(deleted)
+    }
+
+    /// Collected dates within `[from, to]`, oldest first
+    pub fn get_trending_dates_between(&self, from: &str, to: &str) -> Result<Vec<String>> {
+        let conn = self.lock("get_trending_dates_between", || format!("from={:?} to={:?}", from, to));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT DISTINCT CAST(date AS VARCHAR) FROM trending_repos
+               WHERE date BETWEEN CAST(? AS DATE) AND CAST(? AS DATE) ORDER BY 1"#
+        )?;
+
+        let mut dates = stmt.query_map(params![from, to], |row| row.get(0))?
+            .collect::<Result<BTreeSet<String>, _>>()?;
+        dates.extend(self.remote_dates().filter(|d| d.as_str() >= from && d.as_str() <= to).cloned());
+
+        Ok(dates.into_iter().collect())
+    }
+
+    /// Dates a repo trended on before the given date, newest first
+    pub fn get_repo_appearance_dates(&self, repo_id: i64, before: &str) -> Result<Vec<String>> {
+        let conn = self.lock("get_repo_appearance_dates", || format!("repo_id={} before={:?}", repo_id, before));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT CAST(date AS VARCHAR) FROM trending_repos
+               WHERE repo_id = ? AND date < ? ORDER BY date DESC"#
+        )?;
+
+        let dates = stmt.query_map(params![repo_id, before], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+
+        Ok(dates)
+    }
+
+    /// Each language's share on `date` with mean/stddev over the preceding `window_days`
+    pub fn get_language_share_stats(&self, date: &str, window_days: i64) -> Result<Vec<LanguageShareStats>> {
+        let conn = self.lock("get_language_share_stats", || format!("date={:?} window_days={}", date, window_days));
+
+        let mut stmt = conn.prepare(&format!(
+            r#"SELECT t.language, t.normalized_percentage,
+                      AVG(h.normalized_percentage), STDDEV_SAMP(h.normalized_percentage),
+                      COUNT(h.normalized_percentage)
+               FROM daily_language_trends t
+               LEFT JOIN daily_language_trends h
+                 ON h.language = t.language
+                AND h.date < t.date
+                AND h.date >= t.date - INTERVAL {} DAY
+               WHERE t.date = ?
+               GROUP BY t.language, t.normalized_percentage"#,
+            window_days
+        ))?;
+
+        let stats = stmt.query_map(params![date], |row| {
+            Ok(LanguageShareStats {
+                language: row.get(0)?,
+                share: row.get(1)?,
+                mean: row.get(2)?,
+                stddev: row.get(3)?,
+                samples: row.get(4)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(stats)
+    }
+
+    pub fn save_language_alert(&self, alert: &LanguageAlert) -> Result<()> {
+        let conn = self.lock("save_language_alert", || format!("date={:?} language={:?}", alert.date, alert.language));
+
+        conn.execute(
+            r#"INSERT INTO language_alerts (date, language, share, trailing_mean, trailing_stddev, z_score)
+               VALUES (?, ?, ?, ?, ?, ?)
+               ON CONFLICT (date, language) DO UPDATE SET
+                   share = excluded.share,
+                   trailing_mean = excluded.trailing_mean,
+                   trailing_stddev = excluded.trailing_stddev,
+                   z_score = excluded.z_score"#,
+            params![
+                alert.date,
+                alert.language,
+                alert.share,
+                alert.trailing_mean,
+                alert.trailing_stddev,
+                alert.z_score,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Alerts for a single date, or for the last 30 days when no date is given
+    pub fn get_language_alerts(&self, date: Option<&str>) -> Result<Vec<LanguageAlert>> {
+        let conn = self.lock("get_language_alerts", || format!("date={:?}", date));
+
+        let select = r#"SELECT CAST(date AS VARCHAR), language, share, trailing_mean, trailing_stddev, z_score
+                        FROM language_alerts"#;
+
+        let alerts = match date {
+            Some(date) => {
+                let mut stmt = conn.prepare(&format!("{} WHERE date = ? ORDER BY ABS(z_score) DESC", select))?;
+                let rows = stmt.query_map(params![date], language_alert_from_row)?.collect::<Result<Vec<_>, _>>()?;
+                rows
+            }
+            None => {
+                let mut stmt = conn.prepare(&format!(
+                    "{} WHERE date >= current_date - INTERVAL 30 DAY ORDER BY date DESC, ABS(z_score) DESC",
+                    select
+                ))?;
+                let rows = stmt.query_map([], language_alert_from_row)?.collect::<Result<Vec<_>, _>>()?;
+                rows
+            }
+        };
+
+        Ok(alerts)
+    }
+
+    /// `date`'s repos with a total star count, paired with the count of their latest earlier
+    /// appearance that had one: `(repo_id, repo_name, previous_date, previous_stars, total_stars)`
+    pub fn get_star_progress(&self, date: &str) -> Result<Vec<(i64, String, String, i64, i64)>> {
+        let conn = self.lock("get_star_progress", || format!("date={:?}", date));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT r.repo_id, r.repo_name, CAST(p.date AS VARCHAR), p.total_stars, r.total_stars
+               FROM trending_repos r
+               JOIN (
+                   SELECT repo_id, MAX(date) AS date, arg_max(total_stars, date) AS total_stars
+                   FROM trending_repos
+                   WHERE date < ? AND total_stars IS NOT NULL
+                   GROUP BY repo_id
+               ) p ON p.repo_id = r.repo_id
+               WHERE r.date = ? AND r.total_stars IS NOT NULL
+               ORDER BY r.repo_id"#
+        )?;
+
+        let rows = stmt.query_map(params![date, date], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    pub fn save_star_milestone(&self, milestone: &StarMilestone) -> Result<()> {
+        let conn = self.lock("save_star_milestone", || format!("date={:?} repo_id={}", milestone.date, milestone.repo_id));
+
+        conn.execute(
+            r#"INSERT INTO star_milestones (date, repo_id, repo_name, milestone, previous_stars, total_stars, previous_date)
+               VALUES (?, ?, ?, ?, ?, ?, ?)
+               ON CONFLICT (date, repo_id, milestone) DO UPDATE SET
+                   previous_stars = excluded.previous_stars,
+                   total_stars = excluded.total_stars,
+                   previous_date = excluded.previous_date"#,
+            params![
+                milestone.date,
+                milestone.repo_id,
+                milestone.repo_name,
+                milestone.milestone,
+                milestone.previous_stars,
+                milestone.total_stars,
+                milestone.previous_date,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Milestones for a single date, or for the last 30 days when no date is given
+    pub fn get_star_milestones(&self, date: Option<&str>) -> Result<Vec<StarMilestone>> {
+        let conn = self.lock("get_star_milestones", || format!("date={:?}", date));
+
+        let select = r#"SELECT CAST(date AS VARCHAR), repo_id, repo_name, milestone, previous_stars, total_stars,
+                               CAST(previous_date AS VARCHAR)
+                        FROM star_milestones"#;
+
+        let milestones = match date {
+            Some(date) => {
+                let mut stmt = conn.prepare(&format!("{} WHERE date = ? ORDER BY milestone DESC, repo_name", select))?;
+                let rows = stmt.query_map(params![date], star_milestone_from_row)?.collect::<Result<Vec<_>, _>>()?;
+                rows
+            }
+            None => {
+                let mut stmt = conn.prepare(&format!(
+                    "{} WHERE date >= current_date - INTERVAL 30 DAY ORDER BY date DESC, milestone DESC, repo_name",
+                    select
+                ))?;
+                let rows = stmt.query_map([], star_milestone_from_row)?.collect::<Result<Vec<_>, _>>()?;
+                rows
+            }
+        };
+
+        Ok(milestones)
+    }
+
+    /// Daily shares of one language over the `days` days ending at `end_date`, oldest first
+    pub fn get_language_history(&self, language: &str, end_date: &str, days: i64) -> Result<Vec<LanguageTrend>> {
+        let conn = self.lock("get_language_history", || format!("language={:?} end_date={:?} days={}", language, end_date, days));
+
+        let mut stmt = conn.prepare(&format!(
+            r#"SELECT CAST(date AS VARCHAR), language, normalized_percentage, repo_count, run_id
+               FROM daily_language_trends
+               WHERE language = ? AND date <= CAST(? AS DATE) AND date > CAST(? AS DATE) - INTERVAL {} DAY
+               ORDER BY date"#,
+            days
+        ))?;
+
+        let trends = stmt.query_map(params![language, end_date, end_date], language_trend_from_row)?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(trends)
+    }
+
+    /// All daily language trends between two dates (inclusive)
+    pub fn get_language_trends_range(&self, from: &str, to: &str) -> Result<Vec<LanguageTrend>> {
+        let conn = self.lock("get_language_trends_range", || format!("from={:?} to={:?}", from, to));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT CAST(date AS VARCHAR), language, normalized_percentage, repo_count, run_id
+               FROM daily_language_trends
+               WHERE date >= ? AND date <= ?
+               ORDER BY date, normalized_percentage DESC"#
+        )?;
+
+        let trends = stmt.query_map(params![from, to], language_trend_from_row)?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(trends)
+    }
+
+    /// Trending repos per `(date, category)` between two dates (inclusive), uncategorized repos
+    /// under `None`; ordered by date
+    pub fn get_category_counts(&self, from: &str, to: &str) -> Result<Vec<(String, Option<String>, i64)>> {
+        let conn = self.lock("get_category_counts", || format!("from={:?} to={:?}", from, to));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT CAST(date AS VARCHAR), category, COUNT(*)
+               FROM trending_repos
+               WHERE date >= ? AND date <= ?
+               GROUP BY date, category
+               ORDER BY date, COUNT(*) DESC, category"#
+        )?;
+
+        let rows = stmt.query_map(params![from, to], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// `(repo_id, summary_tags)` of every repo appearance between two dates (inclusive) that has tags
+    pub fn get_repo_tags(&self, from: &str, to: &str) -> Result<Vec<(i64, Option<String>)>> {
+        let conn = self.lock("get_repo_tags", || format!("from={:?} to={:?}", from, to));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT repo_id, summary_tags FROM trending_repos
+               WHERE date >= ? AND date <= ? AND summary_tags IS NOT NULL"#
+        )?;
+
+        let rows = stmt.query_map(params![from, to], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    pub fn save_trending_developer(&self, dev: &TrendingDeveloper) -> Result<()> {
+        let conn = self.lock("save_trending_developer", || format!("date={:?} login={:?}", dev.date, dev.login));
+
+        conn.execute(
+            r#"INSERT INTO trending_developers (date, login, rank, followers, notable_repos, run_id)
+               VALUES (?, ?, ?, ?, ?, ?)
+               ON CONFLICT (date, login) DO UPDATE SET
+                   rank = excluded.rank,
+                   followers = excluded.followers,
+                   notable_repos = excluded.notable_repos,
+                   run_id = excluded.run_id"#,
+            params![dev.date, dev.login, dev.rank, dev.followers, dev.notable_repos, dev.run_id],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_trending_developers(&self, date: &str) -> Result<Vec<TrendingDeveloper>> {
+        let conn = self.lock("get_trending_developers", || format!("date={:?}", date));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT CAST(date AS VARCHAR), login, rank, followers, notable_repos, run_id
+               FROM trending_developers WHERE date = ? ORDER BY rank"#
+        )?;
+
+        let devs = stmt.query_map(params![date], |row| {
+            Ok(TrendingDeveloper {
+                date: row.get(0)?,
+                login: row.get(1)?,
+                rank: row.get(2)?,
+                followers: row.get(3)?,
+                notable_repos: row.get(4)?,
+                run_id: row.get(5)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(devs)
+    }
+
+    /// Repos whose primary language matches, aggregated over the `days` ending at `end_date`.
+    /// `order_by_stars` ranks by cumulative stars instead of cumulative score.
+    pub fn get_language_top_repos(
+        &self,
+        language: &str,
+        end_date: &str,
+        days: i64,
+        order_by_stars: bool,
+        limit: usize,
+    ) -> Result<Vec<LanguageTopRepo>> {
+        let conn = self.lock("get_language_top_repos", || format!("language={:?} end_date={:?} days={} order_by_stars={} limit={}", language, end_date, days, order_by_stars, limit));
+
+        let order = if order_by_stars { "cumulative_stars" } else { "cumulative_score" };
+        let mut stmt = conn.prepare(&format!(
+            r#"SELECT repo_id,
+                      arg_max(repo_name, date),
+                      arg_max(description, date) FILTER (WHERE description IS NOT NULL),
+                      arg_max(korean_summary, date) FILTER (WHERE korean_summary IS NOT NULL),
+                      COUNT(*),
+                      SUM(COALESCE(total_score, 0)) AS cumulative_score,
+                      CAST(SUM(COALESCE(stars, 0)) AS BIGINT) AS cumulative_stars
+               FROM trending_repos
+               WHERE lower(primary_language) = lower(?)
+                 AND date <= CAST(? AS DATE)
+                 AND date > CAST(? AS DATE) - INTERVAL {} DAY
+               GROUP BY repo_id
+               ORDER BY {} DESC, repo_id
+               LIMIT ?"#,
+            days, order
+        ))?;
+
+        let repos = stmt.query_map(params![language, end_date, end_date, limit as i64], |row| {
+            let repo_name: String = row.get(1)?;
+            Ok(LanguageTopRepo {
+                rank: 0,
+                repo_id: row.get(0)?,
+                github_url: format!("https://github.com/{}", repo_name),
+                repo_name,
+                description: row.get(2)?,
+                korean_summary: row.get(3)?,
+                days_trending: row.get(4)?,
+                cumulative_score: row.get(5)?,
+                cumulative_stars: row.get(6)?,
+            })
+        })?
+        .enumerate()
+        .map(|(i, r)| r.map(|repo| LanguageTopRepo { rank: i + 1, ..repo }))
+        .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(repos)
+    }
+
+    /// Id of the repo that last trended as `repo_name` (case-insensitive), so a name it had
+    /// before being renamed still resolves
+    pub fn resolve_repo_name(&self, repo_name: &str) -> Result<Option<i64>> {
+        let conn = self.lock("resolve_repo_name", || format!("repo_name={:?}", repo_name));
+
+        let repo_id = conn.query_row(
+            r#"SELECT repo_id FROM trending_repos
+               WHERE lower(repo_name) = lower(?)
+               ORDER BY date DESC LIMIT 1"#,
+            params![repo_name],
+            |row| row.get(0),
+        ).optional()?;
+
+        Ok(repo_id)
+    }
+
+    /// Every appearance of a repo, oldest first
+    pub fn get_repo_appearances(&self, repo_id: i64) -> Result<Vec<TrendingRepo>> {
+        let conn = self.lock("get_repo_appearances", || format!("repo_id={}", repo_id));
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM trending_repos WHERE repo_id = ? ORDER BY date",
+            TRENDING_REPO_COLUMNS
+        ))?;
+
+        let repos = stmt.query_map(params![repo_id], trending_repo_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(repos)
+    }
+
+    /// `(repo_id, repo_name, korean_summary, summary_tags)` of the latest tagged appearance of
+    /// every repo but `exclude`
+    pub fn get_latest_repo_tags(&self, exclude: i64) -> Result<Vec<(i64, String, Option<String>, String)>> {
+        let conn = self.lock("get_latest_repo_tags", || format!("exclude={}", exclude));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT repo_id, arg_max(repo_name, date), arg_max(korean_summary, date), arg_max(summary_tags, date)
+               FROM trending_repos
+               WHERE summary_tags IS NOT NULL AND repo_id <> ?
+               GROUP BY repo_id"#
+        )?;
+
+        let rows = stmt.query_map(params![exclude], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Case-insensitive search over names, descriptions and summaries; latest appearance per repo
+    pub fn search_repos(&self, query: &str, limit: usize) -> Result<Vec<TrendingRepo>> {
+        let conn = self.lock("search_repos", || format!("query={:?} limit={}", query, limit));
+
+        let (condition, mut values) = search_condition(query);
+        values.push(Value::BigInt(limit as i64));
+        let mut stmt = conn.prepare(&format!(
+            r#"SELECT {} FROM trending_repos
+               WHERE {}
+               QUALIFY row_number() OVER (PARTITION BY repo_id ORDER BY date DESC) = 1
+               ORDER BY date DESC, rank NULLS LAST, total_score DESC
+               LIMIT ?"#,
+            TRENDING_REPO_COLUMNS, condition
+        ))?;
+
+        let repos = stmt.query_map(params_from_iter(values), trending_repo_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(repos)
+    }
+
+    /// Most recent date with trending repos on or before the given date
+    pub fn get_latest_date_on_or_before(&self, date: &str) -> Result<Option<String>> {
+        let conn = self.lock("get_latest_date_on_or_before", || format!("date={:?}", date));
+
+        let latest: Option<String> = conn.query_row(
+            "SELECT CAST(MAX(date) AS VARCHAR) FROM trending_repos WHERE date <= ?",
+            params![date],
+            |row| row.get(0),
+        )?;
+        let remote = self.remote_dates().filter(|d| d.as_str() <= date).max().cloned();
+
+        Ok(latest.max(remote))
+    }
+
+    pub fn save_repo_issue(&self, date: &str, repo_id: i64, issue: &RepoIssue) -> Result<()> {
+        let conn = self.lock("save_repo_issue", || format!("date={:?} repo_id={} stage={:?}", date, repo_id, issue.stage));
+
+        conn.execute(
+            r#"INSERT INTO repo_issues (date, repo_id, stage, code, message, run_id)
+               VALUES (?, ?, ?, ?, ?, ?)
+               ON CONFLICT (date, repo_id, stage) DO UPDATE SET
+                   code = excluded.code,
+                   message = excluded.message,
+                   run_id = excluded.run_id"#,
+            params![date, repo_id, issue.stage, issue.code, issue.message, issue.run_id],
+        )?;
+
+        Ok(())
+    }
+
+    /// Forget earlier issues before a repo is re-collected
+    pub fn clear_repo_issues(&self, date: &str, repo_id: i64) -> Result<()> {
+        let conn = self.lock("clear_repo_issues", || format!("date={:?} repo_id={}", date, repo_id));
+
+        conn.execute(
+            "DELETE FROM repo_issues WHERE date = ? AND repo_id = ?",
+            params![date, repo_id],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_repo_issues(&self, date: &str, repo_id: i64) -> Result<Vec<RepoIssue>> {
+        let conn = self.lock("get_repo_issues", || format!("date={:?} repo_id={}", date, repo_id));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT stage, code, message, run_id FROM repo_issues
+               WHERE date = ? AND repo_id = ? ORDER BY stage"#
+        )?;
+
+        let issues = stmt.query_map(params![date, repo_id], |row| {
+            Ok(RepoIssue {
+                stage: row.get(0)?,
+                code: row.get(1)?,
+                message: row.get(2)?,
+                run_id: row.get(3)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(issues)
+    }
+
+    /// Store a structured summary; its one-liner doubles as `korean_summary`
+    /// Store a repo's summary and the model that wrote it, or clear both
+    pub fn update_repo_summary(
+        &self,
+        date: &str,
+        repo_id: i64,
+        summary: Option<&StructuredSummary>,
+        model: Option<&str>,
+        summarized_on: Option<&str>,
+        status: &str,
+    ) -> Result<()> {
+        let one_liner = summary.map(|s| s.one_liner.as_str());
+        let features = summary.map(|s| serde_json::to_string(&s.features)).transpose()?;
+        let tech_stack = summary.map(|s| serde_json::to_string(&s.tech_stack)).transpose()?;
+        let tags = summary.map(|s| serde_json::to_string(&s.tags)).transpose()?;
+        let audience = summary.and_then(|s| s.audience.as_deref());
+        let difficulty = summary.and_then(|s| s.difficulty.as_deref());
+        let category = summary.and_then(|s| s.category.as_deref());
+        let conn = self.lock("update_repo_summary", || format!("date={:?} repo_id={} status={:?}", date, repo_id, status));
+
+        conn.execute(
+            r#"UPDATE trending_repos
+               SET korean_summary = ?, summary_features = ?, summary_tech_stack = ?, summary_tags = ?,
+                   audience = ?, difficulty = ?, category = ?, summary_model = ?, summarized_on = CAST(? AS DATE),
+                   summary_status = ?, collected_at = current_timestamp
+               WHERE date = ? AND repo_id = ?"#,
+            params![one_liner, features, tech_stack, tags, audience, difficulty, category, model, summarized_on, status, date, repo_id],
+        )?;
+        refresh_search_text(&conn, SEARCH_ROW, search_row(date, repo_id))?;
+
+        Ok(())
+    }
+
+    /// Raw `(repo_id, language, percentage, weight)` rows of a day, with each repo weighted by
+    /// "score" (total_score) or "stars" (star delta); see `trends::LanguageShares`
+    pub fn get_weighted_repo_languages(&self, date: &str, weighting: &str) -> Result<Vec<(i64, String, f64, f64)>> {
+        let weight = match weighting {
+            "score" => "GREATEST(COALESCE(r.total_score, 0), 0)",
+            "stars" => "GREATEST(COALESCE(r.stars, 0), 0)",
+            other => anyhow::bail!("Unknown weighting '{}' (expected unweighted, score or stars)", other),
+        };
+
+        let conn = self.lock("get_weighted_repo_languages", || format!("date={:?} weighting={:?}", date, weighting));
+
+        let mut stmt = conn.prepare(&format!(
+            r#"SELECT l.repo_id, l.language, l.percentage, CAST({} AS DOUBLE)
+               FROM repo_languages l
+               JOIN trending_repos r ON r.date = l.date AND r.repo_id = l.repo_id
+               WHERE l.date = ?
+               ORDER BY l.repo_id"#,
+            weight
+        ))?;
+
+        let rows = stmt.query_map(params![date], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// `(date, repo_id, language)` of every repo language stored in a date range, grouped by repo day
+    pub fn get_repo_languages_between(&self, from: &str, to: &str) -> Result<Vec<(String, i64, String)>> {
+        let conn = self.lock("get_repo_languages_between", || format!("from={:?} to={:?}", from, to));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT CAST(date AS VARCHAR), repo_id, language
+               FROM repo_languages
+               WHERE date BETWEEN ? AND ?
+               ORDER BY date, repo_id"#
+        )?;
+
+        let rows = stmt.query_map(params![from, to], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Store the (truncated) README fetched for a repo, zstd-compressed
+    pub fn save_readme(&self, date: &str, repo_id: i64, content: &str, content_hash: &str) -> Result<()> {
+        let compressed = zstd::encode_all(content.as_bytes(), README_ZSTD_LEVEL)?;
+        let conn = self.lock("save_readme", || format!("date={:?} repo_id={} bytes={}", date, repo_id, content.len()));
+
+        conn.execute(
+            r#"INSERT INTO repo_readmes (date, repo_id, content, content_hash)
+               VALUES (?, ?, ?, ?)
+               ON CONFLICT (date, repo_id) DO UPDATE SET
+                   content = excluded.content,
+                   content_hash = excluded.content_hash"#,
+            params![date, repo_id, compressed, content_hash],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_readme(&self, date: &str, repo_id: i64) -> Result<Option<String>> {
+        let conn = self.lock("get_readme", || format!("date={:?} repo_id={}", date, repo_id));
+
+        let compressed: Option<Vec<u8>> = conn.query_row(
+            "SELECT content FROM repo_readmes WHERE date = ? AND repo_id = ?",
+            params![date, repo_id],
+            |row| row.get(0),
+        ).optional()?;
+
+        match compressed {
+            Some(bytes) => Ok(Some(String::from_utf8(zstd::decode_all(bytes.as_slice())?)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The README (hash, content) stored at the repo's latest appearance before `before`
+    pub fn get_previous_readme(&self, repo_id: i64, before: &str) -> Result<Option<(Option<String>, String)>> {
+        let conn = self.lock("get_previous_readme", || format!("repo_id={} before={:?}", repo_id, before));
+
+        let row: Option<(Option<String>, Vec<u8>)> = conn.query_row(
+            r#"SELECT content_hash, content FROM repo_readmes
+               WHERE repo_id = ? AND date < ?
+               ORDER BY date DESC LIMIT 1"#,
+            params![repo_id, before],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional()?;
+
+        match row {
+            Some((hash, bytes)) => Ok(Some((hash, String::from_utf8(zstd::decode_all(bytes.as_slice())?)?))),
+            None => Ok(None),
+        }
+    }
+
+    pub fn update_readme_change(&self, date: &str, repo_id: i64, updated: Option<bool>, note: Option<&str>) -> Result<()> {
+        let conn = self.lock("update_readme_change", || format!("date={:?} repo_id={} updated={:?}", date, repo_id, updated));
+
+        conn.execute(
+            r#"UPDATE trending_repos SET readme_updated = ?, readme_change_note = ?
+               WHERE date = ? AND repo_id = ?"#,
+            params![updated, note, date, repo_id],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn update_doc_links(&self, date: &str, repo_id: i64, links: &[String]) -> Result<()> {
+        let links = serde_json::to_string(links)?;
+        let conn = self.lock("update_doc_links", || format!("date={:?} repo_id={}", date, repo_id));
+
+        conn.execute(
+            "UPDATE trending_repos SET doc_links = ? WHERE date = ? AND repo_id = ?",
+            params![links, date, repo_id],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn update_description_translation(&self, date: &str, repo_id: i64, translation: &str) -> Result<()> {
+        let conn = self.lock("update_description_translation", || format!("date={:?} repo_id={}", date, repo_id));
+
+        conn.execute(
+            "UPDATE trending_repos SET description_ko = ? WHERE date = ? AND repo_id = ?",
+            params![translation, date, repo_id],
+        )?;
+        refresh_search_text(&conn, SEARCH_ROW, search_row(date, repo_id))?;
+
+        Ok(())
+    }
+
+    pub fn save_summary_evaluation(&self, date: &str, repo_id: i64, model: &str, summary: &StructuredSummary) -> Result<()> {
+        let features = serde_json::to_string(&summary.features)?;
+        let tech_stack = serde_json::to_string(&summary.tech_stack)?;
+        let conn = self.lock("save_summary_evaluation", || format!("date={:?} repo_id={} model={:?}", date, repo_id, model));
+
+        conn.execute(
+            r#"INSERT INTO summary_evaluations (date, repo_id, model, one_liner, features, tech_stack)
+               VALUES (?, ?, ?, ?, ?, ?)
+               ON CONFLICT (date, repo_id, model) DO UPDATE SET
+                   one_liner = excluded.one_liner,
+                   features = excluded.features,
+                   tech_stack = excluded.tech_stack"#,
+            params![date, repo_id, model, summary.one_liner, features, tech_stack],
+        )?;
+
+        Ok(())
+    }
+
+    /// The summary of the repo's latest appearance before `before`, if it was written on or after
+    /// `fresh_since` in the configured style: (written on, hash of the README it was written from,
+    /// summary, model)
+    pub fn get_reusable_summary(
+        &self,
+        repo_id: i64,
+        before: &str,
+        fresh_since: &str,
+    ) -> Result<Option<(String, String, StructuredSummary, Option<String>)>> {
+        let conn = self.lock("get_reusable_summary", || format!("repo_id={} before={:?} fresh_since={:?}", repo_id, before, fresh_since));
+
+        let row = conn.query_row(
+            r#"SELECT CAST(COALESCE(t.summarized_on, t.date) AS VARCHAR), m.content_hash, t.korean_summary,
+                      t.summary_features, t.summary_tech_stack, t.summary_tags, t.audience, t.difficulty,
+                      t.category, t.summary_model
+               FROM trending_repos t
+               JOIN repo_readmes m ON m.date = t.date AND m.repo_id = t.repo_id
+               WHERE t.repo_id = ? AND t.date < ? AND t.korean_summary IS NOT NULL
+                 AND t.summary_style IS NULL AND m.content_hash IS NOT NULL
+                 AND COALESCE(t.summarized_on, t.date) >= CAST(? AS DATE)
+               ORDER BY t.date DESC LIMIT 1"#,
+            params![repo_id, before, fresh_since],
+            |row| {
+                let list = |json: Option<String>| json.and_then(|j| serde_json::from_str(&j).ok()).unwrap_or_default();
+                let summary = StructuredSummary {
+                    one_liner: row.get(2)?,
+                    features: list(row.get(3)?),
+                    tech_stack: list(row.get(4)?),
+                    tags: list(row.get(5)?),
+                    audience: row.get(6)?,
+                    difficulty: row.get(7)?,
+                    category: row.get(8)?,
+                };
+                Ok((row.get(0)?, row.get(1)?, summary, row.get(9)?))
+            },
+        ).optional()?;
+
+        Ok(row)
+    }
+
+    /// Evaluation summaries of a day, ordered so each repo's models are adjacent
+    pub fn get_summary_evaluations(&self, date: &str) -> Result<Vec<EvaluationSummary>> {
+        let conn = self.lock("get_summary_evaluations", || format!("date={:?}", date));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT e.repo_id, COALESCE(r.repo_name, CAST(e.repo_id AS VARCHAR)), e.model,
+                      e.one_liner, e.features, e.tech_stack
+               FROM summary_evaluations e
+               LEFT JOIN trending_repos r ON r.date = e.date AND r.repo_id = e.repo_id
+               WHERE e.date = ?
+               ORDER BY e.repo_id, e.model"#
+        )?;
+
+        let rows = stmt.query_map(params![date], |row| {
+            let features: Option<String> = row.get(4)?;
+            let tech_stack: Option<String> = row.get(5)?;
+            Ok(EvaluationSummary {
+                repo_id: row.get(0)?,
+                repo_name: row.get(1)?,
+                model: row.get(2)?,
+                one_liner: row.get(3)?,
+                features: features.and_then(|f| serde_json::from_str(&f).ok()).unwrap_or_default(),
+                tech_stack: tech_stack.and_then(|t| serde_json::from_str(&t).ok()).unwrap_or_default(),
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Record a vote (+1 or -1) on the repo's current summary of `date`, replacing the vote
+    /// `client_ip` cast on the same summary before, and return the summary's
+    /// `(upvotes, downvotes)`; `None` when the repo has no summary that day
+    pub fn save_summary_feedback(&self, date: &str, repo_id: i64, vote: i32, comment: Option<&str>, client_ip: Option<&str>) -> Result<Option<(i64, i64)>> {
+        let conn = self.lock("save_summary_feedback", || format!("date={:?} repo_id={} vote={} client_ip={:?}", date, repo_id, vote, client_ip));
+
+        let summary: Option<String> = conn.query_row(
+            "SELECT korean_summary FROM trending_repos WHERE date = ? AND repo_id = ? AND korean_summary IS NOT NULL",
+            params![date, repo_id],
+            |row| row.get(0),
+        ).optional()?;
+        let Some(summary) = summary else {
+            return Ok(None);
+        };
+
+        let revoted = conn.execute(
+            r#"UPDATE summary_feedback SET vote = ?, comment = ?, created_at = current_timestamp
+               WHERE date = ? AND repo_id = ? AND summary = ? AND client_ip IS NOT DISTINCT FROM ?"#,
+            params![vote, comment, date, repo_id, summary, client_ip],
+        )?;
+        if revoted == 0 {
+            conn.execute(
+                "INSERT INTO summary_feedback (date, repo_id, summary, vote, comment, client_ip) VALUES (?, ?, ?, ?, ?, ?)",
+                params![date, repo_id, summary, vote, comment, client_ip],
+            )?;
+        }
+
+        let tally = conn.query_row(
+            r#"SELECT COUNT(*) FILTER (WHERE vote > 0), COUNT(*) FILTER (WHERE vote < 0)
+               FROM summary_feedback
+               WHERE date = ? AND repo_id = ? AND summary = ?"#,
+            params![date, repo_id, summary],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        Ok(Some(tally))
+    }
+
+    /// Current summaries with feedback, most downvoted (net) first; `date` limits the report to one day
+    pub fn get_summary_feedback_report(&self, date: Option<&str>, limit: usize) -> Result<Vec<SummaryFeedbackEntry>> {
+        let conn = self.lock("get_summary_feedback_report", || format!("date={:?} limit={}", date, limit));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT CAST(f.date AS VARCHAR), f.repo_id, r.repo_name, r.korean_summary,
+                      COUNT(*) FILTER (WHERE f.vote > 0), COUNT(*) FILTER (WHERE f.vote < 0),
+                      CAST(MAX(f.created_at) AS VARCHAR)
+               FROM summary_feedback f
+               JOIN trending_repos r
+                 ON r.date = f.date AND r.repo_id = f.repo_id AND r.korean_summary = f.summary
+               WHERE CAST(? AS VARCHAR) IS NULL OR f.date = CAST(? AS DATE)
+               GROUP BY f.date, f.repo_id, r.repo_name, r.korean_summary
+               ORDER BY COUNT(*) FILTER (WHERE f.vote < 0) - COUNT(*) FILTER (WHERE f.vote > 0) DESC,
+                        MAX(f.created_at) DESC
+               LIMIT ?"#
+        )?;
+        let mut entries = stmt.query_map(params![date, date, limit as i64], |row| {
+            Ok(SummaryFeedbackEntry {
+                date: row.get(0)?,
+                repo_id: row.get(1)?,
+                repo_name: row.get(2)?,
+                korean_summary: row.get(3)?,
+                upvotes: row.get(4)?,
+                downvotes: row.get(5)?,
+                comments: vec![],
+                last_feedback_at: row.get(6)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        let mut comments = conn.prepare(
+            r#"SELECT comment FROM summary_feedback
+               WHERE date = CAST(? AS DATE) AND repo_id = ? AND summary = ? AND comment IS NOT NULL
+               ORDER BY created_at DESC
+               LIMIT 5"#
+        )?;
+        for entry in &mut entries {
+            entry.comments = comments
+                .query_map(params![entry.date, entry.repo_id, entry.korean_summary], |row| row.get(0))?
+                .collect::<Result<Vec<String>, _>>()?;
+        }
+
+        Ok(entries)
+    }
+
+    /// The share token of `date`, created as `token` when the day has none yet
+    pub fn get_or_create_share_token(&self, date: &str, token: &str) -> Result<String> {
+        let conn = self.lock("get_or_create_share_token", || format!("date={:?}", date));
+
+        let existing: Option<String> = conn.query_row(
+            "SELECT token FROM share_links WHERE date = ? ORDER BY created_at LIMIT 1",
+            params![date],
+            |row| row.get(0),
+        ).optional()?;
+        if let Some(existing) = existing {
+            return Ok(existing);
+        }
+
+        conn.execute("INSERT INTO share_links (token, date) VALUES (?, ?)", params![token, date])?;
+        Ok(token.to_string())
+    }
+
+    /// Date a share token points at
+    pub fn get_share_link_date(&self, token: &str) -> Result<Option<String>> {
+        let conn = self.lock("get_share_link_date", || format!("token={:?}", token));
+
+        let date = conn.query_row(
+            "SELECT CAST(date AS VARCHAR) FROM share_links WHERE token = ?",
+            params![token],
+            |row| row.get(0),
+        ).optional()?;
+
+        Ok(date)
+    }
+
+    /// Add a run's usage to the day's totals; reruns of the same day accumulate
+    pub fn add_api_usage(&self, date: &str, usage: &[(String, String, UsageCounts)]) -> Result<()> {
+        let conn = self.lock("add_api_usage", || format!("date={:?} entries={}", date, usage.len()));
+
+        for (provider, model, counts) in usage {
+            conn.execute(
+                r#"INSERT INTO api_usage (date, provider, model, requests, prompt_tokens, completion_tokens)
+                   VALUES (?, ?, ?, ?, ?, ?)
+                   ON CONFLICT (date, provider, model) DO UPDATE SET
+                       requests = api_usage.requests + excluded.requests,
+                       prompt_tokens = api_usage.prompt_tokens + excluded.prompt_tokens,
+                       completion_tokens = api_usage.completion_tokens + excluded.completion_tokens"#,
+                params![date, provider, model, counts.requests, counts.prompt_tokens, counts.completion_tokens],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn record_audit(
+        &self,
+        actor: &str,
+        client_ip: Option<&str>,
+        method: &str,
+        path: &str,
+        query: Option<&str>,
+        status: u16,
+    ) -> Result<()> {
+        let conn = self.lock("record_audit", || format!("method={:?} path={:?} status={}", method, path, status));
+
+        conn.execute(
+            r#"INSERT INTO audit_log (actor, client_ip, method, path, query, status)
+               VALUES (?, ?, ?, ?, ?, ?)"#,
+            params![actor, client_ip, method, path, query, status as i32],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn enqueue_job(&self, kind: &str, date: Option<&str>, priority: i32, run_id: Option<&str>) -> Result<Job> {
+        let conn = self.lock("enqueue_job", || format!("kind={:?} date={:?} priority={} run_id={:?}", kind, date, priority, run_id));
+
+        let job = conn.query_row(
+            &format!(
+                r#"INSERT INTO jobs (kind, date, priority, status, run_id) VALUES (?, ?, ?, ?, ?)
+                   RETURNING {}"#,
+                JOB_COLUMNS
+            ),
+            params![kind, date, priority, JOB_QUEUED, run_id],
+            job_from_row,
+        )?;
+
+        Ok(job)
+    }
+
+    /// A queued or running job of `kind` for `date`, so the same work isn't queued twice
+    pub fn find_active_job(&self, kind: &str, date: Option<&str>) -> Result<Option<Job>> {
+        let conn = self.lock("find_active_job", || format!("kind={:?} date={:?}", kind, date));
+
+        let job = conn.query_row(
+            &format!(
+                r#"SELECT {} FROM jobs
+                   WHERE kind = ? AND date IS NOT DISTINCT FROM CAST(? AS VARCHAR) AND status IN (?, ?)
+                   ORDER BY id LIMIT 1"#,
+                JOB_COLUMNS
+            ),
+            params![kind, date, JOB_QUEUED, JOB_RUNNING],
+            job_from_row,
+        ).optional()?;
+
+        Ok(job)
+    }
+
+    /// Mark the highest-priority, oldest queued job running and return it
+    pub fn claim_next_job(&self) -> Result<Option<Job>> {
+        let mut conn = self.lock("claim_next_job", String::new);
+        let tx = conn.transaction()?;
+
+        let id: Option<i64> = tx.query_row(
+            "SELECT id FROM jobs WHERE status = ? ORDER BY priority DESC, id LIMIT 1",
+            params![JOB_QUEUED],
+            |row| row.get(0),
+        ).optional()?;
+        let Some(id) = id else {
+            return Ok(None);
+        };
+
+        let job = tx.query_row(
+            &format!(
+                "UPDATE jobs SET status = ?, started_at = current_timestamp WHERE id = ? RETURNING {}",
+                JOB_COLUMNS
+            ),
+            params![JOB_RUNNING, id],
+            job_from_row,
+        )?;
+        tx.commit()?;
+
+        Ok(Some(job))
+    }
+
+    pub fn finish_job(&self, id: i64, result: std::result::Result<&str, &str>) -> Result<()> {
+        let conn = self.lock("finish_job", || format!("id={} ok={}", id, result.is_ok()));
+
+        let (status, result, error) = match result {
+            Ok(result) => (JOB_DONE, Some(result), None),
+            Err(error) => (JOB_FAILED, None, Some(error)),
+        };
+        conn.execute(
+            "UPDATE jobs SET status = ?, result = ?, error = ?, finished_at = current_timestamp WHERE id = ?",
+            params![status, result, error, id],
+        )?;
+
+        Ok(())
+    }
+
+    /// Cancel a job that hasn't started; false when it doesn't exist or already ran
+    pub fn cancel_job(&self, id: i64) -> Result<bool> {
+        let conn = self.lock("cancel_job", || format!("id={}", id));
+
+        let updated = conn.execute(
+            "UPDATE jobs SET status = ?, finished_at = current_timestamp WHERE id = ? AND status = ?",
+            params![JOB_CANCELLED, id, JOB_QUEUED],
+        )?;
+
+        Ok(updated > 0)
+    }
+
+    /// Put jobs a previous process was running when it stopped back in the queue
+    pub fn requeue_interrupted_jobs(&self) -> Result<usize> {
+        let conn = self.lock("requeue_interrupted_jobs", String::new);
+
+        let requeued = conn.execute(
+            "UPDATE jobs SET status = ?, started_at = NULL WHERE status = ?",
+            params![JOB_QUEUED, JOB_RUNNING],
+        )?;
+
+        Ok(requeued)
+    }
+
+    pub fn get_job(&self, id: i64) -> Result<Option<Job>> {
+        let conn = self.lock("get_job", || format!("id={}", id));
+
+        let job = conn.query_row(
+            &format!("SELECT {} FROM jobs WHERE id = ?", JOB_COLUMNS),
+            params![id],
+            job_from_row,
+        ).optional()?;
+
+        Ok(job)
+    }
+
+    /// Newest jobs first, optionally only those with one status
+    pub fn get_jobs(&self, status: Option<&str>, limit: usize) -> Result<Vec<Job>> {
+        let conn = self.lock("get_jobs", || format!("status={:?} limit={}", status, limit));
+
+        let mut stmt = conn.prepare(&format!(
+            r#"SELECT {} FROM jobs
+               WHERE CAST(? AS VARCHAR) IS NULL OR status = ?
+               ORDER BY id DESC
+               LIMIT ?"#,
+            JOB_COLUMNS
+        ))?;
+
+        let jobs = stmt.query_map(params![status, status, limit as i64], job_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(jobs)
+    }
+
+    /// Collection and backfill jobs that finished in the last `days` days, newest first, each with
+    /// the date its run collected into
+    pub fn get_finished_collections(&self, days: i64) -> Result<Vec<(Job, Option<String>)>> {
+        let conn = self.lock("get_finished_collections", || format!("days={}", days));
+
+        let mut stmt = conn.prepare(&format!(
+            r#"SELECT {}, (SELECT CAST(r.date AS VARCHAR) FROM collection_runs r WHERE r.run_id = jobs.run_id)
+               FROM jobs
+               WHERE kind IN (?, ?) AND status IN (?, ?, ?)
+                 AND finished_at >= current_timestamp - INTERVAL {} DAY
+               ORDER BY id DESC"#,
+            JOB_COLUMNS, days
+        ))?;
+
+        let jobs = stmt.query_map(
+            params![JOB_COLLECT, JOB_BACKFILL, JOB_DONE, JOB_FAILED, JOB_CANCELLED],
+            |row| Ok((job_from_row(row)?, row.get(11)?)),
+        )?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(jobs)
+    }
+
+    /// Repos of `date` that have no summary yet, in rank order
+    /// `(repo_id, repo_name, summary_style)` of the repos still without a summary
+    pub fn get_unsummarized_repos(&self, date: &str) -> Result<Vec<(i64, String, Option<String>)>> {
+        let conn = self.lock("get_unsummarized_repos", || format!("date={:?}", date));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT repo_id, repo_name, summary_style FROM trending_repos
+               WHERE date = ? AND korean_summary IS NULL AND quality_flag IS DISTINCT FROM 'dropped'
+               ORDER BY rank NULLS LAST, repo_id"#
+        )?;
+
+        let repos = stmt.query_map(params![date], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(repos)
+    }
+
+    /// Flush the write-ahead log into the database file, which fails unless the file is writable
+    pub fn check_writable(&self) -> Result<()> {
+        let conn = self.lock("check_writable", String::new);
+        conn.execute_batch("CHECKPOINT")?;
+        Ok(())
+    }
+
+    pub fn save_collection_event(&self, status: &CollectionStatus) -> Result<()> {
+        let conn = self.lock("save_collection_event", || format!("run_id={:?} phase={:?}", status.run_id, status.phase));
+        let repo = status.repo.as_ref();
+
+        conn.execute(
+            r#"INSERT INTO collection_events
+                (run_id, is_running, phase, message, current_count, total_count, repo_event, repo_id, repo_name, repo_error)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+            params![
+                status.run_id,
+                status.is_running,
+                status.phase,
+                status.message,
+                status.current_count as i64,
+                status.total_count as i64,
+                repo.map(|r| r.event.as_str()),
+                repo.map(|r| r.repo_id),
+                repo.map(|r| r.repo_name.as_str()),
+                repo.and_then(|r| r.error.as_deref()),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Progress events of a run in the order they were sent, starting after event `after`
+    pub fn get_collection_events(&self, run_id: &str, after: Option<i64>, limit: usize) -> Result<Vec<CollectionEvent>> {
+        let conn = self.lock("get_collection_events", || format!("run_id={:?} after={:?} limit={}", run_id, after, limit));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT id, CAST(created_at AS VARCHAR), run_id, is_running, phase, message, current_count,
+                      total_count, repo_event, repo_id, repo_name, repo_error
+               FROM collection_events
+               WHERE run_id = ? AND id > ?
+               ORDER BY id
+               LIMIT ?"#
+        )?;
+
+        let events = stmt.query_map(params![run_id, after.unwrap_or(0), limit as i64], |row| {
+            let repo_event: Option<String> = row.get(8)?;
+            let repo = match repo_event {
+                Some(event) => Some(RepoProgress {
+                    event,
+                    repo_id: row.get::<_, Option<i64>>(9)?.unwrap_or(0),
+                    repo_name: row.get::<_, Option<String>>(10)?.unwrap_or_default(),
+                    error: row.get(11)?,
+                    partial: None,
+                }),
+                None => None,
+            };
+            Ok(CollectionEvent {
+                id: row.get(0)?,
+                created_at: row.get(1)?,
+                status: CollectionStatus {
+                    run_id: row.get(2)?,
+                    is_running: row.get(3)?,
+                    phase: row.get(4)?,
+                    message: row.get(5)?,
+                    current_count: row.get::<_, i64>(6)? as usize,
+                    total_count: row.get::<_, i64>(7)? as usize,
+                    repo,
+                },
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(events)
+    }
+
+    pub fn save_run_cutoff(&self, date: &str, run_id: &str, cutoff: &RunCutoff) -> Result<()> {
+        let parse_failures = (!cutoff.parse_failures.is_empty())
+            .then(|| serde_json::to_string(&cutoff.parse_failures))
+            .transpose()?;
+        let circuit_breaks = (!cutoff.circuit_breaks.is_empty())
+            .then(|| serde_json::to_string(&cutoff.circuit_breaks))
+            .transpose()?;
+        let conn = self.lock("save_run_cutoff", || format!("date={:?} run_id={:?}", date, run_id));
+
+        conn.execute(
+            r#"INSERT OR REPLACE INTO collection_runs
+                (run_id, date, candidates, kept, max_repos, min_score, parse_failures, circuit_breaks)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?)"#,
+            params![
+                run_id,
+                date,
+                cutoff.candidates as i64,
+                cutoff.kept as i64,
+                cutoff.max_repos.map(|n| n as i64),
+                cutoff.min_score,
+                parse_failures,
+                circuit_breaks,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Candidates seen and kept by the latest run into `date`
+    pub fn get_run_cutoff(&self, date: &str) -> Result<Option<RunCutoff>> {
+        let conn = self.lock("get_run_cutoff", || format!("date={:?}", date));
+
+        let cutoff = conn.query_row(
+            r#"SELECT candidates, kept, max_repos, min_score, parse_failures, circuit_breaks
+               FROM collection_runs
+               WHERE date = ?
+               ORDER BY created_at DESC
+               LIMIT 1"#,
+            params![date],
+            |row| {
+                Ok(RunCutoff {
+                    candidates: row.get::<_, i64>(0)? as usize,
+                    kept: row.get::<_, i64>(1)? as usize,
+                    max_repos: row.get::<_, Option<i64>>(2)?.map(|n| n as usize),
+                    min_score: row.get(3)?,
+                    parse_failures: row
+                        .get::<_, Option<String>>(4)?
+                        .and_then(|f| serde_json::from_str(&f).ok())
+                        .unwrap_or_default(),
+                    circuit_breaks: row
+                        .get::<_, Option<String>>(5)?
+                        .and_then(|b| serde_json::from_str(&b).ok())
+                        .unwrap_or_default(),
+                })
+            },
+        ).optional()?;
+
+        Ok(cutoff)
+    }
+
+    pub fn save_run_report(&self, report: &CollectionReport) -> Result<()> {
+        let json = serde_json::to_string(report)?;
+        let conn = self.lock("save_run_report", || format!("run_id={:?}", report.run_id));
+
+        conn.execute(
+            r#"INSERT OR REPLACE INTO run_reports (run_id, date, kind, report)
+               VALUES (?, ?, ?, ?)"#,
+            params![report.run_id, report.date, report.kind, json],
+        )?;
+
+        Ok(())
+    }
+
+    /// `None` until the run finished
+    pub fn get_run_report(&self, run_id: &str) -> Result<Option<CollectionReport>> {
+        let conn = self.lock("get_run_report", || format!("run_id={:?}", run_id));
+
+        let json: Option<String> = conn
+            .query_row("SELECT report FROM run_reports WHERE run_id = ?", params![run_id], |row| row.get(0))
+            .optional()?;
+
+        Ok(json.map(|json| serde_json::from_str(&json)).transpose()?)
+    }
+
+    /// Whether the latest run that collected into `date` went wrong: its job failed midway, or its
+    /// report counts repos that failed to be stored or ran into errors (e.g. summaries left out
+    /// while the LLM was down)
+    pub fn last_collection_failed(&self, date: &str) -> Result<bool> {
+        let conn = self.lock("last_collection_failed", || format!("date={:?}", date));
+
+        let last: Option<(Option<String>, Option<String>)> = conn.query_row(
+            r#"SELECT j.status, r.report
+               FROM collection_runs c
+               LEFT JOIN jobs j ON j.run_id = c.run_id
+               LEFT JOIN run_reports r ON r.run_id = c.run_id
+               WHERE c.date = ?
+               ORDER BY c.created_at DESC
+               LIMIT 1"#,
+            params![date],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional()?;
+
+        let Some((status, report)) = last else {
+            return Ok(false);
+        };
+        let report: Option<CollectionReport> = report.map(|json| serde_json::from_str(&json)).transpose()?;
+        Ok(status.as_deref() == Some(JOB_FAILED) || report.is_some_and(|r| r.failed > 0 || r.errored > 0))
+    }
+
+    /// Drop progress events older than `days`; returns the events deleted
+    pub fn prune_collection_events(&self, days: i64) -> Result<usize> {
+        let conn = self.lock("prune_collection_events", || format!("days={}", days));
+
+        let deleted = conn.execute(
+            &format!("DELETE FROM collection_events WHERE created_at < current_timestamp - INTERVAL {} DAY", days),
+            [],
+        )?;
+
+        Ok(deleted)
+    }
+
+    /// Newest audit entries first, optionally only those of one actor
+    pub fn get_audit_log(&self, actor: Option<&str>, limit: usize) -> Result<Vec<AuditEntry>> {
+        let conn = self.lock("get_audit_log", || format!("actor={:?} limit={}", actor, limit));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT id, CAST(created_at AS VARCHAR), actor, client_ip, method, path, query, status
+               FROM audit_log
+               WHERE CAST(? AS VARCHAR) IS NULL OR actor = ?
+               ORDER BY id DESC
+               LIMIT ?"#
+        )?;
+
+        let entries = stmt.query_map(params![actor, actor, limit as i64], |row| {
+            Ok(AuditEntry {
+                id: row.get(0)?,
+                created_at: row.get(1)?,
+                actor: row.get(2)?,
+                client_ip: row.get(3)?,
+                method: row.get(4)?,
+                path: row.get(5)?,
+                query: row.get(6)?,
+                status: row.get(7)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(entries)
+    }
+
+    /// Per-day usage totals of the last `days` days, newest first; the handler fills in the cost
+    pub fn get_daily_usage(&self, days: i64) -> Result<Vec<DailyUsage>> {
+        let conn = self.lock("get_daily_usage", || format!("days={}", days));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT CAST(date AS VARCHAR),
+                      CAST(COALESCE(SUM(requests) FILTER (WHERE provider = ?), 0) AS BIGINT),
+                      CAST(COALESCE(SUM(prompt_tokens), 0) AS BIGINT),
+                      CAST(COALESCE(SUM(completion_tokens), 0) AS BIGINT),
+                      CAST(COALESCE(SUM(requests) FILTER (WHERE provider = ?), 0) AS BIGINT)
+               FROM api_usage
+               WHERE date > current_date - CAST(? AS INTEGER)
+               GROUP BY date
+               ORDER BY date DESC"#
+        )?;
+
+        let rows = stmt.query_map(params![PROVIDER_LLM, PROVIDER_GITHUB, days], |row| {
+            Ok(DailyUsage {
+                date: row.get(0)?,
+                llm_requests: row.get(1)?,
+                prompt_tokens: row.get(2)?,
+                completion_tokens: row.get(3)?,
+                github_requests: row.get(4)?,
+                estimated_cost_usd: 0.0,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    pub fn save_collection_repo(&self, repo: &CollectionRepo) -> Result<()> {
+        let conn = self.lock("save_collection_repo", || format!("collection_id={} date={:?}", repo.collection_id, repo.date));
+
+        conn.execute(
+            r#"INSERT INTO collection_repos (date, collection_id, collection_name, repo_id, repo_name,
+                   rank, stars_growth, total_stars, run_id)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+               ON CONFLICT (date, collection_id, repo_id) DO UPDATE SET
+                   collection_name = excluded.collection_name,
+                   repo_name = excluded.repo_name,
+                   rank = excluded.rank,
+                   stars_growth = excluded.stars_growth,
+                   total_stars = excluded.total_stars,
+                   run_id = excluded.run_id"#,
+            params![
+                repo.date,
+                repo.collection_id,
+                repo.collection_name,
+                repo.repo_id,
+                repo.repo_name,
+                repo.rank,
+                repo.stars_growth,
+                repo.total_stars,
+                repo.run_id,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_collection_repos(&self, collection_id: i64, date: &str) -> Result<Vec<CollectionRepo>> {
+        let conn = self.lock("get_collection_repos", || format!("collection_id={} date={:?}", collection_id, date));
+
+        let mut stmt = conn.prepare(
+            r#"SELECT CAST(date AS VARCHAR), collection_id, collection_name, repo_id, repo_name,
+                      rank, stars_growth, total_stars, run_id
+               FROM collection_repos WHERE collection_id = ? AND date = ? ORDER BY rank"#
+        )?;
+
+        let repos = stmt.query_map(params![collection_id, date], |row| {
+            Ok(CollectionRepo {
+                date: row.get(0)?,
+                collection_id: row.get(1)?,
+                collection_name: row.get(2)?,
+                repo_id: row.get(3)?,
+                repo_name: row.get(4)?,
+                rank: row.get(5)?,
+                stars_growth: row.get(6)?,
+                total_stars: row.get(7)?,
+                run_id: row.get(8)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(repos)
+    }
+
+    /// Names of the tables and views in the main schema, which ad-hoc queries may read
+    pub fn table_names(&self) -> Result<Vec<String>> {
+        let conn = self.lock("table_names", String::new);
+        let mut stmt = conn.prepare(
+            "SELECT table_name FROM information_schema.tables WHERE table_schema = 'main' ORDER BY table_name"
+        )?;
+        let names = stmt.query_map([], |row| row.get(0))?.collect::<Result<Vec<String>, _>>()?;
+        Ok(names)
+    }
+
+    /// Run an ad-hoc query (checked by `services::adhoc::check_read_only`) in a transaction that is
+    /// always rolled back, keeping its first `max_rows` rows. DuckDB is interrupted once the query
+    /// has run for `timeout`. The query gets a connection of its own, so the shared one is neither
+    /// held meanwhile nor left in the transaction; callers should run it on a blocking thread.
+    pub fn run_adhoc_query(&self, sql: &str, max_rows: usize, timeout: Duration) -> Result<AdhocQueryResult> {
+        let conn = self.lock("run_adhoc_query", || sql.chars().take(200).collect()).try_clone()?;
+        let started = Instant::now();
+
+        let interrupt = conn.interrupt_handle();
+        let (finished, watch) = std::sync::mpsc::channel::<()>();
+        let watchdog = std::thread::spawn(move || {
+            if let Err(std::sync::mpsc::RecvTimeoutError::Timeout) = watch.recv_timeout(timeout) {
+                interrupt.interrupt();
+            }
+        });
+
+        conn.execute_batch("BEGIN TRANSACTION")?;
+        let result = read_adhoc_rows(&conn, sql, max_rows);
+        let _ = finished.send(());
+        let _ = watchdog.join();
+        // The connection is dropped with any transaction left open, so a failed rollback isn't fatal
+        if let Err(e) = conn.execute_batch("ROLLBACK") {
+            warn!("Failed to roll back ad-hoc query: {}", e);
+        }
+
+        let elapsed = started.elapsed();
+        let (columns, rows, truncated) = match result {
+            Ok(result) => result,
+            Err(_) if elapsed >= timeout => anyhow::bail!("Query cancelled after {} seconds", timeout.as_secs()),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(AdhocQueryResult {
+            columns,
+            rows,
+            truncated,
+            elapsed_ms: elapsed.as_millis() as u64,
+        })
+    }
+}
+
+impl Clone for Database {
+    fn clone(&self) -> Self {
+        Database {
+            conn: Arc::clone(&self.conn),
+            remote: self.remote.clone(),
+            slow_query_threshold: self.slow_query_threshold,
+        }
+    }
+}
+
+fn language_trend_from_row(row: &duckdb::Row<'_>) -> duckdb::Result<LanguageTrend> {
+    Ok(LanguageTrend {
+        date: row.get(0)?,
+        language: row.get(1)?,
+        normalized_percentage: row.get(2)?,
+        repo_count: row.get(3)?,
+        run_id: row.get(4)?,
+    })
+}
+
+/// Repos matching a free-text query: the raw text anywhere in the name, description or summary,
+/// or its normalized (and, for Korean, romanized) form in `search_text`
+fn search_condition(query: &str) -> (String, Vec<Value>) {
+    let pattern = Value::Text(format!("%{}%", query));
+    let mut condition = "repo_name ILIKE ? OR description ILIKE ? OR korean_summary ILIKE ?".to_string();
+    let mut values = vec![pattern.clone(), pattern.clone(), pattern];
+    for term in search::query_terms(query) {
+        condition.push_str(" OR search_text LIKE ?");
+        values.push(Value::Text(format!("%{}%", term)));
+    }
+    (format!("({})", condition), values)
+}
+
+/// Condition and values selecting one stored repo for `refresh_search_text`
+const SEARCH_ROW: &str = "date = CAST(? AS DATE) AND repo_id = ?";
+
+fn search_row(date: &str, repo_id: i64) -> Vec<Value> {
+    vec![Value::Text(date.to_string()), Value::BigInt(repo_id)]
+}
+
+/// Recompute `search_text` of the stored repos matching `condition` from their searchable fields
+fn refresh_search_text(conn: &Connection, condition: &str, values: Vec<Value>) -> duckdb::Result<()> {
+    let mut stmt = conn.prepare(&format!(
+        r#"SELECT CAST(date AS VARCHAR), repo_id, repo_name, description, description_ko, korean_summary
+           FROM trending_repos WHERE {}"#,
+        condition
+    ))?;
+    let rows = stmt.query_map(params_from_iter(values), |row| {
+        let fields: [Option<String>; 4] = [row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?];
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, fields))
+    })?.collect::<duckdb::Result<Vec<_>>>()?;
+
+    for (date, repo_id, fields) in rows {
+        let fields: Vec<Option<&str>> = fields.iter().map(Option::as_deref).collect();
+        conn.execute(
+            "UPDATE trending_repos SET search_text = ? WHERE date = CAST(? AS DATE) AND repo_id = ?",
+            params![search::search_text(&fields), date, repo_id],
+        )?;
+    }
+    Ok(())
+}
+
+type AdhocRows = (Vec<String>, Vec<Vec<serde_json::Value>>, bool);
+
+/// Column names, the first `max_rows` rows and whether there were more
+fn read_adhoc_rows(conn: &Connection, sql: &str, max_rows: usize) -> duckdb::Result<AdhocRows> {
+    let mut stmt = conn.prepare(sql)?;
+    let mut rows = stmt.query([])?;
+    let columns = rows.as_ref().map(|stmt| stmt.column_names()).unwrap_or_default();
+
+    let mut values = Vec::new();
+    while let Some(row) = rows.next()? {
+        if values.len() == max_rows {
+            return Ok((columns, values, true));
+        }
+        values.push(
+            (0..columns.len())
+                .map(|i| row.get::<_, Value>(i).map(json_value))
+                .collect::<duckdb::Result<Vec<_>>>()?,
+        );
+    }
+    Ok((columns, values, false))
+}
+
+/// A DuckDB value as JSON; 128-bit integers and decimals that JSON numbers cannot hold exactly
+/// are written as text
+fn json_value(value: Value) -> serde_json::Value {
+    use serde_json::json;
+
+    match value {
+        Value::Null => serde_json::Value::Null,
+        Value::Boolean(v) => json!(v),
+        Value::TinyInt(v) => json!(v),
+        Value::SmallInt(v) => json!(v),
+        Value::Int(v) => json!(v),
+        Value::BigInt(v) => json!(v),
+        Value::UTinyInt(v) => json!(v),
+        Value::USmallInt(v) => json!(v),
+        Value::UInt(v) => json!(v),
+        Value::UBigInt(v) => json!(v),
+        Value::HugeInt(v) => json!(v.to_string()),
+        Value::Float(v) => json!(v),
+        Value::Double(v) => json!(v),
+        Value::Decimal(v) => json!(v.to_string()),
+        Value::Text(v) | Value::Enum(v) => json!(v),
+        Value::Date32(days) => chrono::NaiveDate::from_ymd_opt(1970, 1, 1)
+            .and_then(|epoch| epoch.checked_add_signed(chrono::Duration::days(days as i64)))
+            .map_or(serde_json::Value::Null, |date| json!(date.format("%Y-%m-%d").to_string())),
+        Value::Timestamp(unit, v) => {
+            let micros = match unit {
+                TimeUnit::Second => v.saturating_mul(1_000_000),
+                TimeUnit::Millisecond => v.saturating_mul(1_000),
+                TimeUnit::Microsecond => v,
+                TimeUnit::Nanosecond => v / 1_000,
+            };
+            chrono::DateTime::from_timestamp_micros(micros)
+                .map_or(serde_json::Value::Null, |t| json!(t.naive_utc().format("%Y-%m-%d %H:%M:%S%.f").to_string()))
+        }
+        Value::List(items) => serde_json::Value::Array(items.into_iter().map(json_value).collect()),
+        other => json!(format!("{:?}", other)),
+    }
+}
+
+fn trending_repo_from_row(row: &duckdb::Row<'_>) -> duckdb::Result<TrendingRepo> {
+    Ok(TrendingRepo {
+        date: row.get(0)?,
+        repo_id: row.get(1)?,
+        repo_name: row.get(2)?,
+        primary_language: row.get(3)?,
+        description: row.get(4)?,
+        korean_summary: row.get(5)?,
+        stars: row.get(6)?,
+        forks: row.get(7)?,
+        pull_requests: row.get(8)?,
+        pushes: row.get(9)?,
+        total_score: row.get(10)?,
+        contributor_logins: row.get(11)?,
+        collection_names: row.get(12)?,
+        computed_score: row.get(13)?,
+        summary_status: row.get(14)?,
+        run_id: row.get(15)?,
+        readme_updated: row.get(16)?,
+        readme_change_note: row.get(17)?,
+        summary_features: row.get(18)?,
+        summary_tech_stack: row.get(19)?,
+        audience: row.get(20)?,
+        difficulty: row.get(21)?,
+        rank: row.get(22)?,
+        owner: row.get(23)?,
+        total_stars: row.get(24)?,
+        lifecycle: row.get(25)?,
+        description_language: row.get(26)?,
+        description_ko: row.get(27)?,
+        summary_tags: row.get(28)?,
+        category: row.get(29)?,
+        homepage: row.get(30)?,
+        doc_links: row.get(31)?,
+        quality_flag: row.get(32)?,
+        policy_flags: row.get(33)?,
+        summary_model: row.get(34)?,
+    })
+}
+
+fn job_from_row(row: &duckdb::Row<'_>) -> duckdb::Result<Job> {
+    Ok(Job {
+        id: row.get(0)?,
+        kind: row.get(1)?,
+        date: row.get(2)?,
+        priority: row.get(3)?,
+        status: row.get(4)?,
+        run_id: row.get(5)?,
+        result: row.get(6)?,
+        error: row.get(7)?,
+        created_at: row.get(8)?,
+        started_at: row.get(9)?,
+        finished_at: row.get(10)?,
+    })
+}
+
+fn star_milestone_from_row(row: &duckdb::Row<'_>) -> duckdb::Result<StarMilestone> {
+    Ok(StarMilestone {
+        date: row.get(0)?,
+        repo_id: row.get(1)?,
+        repo_name: row.get(2)?,
+        milestone: row.get(3)?,
+        previous_stars: row.get(4)?,
+        total_stars: row.get(5)?,
+        previous_date: row.get(6)?,
+    })
+}
+
+fn language_alert_from_row(row: &duckdb::Row<'_>) -> duckdb::Result<LanguageAlert> {
+    Ok(LanguageAlert {
+        date: row.get(0)?,
+        language: row.get(1)?,
+        share: row.get(2)?,
+        trailing_mean: row.get(3)?,
+        trailing_stddev: row.get(4)?,
+        z_score: row.get(5)?,
+    })
+}
+
+/// Quoted SQL string literal, for statements like COPY and table functions that take no parameters
+fn sql_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
 }
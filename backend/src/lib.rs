@@ -0,0 +1,7 @@
+pub mod api;
+pub mod config;
+pub mod db;
+pub mod mcp;
+pub mod models;
+pub mod services;
+pub mod telemetry;
@@ -1,105 +1,392 @@
-mod api;
-mod config;
-mod db;
-mod models;
-mod services;
-
+use std::convert::Infallible;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
 use std::sync::Arc;
-use axum::{
-    routing::{get, post},
-    Router,
-};
+use std::time::{Duration, SystemTime};
+use axum_server::tls_rustls::RustlsConfig;
+use axum::{http::{HeaderName, Request}, routing::get, Router, ServiceExt};
+use hyper::body::Incoming;
+use sd_notify::NotifyState;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use tokio::net::UnixListener;
+use tower::{Layer, Service, ServiceExt as _};
 use tokio_cron_scheduler::{Job, JobScheduler};
 use tower_http::cors::{Any, CorsLayer};
-use tokio::sync::broadcast;
-use std::sync::atomic::AtomicBool;
-use tracing::{info, error};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing::{info, warn, error};
+use tracing_subscriber::{fmt::writer::BoxMakeWriter, layer::SubscriberExt, util::SubscriberInitExt};
 
-use crate::api::{AppState, get_trends, get_daily_languages, get_weekly_languages, trigger_collect, sse_progress, health_check};
-use crate::config::Config;
-use crate::db::Database;
-use crate::services::DataCollector;
+use daily_git_brief::{api, mcp, telemetry};
+use daily_git_brief::api::{AppState, ResponseCache, health_check};
+use daily_git_brief::config::{Config, TlsConfig};
+use daily_git_brief::db::Database;
+use daily_git_brief::services::{fixtures, http, selfcheck, DigestSender, Providers};
+use daily_git_brief::services::webhooks::WebhookDispatcher;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    // In MCP stdio mode stdout carries the protocol, so logs go to stderr
+    let mcp_stdio = std::env::args().any(|arg| arg == "--mcp");
+    // `--check` prints the self-check report to stdout and exits
+    let check_only = std::env::args().any(|arg| arg == "--check");
+    let log_writer = if mcp_stdio || check_only {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+
     // Initialize tracing
     tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::new(
             std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
         ))
-        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_writer(log_writer))
+        .with(telemetry::LogTailLayer)
         .init();
 
     info!("Starting Daily-Git-Brief backend");
 
     // Load configuration
-    let config = Config::from_env()?;
+    let mut config = Config::from_env()?;
+    telemetry::log_tail().set_capacity(config.log_tail_lines);
     info!("Configuration loaded");
+    // Upstream URLs are rewritten to the local fixture server in record/replay mode
+    fixtures::install(&mut config, http::build_client(&config)?).await?;
     info!("  OSS Insight URL: {}", config.oss_insight_base_url);
     info!("  DeepSeek URL: {}", config.deepseek_base_url);
     info!("  Language threshold: {}%", config.language_threshold * 100.0);
     info!("  Database path: {}", config.database_path);
+    info!("  Workspaces: {}", config.workspaces.len());
+
+    // The MCP tools only read, so the stdio server never opens the database for writing
+    if mcp_stdio {
+        config.read_only = true;
+    }
 
     // Initialize database
-    let db = Database::new(&config.database_path)?;
-    info!("Database initialized");
+    let mut db = open_database(&config)?;
+    info!("Database initialized{}", if config.read_only { " (read-only)" } else { "" });
+    attach_remote_archive(&mut db, &config);
+
+    if mcp_stdio {
+        return mcp::serve_stdio(mcp::McpServer::new(db)).await;
+    }
+
+    let http_client = http::build_client(&config)?;
+    if check_only {
+        let report = selfcheck::run(&config, &db, &http_client).await;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        std::process::exit(if report.ok { 0 } else { 1 });
+    }
 
     // Setup scheduler for daily collection at UTC 00:00
     let scheduler = JobScheduler::new().await?;
-    
-    let collector_config = config.clone();
-    let collector_db = db.clone();
-    
-    scheduler.add(
-        Job::new_async("0 0 0 * * *", move |_uuid, _l| {
-            let config = collector_config.clone();
-            let db = collector_db.clone();
-            Box::pin(async move {
-                info!("Scheduled data collection starting");
-                let collector = DataCollector::new(&config, db);
-                match collector.collect(None).await {
-                    Ok(count) => info!("Scheduled collection complete: {} repos", count),
-                    Err(e) => error!("Scheduled collection failed: {}", e),
-                }
-            })
-        })?
-    ).await?;
-    
-    scheduler.start().await?;
-    info!("Scheduler started (daily at UTC 00:00)");
+    let providers = Providers::from_config(&config, http_client.clone());
+    let state = AppState::new(db.clone(), config.clone(), new_cache(&config), http_client.clone(), providers);
+    if !config.read_only {
+        state.jobs.spawn_worker(state.clone());
+        schedule_collection(&scheduler, state.clone()).await?;
+    }
 
-    // Create app state
-    let (tx, _rx) = broadcast::channel(100);
-    let state = Arc::new(AppState { 
-        db, 
-        config: config.clone(),
-        progress_tx: tx,
-        is_collecting: Arc::new(AtomicBool::new(false)),
-    });
+    // Every workspace gets its own database, collection job and app state
+    let mut workspace_states = Vec::new();
+    for ws in &config.workspaces {
+        let ws_config = config.for_workspace(ws);
+        let mut ws_db = open_database(&ws_config)?;
+        attach_remote_archive(&mut ws_db, &ws_config);
+        info!("Workspace '{}' initialized ({})", ws.name, ws_config.database_path);
+        let ws_cache = new_cache(&ws_config);
+        let ws_providers = Providers::from_config(&ws_config, http_client.clone());
+        let ws_state = AppState::new(ws_db, ws_config, ws_cache, http_client.clone(), ws_providers);
+        if !config.read_only {
+            ws_state.jobs.spawn_worker(ws_state.clone());
+            schedule_collection(&scheduler, ws_state.clone()).await?;
+        }
+        workspace_states.push(ws_state);
+    }
+
+    // Replicas only serve reads; collection and notifications belong to the writer instance
+    if config.read_only {
+        info!("Read-only mode: scheduler disabled");
+    } else {
+        // Digests and webhooks registered in a workspace are delivered from its own database
+        let notifiers = std::iter::once((config.clone(), db.clone()))
+            .chain(workspace_states.iter().map(|ws| (ws.config.clone(), ws.db.clone())))
+            .collect();
+        spawn_gated_scheduler(scheduler, config.clone(), db.clone(), http_client.clone(), notifiers);
+    }
 
     // Build router
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
-        .allow_headers(Any);
+        .allow_headers(Any)
+        .expose_headers([HeaderName::from_static(api::SERVED_DATE_HEADER)]);
 
-    let app = Router::new()
+    let metrics = telemetry::install()?;
+    let mut app = Router::new()
         .route("/health", get(health_check))
-        .route("/api/trends", get(get_trends))
-        .route("/api/languages/daily", get(get_daily_languages))
-        .route("/api/languages/weekly", get(get_weekly_languages))
-        .route("/api/collect", post(trigger_collect))
-        .route("/api/collect/progress", get(sse_progress))
-        .layer(cors)
-        .with_state(state);
+        .route("/metrics", get(move || std::future::ready(metrics.render())))
+        .merge(api::router(state));
+
+    for ws_state in workspace_states {
+        let prefix = format!("/w/{}", ws_state.config.workspace);
+        app = app.nest(&prefix, api::router(ws_state));
+    }
+
+    let app = axum::middleware::from_fn(api::rewrite_workspace_header).layer(app.layer(cors));
 
     // Start server
+    if let Some(path) = &config.unix_socket_path {
+        info!("Server starting at unix:{}", path);
+        return serve_unix_socket(path, config.unix_socket_mode, app).await;
+    }
+
     let addr = format!("{}:{}", config.server_host, config.server_port);
-    info!("Server starting at http://{}", addr);
-    
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
+    // Peer addresses feed the audit log's client_ip
+    let app = app.into_make_service_with_connect_info::<std::net::SocketAddr>();
+
+    match &config.tls {
+        Some(tls) => {
+            let _ = rustls::crypto::ring::default_provider().install_default();
+            let rustls_config = RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path).await?;
+            spawn_certificate_reload(rustls_config.clone(), tls.clone());
+            info!("Server starting at https://{}", addr);
+
+            let listener = std::net::TcpListener::bind(&addr)?;
+            listener.set_nonblocking(true)?;
+            notify_systemd_ready();
+            axum_server::from_tcp_rustls(listener, rustls_config).serve(app).await?;
+        }
+        None => {
+            info!("Server starting at http://{}", addr);
+            let listener = tokio::net::TcpListener::bind(&addr).await?;
+            notify_systemd_ready();
+            axum::serve(listener, app).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Under a `Type=notify` systemd unit, report readiness once the listener is bound and feed the
+/// watchdog at half its interval for as long as the runtime keeps scheduling tasks, so a hung
+/// process gets restarted. Outside systemd both are no-ops.
+fn notify_systemd_ready() {
+    if let Err(e) = sd_notify::notify(false, &[NotifyState::Ready]) {
+        warn!("Failed to notify systemd of readiness: {}", e);
+    }
+
+    let mut watchdog_usec = 0;
+    if sd_notify::watchdog_enabled(false, &mut watchdog_usec) {
+        let interval = Duration::from_micros(watchdog_usec / 2);
+        info!("systemd watchdog enabled, pinging every {:?}", interval);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                if let Err(e) = sd_notify::notify(false, &[NotifyState::Watchdog]) {
+                    warn!("Failed to ping systemd watchdog: {}", e);
+                }
+            }
+        });
+    }
+}
+
+/// axum 0.7 only serves TCP listeners, so Unix socket connections are driven through hyper
+/// directly. There is no peer address, so the audit log relies on the proxy's X-Forwarded-For.
+async fn serve_unix_socket<S>(path: &str, mode: u32, app: S) -> anyhow::Result<()>
+where
+    S: Service<Request<Incoming>, Response = axum::response::Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    // A socket left behind by an unclean shutdown would make the bind fail
+    if std::fs::metadata(path).is_ok_and(|m| m.file_type().is_socket()) {
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    notify_systemd_ready();
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                warn!("Failed to accept Unix socket connection: {}", e);
+                continue;
+            }
+        };
+
+        let app = app.clone();
+        tokio::spawn(async move {
+            let service = hyper::service::service_fn(move |req: Request<Incoming>| app.clone().oneshot(req));
+            if let Err(e) = hyper_util::server::conn::auto::Builder::new(TokioExecutor::new())
+                .serve_connection_with_upgrades(TokioIo::new(stream), service)
+                .await
+            {
+                warn!("Unix socket connection failed: {}", e);
+            }
+        });
+    }
+}
+
+/// Swap in renewed certificates (e.g. from certbot) without a restart; a broken renewal keeps
+/// the certificates already loaded
+fn spawn_certificate_reload(rustls_config: RustlsConfig, tls: TlsConfig) {
+    fn modified(tls: &TlsConfig) -> Option<(SystemTime, SystemTime)> {
+        let cert = std::fs::metadata(&tls.cert_path).and_then(|m| m.modified()).ok()?;
+        let key = std::fs::metadata(&tls.key_path).and_then(|m| m.modified()).ok()?;
+        Some((cert, key))
+    }
+
+    tokio::spawn(async move {
+        let mut last = modified(&tls);
+        loop {
+            tokio::time::sleep(Duration::from_secs(tls.reload_secs)).await;
+            let current = modified(&tls);
+            if current.is_none() || current == last {
+                continue;
+            }
+            last = current;
+
+            match rustls_config.reload_from_pem_file(&tls.cert_path, &tls.key_path).await {
+                Ok(()) => info!("Reloaded TLS certificate from {}", tls.cert_path),
+                Err(e) => error!("Failed to reload TLS certificate, keeping the current one: {}", e),
+            }
+        }
+    });
+}
+
+/// Start the scheduler once the self-check passes, re-checking every minute until it does, so a
+/// broken deployment does not fail its nightly run unattended. `notifiers` are the config and
+/// database of every workspace whose digests and webhooks get sent.
+fn spawn_gated_scheduler(
+    scheduler: JobScheduler,
+    config: Config,
+    db: Database,
+    http_client: reqwest::Client,
+    notifiers: Vec<(Config, Database)>,
+) {
+    tokio::spawn(async move {
+        loop {
+            let report = selfcheck::run(&config, &db, &http_client).await;
+            if report.ok {
+                break;
+            }
+            for check in report.checks.iter().filter(|c| !c.ok) {
+                error!("Self-check '{}' failed: {}", check.name, check.detail);
+            }
+            warn!("Scheduler held back until the self-check passes; retrying in 60s");
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        }
+
+        if let Err(e) = start_scheduled_jobs(&scheduler, &notifiers, &http_client).await {
+            error!("Failed to start scheduler: {}", e);
+        }
+    });
+}
+
+/// Per-minute digest and webhook jobs of every workspace, then start the scheduler with the
+/// collection jobs added before
+async fn start_scheduled_jobs(
+    scheduler: &JobScheduler,
+    notifiers: &[(Config, Database)],
+    http_client: &reqwest::Client,
+) -> anyhow::Result<()> {
+    for (config, db) in notifiers {
+        schedule_notifications(scheduler, config, db, http_client).await?;
+    }
+
+    scheduler.start().await?;
+    info!("Scheduler started (daily at UTC 00:00)");
+
+    Ok(())
+}
+
+/// Per-minute digest and webhook jobs of one workspace
+async fn schedule_notifications(
+    scheduler: &JobScheduler,
+    config: &Config,
+    db: &Database,
+    http_client: &reqwest::Client,
+) -> anyhow::Result<()> {
+    let workspace = config.workspace.clone();
+
+    // Email digests: check every minute which subscribers are due in their local time
+    match DigestSender::new(config, db.clone())? {
+        Some(sender) => {
+            let sender = Arc::new(sender);
+            let workspace = workspace.clone();
+            scheduler.add(
+                Job::new_async("0 * * * * *", move |_uuid, _l| {
+                    let sender = sender.clone();
+                    let workspace = workspace.clone();
+                    Box::pin(async move {
+                        if let Err(e) = sender.send_due().await {
+                            error!("Email digest run failed ({}): {}", workspace, e);
+                        }
+                    })
+                })?
+            ).await?;
+            info!("Email digests enabled ({})", workspace);
+        }
+        None => info!("SMTP not configured, email digests disabled ({})", workspace),
+    }
+
+    // Scheduled webhooks: same per-minute check, posting a digest instead of each event
+    let dispatcher = WebhookDispatcher::new(config, db.clone(), http_client.clone());
+    scheduler.add(
+        Job::new_async("0 * * * * *", move |_uuid, _l| {
+            let dispatcher = dispatcher.clone();
+            let workspace = workspace.clone();
+            Box::pin(async move {
+                if let Err(e) = dispatcher.send_due() {
+                    error!("Scheduled webhook run failed ({}): {}", workspace, e);
+                }
+            })
+        })?
+    ).await?;
+
+    Ok(())
+}
+
+/// Days missing locally are read from the published archive when `ARCHIVE_QUERY_REMOTE` is on;
+/// an unreachable archive only leaves the local data
+fn attach_remote_archive(db: &mut Database, config: &Config) {
+    let Some(archive) = config.archive.as_ref().filter(|a| a.query_remote) else {
+        return;
+    };
+    match db.attach_remote_archive(archive) {
+        Ok(days) => info!("Remote archive attached ({} days in s3://{}/{})", days, archive.bucket, archive.prefix),
+        Err(e) => warn!("Failed to attach remote archive: {:#}", e),
+    }
+}
+
+fn open_database(config: &Config) -> anyhow::Result<Database> {
+    let db = if config.read_only {
+        Database::open_read_only(&config.database_path)?
+    } else {
+        Database::new(&config.database_path)?
+    };
+    Ok(db.with_slow_query_log(config.slow_query_ms))
+}
+
+fn new_cache(config: &Config) -> Arc<ResponseCache> {
+    Arc::new(ResponseCache::new(Duration::from_secs(config.cache_ttl_secs)))
+}
+
+async fn schedule_collection(scheduler: &JobScheduler, state: Arc<AppState>) -> anyhow::Result<()> {
+    scheduler.add(
+        Job::new_async("0 0 0 * * *", move |_uuid, _l| {
+            let state = state.clone();
+            Box::pin(async move {
+                info!("Scheduled data collection starting ({})", state.config.workspace);
+                // Shares the manual trigger's path, so SSE subscribers see daily runs too
+                match api::start_collection(&state, None) {
+                    Ok(Some(_)) => {}
+                    Ok(None) => warn!("Collection already queued ({}), skipping scheduled run", state.config.workspace),
+                    Err(e) => error!("Failed to queue scheduled collection ({}): {}", state.config.workspace, e),
+                }
+            })
+        })?
+    ).await?;
 
     Ok(())
 }
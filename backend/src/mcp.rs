@@ -0,0 +1,187 @@
+//! Minimal Model Context Protocol server exposing the trends database as tools.
+//! Speaks JSON-RPC 2.0 over stdio (`--mcp`) or streamable HTTP (`POST /mcp`).
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tracing::info;
+
+use crate::db::Database;
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Most repos a tool returns, as on the HTTP API
+const MAX_LIMIT: usize = 100;
+
+#[derive(Clone)]
+pub struct McpServer {
+    db: Database,
+}
+
+impl McpServer {
+    pub fn new(db: Database) -> Self {
+        McpServer { db }
+    }
+
+    /// Handle one JSON-RPC message; notifications produce no response
+    pub fn handle(&self, request: Value) -> Option<Value> {
+        let id = request.get("id").cloned();
+        let method = request.get("method").and_then(Value::as_str).unwrap_or_default();
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        // Requests without an id are notifications (e.g. notifications/initialized)
+        let id = id?;
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": {
+                    "name": "daily-git-brief",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+            "tools/call" => self.call_tool(&params),
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method: {}", method))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    fn call_tool(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let name = params.get("name").and_then(Value::as_str).unwrap_or_default();
+        let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+
+        let output = match name {
+            "get_trends" => {
+                let date = args.get("date").and_then(Value::as_str).unwrap_or(today.as_str());
+                let limit = arg_usize(&args, "limit", 25).clamp(1, MAX_LIMIT);
+                self.db
+                    .get_trending_repos(date)
+                    .map(|repos| json!(repos.into_iter().take(limit).collect::<Vec<_>>()))
+            }
+            "search_repos" => {
+                let Some(query) = args.get("query").and_then(Value::as_str) else {
+                    return Err((INVALID_PARAMS, "search_repos requires 'query'".to_string()));
+                };
+                self.db
+                    .search_repos(query, arg_usize(&args, "limit", 20).clamp(1, MAX_LIMIT))
+                    .map(|repos| json!(repos))
+            }
+            "language_history" => {
+                let Some(language) = args.get("language").and_then(Value::as_str) else {
+                    return Err((INVALID_PARAMS, "language_history requires 'language'".to_string()));
+                };
+                let days = arg_usize(&args, "days", 30).min(365) as i64;
+                self.db
+                    .get_language_history(language, &today, days)
+                    .map(|history| json!(history))
+            }
+            _ => return Err((INVALID_PARAMS, format!("Unknown tool: {}", name))),
+        };
+
+        // Tool failures are reported in-band so the agent can see them
+        Ok(match output {
+            Ok(data) => json!({
+                "content": [{ "type": "text", "text": data.to_string() }],
+                "isError": false,
+            }),
+            Err(e) => json!({
+                "content": [{ "type": "text", "text": e.to_string() }],
+                "isError": true,
+            }),
+        })
+    }
+}
+
+fn arg_usize(args: &Value, key: &str, default: usize) -> usize {
+    args.get(key)
+        .and_then(Value::as_u64)
+        .map(|v| v as usize)
+        .unwrap_or(default)
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "get_trends",
+            "description": "Trending GitHub repositories for a date, with Korean summaries and scores",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "date": { "type": "string", "description": "YYYY-MM-DD, defaults to today (UTC)" },
+                    "limit": { "type": "integer", "description": "Maximum repos to return (default 25, at most 100)" },
+                },
+            },
+        },
+        {
+            "name": "search_repos",
+            "description": "Search all trending history by repo name, description or Korean summary",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string" },
+                    "limit": { "type": "integer", "description": "Maximum repos to return (default 20, at most 100)" },
+                },
+                "required": ["query"],
+            },
+        },
+        {
+            "name": "language_history",
+            "description": "Daily normalized share of a programming language among trending repos",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "language": { "type": "string" },
+                    "days": { "type": "integer", "description": "Days of history (default 30, max 365)" },
+                },
+                "required": ["language"],
+            },
+        },
+    ])
+}
+
+/// Serve newline-delimited JSON-RPC on stdin/stdout until stdin closes
+pub async fn serve_stdio(server: McpServer) -> Result<()> {
+    info!("MCP server listening on stdio");
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => server.handle(request),
+            Err(e) => Some(error_response(Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+
+        if let Some(response) = response {
+            stdout.write_all(response.to_string().as_bytes()).await?;
+            stdout.write_all(b"\n").await?;
+            stdout.flush().await?;
+        }
+    }
+
+    Ok(())
+}
@@ -35,6 +35,77 @@ pub struct OssInsightRow {
     pub collection_names: Option<String>,
 }
 
+/// Row envelope of the `/v1/collections` endpoints
+#[derive(Debug, Deserialize)]
+pub struct OssInsightRowsResponse<T> {
+    pub data: OssInsightRows<T>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OssInsightRows<T> {
+    pub rows: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OssInsightCollectionInfo {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OssInsightCollectionRow {
+    pub repo_id: String,
+    pub repo_name: String,
+    pub current_period_growth: Option<String>,
+    pub current_period_rank: Option<String>,
+    pub total: Option<String>,
+}
+
+/// A trending row with its numbers parsed, see `services::ingest`
+#[derive(Debug, Clone)]
+pub struct TrendCandidate {
+    pub repo_id: i64,
+    pub repo_name: String,
+    pub primary_language: Option<String>,
+    pub description: Option<String>,
+    pub stars: Option<i32>,
+    pub forks: Option<i32>,
+    pub pull_requests: Option<i32>,
+    pub pushes: Option<i32>,
+    pub total_score: Option<f64>,
+    pub contributor_logins: Option<String>,
+    pub collection_names: Option<String>,
+}
+
+/// A collection row with its numbers parsed
+#[derive(Debug, Clone)]
+pub struct CollectionEntry {
+    pub repo_id: i64,
+    pub repo_name: String,
+    /// Stars gained in the collection's current ranking period
+    pub period_growth: Option<i64>,
+    pub period_rank: Option<i32>,
+    pub total_stars: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OssInsightDeveloperResponse {
+    pub data: OssInsightDeveloperData,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OssInsightDeveloperData {
+    pub rows: Vec<OssInsightDeveloperRow>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OssInsightDeveloperRow {
+    #[serde(alias = "actor_login", alias = "developer_login", alias = "user_login")]
+    pub login: String,
+    #[serde(alias = "repo_names", alias = "repos")]
+    pub notable_repos: Option<String>,
+}
+
 // Database models
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrendingRepo {
@@ -51,6 +122,50 @@ pub struct TrendingRepo {
     pub total_score: Option<f64>,
     pub contributor_logins: Option<String>,
     pub collection_names: Option<String>,
+    pub computed_score: Option<f64>,
+    /// "pending", "done", "unavailable", "failed" or "filtered"
+    pub summary_status: Option<String>,
+    /// Collection run that last wrote this row
+    pub run_id: Option<String>,
+    /// Whether the README differs from the repo's previous appearance (unset on first appearance)
+    pub readme_updated: Option<bool>,
+    /// LLM one-liner about what changed in the README
+    pub readme_change_note: Option<String>,
+    /// JSON array of key features from the structured summary
+    pub summary_features: Option<String>,
+    /// JSON array of technologies from the structured summary
+    pub summary_tech_stack: Option<String>,
+    /// Intended audience: library, app, tool, course or research
+    pub audience: Option<String>,
+    /// Beginner-friendliness: beginner, intermediate or advanced
+    pub difficulty: Option<String>,
+    /// Upstream position assigned at collection time (1 = top)
+    pub rank: Option<i32>,
+    /// User or organization owning the repo (`owner/name`)
+    pub owner: Option<String>,
+    /// All-time stargazer count from GitHub (`stars` is the upstream period gain)
+    pub total_stars: Option<i64>,
+    /// "new", "recurring" or "resurfaced" relative to the repo's earlier appearances
+    pub lifecycle: Option<String>,
+    /// Language of `description` guessed from its script ("en", "zh", "ja", ...)
+    pub description_language: Option<String>,
+    /// LLM translation of a description in neither Korean nor English
+    pub description_ko: Option<String>,
+    /// JSON array of topic tags from the structured summary
+    pub summary_tags: Option<String>,
+    /// Field of the project: ai-ml, web, mobile, devtools, infrastructure, data, security,
+    /// systems, games, education or other
+    pub category: Option<String>,
+    /// Project site from the GitHub repo settings
+    pub homepage: Option<String>,
+    /// JSON array of documentation links found in the README
+    pub doc_links: Option<String>,
+    /// "flagged" or "dropped" by the quality filter; dropped repos are left out of the brief
+    pub quality_flag: Option<String>,
+    /// JSON array of the content policy violations moderation found
+    pub policy_flags: Option<String>,
+    /// Model that wrote the summary, as routed by README size
+    pub summary_model: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,6 +174,7 @@ pub struct RepoLanguage {
     pub repo_id: i64,
     pub language: String,
     pub percentage: f64,
+    pub run_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,22 +183,392 @@ pub struct LanguageTrend {
     pub language: String,
     pub normalized_percentage: f64,
     pub repo_count: i32,
+    /// Unset for aggregates spanning several days
+    pub run_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendingDeveloper {
+    pub date: String,
+    pub login: String,
+    pub rank: i32,
+    pub followers: Option<i64>,
+    /// Comma-separated repo names
+    pub notable_repos: Option<String>,
+    pub run_id: Option<String>,
+}
+
+/// A repo's daily rank within an OSS Insight collection, tagged with the collection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionRepo {
+    pub date: String,
+    pub collection_id: i64,
+    pub collection_name: String,
+    pub repo_id: i64,
+    pub repo_name: String,
+    pub rank: i32,
+    /// Stars gained in the collection's current ranking period
+    pub stars_growth: Option<i64>,
+    pub total_stars: Option<i64>,
+    pub run_id: Option<String>,
 }
 
 // API Response models
+#[derive(Debug, Serialize)]
+pub struct CollectionTrendsResponse {
+    pub collection_id: i64,
+    pub collection_name: Option<String>,
+    pub date: String,
+    pub repos: Vec<CollectionRepo>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct TrendingRepoResponse {
     pub rank: usize,
     pub repo_id: i64,
     pub repo_name: String,
+    pub owner: Option<String>,
+    pub total_stars: Option<i64>,
+    pub lifecycle: Option<String>,
     pub github_url: String,
     pub primary_language: Option<String>,
     pub languages: Vec<LanguageInfo>,
     pub description: Option<String>,
+    pub description_language: Option<String>,
+    pub description_ko: Option<String>,
     pub korean_summary: Option<String>,
+    pub summary_status: Option<String>,
+    pub readme_updated: Option<bool>,
+    pub readme_change_note: Option<String>,
+    pub features: Vec<String>,
+    pub tech_stack: Vec<String>,
+    pub tags: Vec<String>,
+    pub category: Option<String>,
+    pub audience: Option<String>,
+    pub difficulty: Option<String>,
+    /// Project site, when the repo sets one
+    pub homepage: Option<String>,
+    /// Documentation links found in the README
+    pub doc_links: Vec<String>,
+    /// "flagged" when the quality filter took the repo for low quality
+    pub quality_flag: Option<String>,
+    /// Whether moderation found the description or README against the content policy
+    pub policy_flagged: bool,
+    /// What moderation found, e.g. `keyword:casino` or a moderation API category
+    pub policy_flags: Vec<String>,
+    /// Model that wrote the summary
+    pub summary_model: Option<String>,
     pub stars: Option<i32>,
     pub forks: Option<i32>,
     pub total_score: Option<f64>,
+    pub computed_score: Option<f64>,
+    /// Problems hit while collecting this repo (missing README, failed summary, ...)
+    pub issues: Vec<RepoIssue>,
+    /// Sources the repo was merged from; empty unless `MERGE_SOURCES` is on
+    pub sources: Vec<RepoSource>,
+}
+
+/// A per-repo collection problem, recorded so the UI can explain missing data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoIssue {
+    /// Pipeline stage: "readme", "summary" or "languages"
+    pub stage: String,
+    /// Machine-readable reason, e.g. "not_found", "rate_limited", "timeout"
+    pub code: String,
+    pub message: Option<String>,
+    pub run_id: Option<String>,
+}
+
+/// External API usage of one day, without cost (see `UsagePricing::estimate`)
+#[derive(Debug, Serialize)]
+pub struct DailyUsage {
+    pub date: String,
+    pub llm_requests: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub github_requests: i64,
+    pub estimated_cost_usd: f64,
+}
+
+/// Queued unit of background work (see `services::jobs`)
+#[derive(Debug, Clone, Serialize)]
+pub struct Job {
+    pub id: i64,
+    /// "collect", "backfill", "summarize_missing", "prune" or "export"
+    pub kind: String,
+    pub date: Option<String>,
+    /// Higher runs first; equal priorities run in queue order
+    pub priority: i32,
+    /// "queued", "running", "done", "failed" or "cancelled"
+    pub status: String,
+    /// Collector run id, known before the job starts so progress events can be matched
+    pub run_id: Option<String>,
+    pub result: Option<String>,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateJobRequest {
+    pub kind: String,
+    pub date: Option<String>,
+    pub priority: Option<i32>,
+}
+
+/// A mutating API call, as recorded by the audit middleware
+#[derive(Debug, Serialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub created_at: String,
+    /// "admin" when the request carried the admin token, otherwise "anonymous"
+    pub actor: String,
+    pub client_ip: Option<String>,
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub status: i32,
+}
+
+/// How the repo sets of a day's trend sources overlap
+#[derive(Debug, Serialize)]
+pub struct SourceComparison {
+    pub date: String,
+    pub sources: Vec<SourceSummary>,
+    /// One entry per pair of sources
+    pub overlaps: Vec<SourceOverlap>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SourceSummary {
+    pub source: String,
+    pub repo_count: usize,
+    /// Repos no other source had that day
+    pub exclusive: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SourceOverlap {
+    pub sources: [String; 2],
+    pub shared: Vec<String>,
+    /// Shared repos over the union of both sources (0.0-1.0)
+    pub jaccard: f64,
+}
+
+/// One source's contribution to a merged candidate's score
+#[derive(Debug, Clone, Serialize)]
+pub struct RepoSource {
+    /// "oss_insight" or "collection:<id>"
+    pub source: String,
+    /// Position in that source's list
+    pub rank: i32,
+    /// The source's own score, when it reports one
+    pub score: Option<f64>,
+    pub weight: f64,
+    /// Weighted, normalized score added to the repo's merged score
+    pub contribution: f64,
+}
+
+/// One cell of the activity calendar; days without a collection are omitted
+#[derive(Debug, Serialize)]
+pub struct DailyActivity {
+    pub date: String,
+    pub repos: i64,
+    pub summaries: i64,
+    /// Language with the largest share that day
+    pub dominant_language: Option<String>,
+}
+
+/// Collected days of one year, for archive navigation
+#[derive(Debug, Serialize)]
+pub struct ArchiveYear {
+    pub year: i32,
+    /// Collected days in the year
+    pub days: usize,
+    /// Newest first
+    pub months: Vec<ArchiveMonth>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveMonth {
+    /// 1-12
+    pub month: u32,
+    /// Repos collected over the month
+    pub repos: i64,
+    /// Newest first
+    pub days: Vec<DailyActivity>,
+}
+
+/// An owner's share of a day's trending repos
+#[derive(Debug, Serialize)]
+pub struct OrgTrend {
+    pub owner: String,
+    pub repo_count: i64,
+    pub total_stars: i64,
+    pub repos: Vec<String>,
+}
+
+/// Two repos trending on the same day with contributors in common
+#[derive(Debug, Serialize)]
+pub struct ContributorOverlap {
+    pub repo_id: i64,
+    pub repo_name: String,
+    pub other_repo_id: i64,
+    pub other_repo_name: String,
+    pub shared_count: i64,
+    pub shared_logins: Vec<String>,
+}
+
+/// What deleting a day would remove, and the token confirming it
+#[derive(Debug, Serialize)]
+pub struct DeleteDayPreview {
+    pub date: String,
+    pub rows: std::collections::BTreeMap<String, i64>,
+    pub confirm_token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteDayResult {
+    pub date: String,
+    pub deleted_rows: usize,
+}
+
+/// One model's summary of a repo in the A/B evaluation sample
+#[derive(Debug, Serialize)]
+pub struct EvaluationSummary {
+    pub repo_id: i64,
+    pub repo_name: String,
+    pub model: String,
+    pub one_liner: String,
+    pub features: Vec<String>,
+    pub tech_stack: Vec<String>,
+}
+
+/// Summaries of the same repo by each evaluated model
+#[derive(Debug, Serialize)]
+pub struct EvaluationPair {
+    pub repo_id: i64,
+    pub repo_name: String,
+    pub summaries: Vec<ModelSummary>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelSummary {
+    pub model: String,
+    pub one_liner: String,
+    pub features: Vec<String>,
+    pub tech_stack: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SummaryFeedbackRequest {
+    /// "up" or "down"
+    pub vote: String,
+    pub comment: Option<String>,
+    /// Day of the rated summary (default today)
+    pub date: Option<String>,
+}
+
+/// Votes on a repo's current summary of one day
+#[derive(Debug, Serialize)]
+pub struct SummaryFeedbackTally {
+    pub date: String,
+    pub repo_id: i64,
+    pub upvotes: i64,
+    pub downvotes: i64,
+    /// The summary was dropped and queued for regeneration
+    pub requeued: bool,
+}
+
+/// A summary with its feedback, as listed in the admin report
+#[derive(Debug, Serialize)]
+pub struct SummaryFeedbackEntry {
+    pub date: String,
+    pub repo_id: i64,
+    pub repo_name: String,
+    pub korean_summary: String,
+    pub upvotes: i64,
+    pub downvotes: i64,
+    /// Newest first, at most five
+    pub comments: Vec<String>,
+    pub last_feedback_at: String,
+}
+
+/// A public, read-only link to one day's brief
+#[derive(Debug, Serialize)]
+pub struct ShareLink {
+    pub token: String,
+    pub date: String,
+    /// Path of the shared brief on this server, e.g. `/share/<token>`
+    pub path: String,
+}
+
+/// A day's brief as served on a share link
+#[derive(Debug, Serialize)]
+pub struct SharedBrief {
+    pub date: String,
+    pub repos: Vec<TrendingRepoResponse>,
+    pub languages: Vec<LanguageTrend>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RequeueSummaryRequest {
+    pub date: String,
+    pub repo_id: i64,
+    /// Style of this one summary; unset fields keep the configured style
+    #[serde(default)]
+    pub style: Option<SummaryStyleOverride>,
+}
+
+/// Per-request changes to the configured summary style (`0` lifts a length limit)
+#[derive(Debug, Default, Deserialize)]
+pub struct SummaryStyleOverride {
+    pub max_chars: Option<usize>,
+    pub max_sentences: Option<usize>,
+    pub tone: Option<String>,
+    pub include_tech_stack: Option<bool>,
+}
+
+/// Position after the last row of an archive page, ordered by date desc then repo id.
+/// Encoded as `YYYY-MM-DD_<repo_id>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveCursor {
+    pub date: String,
+    pub repo_id: i64,
+}
+
+impl ArchiveCursor {
+    pub fn parse(value: &str) -> Option<Self> {
+        let (date, repo_id) = value.split_once('_')?;
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+        Some(ArchiveCursor {
+            date: date.to_string(),
+            repo_id: repo_id.parse().ok()?,
+        })
+    }
+
+    pub fn encode(&self) -> String {
+        format!("{}_{}", self.date, self.repo_id)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RenderedReadme {
+    pub repo_id: i64,
+    pub date: String,
+    /// Sanitized HTML rendered from the stored README
+    pub html: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrendingDeveloperResponse {
+    pub rank: i32,
+    pub login: String,
+    pub github_url: String,
+    pub avatar_url: String,
+    pub followers: Option<i64>,
+    pub notable_repos: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,6 +583,72 @@ pub struct ChatCompletionRequest {
     pub model: String,
     pub messages: Vec<ChatMessage>,
     pub max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+    /// Server-sent chunks instead of one response
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StreamOptions {
+    /// Ask for a final chunk carrying the token usage
+    pub include_usage: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResponseFormat {
+    /// "json_object" asks the model for a single JSON object
+    #[serde(rename = "type")]
+    pub format_type: String,
+}
+
+/// README summary as returned by the LLM in JSON mode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructuredSummary {
+    pub one_liner: String,
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default)]
+    pub tech_stack: Vec<String>,
+    /// Topic tags in GitHub topic form (see `tags::normalize_tag`)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// One of `llm::AUDIENCES`
+    #[serde(default)]
+    pub audience: Option<String>,
+    /// One of `llm::DIFFICULTIES`
+    #[serde(default)]
+    pub difficulty: Option<String>,
+    /// One of `llm::CATEGORIES`
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+/// The LLM's second opinion on a repo the quality heuristics caught
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualityVerdict {
+    pub low_quality: bool,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// A quality filter decision, kept for audit
+#[derive(Debug, Clone, Serialize)]
+pub struct QualityDecision {
+    pub date: String,
+    pub repo_id: i64,
+    pub repo_name: String,
+    /// "kept", "flagged" or "dropped"
+    pub decision: String,
+    /// What the heuristics caught, e.g. `link density 0.82 (120 links)`
+    pub reasons: Vec<String>,
+    /// `None` when the LLM was not asked or could not judge
+    pub llm_verdict: Option<QualityVerdict>,
+    pub run_id: Option<String>,
+    pub created_at: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -108,6 +660,14 @@ pub struct ChatMessage {
 #[derive(Debug, Deserialize)]
 pub struct ChatCompletionResponse {
     pub choices: Vec<ChatChoice>,
+    #[serde(default)]
+    pub usage: Option<ChatUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatUsage {
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -115,18 +675,456 @@ pub struct ChatChoice {
     pub message: ChatMessage,
 }
 
-// GitHub API models
+/// One server-sent event of a streamed completion
 #[derive(Debug, Deserialize)]
+pub struct ChatCompletionChunk {
+    #[serde(default)]
+    pub choices: Vec<ChatChunkChoice>,
+    /// Only on the last chunk, when asked for with `include_usage`
+    #[serde(default)]
+    pub usage: Option<ChatUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatChunkChoice {
+    pub delta: ChatDelta,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatDelta {
+    #[serde(default)]
+    pub content: Option<String>,
+}
+
+// GitHub API models
+#[derive(Debug, Clone, Deserialize)]
 pub struct GitHubRepoInfo {
     pub default_branch: String,
+    #[serde(default)]
+    pub stargazers_count: i64,
+    #[serde(default)]
+    pub homepage: Option<String>,
+    /// Same as OSS Insight's `repo_id`; GitHub answers for a renamed repo under its old name too
+    #[serde(default)]
+    pub id: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitHubUserInfo {
+    pub followers: i64,
 }
 
 pub type GitHubLanguages = std::collections::HashMap<String, u64>;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CollectionStatus {
+    /// Identifies the collection run, so consumers can ignore events of stale runs
+    pub run_id: String,
     pub is_running: bool,
+    /// "metadata", "summaries" or "complete"
+    pub phase: String,
     pub message: String,
     pub current_count: usize,
     pub total_count: usize,
+    /// Set on per-repo events; phase-level events leave it out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<RepoProgress>,
+}
+
+/// How a run's OSS Insight candidates were cut down to `MAX_REPOS_PER_RUN`, which of their
+/// values failed to parse, and which upstream calls open circuit breakers refused
+#[derive(Debug, Clone, Serialize)]
+pub struct RunCutoff {
+    /// Repos OSS Insight returned (after the language filter)
+    pub candidates: usize,
+    /// Repos the run went on to collect
+    pub kept: usize,
+    /// The limit in force; `None` when the run kept every candidate
+    pub max_repos: Option<usize>,
+    /// Score of the lowest-ranked repo kept (its merged score when sources are merged)
+    pub min_score: Option<f64>,
+    /// Unparsable upstream values per field; rows with an unparsable repo id were dropped
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub parse_failures: std::collections::BTreeMap<String, usize>,
+    /// Calls refused per upstream host while its circuit was open
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub circuit_breaks: std::collections::BTreeMap<String, usize>,
+}
+
+impl RunCutoff {
+    pub fn trimmed(&self) -> usize {
+        self.candidates - self.kept
+    }
+}
+
+/// What a run did with one repo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoReport {
+    pub repo_id: i64,
+    pub repo_name: String,
+    /// `collected`, `partial` (stored without its summary or languages), `skipped` or `failed`
+    pub outcome: String,
+    /// Why the repo was skipped, failed or is missing parts, in the order they came up
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<String>,
+    /// A fetch, summary or store of the repo failed, as opposed to e.g. it having no README
+    #[serde(default)]
+    pub errored: bool,
+}
+
+/// Per-repo result of a collection or summary retry run, stored with the run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionReport {
+    pub run_id: String,
+    pub date: String,
+    /// The job kind: `collect`, `backfill` or `summarize_missing`
+    pub kind: String,
+    pub collected: usize,
+    pub partial: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// Repos that ran into an error, whatever their outcome
+    #[serde(default)]
+    pub errored: usize,
+    pub repos: Vec<RepoReport>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdhocQueryRequest {
+    /// A single read-only statement (SELECT, WITH, SHOW, DESCRIBE, ...)
+    pub sql: String,
+    /// "json" (default) or "csv"
+    pub format: Option<String>,
+    /// Row limit below `ADMIN_QUERY_MAX_ROWS`
+    pub max_rows: Option<usize>,
+}
+
+/// Rows of an ad-hoc `/api/admin/query`
+#[derive(Debug, Clone, Serialize)]
+pub struct AdhocQueryResult {
+    pub columns: Vec<String>,
+    /// One value per column; dates and timestamps as text, unsupported types as their debug form
+    pub rows: Vec<Vec<serde_json::Value>>,
+    /// The query returned more rows than the limit; only the first ones are included
+    pub truncated: bool,
+    pub elapsed_ms: u64,
+}
+
+/// A stored progress event, as sent on the progress stream plus when it was recorded
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionEvent {
+    pub id: i64,
+    pub created_at: String,
+    #[serde(flatten)]
+    pub status: CollectionStatus,
+}
+
+/// What happened to one repo, carried by a per-repo progress event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoProgress {
+    /// "repo_started", "readme_fetched", "summary_partial", "summary_generated" or "repo_failed"
+    pub event: String,
+    pub repo_id: i64,
+    pub repo_name: String,
+    /// Failure reason of "repo_failed"
+    pub error: Option<String>,
+    /// One-liner written so far, on "summary_partial" (streamed summaries only; not stored)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partial: Option<String>,
+}
+
+// Email digest subscriptions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestSubscription {
+    pub id: i64,
+    pub email: String,
+    /// "daily" or "weekly"
+    pub frequency: String,
+    /// Local delivery time in HH:MM
+    pub delivery_time: String,
+    /// IANA timezone name, e.g. "Asia/Seoul"
+    pub timezone: String,
+    /// Local weekday for weekly digests (0 = Monday ... 6 = Sunday)
+    pub weekday: Option<i32>,
+    /// Local date the last digest was sent on
+    pub last_sent_date: Option<String>,
+    /// Whether the address confirmed the subscription; only confirmed ones get digests
+    pub confirmed: bool,
+    /// Secret of the unsubscribe link in every email, never served by the API
+    #[serde(skip)]
+    pub unsubscribe_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSubscriptionRequest {
+    pub email: String,
+    pub frequency: Option<String>,
+    pub delivery_time: Option<String>,
+    pub timezone: Option<String>,
+    pub weekday: Option<i32>,
+}
+
+// Webhook subscriptions
+#[derive(Debug, Clone, Serialize)]
+pub struct Webhook {
+    pub id: i64,
+    pub url: String,
+    /// Events delivered to this hook; empty = all
+    pub events: Vec<String>,
+    /// Payload shape: "json" (raw event) or a channel template ("slack", "discord", "telegram")
+    pub format: String,
+    /// HMAC-SHA256 key for `X-Webhook-Signature`; only returned when the hook is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    /// Scheduled digest delivery instead of per-event posts
+    pub schedule: Option<DeliverySchedule>,
+    pub created_at: Option<String>,
+}
+
+/// When a webhook receives a digest of the newest brief, and how much of it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliverySchedule {
+    /// "daily" or "weekly"
+    pub frequency: String,
+    /// Local delivery time in HH:MM
+    #[serde(default = "default_delivery_time")]
+    pub delivery_time: String,
+    /// IANA timezone name, e.g. "Asia/Seoul"
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// Local weekday for weekly digests (0 = Monday ... 6 = Sunday)
+    pub weekday: Option<i32>,
+    /// Repos included in the digest
+    #[serde(default = "default_top_n")]
+    pub top_n: i64,
+    /// Local date the last digest was sent on
+    #[serde(default, skip_deserializing)]
+    pub last_sent_date: Option<String>,
+}
+
+fn default_delivery_time() -> String {
+    "09:00".to_string()
+}
+
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
+fn default_top_n() -> i64 {
+    10
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// "json" (default), "slack", "discord" or "telegram"
+    pub format: Option<String>,
+    /// Post a scheduled digest instead of individual events
+    pub schedule: Option<DeliverySchedule>,
+    /// Generated when omitted
+    pub secret: Option<String>,
+}
+
+/// One attempt at delivering an event to a webhook
+#[derive(Debug, Serialize)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    /// Shared by every attempt of the same event delivery (`X-Webhook-Delivery`)
+    pub delivery_id: String,
+    pub event: String,
+    pub attempt: i32,
+    pub success: bool,
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+    pub duration_ms: i64,
+    pub created_at: String,
+}
+
+/// Either explicit `dates` or an inclusive `from`..`to` range
+#[derive(Debug, Deserialize)]
+pub struct BatchTrendsRequest {
+    pub dates: Option<Vec<String>>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// Repos per date (default 10)
+    pub limit: Option<usize>,
+}
+
+// Language share anomalies
+#[derive(Debug, Clone)]
+pub struct LanguageShareStats {
+    pub language: String,
+    pub share: f64,
+    pub mean: Option<f64>,
+    pub stddev: Option<f64>,
+    pub samples: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageAlert {
+    pub date: String,
+    pub language: String,
+    pub share: f64,
+    pub trailing_mean: f64,
+    pub trailing_stddev: f64,
+    pub z_score: f64,
+}
+
+/// A repo whose total stars passed a milestone since its previous appearance
+#[derive(Debug, Clone, Serialize)]
+pub struct StarMilestone {
+    pub date: String,
+    pub repo_id: i64,
+    pub repo_name: String,
+    pub milestone: i64,
+    pub previous_stars: i64,
+    pub total_stars: i64,
+    /// Date of the appearance `previous_stars` was recorded on
+    pub previous_date: String,
+}
+
+// Language share forecasting
+#[derive(Debug, Clone, Serialize)]
+pub struct ForecastPoint {
+    pub date: String,
+    pub predicted: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageForecast {
+    pub language: String,
+    pub history_days: usize,
+    pub moving_average: f64,
+    pub slope_per_day: f64,
+    /// "rising", "falling" or "flat"
+    pub direction: String,
+    pub forecast: Vec<ForecastPoint>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageChange {
+    pub language: String,
+    pub this_week: f64,
+    pub last_week: f64,
+    pub absolute_change: f64,
+    /// `None` when the language had no share last week
+    pub relative_change: Option<f64>,
+    pub rank: usize,
+    pub previous_rank: Option<usize>,
+    /// Positive = moved up
+    pub rank_change: Option<i64>,
+}
+
+/// How often a tag was given to trending repos over a window
+#[derive(Debug, Clone, Serialize)]
+pub struct TagCount {
+    pub tag: String,
+    /// Repo appearances carrying the tag (a repo trending on three days counts three times)
+    pub appearances: i64,
+    /// Distinct repos carrying the tag
+    pub repos: i64,
+}
+
+/// Share of one day's trending repos in a summary category
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryShare {
+    pub date: String,
+    /// "uncategorized" for repos without a (valid) category, so a day's shares add up to 100
+    pub category: String,
+    pub repo_count: i64,
+    /// Percentage of the day's trending repos
+    pub share: f64,
+}
+
+/// A language whose daily share changed between two collected days
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageMover {
+    pub language: String,
+    pub share: f64,
+    pub previous_share: f64,
+    /// Percentage points gained (negative = lost)
+    pub change: f64,
+    /// `None` on a day the language had no share
+    pub rank: Option<usize>,
+    pub previous_rank: Option<usize>,
+}
+
+/// Languages appearing together in trending repos, shaped for chord/heatmap charts
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageCooccurrence {
+    /// Repo appearances counted (a repo trending on two days counts twice)
+    pub repos: usize,
+    /// Row/column labels of `matrix`, most common first
+    pub languages: Vec<String>,
+    /// `matrix[i][j]` = repos using both languages; the diagonal is each language's repo count
+    pub matrix: Vec<Vec<i64>>,
+    /// Off-diagonal cells, most frequent pair first
+    pub pairs: Vec<LanguagePair>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguagePair {
+    pub languages: [String; 2],
+    pub repo_count: i64,
+}
+
+/// Everything the archive knows about one repo, over all the days it trended
+#[derive(Debug, Clone, Serialize)]
+pub struct RepoProfile {
+    pub repo_id: i64,
+    /// Name at its latest appearance
+    pub repo_name: String,
+    pub github_url: String,
+    /// Names it trended under before being renamed, latest first
+    pub previous_names: Vec<String>,
+    pub trending_days: i64,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub best_rank: Option<i32>,
+    /// Latest day it reached `best_rank`
+    pub best_rank_date: Option<String>,
+    /// One point per day it trended, oldest first
+    pub star_history: Vec<StarPoint>,
+    /// Summary of the latest appearance that has one
+    pub latest_summary: Option<StructuredSummary>,
+    pub summary_date: Option<String>,
+    /// Tags of its summaries, most frequent first
+    pub topics: Vec<String>,
+    /// Repos whose latest summary shares the most topics with it
+    pub similar: Vec<SimilarRepo>,
+}
+
+/// A repo's stars on one day it trended
+#[derive(Debug, Clone, Serialize)]
+pub struct StarPoint {
+    pub date: String,
+    /// Stars gained over the trending window
+    pub stars: Option<i32>,
+    pub total_stars: Option<i64>,
+    pub rank: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SimilarRepo {
+    pub repo_id: i64,
+    pub repo_name: String,
+    pub korean_summary: Option<String>,
+    pub shared_topics: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageTopRepo {
+    pub rank: usize,
+    pub repo_id: i64,
+    pub repo_name: String,
+    pub github_url: String,
+    pub description: Option<String>,
+    pub korean_summary: Option<String>,
+    pub days_trending: i64,
+    pub cumulative_score: f64,
+    pub cumulative_stars: i64,
 }
@@ -0,0 +1,353 @@
+/// Statements an ad-hoc query may start with. The query also runs in a transaction that is rolled
+/// back, but statements acting outside it (ATTACH, COPY, EXPORT, INSTALL, SET, ...) are refused
+/// before they reach DuckDB.
+const READ_ONLY_KEYWORDS: &[&str] = &[
+    "SELECT", "WITH", "FROM", "VALUES", "TABLE", "SHOW", "DESCRIBE", "SUMMARIZE", "EXPLAIN",
+];
+
+/// Statements EXPLAIN may be followed by. `EXPLAIN ANALYZE` runs the statement, so a COPY or
+/// DELETE behind it would act just as if it came first.
+const EXPLAINABLE_KEYWORDS: &[&str] = &["SELECT", "WITH", "FROM", "VALUES", "TABLE"];
+
+/// Schemas an ad-hoc query may read besides the database's own tables
+const READABLE_SCHEMAS: &[&str] = &["information_schema"];
+
+/// Table functions that only generate rows. Every other one (`read_csv`, `read_text`, `glob`,
+/// `query_table`, ...) could reach files or hosts beyond the database, as can a quoted path in
+/// place of a table, so both are refused.
+const GENERATOR_FUNCTIONS: &[&str] = &["range", "generate_series", "unnest"];
+
+/// Functions whose arguments may contain `FROM` without naming a table
+const FROM_ARGUMENT_FUNCTIONS: &[&str] = &["EXTRACT", "TRIM", "SUBSTRING", "OVERLAY"];
+
+/// Words following SHOW, DESCRIBE, SUMMARIZE or TABLE that don't name a table
+const NOT_TABLE_WORDS: &[&str] = &["SELECT", "WITH", "FROM", "VALUES", "TABLE", "TABLES", "ALL", "DATABASES"];
+
+/// Words ending the table list of a FROM clause
+const CLAUSE_WORDS: &[&str] = &[
+    "WHERE", "GROUP", "HAVING", "ORDER", "LIMIT", "OFFSET", "QUALIFY", "WINDOW", "UNION", "EXCEPT",
+    "INTERSECT", "SELECT",
+];
+
+/// The query without trailing semicolons, or why it is refused: it must be a single statement
+/// starting with one of `READ_ONLY_KEYWORDS` that only reads `tables`, its own CTEs and
+/// `READABLE_SCHEMAS`
+pub fn check_read_only<'a>(sql: &'a str, tables: &[String]) -> Result<&'a str, String> {
+    let sql = sql.trim().trim_end_matches(|c: char| c == ';' || c.is_whitespace());
+    let code = code_only(sql);
+
+    if code.contains(';') {
+        return Err("Only a single statement is allowed".to_string());
+    }
+    let keyword = code
+        .trim_start_matches(|c: char| c == '(' || c.is_whitespace())
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .next()
+        .unwrap_or_default()
+        .to_ascii_uppercase();
+    if keyword.is_empty() {
+        return Err("The query is empty".to_string());
+    }
+    if !READ_ONLY_KEYWORDS.contains(&keyword.as_str()) {
+        return Err(format!(
+            "{} statements are not allowed; start with one of {}",
+            keyword,
+            READ_ONLY_KEYWORDS.join(", ")
+        ));
+    }
+    let tokens = tokenize(&code);
+    if keyword == "EXPLAIN" {
+        let explained = tokens
+            .iter()
+            .filter(|t| t.is_word_token())
+            .skip(1)
+            .find(|t| !t.is_any_word(&["ANALYZE", "ANALYSE"]));
+        if !explained.is_some_and(|t| t.is_any_word(EXPLAINABLE_KEYWORDS)) {
+            return Err(format!("EXPLAIN may only explain a query starting with one of {}", EXPLAINABLE_KEYWORDS.join(", ")));
+        }
+    }
+    check_sources(&tokens, tables)?;
+    Ok(sql)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token<'a> {
+    Word(&'a str),
+    /// A string literal or quoted identifier, blanked by `code_only`
+    Quoted,
+    Symbol(char),
+}
+
+impl Token<'_> {
+    fn is_word(&self, word: &str) -> bool {
+        matches!(self, Token::Word(w) if w.eq_ignore_ascii_case(word))
+    }
+
+    fn is_word_token(&self) -> bool {
+        matches!(self, Token::Word(_))
+    }
+
+    fn is_any_word(&self, words: &[&str]) -> bool {
+        words.iter().any(|word| self.is_word(word))
+    }
+}
+
+fn tokenize(code: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = code;
+
+    while let Some(c) = rest.chars().next() {
+        if c.is_alphanumeric() || c == '_' {
+            let end = rest.find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$')).unwrap_or(rest.len());
+            tokens.push(Token::Word(&rest[..end]));
+            rest = &rest[end..];
+            continue;
+        }
+        match c {
+            '\'' | '"' => tokens.push(Token::Quoted),
+            c if c.is_whitespace() => {}
+            c => tokens.push(Token::Symbol(c)),
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    tokens
+}
+
+/// Refuse every row source of the query that is not one of `tables`, a CTE of the query, a
+/// subquery, a view of `READABLE_SCHEMAS` or one of `GENERATOR_FUNCTIONS`
+fn check_sources(tokens: &[Token<'_>], tables: &[String]) -> Result<(), String> {
+    // `name AS (` (optionally MATERIALIZED) defines a CTE
+    let ctes: Vec<&str> = tokens
+        .windows(3)
+        .filter_map(|w| match w {
+            [Token::Word(name), as_, next] if as_.is_word("AS") && (*next == Token::Symbol('(') || next.is_word("MATERIALIZED")) => {
+                Some(*name)
+            }
+            _ => None,
+        })
+        .collect();
+
+    // Open parentheses, each noting whether it holds the arguments of a FROM_ARGUMENT_FUNCTIONS call
+    let mut parens: Vec<bool> = Vec::new();
+    // Nesting depths at which a FROM clause still lists tables
+    let mut from_lists: Vec<usize> = Vec::new();
+    let mut expect_source = false;
+
+    for (i, token) in tokens.iter().enumerate() {
+        let previous = i.checked_sub(1).map(|p| tokens[p]);
+        if expect_source {
+            expect_source = false;
+            match token {
+                // A subquery, checked as the tokens go on
+                Token::Symbol('(') => {}
+                Token::Word(_) if token.is_word("LATERAL") => {
+                    expect_source = true;
+                    continue;
+                }
+                Token::Word(_) => check_source(&tokens[i..], tables, &ctes)?,
+                _ => return Err("Only tables of this database may be queried, by unquoted name".to_string()),
+            }
+        }
+
+        let depth = parens.len();
+        match token {
+            Token::Symbol('(') => parens.push(previous.is_some_and(|p| p.is_any_word(FROM_ARGUMENT_FUNCTIONS))),
+            Token::Symbol(')') => {
+                from_lists.retain(|&d| d < depth);
+                parens.pop();
+            }
+            Token::Symbol(',') if from_lists.last() == Some(&depth) => expect_source = true,
+            Token::Word(_) if token.is_any_word(&["FROM", "JOIN"]) => {
+                let in_arguments = parens.last() == Some(&true);
+                let distinct_from = previous.is_some_and(|p| p.is_word("DISTINCT"));
+                if !in_arguments && !distinct_from {
+                    from_lists.retain(|&d| d != depth);
+                    from_lists.push(depth);
+                    expect_source = true;
+                }
+            }
+            Token::Word(_) if token.is_any_word(CLAUSE_WORDS) => from_lists.retain(|&d| d != depth),
+            Token::Word(_) if token.is_any_word(&["SHOW", "DESCRIBE", "SUMMARIZE", "TABLE"]) => {
+                let starts_statement = i == 0 || previous.is_some_and(|p| p == Token::Symbol('(') || p.is_any_word(&["EXPLAIN", "ANALYZE"]));
+                let names_table = tokens.get(i + 1).is_some_and(|next| !next.is_any_word(NOT_TABLE_WORDS) && *next != Token::Symbol('('));
+                if starts_statement && names_table {
+                    expect_source = true;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Check the row source named at the start of `tokens`: `table`, `schema.table` or `function(...)`
+fn check_source(tokens: &[Token<'_>], tables: &[String], ctes: &[&str]) -> Result<(), String> {
+    let mut parts = Vec::new();
+    let mut rest = tokens;
+    while let [Token::Word(part), tail @ ..] = rest {
+        parts.push(*part);
+        match tail {
+            [Token::Symbol('.'), tail @ ..] => rest = tail,
+            _ => {
+                rest = tail;
+                break;
+            }
+        }
+    }
+    let name = parts.join(".");
+
+    if rest.first() == Some(&Token::Symbol('(')) {
+        return match parts.as_slice() {
+            [function] if GENERATOR_FUNCTIONS.iter().any(|f| f.eq_ignore_ascii_case(function)) => Ok(()),
+            _ => Err(format!("Table function {}() is not allowed; query the tables of this database", name)),
+        };
+    }
+
+    let is_table = |table: &str| tables.iter().any(|t| t.eq_ignore_ascii_case(table));
+    let allowed = match parts.as_slice() {
+        [table] => is_table(table) || ctes.iter().any(|cte| cte.eq_ignore_ascii_case(table)),
+        [schema, table] if schema.eq_ignore_ascii_case("main") => is_table(table),
+        [schema, _] => READABLE_SCHEMAS.iter().any(|s| s.eq_ignore_ascii_case(schema)),
+        _ => false,
+    };
+    if allowed {
+        Ok(())
+    } else {
+        Err(format!("{} is not a table of this database", name))
+    }
+}
+
+/// `sql` with string literals, quoted identifiers and comments blanked out, so keywords and
+/// semicolons inside them are not mistaken for statements. Each quoted span is left as one bare
+/// quote character.
+fn code_only(sql: &str) -> String {
+    let mut code = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' | '"' => {
+                // A doubled quote inside a literal closes and reopens it, which blanks the same
+                for next in chars.by_ref() {
+                    if next == c {
+                        break;
+                    }
+                }
+                // Doubled quotes are collapsed into one
+                if code.ends_with(c) {
+                    continue;
+                }
+                code.push(c);
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+                code.push('\n');
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+                code.push(' ');
+            }
+            _ => code.push(c),
+        }
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tables() -> Vec<String> {
+        vec!["trending_repos".to_string(), "repo_languages".to_string()]
+    }
+
+    fn check(sql: &str) -> Result<&str, String> {
+        check_read_only(sql, &tables())
+    }
+
+    #[test]
+    fn code_only_blanks_literals_and_comments() {
+        assert_eq!(code_only("SELECT 'a;b', \"x;y\" -- c;d\nFROM t /* ; */"), "SELECT ', \" \nFROM t  ");
+        assert_eq!(code_only("SELECT 'it''s'"), "SELECT '");
+    }
+
+    #[test]
+    fn reads_of_own_tables_are_allowed() {
+        for sql in [
+            "SELECT * FROM trending_repos;",
+            "select r.repo_name, l.language from trending_repos r join repo_languages l using (repo_id)",
+            "FROM trending_repos SELECT repo_name, stars",
+            "SELECT * FROM trending_repos, repo_languages WHERE trending_repos.repo_id = repo_languages.repo_id",
+            "WITH top AS (SELECT * FROM trending_repos LIMIT 10) SELECT * FROM top",
+            "SELECT * FROM (SELECT repo_id FROM main.trending_repos) t",
+            "SELECT table_name FROM information_schema.tables",
+            "SELECT EXTRACT(year FROM date), TRIM(BOTH 'x' FROM repo_name) FROM trending_repos",
+            "SELECT * FROM trending_repos WHERE owner IS DISTINCT FROM 'acme'",
+            "SELECT * FROM range(10)",
+            "SELECT 'read_csv(''/etc/passwd'')' FROM trending_repos",
+            "DESCRIBE trending_repos",
+            "SUMMARIZE SELECT stars FROM trending_repos",
+            "SHOW TABLES",
+            "EXPLAIN SELECT * FROM trending_repos",
+            "EXPLAIN ANALYZE WITH top AS (SELECT * FROM trending_repos) SELECT * FROM top",
+        ] {
+            assert!(check(sql).is_ok(), "{}: {:?}", sql, check(sql));
+        }
+        assert_eq!(check("SELECT 1; \n"), Ok("SELECT 1"));
+    }
+
+    #[test]
+    fn writes_and_multiple_statements_are_refused() {
+        for sql in [
+            "DELETE FROM trending_repos",
+            "ATTACH 'other.db'",
+            "COPY trending_repos TO '/tmp/out.csv'",
+            "INSTALL httpfs",
+            "EXPLAIN ANALYZE COPY trending_repos TO '/tmp/x.csv'",
+            "EXPLAIN ANALYZE DELETE FROM trending_repos",
+            "EXPLAIN INSERT INTO trending_repos VALUES (1)",
+            "SELECT 1; DROP TABLE trending_repos",
+            "",
+        ] {
+            assert!(check(sql).is_err(), "{} was allowed", sql);
+        }
+        // Semicolons in literals and comments don't split the statement
+        assert!(check("SELECT ';' FROM trending_repos -- ; DROP").is_ok());
+    }
+
+    #[test]
+    fn files_hosts_and_unknown_tables_are_refused() {
+        for sql in [
+            "SELECT * FROM read_text('/proc/self/environ')",
+            "SELECT * FROM read_csv('http://169.254.169.254/latest/meta-data')",
+            "SELECT * FROM '/etc/passwd'",
+            "SELECT * FROM \"data.csv\"",
+            "SELECT * FROM trending_repos, read_text('/proc/self/environ')",
+            "SELECT * FROM trending_repos t JOIN read_csv('x.csv') c ON t.repo_id = c.id",
+            "SELECT * FROM trending_repos a JOIN repo_languages b ON a.repo_id = b.repo_id, glob('/*')",
+            "SELECT (SELECT content FROM read_text('/etc/hosts'))",
+            "SELECT * FROM LATERAL read_blob('/etc/hosts')",
+            "WITH x AS (FROM read_parquet('s3://bucket/x.parquet')) SELECT * FROM x",
+            "SELECT * FROM duckdb_secrets()",
+            "SELECT * FROM query_table('trending_repos')",
+            "SELECT * FROM other_db.main.trending_repos",
+            "SELECT * FROM secrets",
+            "DESCRIBE '/etc/passwd'",
+            "SUMMARIZE read_csv('/etc/passwd')",
+            "TABLE read_text",
+        ] {
+            assert!(check(sql).is_err(), "{} was allowed", sql);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+use tracing::info;
+
+use crate::db::Database;
+use crate::models::LanguageAlert;
+
+/// Trailing window the baseline mean/stddev is computed over
+pub const TRAILING_WINDOW_DAYS: i64 = 30;
+
+/// Flag languages whose share on `date` deviates more than `threshold` standard deviations
+/// from their trailing mean, store them and return the new alerts.
+pub fn detect_language_anomalies(
+    db: &Database,
+    date: &str,
+    threshold: f64,
+    min_days: i64,
+) -> Result<Vec<LanguageAlert>> {
+    let alerts: Vec<LanguageAlert> = db
+        .get_language_share_stats(date, TRAILING_WINDOW_DAYS)?
+        .into_iter()
+        .filter(|s| s.samples >= min_days)
+        .filter_map(|s| {
+            let mean = s.mean?;
+            let stddev = s.stddev.filter(|sd| *sd > 0.0)?;
+            let z_score = (s.share - mean) / stddev;
+            (z_score.abs() > threshold).then(|| LanguageAlert {
+                date: date.to_string(),
+                language: s.language,
+                share: s.share,
+                trailing_mean: mean,
+                trailing_stddev: stddev,
+                z_score,
+            })
+        })
+        .collect();
+
+    for alert in &alerts {
+        db.save_language_alert(alert)?;
+        info!(
+            "Language share anomaly on {}: {} at {:.1}% (mean {:.1}%, z={:.2})",
+            date, alert.language, alert.share, alert.trailing_mean, alert.z_score
+        );
+    }
+
+    Ok(alerts)
+}
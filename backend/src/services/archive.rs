@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use futures::TryStreamExt;
+use object_store::aws::AmazonS3Builder;
+use object_store::path::Path as ObjectPath;
+use object_store::{ClientOptions, ObjectStore, PutPayload};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::config::{ArchiveConfig, Config};
+use crate::db::Database;
+use crate::services::digest;
+use crate::services::templates::NotificationTemplates;
+use crate::services::tts;
+
+/// Day tables published as `<table>.parquet`, in the same schema as the local tables
+pub const ARCHIVED_TABLES: &[&str] = &["trending_repos", "daily_language_trends"];
+
+/// Uploads each collected day to object storage: Parquet copies of the day tables, the repos as
+/// JSON (the `/api/export/trends` format), the brief rendered from `digest.html` and, when
+/// text-to-speech is on, its recording as `brief.mp3`
+pub struct Archiver {
+    store: Arc<dyn ObjectStore>,
+    config: ArchiveConfig,
+    db: Database,
+    templates: NotificationTemplates,
+    /// `TTS_AUDIO_DIR`, when the brief is recorded
+    audio_dir: Option<String>,
+}
+
+impl Archiver {
+    /// Returns `None` when no archive bucket is configured
+    pub fn new(config: &Config, db: Database) -> Result<Option<Self>> {
+        let Some(archive) = &config.archive else {
+            return Ok(None);
+        };
+
+        let mut builder = AmazonS3Builder::from_env()
+            .with_bucket_name(&archive.bucket)
+            .with_region(&archive.region);
+        if let Some(endpoint) = &archive.endpoint {
+            builder = builder
+                .with_endpoint(endpoint)
+                .with_allow_http(endpoint.starts_with("http://"));
+        }
+        if let (Some(key_id), Some(secret)) = (&archive.access_key_id, &archive.secret_access_key) {
+            builder = builder.with_access_key_id(key_id).with_secret_access_key(secret);
+        }
+        if let Some(proxy) = &config.outbound_proxy {
+            builder = builder.with_client_options(ClientOptions::new().with_proxy_url(proxy));
+        }
+
+        Ok(Some(Archiver {
+            store: Arc::new(builder.build().context("Invalid archive storage settings")?),
+            config: archive.clone(),
+            db,
+            templates: NotificationTemplates::load(config.notification_templates_dir.as_deref())?,
+            audio_dir: config.tts.as_ref().map(|tts| tts.audio_dir.clone()),
+        }))
+    }
+
+    /// Upload everything archived for `date`, replacing earlier uploads; returns the objects written
+    pub async fn publish(&self, date: &str) -> Result<usize> {
+        let prefix = self.config.day_prefix(date);
+        let mut uploaded = 0;
+
+        for table in ARCHIVED_TABLES {
+            let bytes = self.parquet_bytes(table, date).await?;
+            self.put(&format!("{}/{}.parquet", prefix, table), bytes).await?;
+            uploaded += 1;
+        }
+
+        let repos = self.db.get_trending_repos(date)?;
+        self.put(&format!("{}/trending_repos.json", prefix), serde_json::to_vec(&repos)?).await?;
+        uploaded += 1;
+
+        let context = digest::digest_context(&self.db, date, false, usize::MAX)?;
+        let html = self.templates.render("digest.html", &context)?;
+        self.put(&format!("{}/brief.html", prefix), html.into_bytes()).await?;
+        uploaded += 1;
+
+        if let Some(dir) = &self.audio_dir {
+            let path = tts::audio_path(dir, date);
+            if path.exists() {
+                self.put(&format!("{}/brief.mp3", prefix), tokio::fs::read(&path).await?).await?;
+                uploaded += 1;
+            }
+        }
+
+        info!("Archived {} objects for {} to s3://{}/{}", uploaded, date, self.config.bucket, prefix);
+        Ok(uploaded)
+    }
+
+    /// Delete archived objects older than the retention period; returns the objects deleted
+    pub async fn prune(&self) -> Result<usize> {
+        if self.config.retention_days == 0 {
+            return Ok(0);
+        }
+
+        let cutoff = Utc::now() - chrono::Duration::days(self.config.retention_days as i64);
+        let prefix = ObjectPath::from(self.config.prefix.as_str());
+        let expired: Vec<ObjectPath> = self
+            .store
+            .list(Some(&prefix))
+            .try_filter(|meta| futures::future::ready(meta.last_modified < cutoff))
+            .map_ok(|meta| meta.location)
+            .try_collect()
+            .await?;
+
+        let mut deleted = 0;
+        for location in expired {
+            match self.store.delete(&location).await {
+                Ok(()) => deleted += 1,
+                Err(e) => warn!("Failed to delete archived {}: {}", location, e),
+            }
+        }
+
+        if deleted > 0 {
+            info!("Pruned {} archived objects older than {} days", deleted, self.config.retention_days);
+        }
+        Ok(deleted)
+    }
+
+    /// DuckDB writes Parquet to a file, so each table goes through a temporary one
+    async fn parquet_bytes(&self, table: &str, date: &str) -> Result<Vec<u8>> {
+        let path = std::env::temp_dir().join(format!("daily-git-brief-{}.parquet", Uuid::new_v4()));
+        let result = match self.db.export_day_parquet(table, date, &path) {
+            Ok(()) => tokio::fs::read(&path).await.map_err(Into::into),
+            Err(e) => Err(e),
+        };
+        let _ = tokio::fs::remove_file(&path).await;
+        result.with_context(|| format!("Failed to export {} for {}", table, date))
+    }
+
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<()> {
+        self.store
+            .put(&ObjectPath::from(key), PutPayload::from(bytes))
+            .await
+            .with_context(|| format!("Failed to upload {}", key))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use metrics::{counter, gauge};
+use reqwest::Url;
+use tracing::warn;
+
+use crate::config::Config;
+use crate::telemetry;
+
+/// A call refused because its host's circuit is open
+#[derive(Debug, Clone)]
+pub struct CircuitOpen {
+    pub host: String,
+    /// Until the next call is let through
+    pub retry_in: Duration,
+}
+
+impl std::fmt::Display for CircuitOpen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Circuit open for {}, retrying in {}s", self.host, self.retry_in.as_secs().max(1))
+    }
+}
+
+impl std::error::Error for CircuitOpen {}
+
+#[derive(Default)]
+struct HostCircuit {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    /// Calls refused since the last `take_rejected`
+    rejected: usize,
+}
+
+/// Per-host circuit breakers of the GitHub and LLM clients. After `CIRCUIT_BREAKER_FAILURES`
+/// consecutive failures (transport errors and 5xx responses) a host's calls fail immediately for
+/// `CIRCUIT_BREAKER_COOLDOWN_SECS`. Calls go through again after that; a success closes the
+/// circuit, a failure reopens it.
+pub struct CircuitBreakers {
+    /// 0 disables the breakers
+    threshold: u32,
+    cooldown: Duration,
+    hosts: Mutex<HashMap<String, HostCircuit>>,
+}
+
+impl CircuitBreakers {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        CircuitBreakers { threshold, cooldown, hosts: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(config.circuit_breaker_failures, Duration::from_secs(config.circuit_breaker_cooldown_secs))
+    }
+
+    /// Breakers that never open
+    pub fn disabled() -> Self {
+        Self::new(0, Duration::ZERO)
+    }
+
+    /// `Err(CircuitOpen)` while the circuit of `url`'s host is open
+    pub fn check(&self, url: &str) -> Result<(), CircuitOpen> {
+        if self.threshold == 0 {
+            return Ok(());
+        }
+        let host = host_of(url);
+        let mut hosts = self.hosts.lock().unwrap();
+        let Some(circuit) = hosts.get_mut(&host) else {
+            return Ok(());
+        };
+        match circuit.open_until {
+            Some(until) if until > Instant::now() => {
+                circuit.rejected += 1;
+                counter!(telemetry::CIRCUIT_BREAKER_REJECTED, "host" => host.clone()).increment(1);
+                Err(CircuitOpen { retry_in: until - Instant::now(), host })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Record the outcome of a call to `url`
+    pub fn record(&self, url: &str, ok: bool) {
+        if self.threshold == 0 {
+            return;
+        }
+        let host = host_of(url);
+        let mut hosts = self.hosts.lock().unwrap();
+        let circuit = hosts.entry(host.clone()).or_default();
+        if ok {
+            if circuit.open_until.take().is_some() {
+                gauge!(telemetry::CIRCUIT_BREAKER_OPEN, "host" => host).set(0.0);
+            }
+            circuit.consecutive_failures = 0;
+            return;
+        }
+
+        circuit.consecutive_failures += 1;
+        if circuit.consecutive_failures >= self.threshold {
+            if circuit.open_until.is_none_or(|until| until <= Instant::now()) {
+                warn!(
+                    "Opening the circuit for {} after {} consecutive failures, for {:?}",
+                    host, circuit.consecutive_failures, self.cooldown
+                );
+            }
+            circuit.open_until = Some(Instant::now() + self.cooldown);
+            gauge!(telemetry::CIRCUIT_BREAKER_OPEN, "host" => host).set(1.0);
+        }
+    }
+
+    /// Calls refused per host since the last take, for the run report
+    pub fn take_rejected(&self) -> BTreeMap<String, usize> {
+        let mut hosts = self.hosts.lock().unwrap();
+        hosts
+            .iter_mut()
+            .filter(|(_, circuit)| circuit.rejected > 0)
+            .map(|(host, circuit)| (host.clone(), std::mem::take(&mut circuit.rejected)))
+            .collect()
+    }
+}
+
+fn host_of(url: &str) -> String {
+    Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| url.to_string())
+}
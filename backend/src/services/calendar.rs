@@ -0,0 +1,91 @@
+use chrono::NaiveDateTime;
+
+use crate::models::Job;
+use crate::services::jobs::{JOB_BACKFILL, JOB_CANCELLED, JOB_DONE};
+
+/// iCalendar lines are folded after this many octets (RFC 5545 3.1)
+const MAX_LINE_OCTETS: usize = 75;
+
+/// `calendar.ics` with one event per finished collection: when a day's brief was published, or
+/// when its run failed or was cancelled. `jobs` pairs each job with the date it collected into.
+pub fn render(workspace: &str, site_url: Option<&str>, jobs: &[(Job, Option<String>)]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Daily Git Brief//Collection runs//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("X-WR-CALNAME:{}", escape(&format!("Daily Git Brief ({})", workspace))),
+    ];
+
+    for (job, date) in jobs {
+        // Jobs cancelled while queued never started
+        let (Some(start), Some(end)) = (
+            ical_timestamp(job.started_at.as_deref().unwrap_or(&job.created_at)),
+            job.finished_at.as_deref().and_then(ical_timestamp),
+        ) else {
+            continue;
+        };
+        // Runs that failed before storing anything only have their start date
+        let date = date
+            .as_deref()
+            .or(job.date.as_deref())
+            .or(job.started_at.as_deref().unwrap_or(&job.created_at).get(..10))
+            .unwrap_or_default();
+        let kind = if job.kind == JOB_BACKFILL { "Backfill" } else { "Daily brief" };
+        let summary = match job.status.as_str() {
+            JOB_DONE => format!("{} published: {}", kind, date),
+            JOB_CANCELLED => format!("{} skipped: {}", kind, date),
+            _ => format!("{} failed: {}", kind, date),
+        };
+        let description = job.error.as_deref().or(job.result.as_deref()).unwrap_or_default();
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:job-{}@{}.daily-git-brief", job.id, workspace));
+        lines.push(format!("DTSTAMP:{}", end));
+        lines.push(format!("DTSTART:{}", start));
+        lines.push(format!("DTEND:{}", end));
+        lines.push(format!("SUMMARY:{}", escape(&summary)));
+        if !description.is_empty() {
+            lines.push(format!("DESCRIPTION:{}", escape(description)));
+        }
+        if let (Some(site_url), JOB_DONE) = (site_url, job.status.as_str()) {
+            lines.push(format!("URL:{}/?date={}", site_url, date));
+        }
+        lines.push(format!("STATUS:{}", if job.status == JOB_CANCELLED { "CANCELLED" } else { "CONFIRMED" }));
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line) + "\r\n").collect()
+}
+
+/// A stored UTC timestamp ("2024-01-01 00:00:01.234") in iCalendar form ("20240101T000001Z")
+fn ical_timestamp(value: &str) -> Option<String> {
+    let parsed = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f").ok()?;
+    Some(parsed.format("%Y%m%dT%H%M%SZ").to_string())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+        .replace('\r', "")
+}
+
+/// Split a content line into 75-octet pieces, continued with a leading space, without breaking
+/// a UTF-8 character
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / MAX_LINE_OCTETS * 3);
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            // The leading space counts towards the continued line
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded
+}
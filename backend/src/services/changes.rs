@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde_json::json;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::warn;
+
+use crate::api::ResponseCache;
+use crate::models::{LanguageAlert, StarMilestone};
+use crate::services::sitemap::Sitemap;
+use crate::services::webhooks::{self, WebhookDispatcher};
+
+/// Changes buffered per subscriber; a run stores two per repo, so this covers a full day
+const CHANGE_FEED_CAPACITY: usize = 1024;
+
+/// Something the collector wrote, published as it is stored
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Change {
+    RepoStored { date: String, repo_id: i64, repo_name: String, rank: i32 },
+    /// `status` is one of the `SUMMARY_*` values of the collector
+    SummaryStored { date: String, repo_id: i64, repo_name: String, status: String },
+    LanguageTrendsStored { date: String },
+    LanguageAlert(LanguageAlert),
+    StarMilestone(StarMilestone),
+    CollectionCompleted { date: String, run_id: String, collected: usize, backfill: bool },
+}
+
+/// In-process feed of stored data. Downstream subsystems subscribe to it and react to each
+/// write instead of re-querying the database once a run is over.
+#[derive(Clone)]
+pub struct ChangeFeed {
+    tx: broadcast::Sender<Change>,
+}
+
+impl ChangeFeed {
+    pub fn new() -> Self {
+        let (tx, _rx) = broadcast::channel(CHANGE_FEED_CAPACITY);
+        ChangeFeed { tx }
+    }
+
+    /// Publishing never blocks the writer; changes nobody listens to are dropped
+    pub fn publish(&self, change: Change) {
+        let _ = self.tx.send(change);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Change> {
+        self.tx.subscribe()
+    }
+
+    /// Drop cached API responses whenever served data changes, so a running collection shows
+    /// up as it is stored
+    pub fn spawn_cache_invalidation(&self, cache: Arc<ResponseCache>) {
+        let mut rx = self.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match rx.recv().await {
+                    // Missed changes could have touched anything, so they invalidate too
+                    Ok(_) | Err(RecvError::Lagged(_)) => cache.clear(),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// Rebuild the sitemap once a run has stored a new date
+    pub fn spawn_sitemap_regeneration(&self, sitemap: Sitemap) {
+        let mut rx = self.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(Change::CollectionCompleted { .. }) | Err(RecvError::Lagged(_)) => {
+                        if let Err(e) = sitemap.regenerate() {
+                            warn!("Failed to regenerate the sitemap: {}", e);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// Post alerts, milestones and finished runs to the registered webhooks
+    pub fn spawn_webhook_delivery(&self, dispatcher: WebhookDispatcher) {
+        let mut rx = self.subscribe();
+        tokio::spawn(async move {
+            loop {
+                let change = match rx.recv().await {
+                    Ok(change) => change,
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!("Webhook delivery fell behind the change feed, {} changes not delivered", missed);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+
+                match change {
+                    Change::LanguageAlert(alert) => dispatcher.emit(webhooks::EVENT_LANGUAGE_ALERT, json!(alert)),
+                    Change::StarMilestone(milestone) => dispatcher.emit(webhooks::EVENT_STAR_MILESTONE, json!(milestone)),
+                    Change::CollectionCompleted { date, run_id, collected, backfill } => {
+                        dispatcher.emit(webhooks::EVENT_COLLECTION_COMPLETED, json!({
+                            "date": date,
+                            "run_id": run_id,
+                            "collected": collected,
+                            "backfill": backfill,
+                        }))
+                    }
+                    Change::RepoStored { .. } | Change::SummaryStored { .. } | Change::LanguageTrendsStored { .. } => {}
+                }
+            }
+        });
+    }
+}
+
+impl Default for ChangeFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
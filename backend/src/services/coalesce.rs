@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use futures::future::BoxFuture;
+use metrics::counter;
+use tokio::sync::watch;
+
+use crate::config::SummaryStyle;
+use crate::models::{GitHubRepoInfo, LanguageInfo, QualityVerdict, StructuredSummary};
+use crate::services::errors::ServiceError;
+use crate::services::providers::{RepoMetadataSource, SummaryProvider};
+use crate::telemetry;
+
+/// A finished call as handed to the callers that waited on it
+type Outcome<V> = Option<Result<V, Arc<anyhow::Error>>>;
+
+/// Calls in flight by key: the first caller of a key makes the call, and callers arriving
+/// while it runs wait for its result instead of making the same call again. Nothing is cached
+/// once the call is done.
+pub struct InFlight<K, V> {
+    /// Labels the `coalesced_calls_total` metric
+    name: &'static str,
+    calls: Mutex<HashMap<K, watch::Receiver<Outcome<V>>>>,
+}
+
+/// Forgets the leader's call when it finishes or is dropped midway; waiting callers then see
+/// the channel close and make the call themselves
+struct Leader<'a, K: Hash + Eq, V> {
+    calls: &'a Mutex<HashMap<K, watch::Receiver<Outcome<V>>>>,
+    key: K,
+}
+
+impl<K: Hash + Eq, V> Drop for Leader<'_, K, V> {
+    fn drop(&mut self) {
+        self.calls.lock().unwrap().remove(&self.key);
+    }
+}
+
+impl<K, V> InFlight<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    pub fn new(name: &'static str) -> Self {
+        InFlight { name, calls: Mutex::new(HashMap::new()) }
+    }
+
+    /// `call`'s result, or the result of the identical call another caller has in flight
+    pub async fn run<F, Fut>(&self, key: K, call: F) -> Result<V>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V>>,
+    {
+        let waiting = {
+            let mut calls = self.calls.lock().unwrap();
+            match calls.get(&key) {
+                Some(rx) => Err(rx.clone()),
+                None => {
+                    let (tx, rx) = watch::channel(None);
+                    calls.insert(key.clone(), rx);
+                    Ok(tx)
+                }
+            }
+        };
+
+        match waiting {
+            Ok(tx) => {
+                let leader = Leader { calls: &self.calls, key };
+                let result = call().await;
+                drop(leader);
+                let shared = match &result {
+                    Ok(value) => Ok(value.clone()),
+                    Err(e) => Err(Arc::new(copy_error(e))),
+                };
+                // Nobody waiting is fine
+                let _ = tx.send(Some(shared));
+                result
+            }
+            Err(mut rx) => {
+                let outcome = match rx.wait_for(Option::is_some).await {
+                    Ok(outcome) => outcome.clone(),
+                    Err(_) => None,
+                };
+                match outcome {
+                    Some(result) => {
+                        counter!(telemetry::COALESCED_CALLS, "call" => self.name).increment(1);
+                        result.map_err(|e| copy_error(&e))
+                    }
+                    // The leader was cancelled before it got a result
+                    None => call().await,
+                }
+            }
+        }
+    }
+}
+
+/// `anyhow` errors can't be cloned: callers sharing a failed call each get a copy of its
+/// `ServiceError`, so they still see what kind of failure it was, or else its message
+fn copy_error(err: &anyhow::Error) -> anyhow::Error {
+    match err.downcast_ref::<ServiceError>() {
+        Some(e) => e.clone().into(),
+        None => anyhow!("{:#}", err),
+    }
+}
+
+/// GitHub lookups of the same repo coalesced, e.g. a collection run and a requeued summary
+/// fetching one README at the same time
+pub struct CoalescingMetadata {
+    inner: Arc<dyn RepoMetadataSource>,
+    /// Keyed by repo and the bits of the language threshold
+    languages: InFlight<(String, u64), Vec<LanguageInfo>>,
+    info: InFlight<String, Option<GitHubRepoInfo>>,
+    readmes: InFlight<String, Option<String>>,
+}
+
+impl CoalescingMetadata {
+    pub fn new(inner: Arc<dyn RepoMetadataSource>) -> Self {
+        CoalescingMetadata {
+            inner,
+            languages: InFlight::new("repo_languages"),
+            info: InFlight::new("repo_info"),
+            readmes: InFlight::new("readme"),
+        }
+    }
+}
+
+impl RepoMetadataSource for CoalescingMetadata {
+    fn get_repo_languages<'a>(&'a self, repo_name: &'a str, threshold: f64) -> BoxFuture<'a, Result<Vec<LanguageInfo>>> {
+        Box::pin(self.languages.run((repo_name.to_string(), threshold.to_bits()), || {
+            self.inner.get_repo_languages(repo_name, threshold)
+        }))
+    }
+
+    fn get_repo_info<'a>(&'a self, repo_name: &'a str) -> BoxFuture<'a, Result<Option<GitHubRepoInfo>>> {
+        Box::pin(self.info.run(repo_name.to_string(), || self.inner.get_repo_info(repo_name)))
+    }
+
+    fn get_readme<'a>(&'a self, repo_name: &'a str) -> BoxFuture<'a, Result<Option<String>>> {
+        Box::pin(self.readmes.run(repo_name.to_string(), || self.inner.get_readme(repo_name)))
+    }
+
+    fn get_user_followers<'a>(&'a self, login: &'a str) -> BoxFuture<'a, Result<Option<i64>>> {
+        self.inner.get_user_followers(login)
+    }
+}
+
+/// Summaries of the same repo, README and style coalesced. While a streamed summary is shared,
+/// only the caller that started it gets the partial one-liners.
+pub struct CoalescingSummaries {
+    inner: Arc<dyn SummaryProvider>,
+    /// Keyed by repo, README and style
+    summaries: InFlight<(String, String, SummaryStyle), Option<StructuredSummary>>,
+}
+
+impl CoalescingSummaries {
+    pub fn new(inner: Arc<dyn SummaryProvider>) -> Self {
+        CoalescingSummaries { inner, summaries: InFlight::new("summary") }
+    }
+}
+
+impl SummaryProvider for CoalescingSummaries {
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+
+    fn summarize_readme_korean<'a>(
+        &'a self,
+        readme: &'a str,
+        repo_name: &'a str,
+        style: &'a SummaryStyle,
+    ) -> BoxFuture<'a, Result<Option<StructuredSummary>>> {
+        Box::pin(self.summaries.run((repo_name.to_string(), readme.to_string(), style.clone()), || {
+            self.inner.summarize_readme_korean(readme, repo_name, style)
+        }))
+    }
+
+    fn summarize_readme_korean_streaming<'a>(
+        &'a self,
+        readme: &'a str,
+        repo_name: &'a str,
+        style: &'a SummaryStyle,
+        on_partial: &'a (dyn Fn(&str) + Send + Sync),
+    ) -> BoxFuture<'a, Result<Option<StructuredSummary>>> {
+        Box::pin(self.summaries.run((repo_name.to_string(), readme.to_string(), style.clone()), || {
+            self.inner.summarize_readme_korean_streaming(readme, repo_name, style, on_partial)
+        }))
+    }
+
+    fn describe_readme_change<'a>(&'a self, previous: &'a str, current: &'a str, repo_name: &'a str) -> BoxFuture<'a, Result<Option<String>>> {
+        self.inner.describe_readme_change(previous, current, repo_name)
+    }
+
+    fn translate_description_korean<'a>(&'a self, description: &'a str, repo_name: &'a str) -> BoxFuture<'a, Result<Option<String>>> {
+        self.inner.translate_description_korean(description, repo_name)
+    }
+
+    fn judge_quality<'a>(&'a self, readme: &'a str, repo_name: &'a str, reasons: &'a [String]) -> BoxFuture<'a, Result<Option<QualityVerdict>>> {
+        self.inner.judge_quality(readme, repo_name, reasons)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn concurrent_identical_calls_are_coalesced() {
+        let in_flight: InFlight<&str, usize> = InFlight::new("test");
+        let counter = AtomicUsize::new(0);
+        let calls = &counter;
+        let call = move || async move {
+            let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok::<_, anyhow::Error>(n)
+        };
+
+        let (first, second, other) = tokio::join!(
+            in_flight.run("acme/rocket", call),
+            in_flight.run("acme/rocket", call),
+            in_flight.run("acme/widget", call),
+        );
+        assert_eq!(first.expect("first"), second.expect("second"));
+        assert!(other.is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // Finished calls are not cached
+        in_flight.run("acme/rocket", call).await.expect("again");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -1,182 +1,1381 @@
 use anyhow::Result;
 use chrono::Utc;
-use std::collections::HashMap;
+use metrics::{counter, gauge};
 use tracing::{info, warn};
 
-use crate::config::Config;
+use crate::config::{Config, LanguageRules, QualityRules, ScoreWeights, StagePolicy, SummaryStyle, QUALITY_OFF};
 use crate::db::Database;
-use crate::models::{TrendingRepo, RepoLanguage, LanguageTrend, CollectionStatus};
-use crate::services::{OssInsightClient, GitHubClient, LlmClient};
+use crate::models::{TrendingRepo, RepoLanguage, CollectionStatus, RepoProgress, TrendCandidate, TrendingDeveloper, CollectionRepo, RepoIssue, LanguageInfo, LanguageTrend, StructuredSummary, RunCutoff, CollectionEntry, RepoSource, QualityDecision, CollectionReport, RepoReport};
+use crate::services::providers::{Providers, RepoMetadataSource, SummaryProvider, TrendSource};
+use crate::services::{anomaly, ingest, jobs, langdetect, links, milestones, pipeline, quality, readme, sources};
+use crate::services::archive::Archiver;
+use crate::services::breaker::CircuitBreakers;
+use crate::services::errors::ServiceError;
+use crate::services::moderation::Moderator;
+use crate::services::routing::SummaryRouter;
+use crate::services::tts::BriefAudio;
+use crate::services::changes::{Change, ChangeFeed};
+use crate::services::usage::UsageMeter;
+use crate::services::scoring::{self, ScoreInputs};
+use crate::services::trends::{self, LanguageShares};
+use crate::telemetry;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use tokio::sync::broadcast;
+use uuid::Uuid;
+
+const MAX_NOTABLE_REPOS: usize = 5;
+
+// Values of `trending_repos.summary_status`
+pub const SUMMARY_PENDING: &str = "pending";
+pub const SUMMARY_DONE: &str = "done";
+pub const SUMMARY_UNAVAILABLE: &str = "unavailable";
+pub const SUMMARY_FAILED: &str = "failed";
+/// Dropped by the quality filter before summarizing
+pub const SUMMARY_FILTERED: &str = "filtered";
+
+// Values of `CollectionStatus::phase`
+pub const PHASE_METADATA: &str = "metadata";
+pub const PHASE_SUMMARIES: &str = "summaries";
+pub const PHASE_COMPLETE: &str = "complete";
+
+// Values of `RepoProgress::event`
+/// The repo entered the phase named by the event
+pub const REPO_STARTED: &str = "repo_started";
+pub const README_FETCHED: &str = "readme_fetched";
+/// The summary's one-liner so far, while the LLM streams it; sent live but never stored
+pub const SUMMARY_PARTIAL: &str = "summary_partial";
+pub const SUMMARY_GENERATED: &str = "summary_generated";
+pub const REPO_FAILED: &str = "repo_failed";
+
+// Values of `RepoReport::outcome`, from best to worst
+pub const OUTCOME_COLLECTED: &str = "collected";
+/// Stored, but without its summary or languages
+pub const OUTCOME_PARTIAL: &str = "partial";
+/// Already summarized (only its metrics were refreshed) or out of the brief (dropped by the quality filter)
+pub const OUTCOME_SKIPPED: &str = "skipped";
+pub const OUTCOME_FAILED: &str = "failed";
+const OUTCOMES: &[&str] = &[OUTCOME_COLLECTED, OUTCOME_PARTIAL, OUTCOME_SKIPPED, OUTCOME_FAILED];
+
+/// A stored repo still waiting for its summary
+struct PendingSummary {
+    repo_id: i64,
+    repo_name: String,
+    /// Description the quality filter matches its patterns against; unset when retrying summaries
+    description: Option<String>,
+    /// Description to translate into Korean alongside the summary
+    foreign_description: Option<String>,
+    /// Style set when the summary was requeued; unset = `SUMMARY_*` settings
+    style: Option<SummaryStyle>,
+    /// Whether a fresh summary of an earlier appearance may stand in for a new one; off when
+    /// retrying summaries, which may have been requeued for being bad
+    reuse: bool,
+}
+
+/// A fetched README and how it compares with the repo's previous appearance
+struct FetchedReadme {
+    content: String,
+    hash: String,
+    /// `None` on the repo's first appearance with a README
+    updated: Option<bool>,
+    change_note: Option<String>,
+}
+
+/// Result of the README and summarize stages for one repo
+enum SummaryOutcome {
+    /// The summary and the model that wrote it
+    Done(StructuredSummary, String),
+    /// A fresh summary of the same README from an earlier appearance, its model and the date it was written
+    Reused(StructuredSummary, Option<String>, String),
+    /// The LLM returned nothing usable
+    Unavailable,
+    NoReadme,
+    ReadmeFailed(anyhow::Error),
+    SummaryFailed(anyhow::Error),
+    /// Dropped by the quality filter
+    Filtered,
+}
+
+/// Sends the progress events of one phase; shared by the concurrent stages of the phase
+struct PhaseProgress<'a> {
+    tx: Option<&'a broadcast::Sender<CollectionStatus>>,
+    run_id: &'a str,
+    phase: &'static str,
+    total: usize,
+    done: AtomicUsize,
+}
+
+impl<'a> PhaseProgress<'a> {
+    fn new(tx: Option<&'a broadcast::Sender<CollectionStatus>>, run_id: &'a str, phase: &'static str, total: usize) -> Self {
+        PhaseProgress { tx, run_id, phase, total, done: AtomicUsize::new(0) }
+    }
+
+    /// A repo made it through the phase
+    fn advance(&self, message: String) {
+        self.done.fetch_add(1, Ordering::SeqCst);
+        self.send(message, None);
+    }
+
+    fn repo(&self, event: &str, repo_id: i64, repo_name: &str, error: Option<String>) {
+        let message = match event {
+            REPO_STARTED => format!("Processing {}", repo_name),
+            README_FETCHED => format!("Fetched README of {}", repo_name),
+            SUMMARY_PARTIAL => format!("Summarizing {}", repo_name),
+            SUMMARY_GENERATED => format!("Summarized {}", repo_name),
+            _ => format!("{} failed", repo_name),
+        };
+        self.send(message, Some(RepoProgress {
+            event: event.to_string(),
+            repo_id,
+            repo_name: repo_name.to_string(),
+            error,
+            partial: None,
+        }));
+    }
+
+    fn partial(&self, repo_id: i64, repo_name: &str, one_liner: &str) {
+        self.send(format!("Summarizing {}", repo_name), Some(RepoProgress {
+            event: SUMMARY_PARTIAL.to_string(),
+            repo_id,
+            repo_name: repo_name.to_string(),
+            error: None,
+            partial: Some(one_liner.to_string()),
+        }));
+    }
+
+    fn send(&self, message: String, repo: Option<RepoProgress>) {
+        if let Some(tx) = self.tx {
+            let _ = tx.send(CollectionStatus {
+                run_id: self.run_id.to_string(),
+                is_running: true,
+                phase: self.phase.to_string(),
+                message,
+                current_count: self.done.load(Ordering::SeqCst),
+                total_count: self.total,
+                repo,
+            });
+        }
+    }
+}
+
+/// The outcome of every repo a run touched, in the order the run first reported on them. Each
+/// stage reports on the repos it handled, and a repo's outcome only gets worse as they do.
+struct RunReport {
+    repos: Mutex<Vec<RepoReport>>,
+}
+
+impl RunReport {
+    fn new() -> Self {
+        RunReport { repos: Mutex::new(Vec::new()) }
+    }
+
+    fn record(&self, repo_id: i64, repo_name: &str, outcome: &str, reason: Option<String>) {
+        self.add(repo_id, repo_name, outcome, reason, false);
+    }
 
+    /// Like `record`, for an outcome caused by a failed call or write
+    fn record_error(&self, repo_id: i64, repo_name: &str, outcome: &str, reason: String) {
+        self.add(repo_id, repo_name, outcome, Some(reason), true);
+    }
+
+    fn add(&self, repo_id: i64, repo_name: &str, outcome: &str, reason: Option<String>, errored: bool) {
+        let rank = |outcome: &str| OUTCOMES.iter().position(|o| *o == outcome).unwrap_or(0);
+        let mut repos = self.repos.lock().unwrap();
+        match repos.iter_mut().find(|r| r.repo_id == repo_id) {
+            Some(repo) => {
+                if rank(outcome) > rank(&repo.outcome) {
+                    repo.outcome = outcome.to_string();
+                }
+                repo.reasons.extend(reason);
+                repo.errored |= errored;
+            }
+            None => repos.push(RepoReport {
+                repo_id,
+                repo_name: repo_name.to_string(),
+                outcome: outcome.to_string(),
+                reasons: reason.into_iter().collect(),
+                errored,
+            }),
+        }
+    }
+
+    fn finish(self, run_id: &str, date: &str, kind: &str) -> CollectionReport {
+        let repos = self.repos.into_inner().unwrap();
+        let count = |outcome: &str| repos.iter().filter(|r| r.outcome == outcome).count();
+        CollectionReport {
+            run_id: run_id.to_string(),
+            date: date.to_string(),
+            kind: kind.to_string(),
+            collected: count(OUTCOME_COLLECTED),
+            partial: count(OUTCOME_PARTIAL),
+            skipped: count(OUTCOME_SKIPPED),
+            failed: count(OUTCOME_FAILED),
+            errored: repos.iter().filter(|r| r.errored).count(),
+            repos,
+        }
+    }
+}
+
+/// Runs one collection; each instance gets its own run id
 pub struct DataCollector {
-    oss_client: OssInsightClient,
-    github_client: GitHubClient,
-    llm_client: LlmClient,
+    run_id: String,
+    /// Labels the run's metrics
+    workspace: String,
+    usage: Arc<UsageMeter>,
+    breakers: Arc<CircuitBreakers>,
+    trends: Arc<dyn TrendSource>,
+    metadata: Arc<dyn RepoMetadataSource>,
+    summaries: Arc<dyn SummaryProvider>,
     db: Database,
     language_threshold: f64,
+    language_filter: Vec<String>,
+    max_repos_per_run: usize,
+    merge_sources: bool,
+    source_weights: Vec<(String, f64)>,
+    collections: Vec<i64>,
+    score_weights: ScoreWeights,
+    anomaly_stddev_threshold: f64,
+    anomaly_min_days: i64,
+    star_milestones: Vec<i64>,
+    changes: ChangeFeed,
+    archiver: Option<Archiver>,
+    audio: Option<BriefAudio>,
+    readme_change_notes: bool,
+    translate_descriptions: bool,
+    /// Second model summarizing a sample of repos for A/B comparison
+    eval_summaries: Option<Arc<dyn SummaryProvider>>,
+    eval_fraction: f64,
+    language_rules: LanguageRules,
+    quality_rules: QualityRules,
+    moderator: Option<Moderator>,
+    summary_style: SummaryStyle,
+    summary_router: SummaryRouter,
+    /// Days a summary stays fresh
+    summary_max_age_days: i64,
+    metadata_stage: StagePolicy,
+    readme_stage: StagePolicy,
+    summary_stage: StagePolicy,
+    /// Set for the rest of the run once GitHub or the LLM rejected our credentials
+    github_abort: pipeline::Abort,
+    llm_abort: pipeline::Abort,
 }
 
 impl DataCollector {
-    pub fn new(config: &Config, db: Database) -> Self {
+    pub fn new(config: &Config, db: Database, providers: &Providers, changes: ChangeFeed) -> Self {
         DataCollector {
-            oss_client: OssInsightClient::new(&config.oss_insight_base_url),
-            github_client: GitHubClient::new(&config.github_api_url, config.github_token.clone()),
-            llm_client: LlmClient::new(&config.deepseek_base_url, &config.deepseek_api_key, &config.deepseek_model),
+            run_id: Uuid::new_v4().to_string(),
+            workspace: config.workspace.clone(),
+            trends: providers.trends.clone(),
+            metadata: providers.metadata.clone(),
+            summaries: providers.summaries.clone(),
+            changes,
+            // A broken archive setup is reported but must not stop collection
+            archiver: Archiver::new(config, db.clone()).unwrap_or_else(|e| {
+                warn!("Archive publishing disabled: {:#}", e);
+                None
+            }),
+            // Likewise a template error only costs the recording
+            audio: BriefAudio::new(config, db.clone(), providers).unwrap_or_else(|e| {
+                warn!("Brief audio disabled: {:#}", e);
+                None
+            }),
             db,
             language_threshold: config.language_threshold,
+            language_filter: config.language_filter.clone(),
+            max_repos_per_run: config.max_repos_per_run,
+            merge_sources: config.merge_sources,
+            source_weights: config.source_weights.clone(),
+            collections: config.collections.clone(),
+            score_weights: config.score_weights.clone(),
+            anomaly_stddev_threshold: config.anomaly_stddev_threshold,
+            anomaly_min_days: config.anomaly_min_days,
+            star_milestones: config.star_milestones.clone(),
+            readme_change_notes: config.readme_change_notes,
+            translate_descriptions: config.translate_descriptions,
+            eval_summaries: providers.eval_summaries.clone(),
+            eval_fraction: config.eval_fraction,
+            usage: providers.usage.clone(),
+            breakers: providers.breakers.clone(),
+            language_rules: config.language_rules.clone(),
+            quality_rules: config.quality_rules.clone(),
+            moderator: Moderator::new(config, providers),
+            summary_style: config.summary_style.clone(),
+            summary_router: SummaryRouter::new(config, providers),
+            summary_max_age_days: config.summary_max_age_days,
+            metadata_stage: config.metadata_stage,
+            readme_stage: config.readme_stage,
+            summary_stage: config.summary_stage,
+            github_abort: pipeline::Abort::default(),
+            llm_abort: pipeline::Abort::default(),
         }
     }
 
-    pub async fn collect(&self, progress_tx: Option<broadcast::Sender<CollectionStatus>>) -> Result<usize> {
-        let today = Utc::now().format("%Y-%m-%d").to_string();
-        info!("Starting data collection for {}", today);
+    /// Run under an id handed out before the run started, e.g. when it was queued
+    pub fn with_run_id(mut self, run_id: String) -> Self {
+        self.run_id = run_id;
+        self
+    }
+
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    /// Collect into `date` (default today). Upstream sources only expose current data, so a past
+    /// date keeps the rows it has and only gets the repos it lacks from the current snapshot,
+    /// plus their missing summaries; rankings that are meaningless out of their day
+    /// (developers, collections) are skipped, and language trends are recomputed from all repos
+    /// stored for that date. The run's report, also stored, says what became of each repo.
+    pub async fn collect(&self, date: Option<&str>, progress_tx: Option<broadcast::Sender<CollectionStatus>>) -> Result<CollectionReport> {
+        let current = Utc::now().format("%Y-%m-%d").to_string();
+        // `today` is the date rows are stored under
+        let today = date.map(str::to_string).unwrap_or_else(|| current.clone());
+        let backfill = today != current;
+        info!("Starting data collection for {} (run {})", today, self.run_id);
+        if backfill {
+            warn!("Collecting past date {} from current upstream data", today);
+        }
+        // Refusals before the run started belong to no run's report
+        self.breakers.take_rejected();
+
+        // Step 1: Fetch trending repos from OSS Insight, parsing their numbers once
+        let mut parse_failures = ingest::ParseFailures::new();
+        let mut oss_repos = ingest::trend_candidates(self.trends.get_trending_repos().await?, &mut parse_failures);
+        if !self.language_filter.is_empty() {
+            oss_repos.retain(|r| {
+                r.primary_language.as_ref().is_some_and(|lang| {
+                    self.language_filter.iter().any(|f| f.eq_ignore_ascii_case(lang))
+                })
+            });
+        }
 
-        // Step 1: Fetch trending repos from OSS Insight
-        let oss_repos = self.oss_client.get_trending_repos().await?;
+        // Step 1b: Merge the configured collections in; they are then stored from here in step 7
+        let mut collections = Vec::new();
+        let mut merged: HashMap<i64, (f64, Vec<RepoSource>)> = HashMap::new();
+        if self.merge_sources && !backfill && !self.collections.is_empty() {
+            let mut lists = vec![(sources::SOURCE_OSS_INSIGHT.to_string(), std::mem::take(&mut oss_repos))];
+            for &collection_id in &self.collections {
+                match self.fetch_collection(collection_id, &mut parse_failures).await {
+                    Ok((name, rows)) => {
+                        lists.push((format!("collection:{}", collection_id), rows.iter().map(collection_candidate).collect()));
+                        collections.push((collection_id, name, rows));
+                    }
+                    Err(e) => warn!("Failed to fetch collection {} for merging: {}", collection_id, e),
+                }
+            }
+            for candidate in sources::merge_candidates(lists, &self.source_weights) {
+                merged.insert(candidate.row.repo_id, (candidate.score, candidate.provenance));
+                oss_repos.push(candidate.row);
+            }
+            info!("Merged {} sources into {} candidates", collections.len() + 1, oss_repos.len());
+        }
+
+        let mut cutoff = self.trim_to_max_repos(&mut oss_repos, &merged);
+        cutoff.parse_failures = parse_failures;
+        if let Err(e) = self.db.save_run_cutoff(&today, &self.run_id, &cutoff) {
+            warn!("Failed to save the run's cut-off: {}", e);
+        }
+        for repo in &oss_repos {
+            if let Some((_, provenance)) = merged.get(&repo.repo_id) {
+                if let Err(e) = self.db.save_repo_sources(&today, repo.repo_id, provenance) {
+                    warn!("Failed to save sources of {}: {}", repo.repo_name, e);
+                }
+            }
+        }
         let total_repos = oss_repos.len();
-        info!("Fetched {} repos from OSS Insight", total_repos);
+        let fetched = if cutoff.trimmed() > 0 {
+            format!(
+                "Fetched {} repos from OSS Insight, kept the top {} by score",
+                cutoff.candidates, total_repos
+            )
+        } else {
+            format!("Fetched {} repos from OSS Insight", total_repos)
+        };
+        info!("{}", fetched);
 
         if let Some(tx) = &progress_tx {
             let _ = tx.send(CollectionStatus {
+                run_id: self.run_id.clone(),
                 is_running: true,
-                message: format!("Fetched {} repos from OSS Insight", total_repos),
+                phase: PHASE_METADATA.to_string(),
+                message: fetched,
                 current_count: 0,
                 total_count: total_repos,
+                repo: None,
             });
         }
 
-        let mut language_stats: HashMap<String, (f64, i32)> = HashMap::new();
+        let mut shares = LanguageShares::new(&self.language_rules);
         let mut collected_count = 0;
+        let mut skipped_count = 0;
+        let mut pending_summaries: Vec<PendingSummary> = Vec::new();
+        let report = RunReport::new();
 
-        // Get existing repo IDs that already have summaries (to skip)
-        let existing_ids = self.db.get_existing_repo_ids(&today).unwrap_or_default();
-        let skipped_count = existing_ids.len();
-        if skipped_count > 0 {
-            info!("Skipping {} repos that already have summaries", skipped_count);
+        // Repos that already have fresh summaries still get their metrics refreshed, but skip the README/LLM work
+        let existing_ids = self.db.get_fresh_summary_ids(&today, &self.fresh_since()).unwrap_or_default();
+        if !existing_ids.is_empty() {
+            info!("{} repos already have fresh summaries; refreshing only their metrics", existing_ids.len());
         }
-
-        for (i, oss_repo) in oss_repos.iter().enumerate() {
-            let repo_id: i64 = oss_repo.repo_id.parse().unwrap_or(0);
-            let repo_name = &oss_repo.repo_name;
-
-            // Skip if already has summary for today
-            if existing_ids.contains(&repo_id) {
-                info!("Skipping {} (already has summary)", repo_name);
-                continue;
+        // A past date keeps the metrics and rank its rows were stored with: today's snapshot only
+        // fills in the repos it lacks, ranked after the stored ones, and summaries still missing
+        let stored_ranks = if backfill {
+            self.db.get_stored_ranks(&today).unwrap_or_else(|e| {
+                warn!("Failed to load the stored repos of {}: {}", today, e);
+                HashMap::new()
+            })
+        } else {
+            HashMap::new()
+        };
+        let mut kept_count = 0;
+        for oss_repo in oss_repos.iter().filter(|r| stored_ranks.contains_key(&r.repo_id)) {
+            kept_count += 1;
+            report.record(oss_repo.repo_id, &oss_repo.repo_name, OUTCOME_SKIPPED, Some(format!("Already stored for {}; its metrics and rank are kept", today)));
+            if !existing_ids.contains(&oss_repo.repo_id) {
+                pending_summaries.push(PendingSummary {
+                    repo_id: oss_repo.repo_id,
+                    repo_name: oss_repo.repo_name.clone(),
+                    description: oss_repo.description.clone(),
+                    foreign_description: None,
+                    style: None,
+                    reuse: true,
+                });
             }
+        }
+        let first_rank = stored_ranks.values().flatten().max().copied().unwrap_or(0) + 1;
 
-            // Step 2: Fetch README and generate Korean summary
-            let korean_summary = match self.github_client.get_readme(repo_name).await {
-                Ok(Some(readme)) => {
-                    match self.llm_client.summarize_readme_korean(&readme, repo_name).await {
-                        Ok(summary) => summary,
-                        Err(e) => {
-                            warn!("Failed to summarize README for {}: {}", repo_name, e);
-                            None
-                        }
+        // Rank is fixed here, in upstream (or, when trimmed, score) order
+        let queued: Vec<(i32, &TrendCandidate)> = oss_repos
+            .iter()
+            .filter(|r| !stored_ranks.contains_key(&r.repo_id))
+            .enumerate()
+            .map(|(i, r)| (first_rank + i as i32, r))
+            .collect();
+        let total_queued = queued.len();
+        let progress = PhaseProgress::new(progress_tx.as_ref(), &self.run_id, PHASE_METADATA, total_queued);
+        let progress = &progress;
+
+        // Phase 1: fetch metadata -> persist, so the brief is servable right away
+        let (source_tx, source_rx) = pipeline::channel();
+        let (fetched_tx, mut fetched_rx) = pipeline::channel();
+        let persist = async {
+            while let Some((rank, oss_repo, languages, total_stars, homepage)) = fetched_rx.recv().await {
+                if let Err(e) = &languages {
+                    report.record_error(oss_repo.repo_id, &oss_repo.repo_name, OUTCOME_PARTIAL, format!("Languages fetch failed: {:#}", e));
+                }
+                match self.persist_metadata(&today, rank, oss_repo, languages, total_stars, homepage, &mut shares) {
+                    Some(_) if existing_ids.contains(&oss_repo.repo_id) => {
+                        skipped_count += 1;
+                        report.record(oss_repo.repo_id, &oss_repo.repo_name, OUTCOME_SKIPPED, Some("Already summarized; metrics refreshed".to_string()));
+                    }
+                    Some(pending) => {
+                        collected_count += 1;
+                        report.record(oss_repo.repo_id, &oss_repo.repo_name, OUTCOME_COLLECTED, None);
+                        pending_summaries.push(pending);
+                    }
+                    None => {
+                        let error = "Failed to store the repo".to_string();
+                        report.record_error(oss_repo.repo_id, &oss_repo.repo_name, OUTCOME_FAILED, error.clone());
+                        progress.repo(REPO_FAILED, oss_repo.repo_id, &oss_repo.repo_name, Some(error));
                     }
                 }
-                Ok(None) => None,
-                Err(e) => {
-                    warn!("Failed to fetch README for {}: {}", repo_name, e);
+                progress.advance(format!("Stored {}", oss_repo.repo_name));
+            }
+        };
+        tokio::join!(
+            pipeline::feed(source_tx, queued),
+            pipeline::run_stage(source_rx, fetched_tx, self.metadata_stage.concurrency, |(rank, oss_repo)| async move {
+                progress.repo(REPO_STARTED, oss_repo.repo_id, &oss_repo.repo_name, None);
+                let languages = self.github_abort.guard(pipeline::with_retry(&self.metadata_stage, &format!("Languages of {}", oss_repo.repo_name), || {
+                    self.metadata.get_repo_languages(&oss_repo.repo_name, self.language_threshold)
+                })).await;
+                let info = self.github_abort.guard(pipeline::with_retry(&self.metadata_stage, &format!("Repo info of {}", oss_repo.repo_name), || {
+                    self.metadata.get_repo_info(&oss_repo.repo_name)
+                })).await.unwrap_or_else(|e| {
+                    warn!("Failed to fetch repo info for {}: {}", oss_repo.repo_name, e);
                     None
-                }
-            };
+                });
+                let homepage = links::homepage_url(info.as_ref().and_then(|i| i.homepage.as_deref()));
+                // Today's star count says nothing about a past date, so backfills leave it unset
+                let total_stars = info
+                    .map(|i| i.stargazers_count)
+                    .filter(|_| !backfill && !self.star_milestones.is_empty());
+                (rank, oss_repo, languages, total_stars, homepage)
+            }),
+            persist,
+        );
 
-            // Step 3: Fetch language statistics
-            let languages = match self.github_client.get_repo_languages(repo_name, self.language_threshold).await {
-                Ok(langs) => langs,
+        // Step 4: Calculate and save daily language trends (normalized, after exclusions/remaps)
+        let trends = if backfill {
+            // A re-collected day may hold repos this run skipped, so count everything stored
+            match self.backfill_language_trends(&today) {
+                Ok(trends) => trends,
                 Err(e) => {
-                    warn!("Failed to fetch languages for {}: {}", repo_name, e);
+                    warn!("Failed to recompute language trends for {}: {}", today, e);
                     vec![]
                 }
-            };
+            }
+        } else {
+            let trends = shares.into_trends(&today, Some(&self.run_id));
+            for trend in &trends {
+                if let Err(e) = self.db.save_language_trend(trend) {
+                    warn!("Failed to save language trend for {}: {}", trend.language, e);
+                }
+            }
+            self.changes.publish(Change::LanguageTrendsStored { date: today.clone() });
+            trends
+        };
 
-            // Save repo languages
-            for lang in &languages {
-                let repo_lang = RepoLanguage {
-                    date: today.clone(),
-                    repo_id,
-                    language: lang.language.clone(),
-                    percentage: lang.percentage,
-                };
-                if let Err(e) = self.db.save_repo_language(&repo_lang) {
-                    warn!("Failed to save language for {}: {}", repo_name, e);
+        if !trends.is_empty() {
+            info!("Saved {} language trends", trends.len());
+
+            // Step 5: Flag language share spikes against the trailing window
+            match anomaly::detect_language_anomalies(
+                &self.db,
+                &today,
+                self.anomaly_stddev_threshold,
+                self.anomaly_min_days,
+            ) {
+                Ok(alerts) if !alerts.is_empty() => {
+                    info!("Raised {} language alerts", alerts.len());
+                    for alert in alerts {
+                        self.changes.publish(Change::LanguageAlert(alert));
+                    }
                 }
+                Ok(_) => {}
+                Err(e) => warn!("Language anomaly detection failed: {}", e),
+            }
+        }
 
-                // Accumulate for daily trend
-                let entry = language_stats.entry(lang.language.clone()).or_insert((0.0, 0));
-                entry.0 += lang.percentage;
-                entry.1 += 1;
+        // Step 5b: Repos whose total stars passed a milestone since they last trended
+        if !backfill && !self.star_milestones.is_empty() {
+            match milestones::detect_star_milestones(&self.db, &today, &self.star_milestones) {
+                Ok(crossed) if !crossed.is_empty() => {
+                    info!("Raised {} star milestones", crossed.len());
+                    for milestone in crossed {
+                        self.changes.publish(Change::StarMilestone(milestone));
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Star milestone detection failed: {}", e),
             }
+        }
 
-            // Save trending repo
-            let trending_repo = TrendingRepo {
-                date: today.clone(),
-                repo_id,
-                repo_name: repo_name.clone(),
-                primary_language: oss_repo.primary_language.clone(),
-                description: oss_repo.description.clone(),
-                korean_summary,
-                stars: oss_repo.stars.as_ref().and_then(|s| s.parse().ok()),
-                forks: oss_repo.forks.as_ref().and_then(|s| s.parse().ok()),
-                pull_requests: oss_repo.pull_requests.as_ref().and_then(|s| s.parse().ok()),
-                pushes: oss_repo.pushes.as_ref().and_then(|s| s.parse().ok()),
-                total_score: oss_repo.total_score.as_ref().and_then(|s| s.parse().ok()),
-                contributor_logins: oss_repo.contributor_logins.clone(),
-                collection_names: oss_repo.collection_names.clone(),
-            };
+        // Phase 2: fetch README -> summarize -> persist, now that the brief is already servable
+        self.summarize_pending(&today, pending_summaries, progress_tx.as_ref(), &report).await;
 
-            if let Err(e) = self.db.save_trending_repo(&trending_repo) {
-                warn!("Failed to save trending repo {}: {}", repo_name, e);
-            } else {
-                collected_count += 1;
+        if !backfill {
+            // Step 6: Trending developers
+            match self.collect_developers(&today, &oss_repos).await {
+                Ok(count) => info!("Saved {} trending developers", count),
+                Err(e) => warn!("Failed to collect trending developers: {}", e),
             }
 
-            // Rate limiting: small delay between repos
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+            // Step 7: Configured OSS Insight collections, unless already fetched for merging
+            if collections.is_empty() {
+                for &collection_id in &self.collections {
+                    match self.fetch_collection(collection_id, &mut cutoff.parse_failures).await {
+                        Ok((name, rows)) => collections.push((collection_id, name, rows)),
+                        Err(e) => warn!("Failed to collect collection {}: {}", collection_id, e),
+                    }
+                }
+                if !cutoff.parse_failures.is_empty() {
+                    if let Err(e) = self.db.save_run_cutoff(&today, &self.run_id, &cutoff) {
+                        warn!("Failed to save the run's parse failures: {}", e);
+                    }
+                }
+            }
+            for (collection_id, name, rows) in &collections {
+                let count = self.save_collection(&today, *collection_id, name, rows);
+                info!("Saved {} repos of collection {}", count, collection_id);
+            }
+        }
 
-            if let Some(tx) = &progress_tx {
-                let _ = tx.send(CollectionStatus {
-                    is_running: true,
-                    message: format!("Processed {}", repo_name),
-                    current_count: i + 1,
-                    total_count: total_repos,
-                });
+        cutoff.circuit_breaks = self.breakers.take_rejected();
+        if !cutoff.circuit_breaks.is_empty() {
+            warn!("Open circuits refused calls during the run: {:?}", cutoff.circuit_breaks);
+            if let Err(e) = self.db.save_run_cutoff(&today, &self.run_id, &cutoff) {
+                warn!("Failed to save the run's circuit breaks: {}", e);
             }
         }
 
-        // Step 4: Calculate and save daily language trends (normalized)
-        let total_percentage: f64 = language_stats.values().map(|(p, _)| p).sum();
-        
-        if total_percentage > 0.0 {
-            for (language, (sum_percentage, repo_count)) in &language_stats {
-                let normalized = (sum_percentage / total_percentage) * 100.0;
-                let trend = LanguageTrend {
-                    date: today.clone(),
-                    language: language.clone(),
-                    normalized_percentage: normalized,
-                    repo_count: *repo_count,
-                };
-                if let Err(e) = self.db.save_language_trend(&trend) {
-                    warn!("Failed to save language trend for {}: {}", language, e);
-                }
+        // Usage is booked on the day it was spent, not the day collected into
+        if let Err(e) = self.db.add_api_usage(&current, &self.usage.take()) {
+            warn!("Failed to save API usage: {}", e);
+        }
+
+        // Step 7b: Read the brief aloud, before archiving so the recording is published with it
+        if let Some(audio) = &self.audio {
+            if let Err(e) = audio.generate(&today).await {
+                warn!("Failed to record the brief of {}: {:#}", today, e);
             }
-            info!("Saved {} language trends", language_stats.len());
         }
 
-        info!("Data collection complete. Collected {} repos.", collected_count);
+        // Step 8: Publish the day's exports to object storage
+        if let Some(archiver) = &self.archiver {
+            if let Err(e) = archiver.publish(&today).await {
+                warn!("Failed to archive {}: {:#}", today, e);
+            }
+            if let Err(e) = archiver.prune().await {
+                warn!("Failed to prune archive: {:#}", e);
+            }
+        }
+
+        let report = report.finish(&self.run_id, &today, if backfill { jobs::JOB_BACKFILL } else { jobs::JOB_COLLECT });
+        if let Err(e) = self.db.save_run_report(&report) {
+            warn!("Failed to save the run's report: {}", e);
+        }
+
+        info!(
+            "Data collection complete. Collected {} repos ({} partly), skipped {}, {} failed.",
+            collected_count, report.partial, report.skipped, report.failed
+        );
+        self.record_run_metrics(backfill, collected_count, skipped_count + kept_count, total_queued - collected_count - skipped_count);
+        self.changes.publish(Change::CollectionCompleted {
+            date: today.clone(),
+            run_id: self.run_id.clone(),
+            collected: collected_count,
+            backfill,
+        });
         
         if let Some(tx) = &progress_tx {
             let _ = tx.send(CollectionStatus {
+                run_id: self.run_id.clone(),
                 is_running: false,
+                phase: PHASE_COMPLETE.to_string(),
                 message: format!("Collection complete. Collected {} repos.", collected_count),
                 current_count: total_repos,
                 total_count: total_repos,
+                repo: None,
             });
         }
         
-        Ok(collected_count)
+        Ok(report)
+    }
+
+    /// Keep the `MAX_REPOS_PER_RUN` highest-scoring candidates, so only those are summarized;
+    /// merged candidates are ranked by their merged score
+    fn trim_to_max_repos(&self, oss_repos: &mut Vec<TrendCandidate>, merged: &HashMap<i64, (f64, Vec<RepoSource>)>) -> RunCutoff {
+        let candidates = oss_repos.len();
+        let score = |r: &TrendCandidate| match merged.get(&r.repo_id) {
+            Some((score, _)) => Some(*score),
+            None => r.total_score,
+        };
+
+        if self.max_repos_per_run > 0 && candidates > self.max_repos_per_run {
+            // Stable, so equal scores keep their upstream order
+            oss_repos.sort_by(|a, b| score(b).unwrap_or(f64::MIN).total_cmp(&score(a).unwrap_or(f64::MIN)));
+            oss_repos.truncate(self.max_repos_per_run);
+        }
+
+        RunCutoff {
+            candidates,
+            kept: oss_repos.len(),
+            max_repos: Some(self.max_repos_per_run).filter(|&n| n > 0),
+            min_score: oss_repos.last().and_then(score),
+            parse_failures: Default::default(),
+            circuit_breaks: Default::default(),
+        }
+    }
+
+    fn record_run_metrics(&self, backfill: bool, collected: usize, skipped: usize, failed: usize) {
+        let kind = if backfill { "backfill" } else { "collect" };
+        counter!(telemetry::COLLECTOR_RUNS, "workspace" => self.workspace.clone(), "kind" => kind).increment(1);
+        for (outcome, count) in [("collected", collected), ("skipped", skipped), ("failed", failed)] {
+            let labels = [("workspace", self.workspace.clone()), ("outcome", outcome.to_string())];
+            counter!(telemetry::COLLECTOR_REPOS, &labels).increment(count as u64);
+            gauge!(telemetry::COLLECTOR_LAST_RUN_REPOS, &labels).set(count as f64);
+        }
+    }
+
+    /// Phase 2 of a run: fetch README -> summarize -> persist for each stored repo
+    async fn summarize_pending(
+        &self,
+        today: &str,
+        pending_summaries: Vec<PendingSummary>,
+        progress_tx: Option<&broadcast::Sender<CollectionStatus>>,
+        report: &RunReport,
+    ) {
+        let progress = PhaseProgress::new(progress_tx, &self.run_id, PHASE_SUMMARIES, pending_summaries.len());
+        let progress = &progress;
+        let (source_tx, source_rx) = pipeline::channel();
+        let (readme_tx, readme_rx) = pipeline::channel();
+        let (summary_tx, mut summary_rx) = pipeline::channel();
+        let persist = async {
+            while let Some((pending, readme, outcome, evaluation)) = summary_rx.recv().await {
+                if let (SummaryOutcome::Done(summary, model), Some(evaluation)) = (&outcome, &evaluation) {
+                    self.persist_evaluation(today, &pending, (model, summary), evaluation);
+                }
+                let failure = match &outcome {
+                    SummaryOutcome::Done(..) | SummaryOutcome::Reused(..) => None,
+                    SummaryOutcome::Unavailable => Some("The LLM returned no usable summary".to_string()),
+                    SummaryOutcome::NoReadme => Some("No README".to_string()),
+                    SummaryOutcome::ReadmeFailed(e) => Some(format!("README fetch failed: {:#}", e)),
+                    SummaryOutcome::SummaryFailed(e) => Some(format!("Summary failed: {:#}", e)),
+                    SummaryOutcome::Filtered => Some("Dropped by the quality filter".to_string()),
+                };
+                let outcome_of_repo = match &outcome {
+                    SummaryOutcome::Done(..) | SummaryOutcome::Reused(..) => OUTCOME_COLLECTED,
+                    SummaryOutcome::Filtered => OUTCOME_SKIPPED,
+                    _ => OUTCOME_PARTIAL,
+                };
+                let reason = match &outcome {
+                    SummaryOutcome::Reused(_, _, summarized_on) => Some(format!("Reused the summary written on {}", summarized_on)),
+                    _ => failure.clone(),
+                };
+                match (&outcome, reason) {
+                    (SummaryOutcome::ReadmeFailed(_) | SummaryOutcome::SummaryFailed(_), Some(reason)) => {
+                        report.record_error(pending.repo_id, &pending.repo_name, outcome_of_repo, reason)
+                    }
+                    (_, reason) => report.record(pending.repo_id, &pending.repo_name, outcome_of_repo, reason),
+                }
+                let status = self.persist_summary(today, &pending, readme, outcome);
+
+                match failure {
+                    None => progress.repo(SUMMARY_GENERATED, pending.repo_id, &pending.repo_name, None),
+                    Some(error) => progress.repo(REPO_FAILED, pending.repo_id, &pending.repo_name, Some(error)),
+                }
+                progress.advance(format!("Summarized {} ({})", pending.repo_name, status));
+            }
+        };
+        tokio::join!(
+            pipeline::feed(source_tx, pending_summaries),
+            pipeline::run_stage(source_rx, readme_tx, self.readme_stage.concurrency, |pending: PendingSummary| async move {
+                progress.repo(REPO_STARTED, pending.repo_id, &pending.repo_name, None);
+                let readme = self.github_abort.guard(pipeline::with_retry(&self.readme_stage, &format!("README of {}", pending.repo_name), || {
+                    self.metadata.get_readme(&pending.repo_name)
+                })).await;
+                if let Ok(Some(_)) = &readme {
+                    progress.repo(README_FETCHED, pending.repo_id, &pending.repo_name, None);
+                }
+                (pending, readme)
+            }),
+            pipeline::run_stage(readme_rx, summary_tx, self.summary_stage.concurrency, |(pending, readme): (PendingSummary, Result<Option<String>>)| async move {
+                let content = readme.as_ref().ok().and_then(|r| r.as_deref());
+                if self.screen_quality(today, &pending, content).await == Some(quality::DECISION_DROPPED) {
+                    return (pending, None, SummaryOutcome::Filtered, None);
+                }
+                self.moderate(today, &pending, content).await;
+                let (readme, outcome, evaluation) = match readme {
+                    Ok(Some(readme)) => {
+                        let (outcome, evaluation) = match self.reusable_summary(today, &pending, &readme) {
+                            Some(reused) => (reused, None),
+                            None => {
+                                let summaries = self.summary_router.route(&readme);
+                                let on_partial = |one_liner: &str| progress.partial(pending.repo_id, &pending.repo_name, one_liner);
+                                let summary = self.llm_abort.guard(pipeline::with_retry(&self.summary_stage, &format!("Summary of {}", pending.repo_name), || {
+                                    summaries.summarize_readme_korean_streaming(&readme, &pending.repo_name, self.style_of(&pending), &on_partial)
+                                })).await;
+                                let outcome = match summary {
+                                    Ok(Some(summary)) => SummaryOutcome::Done(summary, summaries.model().to_string()),
+                                    Ok(None) => SummaryOutcome::Unavailable,
+                                    Err(e) => SummaryOutcome::SummaryFailed(e),
+                                };
+                                let evaluation = match &outcome {
+                                    SummaryOutcome::Done(..) => self.evaluate_summary(&pending, &readme).await,
+                                    _ => None,
+                                };
+                                (outcome, evaluation)
+                            }
+                        };
+                        (Some(self.compare_readme(today, &pending, readme).await), outcome, evaluation)
+                    }
+                    Ok(None) => (None, SummaryOutcome::NoReadme, None),
+                    Err(e) => (None, SummaryOutcome::ReadmeFailed(e), None),
+                };
+                self.translate_description(today, &pending).await;
+                (pending, readme, outcome, evaluation)
+            }),
+            persist,
+        );
+    }
+
+    /// Retry the summaries of `date`'s repos that have none, e.g. after an LLM or GitHub outage
+    pub async fn summarize_missing(&self, date: &str, progress_tx: Option<broadcast::Sender<CollectionStatus>>) -> Result<CollectionReport> {
+        let pending: Vec<PendingSummary> = self
+            .db
+            .get_unsummarized_repos(date)?
+            .into_iter()
+            .map(|(repo_id, repo_name, style)| {
+                let style = style.and_then(|json| match serde_json::from_str(&json) {
+                    Ok(style) => Some(style),
+                    Err(e) => {
+                        warn!("Ignoring the stored summary style of {}: {}", repo_name, e);
+                        None
+                    }
+                });
+                PendingSummary { repo_id, repo_name, description: None, foreign_description: None, style, reuse: false }
+            })
+            .collect();
+        let total = pending.len();
+        info!("Summarizing {} repos of {} without a summary (run {})", total, date, self.run_id);
+
+        let report = RunReport::new();
+        self.summarize_pending(date, pending, progress_tx.as_ref(), &report).await;
+        let report = report.finish(&self.run_id, date, jobs::JOB_SUMMARIZE_MISSING);
+        if let Err(e) = self.db.save_run_report(&report) {
+            warn!("Failed to save the run's report: {}", e);
+        }
+
+        let current = Utc::now().format("%Y-%m-%d").to_string();
+        if let Err(e) = self.db.add_api_usage(&current, &self.usage.take()) {
+            warn!("Failed to save API usage: {}", e);
+        }
+
+        if let Some(tx) = &progress_tx {
+            let _ = tx.send(CollectionStatus {
+                run_id: self.run_id.clone(),
+                is_running: false,
+                phase: PHASE_COMPLETE.to_string(),
+                message: format!("Summarized {} repos of {}", total, date),
+                current_count: total,
+                total_count: total,
+                repo: None,
+            });
+        }
+
+        Ok(report)
+    }
+
+    /// Recompute and replace `date`'s language trends from the repo languages stored for it,
+    /// under the current threshold and trend rules
+    pub fn backfill_language_trends(&self, date: &str) -> Result<Vec<LanguageTrend>> {
+        let trends = trends::stored_language_trends(
+            &self.db,
+            &self.language_rules,
+            date,
+            "unweighted",
+            self.language_threshold,
+            Some(&self.run_id),
+        )?;
+        self.db.replace_language_trends(date, &trends)?;
+        self.changes.publish(Change::LanguageTrendsStored { date: date.to_string() });
+        Ok(trends)
+    }
+
+    /// Persist stage of phase 1: store a repo and its languages, queueing it for a summary
+    #[allow(clippy::too_many_arguments)]
+    fn persist_metadata(
+        &self,
+        today: &str,
+        rank: i32,
+        oss_repo: &TrendCandidate,
+        languages: Result<Vec<LanguageInfo>>,
+        total_stars: Option<i64>,
+        homepage: Option<String>,
+        shares: &mut LanguageShares<'_>,
+    ) -> Option<PendingSummary> {
+        let repo_id = oss_repo.repo_id;
+        let repo_name = &oss_repo.repo_name;
+
+        if let Err(e) = self.db.clear_repo_issues(today, repo_id) {
+            warn!("Failed to clear issues for {}: {}", repo_name, e);
+        }
+
+        let languages = match languages {
+            Ok(langs) => langs,
+            Err(e) => {
+                warn!("Failed to fetch languages for {}: {}", repo_name, e);
+                self.record_issue(today, repo_id, "languages", issue_code(&e), Some(e.to_string()));
+                vec![]
+            }
+        };
+
+        // Save repo languages
+        for lang in &languages {
+            let repo_lang = RepoLanguage {
+                date: today.to_string(),
+                repo_id,
+                language: lang.language.clone(),
+                percentage: lang.percentage,
+                run_id: Some(self.run_id.clone()),
+            };
+            if let Err(e) = self.db.save_repo_language(&repo_lang) {
+                warn!("Failed to save language for {}: {}", repo_name, e);
+            }
+        }
+
+        // Accumulate for daily trend
+        shares.add_repo(languages.iter().map(|l| (l.language.as_str(), l.percentage)), 1.0);
+
+        // Save trending repo; the summary is filled in by phase 2
+        let mut trending_repo = TrendingRepo {
+            date: today.to_string(),
+            repo_id,
+            repo_name: repo_name.clone(),
+            primary_language: oss_repo.primary_language.clone(),
+            description: oss_repo.description.clone(),
+            korean_summary: None,
+            description_language: oss_repo.description.as_deref().and_then(langdetect::detect_language).map(str::to_string),
+            description_ko: None,
+            stars: oss_repo.stars,
+            forks: oss_repo.forks,
+            pull_requests: oss_repo.pull_requests,
+            pushes: oss_repo.pushes,
+            total_score: oss_repo.total_score,
+            contributor_logins: oss_repo.contributor_logins.clone(),
+            collection_names: oss_repo.collection_names.clone(),
+            computed_score: None,
+            summary_status: Some(SUMMARY_PENDING.to_string()),
+            run_id: Some(self.run_id.clone()),
+            readme_updated: None,
+            readme_change_note: None,
+            summary_features: None,
+            summary_tech_stack: None,
+            summary_tags: None,
+            category: None,
+            audience: None,
+            difficulty: None,
+            rank: Some(rank),
+            owner: repo_name.split_once('/').map(|(owner, _)| owner.to_string()),
+            total_stars,
+            lifecycle: None,
+            homepage,
+            doc_links: None,
+            quality_flag: None,
+            policy_flags: None,
+            summary_model: None,
+        };
+        let previous_dates = self.db
+            .get_repo_appearance_dates(repo_id, today)
+            .unwrap_or_default();
+        let (days_since_first_seen, streak) = scoring::history_signals(today, &previous_dates);
+        trending_repo.lifecycle = Some(scoring::lifecycle(days_since_first_seen, streak).to_string());
+        trending_repo.computed_score = Some(self.compute_score(&trending_repo, days_since_first_seen, streak));
+
+        match self.db.save_trending_repo(&trending_repo) {
+            Ok(()) => {
+                let logins: Vec<&str> = oss_repo
+                    .contributor_logins
+                    .as_deref()
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty())
+                    .collect();
+                if let Err(e) = self.db.save_repo_contributors(today, repo_id, &logins) {
+                    warn!("Failed to save contributors of {}: {}", repo_name, e);
+                }
+                self.changes.publish(Change::RepoStored {
+                    date: today.to_string(),
+                    repo_id,
+                    repo_name: repo_name.clone(),
+                    rank,
+                });
+                let translate = self.translate_descriptions
+                    && langdetect::needs_translation(trending_repo.description_language.as_deref());
+                let foreign_description = trending_repo.description.clone().filter(|_| translate);
+                Some(PendingSummary {
+                    repo_id,
+                    repo_name: repo_name.clone(),
+                    description: trending_repo.description.clone(),
+                    foreign_description,
+                    style: None,
+                    reuse: true,
+                })
+            }
+            Err(e) => {
+                warn!("Failed to save trending repo {}: {}", repo_name, e);
+                None
+            }
+        }
+    }
+
+    /// Filter stage of phase 2: judge the repo under `QUALITY_FILTER`, recording the decision
+    /// and marking the repo. `None` when the filter is off or caught nothing.
+    async fn screen_quality(&self, today: &str, pending: &PendingSummary, readme: Option<&str>) -> Option<&'static str> {
+        if self.quality_rules.mode == QUALITY_OFF {
+            return None;
+        }
+        let reasons = quality::heuristic_reasons(&self.quality_rules, &pending.repo_name, pending.description.as_deref(), readme);
+
+        let decision = if reasons.is_empty() {
+            None
+        } else {
+            let verdict = if self.quality_rules.llm_judge {
+                self.summaries
+                    .judge_quality(readme.unwrap_or_default(), &pending.repo_name, &reasons)
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!("Quality judgment failed for {}: {}", pending.repo_name, e);
+                        None
+                    })
+            } else {
+                None
+            };
+            let decision = quality::decide(&self.quality_rules, verdict.as_ref());
+            info!("Quality filter {} {} ({})", decision, pending.repo_name, reasons.join("; "));
+            let record = QualityDecision {
+                date: today.to_string(),
+                repo_id: pending.repo_id,
+                repo_name: pending.repo_name.clone(),
+                decision: decision.to_string(),
+                reasons,
+                llm_verdict: verdict,
+                run_id: Some(self.run_id.clone()),
+                created_at: None,
+            };
+            if let Err(e) = self.db.save_quality_decision(&record) {
+                warn!("Failed to record the quality decision on {}: {}", pending.repo_name, e);
+            }
+            Some(decision)
+        };
+
+        // A re-collected repo may have been marked by an earlier run
+        let flag = decision.filter(|&d| d != quality::DECISION_KEPT);
+        if let Err(e) = self.db.update_quality_flag(today, pending.repo_id, flag) {
+            warn!("Failed to mark the quality of {}: {}", pending.repo_name, e);
+        }
+        decision
+    }
+
+    /// Moderation stage of phase 2, before the summary is stored: flag the repo when its
+    /// description or README violates the content policy
+    async fn moderate(&self, today: &str, pending: &PendingSummary, readme: Option<&str>) {
+        let Some(moderator) = &self.moderator else {
+            return;
+        };
+        match moderator.screen(pending.description.as_deref(), readme).await {
+            Ok(violations) => {
+                if !violations.is_empty() {
+                    info!("Moderation flagged {} ({})", pending.repo_name, violations.join(", "));
+                }
+                if let Err(e) = self.db.update_policy_flags(today, pending.repo_id, &violations) {
+                    warn!("Failed to save the policy flags of {}: {}", pending.repo_name, e);
+                }
+            }
+            Err(e) => {
+                warn!("Moderation failed for {}: {}", pending.repo_name, e);
+                self.record_issue(today, pending.repo_id, "moderation", issue_code(&e), Some(e.to_string()));
+            }
+        }
+    }
+
+    /// Store a Korean translation of a description in neither Korean nor English; a failed
+    /// translation only leaves the original description to show
+    async fn translate_description(&self, today: &str, pending: &PendingSummary) {
+        let Some(description) = &pending.foreign_description else {
+            return;
+        };
+        let translation = self.llm_abort.guard(pipeline::with_retry(&self.summary_stage, &format!("Description of {}", pending.repo_name), || {
+            self.summaries.translate_description_korean(description, &pending.repo_name)
+        })).await;
+
+        match translation {
+            Ok(Some(translation)) => {
+                if let Err(e) = self.db.update_description_translation(today, pending.repo_id, translation.trim()) {
+                    warn!("Failed to save the description translation of {}: {}", pending.repo_name, e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to translate the description of {}: {}", pending.repo_name, e),
+        }
+    }
+
+    fn style_of<'a>(&'a self, pending: &'a PendingSummary) -> &'a SummaryStyle {
+        pending.style.as_ref().unwrap_or(&self.summary_style)
+    }
+
+    /// Summarize a sampled repo with the evaluation model as well; `None` when not sampled
+    async fn evaluate_summary(&self, pending: &PendingSummary, readme: &str) -> Option<StructuredSummary> {
+        let eval_client = self.eval_summaries.as_ref()?;
+        if !in_eval_sample(pending.repo_id, self.eval_fraction) {
+            return None;
+        }
+
+        match eval_client.summarize_readme_korean(readme, &pending.repo_name, self.style_of(pending)).await {
+            Ok(summary) => summary,
+            Err(e) => {
+                warn!("Evaluation summary failed for {} ({}): {}", pending.repo_name, eval_client.model(), e);
+                None
+            }
+        }
+    }
+
+    /// Store the primary and evaluation summaries side by side, labelled with their models
+    fn persist_evaluation(
+        &self,
+        today: &str,
+        pending: &PendingSummary,
+        (primary_model, primary): (&str, &StructuredSummary),
+        evaluation: &StructuredSummary,
+    ) {
+        let Some(eval_client) = &self.eval_summaries else {
+            return;
+        };
+
+        for (model, summary) in [(primary_model, primary), (eval_client.model(), evaluation)] {
+            if let Err(e) = self.db.save_summary_evaluation(today, pending.repo_id, model, summary) {
+                warn!("Failed to save {} evaluation summary for {}: {}", model, pending.repo_name, e);
+            }
+        }
+    }
+
+    /// Oldest date a summary may have been written on and still be fresh now. Ages count from
+    /// the day a summary was written, not the day it was collected into, which differ in backfills.
+    fn fresh_since(&self) -> String {
+        (Utc::now().date_naive() - chrono::Duration::days(self.summary_max_age_days)).format("%Y-%m-%d").to_string()
+    }
+
+    /// The summary of the repo's previous appearance when it is still fresh and was written from
+    /// the same README, so the LLM needn't be asked again
+    fn reusable_summary(&self, today: &str, pending: &PendingSummary, readme: &str) -> Option<SummaryOutcome> {
+        if !pending.reuse {
+            return None;
+        }
+        let previous = self.db.get_reusable_summary(pending.repo_id, today, &self.fresh_since()).unwrap_or_else(|e| {
+            warn!("Failed to load previous summary for {}: {}", pending.repo_name, e);
+            None
+        });
+        let (summarized_on, hash, summary, model) = previous?;
+        (hash == readme::content_hash(readme)).then(|| SummaryOutcome::Reused(summary, model, summarized_on))
+    }
+
+    /// Compare a fetched README with the one stored at the repo's previous appearance
+    async fn compare_readme(&self, today: &str, pending: &PendingSummary, content: String) -> FetchedReadme {
+        let hash = readme::content_hash(&content);
+        let previous = self.db.get_previous_readme(pending.repo_id, today).unwrap_or_else(|e| {
+            warn!("Failed to load previous README for {}: {}", pending.repo_name, e);
+            None
+        });
+
+        let (updated, change_note) = match previous {
+            None => (None, None),
+            Some((previous_hash, previous_content)) => {
+                // READMEs stored before hashes were kept are hashed on the fly
+                let previous_hash = previous_hash.unwrap_or_else(|| readme::content_hash(&previous_content));
+                if previous_hash == hash {
+                    (Some(false), None)
+                } else if self.readme_change_notes {
+                    let note = self.summaries
+                        .describe_readme_change(&previous_content, &content, &pending.repo_name)
+                        .await
+                        .unwrap_or_else(|e| {
+                            warn!("Failed to describe README change for {}: {}", pending.repo_name, e);
+                            None
+                        });
+                    (Some(true), note)
+                } else {
+                    (Some(true), None)
+                }
+            }
+        };
+
+        FetchedReadme {
+            content,
+            hash,
+            updated,
+            change_note,
+        }
+    }
+
+    /// Persist stage of phase 2: store the README and summary, or record why there is none
+    fn persist_summary(
+        &self,
+        today: &str,
+        pending: &PendingSummary,
+        readme: Option<FetchedReadme>,
+        outcome: SummaryOutcome,
+    ) -> &'static str {
+        let (repo_id, repo_name) = (pending.repo_id, &pending.repo_name);
+        if let Some(readme) = readme {
+            if let Err(e) = self.db.save_readme(today, repo_id, &readme.content, &readme.hash) {
+                warn!("Failed to save README for {}: {}", repo_name, e);
+            }
+            if let Err(e) = self.db.update_readme_change(today, repo_id, readme.updated, readme.change_note.as_deref()) {
+                warn!("Failed to save README change for {}: {}", repo_name, e);
+            }
+            if let Err(e) = self.db.update_doc_links(today, repo_id, &links::doc_links(&readme.content, repo_name)) {
+                warn!("Failed to save documentation links for {}: {}", repo_name, e);
+            }
+        }
+        // Written now, whichever date the run collects into
+        let written_on = Utc::now().format("%Y-%m-%d").to_string();
+        let (summary, status) = match outcome {
+            SummaryOutcome::Done(summary, model) => (Some((summary, Some(model), written_on)), SUMMARY_DONE),
+            SummaryOutcome::Reused(summary, model, summarized_on) => (Some((summary, model, summarized_on)), SUMMARY_DONE),
+            SummaryOutcome::Unavailable => {
+                self.record_issue(today, repo_id, "summary", "summary_unavailable", None);
+                (None, SUMMARY_UNAVAILABLE)
+            }
+            SummaryOutcome::NoReadme => {
+                self.record_issue(today, repo_id, "readme", "not_found", None);
+                (None, SUMMARY_UNAVAILABLE)
+            }
+            SummaryOutcome::ReadmeFailed(e) => {
+                warn!("Failed to fetch README for {}: {}", repo_name, e);
+                self.record_issue(today, repo_id, "readme", issue_code(&e), Some(e.to_string()));
+                (None, SUMMARY_FAILED)
+            }
+            SummaryOutcome::SummaryFailed(e) => {
+                warn!("Failed to summarize README for {}: {}", repo_name, e);
+                self.record_issue(today, repo_id, "summary", issue_code(&e), Some(e.to_string()));
+                (None, SUMMARY_FAILED)
+            }
+            SummaryOutcome::Filtered => (None, SUMMARY_FILTERED),
+        };
+
+        let (summary, model, summarized_on) = match summary {
+            Some((summary, model, summarized_on)) => (Some(summary), model, Some(summarized_on)),
+            None => (None, None, None),
+        };
+        match self.db.update_repo_summary(today, repo_id, summary.as_ref(), model.as_deref(), summarized_on.as_deref(), status) {
+            Ok(()) => self.changes.publish(Change::SummaryStored {
+                date: today.to_string(),
+                repo_id,
+                repo_name: repo_name.clone(),
+                status: status.to_string(),
+            }),
+            Err(e) => warn!("Failed to save summary for {}: {}", repo_name, e),
+        }
+        status
+    }
+
+    fn record_issue(&self, date: &str, repo_id: i64, stage: &str, code: &str, message: Option<String>) {
+        let issue = RepoIssue {
+            stage: stage.to_string(),
+            code: code.to_string(),
+            message,
+            run_id: Some(self.run_id.clone()),
+        };
+        if let Err(e) = self.db.save_repo_issue(date, repo_id, &issue) {
+            warn!("Failed to record {} issue for repo {}: {}", stage, repo_id, e);
+        }
+    }
+
+    fn compute_score(&self, repo: &TrendingRepo, days_since_first_seen: Option<i64>, streak: i32) -> f64 {
+        scoring::compute_score(&self.score_weights, &ScoreInputs {
+            stars: repo.stars,
+            forks: repo.forks,
+            pull_requests: repo.pull_requests,
+            pushes: repo.pushes,
+            days_since_first_seen,
+            streak,
+        })
+    }
+
+    async fn collect_developers(&self, date: &str, oss_repos: &[TrendCandidate]) -> Result<usize> {
+        let developers = self.trends.get_trending_developers().await?;
+        let mut saved = 0;
+
+        for (i, dev) in developers.iter().enumerate() {
+            let followers = match self.metadata.get_user_followers(&dev.login).await {
+                Ok(followers) => followers,
+                Err(e) => {
+                    warn!("Failed to fetch followers for {}: {}", dev.login, e);
+                    None
+                }
+            };
+
+            // Upstream repos first, then today's trending repos they contributed to
+            let mut notable: Vec<String> = Vec::new();
+            let upstream = dev.notable_repos.as_deref().unwrap_or_default().split(',');
+            let contributed = oss_repos
+                .iter()
+                .filter(|r| {
+                    r.contributor_logins
+                        .as_deref()
+                        .is_some_and(|logins| logins.split(',').any(|l| l.trim() == dev.login))
+                })
+                .map(|r| r.repo_name.as_str());
+            for name in upstream.chain(contributed).map(str::trim) {
+                if !name.is_empty() && !notable.iter().any(|n| n == name) && notable.len() < MAX_NOTABLE_REPOS {
+                    notable.push(name.to_string());
+                }
+            }
+
+            let developer = TrendingDeveloper {
+                date: date.to_string(),
+                login: dev.login.clone(),
+                rank: i as i32 + 1,
+                followers,
+                notable_repos: (!notable.is_empty()).then(|| notable.join(",")),
+                run_id: Some(self.run_id.clone()),
+            };
+
+            if let Err(e) = self.db.save_trending_developer(&developer) {
+                warn!("Failed to save trending developer {}: {}", dev.login, e);
+            } else {
+                saved += 1;
+            }
+
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+
+        Ok(saved)
+    }
+
+    /// A collection's display name and ranked repos, counting unparsable values in `parse_failures`
+    async fn fetch_collection(&self, collection_id: i64, parse_failures: &mut ingest::ParseFailures) -> Result<(String, Vec<CollectionEntry>)> {
+        let name = self.trends.get_collection_name(collection_id).await?
+            .unwrap_or_else(|| collection_id.to_string());
+        let rows = self.trends.get_collection_repos(collection_id).await?;
+        Ok((name, ingest::collection_entries(rows, parse_failures)))
+    }
+
+    fn save_collection(&self, date: &str, collection_id: i64, name: &str, rows: &[CollectionEntry]) -> usize {
+        let mut saved = 0;
+
+        for (i, row) in rows.iter().enumerate() {
+            let repo = CollectionRepo {
+                date: date.to_string(),
+                collection_id,
+                collection_name: name.to_string(),
+                repo_id: row.repo_id,
+                repo_name: row.repo_name.clone(),
+                rank: row.period_rank.unwrap_or(i as i32 + 1),
+                stars_growth: row.period_growth,
+                total_stars: row.total_stars,
+                run_id: Some(self.run_id.clone()),
+            };
+
+            if let Err(e) = self.db.save_collection_repo(&repo) {
+                warn!("Failed to save {} of collection {}: {}", repo.repo_name, collection_id, e);
+            } else {
+                saved += 1;
+            }
+        }
+
+        saved
+    }
+}
+
+/// A collection entry as a trending candidate; its period growth stands in for the score
+fn collection_candidate(row: &CollectionEntry) -> TrendCandidate {
+    TrendCandidate {
+        repo_id: row.repo_id,
+        repo_name: row.repo_name.clone(),
+        primary_language: None,
+        description: None,
+        stars: row.period_growth.and_then(|g| i32::try_from(g).ok()),
+        forks: None,
+        pull_requests: None,
+        pushes: None,
+        total_score: row.period_growth.map(|g| g as f64),
+        contributor_logins: None,
+        collection_names: None,
+    }
+}
+
+/// Stable pseudo-random sampling by repo id, so reruns evaluate the same repos
+fn in_eval_sample(repo_id: i64, fraction: f64) -> bool {
+    let bucket = (repo_id as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 11;
+    (bucket as f64 / (1u64 << 53) as f64) < fraction
+}
+
+/// Map a fetch error to a stable code the UI can explain
+fn issue_code(err: &anyhow::Error) -> &'static str {
+    if let Some(e) = err.downcast_ref::<ServiceError>() {
+        return e.code();
+    }
+    match err.downcast_ref::<reqwest::Error>() {
+        Some(e) if e.is_timeout() => "timeout",
+        Some(e) => match e.status().map(|s| s.as_u16()) {
+            Some(403) | Some(429) => "rate_limited",
+            Some(401) => "unauthorized",
+            Some(404) => "not_found",
+            Some(_) => "upstream_error",
+            None => "network_error",
+        },
+        None => "error",
     }
 }
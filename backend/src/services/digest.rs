@@ -0,0 +1,305 @@
+use anyhow::Result;
+use chrono::{DateTime, Datelike, NaiveTime, Utc};
+use chrono_tz::Tz;
+use lettre::message::MultiPart;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::config::Config;
+use crate::db::Database;
+use crate::models::{DeliverySchedule, DigestSubscription};
+use crate::services::templates::NotificationTemplates;
+
+const DIGEST_REPO_LIMIT: usize = 10;
+const DIGEST_LANGUAGE_LIMIT: usize = 5;
+
+pub struct DigestSender {
+    mailer: AsyncSmtpTransport<Tokio1Executor>,
+    from: String,
+    db: Database,
+    templates: NotificationTemplates,
+    /// Origin and workspace prefix of the confirm and unsubscribe links (needs `SITE_URL`)
+    links: Option<String>,
+}
+
+/// What `send_digest` did for one subscriber
+enum DigestOutcome {
+    Sent,
+    /// Nothing collected yet; the subscriber stays due
+    Skipped,
+}
+
+impl DigestSender {
+    /// Returns `None` when SMTP is not configured
+    pub fn new(config: &Config, db: Database) -> Result<Option<Self>> {
+        let (Some(host), Some(from)) = (&config.smtp_host, &config.smtp_from) else {
+            return Ok(None);
+        };
+
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?
+            .port(config.smtp_port);
+
+        if let (Some(username), Some(password)) = (&config.smtp_username, &config.smtp_password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        Ok(Some(DigestSender {
+            mailer: builder.build(),
+            from: from.clone(),
+            db,
+            templates: NotificationTemplates::load(config.notification_templates_dir.as_deref())?,
+            links: subscription_links(config),
+        }))
+    }
+
+    /// Whether confirmation emails can link back to this server
+    pub fn can_confirm(&self) -> bool {
+        self.links.is_some()
+    }
+
+    /// Ask the address of a new subscription to confirm it; no digest goes out before that
+    pub async fn send_confirmation(&self, sub: &DigestSubscription, confirm_token: &str) -> Result<()> {
+        let Some(links) = &self.links else {
+            anyhow::bail!("SITE_URL is not set, so there is no confirmation link to send");
+        };
+
+        let text = format!(
+            "Someone asked to send a {} Daily-Git-Brief digest to this address.\n\n\
+             Confirm the subscription: {}/api/subscriptions/confirm/{}\n\n\
+             If that wasn't you, ignore this email and nothing will be sent.\n",
+            sub.frequency, links, confirm_token
+        );
+        let email = Message::builder()
+            .from(self.from.parse()?)
+            .to(sub.email.parse()?)
+            .subject("Confirm your Daily-Git-Brief digest")
+            .body(text)?;
+
+        self.mailer.send(email).await?;
+        Ok(())
+    }
+
+    /// Send digests to every subscriber whose local delivery time has passed today
+    pub async fn send_due(&self) -> Result<usize> {
+        let now = Utc::now();
+        let mut sent = 0;
+
+        for sub in self.db.get_subscriptions()? {
+            let Some(local_date) = due_local_date(&sub, now) else {
+                continue;
+            };
+
+            match self.send_digest(&sub).await {
+                Ok(DigestOutcome::Sent) => {
+                    self.db.mark_subscription_sent(sub.id, &local_date)?;
+                    sent += 1;
+                }
+                Ok(DigestOutcome::Skipped) => {}
+                Err(e) => warn!("Failed to send {} digest to {}: {}", sub.frequency, sub.email, e),
+            }
+        }
+
+        if sent > 0 {
+            info!("Sent {} email digests", sent);
+        }
+
+        Ok(sent)
+    }
+
+    async fn send_digest(&self, sub: &DigestSubscription) -> Result<DigestOutcome> {
+        let Some(date) = self.db.get_latest_date()? else {
+            warn!("No collected data yet, skipping digest for {}", sub.email);
+            return Ok(DigestOutcome::Skipped);
+        };
+
+        let weekly = sub.frequency == "weekly";
+        let subject = if weekly {
+            format!("Daily-Git-Brief weekly digest ({})", date)
+        } else {
+            format!("Daily-Git-Brief {}", date)
+        };
+
+        let unsubscribe_url = self
+            .links
+            .as_ref()
+            .map(|links| format!("{}/api/subscriptions/unsubscribe/{}", links, sub.unsubscribe_token));
+        let (text, html) = self.build_bodies(&date, weekly, unsubscribe_url)?;
+        let email = Message::builder()
+            .from(self.from.parse()?)
+            .to(sub.email.parse()?)
+            .subject(subject)
+            .multipart(MultiPart::alternative_plain_html(text, html))?;
+
+        self.mailer.send(email).await?;
+        Ok(DigestOutcome::Sent)
+    }
+
+    /// Plain-text and HTML bodies rendered from the `digest.txt` / `digest.html` templates
+    fn build_bodies(&self, date: &str, weekly: bool, unsubscribe_url: Option<String>) -> Result<(String, String)> {
+        let mut context = digest_context(&self.db, date, weekly, DIGEST_REPO_LIMIT)?;
+        context["unsubscribe_url"] = json!(unsubscribe_url);
+
+        Ok((
+            self.templates.render("digest.txt", &context)?,
+            self.templates.render("digest.html", &context)?,
+        ))
+    }
+}
+
+/// Template context of a digest of `date`: its top `repo_limit` repos, top languages (of the
+/// week ending there for weekly digests), language alerts, star milestones and the run's
+/// `MAX_REPOS_PER_RUN` cut-off
+pub fn digest_context(db: &Database, date: &str, weekly: bool, repo_limit: usize) -> Result<serde_json::Value> {
+    let repos = db.get_trending_repos(date)?;
+    let languages = if weekly {
+        db.get_weekly_language_trends(date)?
+    } else {
+        db.get_daily_language_trends(date)?
+    };
+
+    Ok(json!({
+        "date": date,
+        "weekly": weekly,
+        "repos": repos.iter().take(repo_limit).enumerate().map(|(rank, repo)| json!({
+            "rank": rank + 1,
+            "repo_name": repo.repo_name,
+            "url": format!("https://github.com/{}", repo.repo_name),
+            "summary": repo.korean_summary.as_ref().or(repo.description.as_ref()),
+            "stars": repo.stars,
+        })).collect::<Vec<_>>(),
+        "languages": languages.iter().take(DIGEST_LANGUAGE_LIMIT).map(|trend| json!({
+            "language": trend.language,
+            "share": trend.normalized_percentage,
+        })).collect::<Vec<_>>(),
+        "alerts": db.get_language_alerts(Some(date))?,
+        "milestones": db.get_star_milestones(Some(date))?,
+        "cutoff": db.get_run_cutoff(date)?.filter(|c| c.trimmed() > 0),
+    }))
+}
+
+/// Base of the subscription links emails carry: `SITE_URL`, plus `/w/{name}` outside the
+/// default workspace
+fn subscription_links(config: &Config) -> Option<String> {
+    let site_url = config.site_url.as_deref()?.trim_end_matches('/');
+    Some(if config.workspace == "default" {
+        site_url.to_string()
+    } else {
+        format!("{}/w/{}", site_url, config.workspace)
+    })
+}
+
+/// Returns the subscriber-local date if a digest is due at `now`
+pub fn due_local_date(sub: &DigestSubscription, now: DateTime<Utc>) -> Option<String> {
+    schedule_due(
+        &DeliverySchedule {
+            frequency: sub.frequency.clone(),
+            delivery_time: sub.delivery_time.clone(),
+            timezone: sub.timezone.clone(),
+            weekday: sub.weekday,
+            top_n: DIGEST_REPO_LIMIT as i64,
+            last_sent_date: sub.last_sent_date.clone(),
+        },
+        now,
+    )
+}
+
+/// Returns the schedule-local date if a delivery is due at `now`
+pub fn schedule_due(schedule: &DeliverySchedule, now: DateTime<Utc>) -> Option<String> {
+    let tz: Tz = schedule.timezone.parse().ok()?;
+    let delivery_time = NaiveTime::parse_from_str(&schedule.delivery_time, "%H:%M").ok()?;
+
+    let local = now.with_timezone(&tz);
+    let local_date = local.date_naive().format("%Y-%m-%d").to_string();
+
+    if local.time() < delivery_time {
+        return None;
+    }
+    if schedule.last_sent_date.as_deref() == Some(local_date.as_str()) {
+        return None;
+    }
+    if schedule.frequency == "weekly"
+        && schedule.weekday != Some(local.weekday().num_days_from_monday() as i32)
+    {
+        return None;
+    }
+
+    Some(local_date)
+}
+
+/// Check the frequency, time, timezone and weekday of a delivery schedule
+pub fn validate_schedule(frequency: &str, delivery_time: &str, timezone: &str, weekday: Option<i32>) -> Result<(), String> {
+    if frequency != "daily" && frequency != "weekly" {
+        return Err("frequency must be 'daily' or 'weekly'".to_string());
+    }
+    if NaiveTime::parse_from_str(delivery_time, "%H:%M").is_err() {
+        return Err("delivery_time must be HH:MM".to_string());
+    }
+    if timezone.parse::<Tz>().is_err() {
+        return Err(format!("Unknown timezone: {}", timezone));
+    }
+    if frequency == "weekly" && !matches!(weekday, Some(0..=6)) {
+        return Err("weekly digests need a weekday between 0 (Monday) and 6 (Sunday)".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscription(frequency: &str, delivery_time: &str, timezone: &str, weekday: Option<i32>) -> DigestSubscription {
+        DigestSubscription {
+            id: 1,
+            email: "reader@example.com".to_string(),
+            frequency: frequency.to_string(),
+            delivery_time: delivery_time.to_string(),
+            timezone: timezone.to_string(),
+            weekday,
+            last_sent_date: None,
+            confirmed: true,
+            unsubscribe_token: "token".to_string(),
+        }
+    }
+
+    fn at(utc: &str) -> DateTime<Utc> {
+        utc.parse().expect("timestamp")
+    }
+
+    #[test]
+    fn daily_digests_are_due_from_the_local_delivery_time() {
+        let sub = subscription("daily", "09:00", "Asia/Seoul", None);
+        // 08:30 and 09:00 in Seoul
+        assert_eq!(due_local_date(&sub, at("2026-10-15T23:30:00Z")), None);
+        assert_eq!(due_local_date(&sub, at("2026-10-16T00:00:00Z")).as_deref(), Some("2026-10-16"));
+
+        let sent = DigestSubscription { last_sent_date: Some("2026-10-16".to_string()), ..sub };
+        assert_eq!(due_local_date(&sent, at("2026-10-16T05:00:00Z")), None);
+        // The next local day is due again
+        assert_eq!(due_local_date(&sent, at("2026-10-17T00:00:00Z")).as_deref(), Some("2026-10-17"));
+    }
+
+    #[test]
+    fn weekly_digests_follow_the_local_weekday() {
+        // 2026-10-16 is a Friday (4)
+        let seoul = subscription("weekly", "00:30", "Asia/Seoul", Some(4));
+        // Thursday 16:00 UTC is already Friday 01:00 in Seoul
+        assert_eq!(due_local_date(&seoul, at("2026-10-15T16:00:00Z")).as_deref(), Some("2026-10-16"));
+        assert_eq!(due_local_date(&seoul, at("2026-10-16T16:00:00Z")), None);
+
+        let los_angeles = subscription("weekly", "10:00", "America/Los_Angeles", Some(4));
+        // Friday 09:00 UTC is still Friday 02:00 in Los Angeles, before delivery time
+        assert_eq!(due_local_date(&los_angeles, at("2026-10-16T09:00:00Z")), None);
+        assert_eq!(due_local_date(&los_angeles, at("2026-10-16T17:00:00Z")).as_deref(), Some("2026-10-16"));
+        // Saturday 01:00 UTC is Friday evening there
+        assert_eq!(due_local_date(&los_angeles, at("2026-10-17T01:00:00Z")).as_deref(), Some("2026-10-16"));
+    }
+
+    #[test]
+    fn unparsable_schedules_are_never_due() {
+        let now = at("2026-10-16T12:00:00Z");
+        assert_eq!(due_local_date(&subscription("daily", "09:00", "Mars/Olympus", None), now), None);
+        assert_eq!(due_local_date(&subscription("daily", "9am", "UTC", None), now), None);
+    }
+}
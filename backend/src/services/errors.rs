@@ -0,0 +1,102 @@
+use reqwest::StatusCode;
+
+use crate::services::breaker::CircuitOpen;
+
+/// Why a call to GitHub, the LLM or OSS Insight failed. Carries messages rather than the
+/// underlying errors so it can be cloned to every caller of a coalesced call.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ServiceError {
+    /// 403 or 429: a quota ran out
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
+    /// 401: the token or API key was rejected
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+    #[error("Timed out: {0}")]
+    Timeout(String),
+    /// The response arrived but could not be read, or the model's answer was unusable
+    #[error("Malformed response: {0}")]
+    Malformed(String),
+    #[error(transparent)]
+    CircuitOpen(#[from] CircuitOpen),
+    /// Any other error status, 5xx included
+    #[error("Upstream error {status}: {detail}")]
+    Upstream { status: u16, detail: String },
+    /// No response at all: connection refused or reset, DNS, TLS, ...
+    #[error("Network error: {0}")]
+    Network(String),
+}
+
+/// What the collector does about a failed call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorAction {
+    /// Worth another attempt after a backoff
+    Retry,
+    /// This item's call can't succeed; move on to the next item
+    Skip,
+    /// No call to the same upstream can succeed; stop calling it for the rest of the run
+    Abort,
+}
+
+pub type ServiceResult<T> = Result<T, ServiceError>;
+
+impl ServiceError {
+    /// Error of a response with a non-success `status`
+    pub fn from_status(status: StatusCode, detail: impl Into<String>) -> Self {
+        let detail = detail.into();
+        match status.as_u16() {
+            401 => ServiceError::Unauthorized(detail),
+            403 | 429 => ServiceError::RateLimited(detail),
+            404 => ServiceError::NotFound(detail),
+            408 | 504 => ServiceError::Timeout(detail),
+            status => ServiceError::Upstream { status, detail },
+        }
+    }
+
+    pub fn action(&self) -> ErrorAction {
+        match self {
+            ServiceError::RateLimited(_) | ServiceError::Timeout(_) | ServiceError::Network(_) => ErrorAction::Retry,
+            // Models answer differently on another attempt
+            ServiceError::Malformed(_) => ErrorAction::Retry,
+            ServiceError::Upstream { status, .. } if *status >= 500 => ErrorAction::Retry,
+            ServiceError::Upstream { .. } | ServiceError::NotFound(_) => ErrorAction::Skip,
+            // Retrying only adds refusals until the circuit's cooldown is over
+            ServiceError::CircuitOpen(_) => ErrorAction::Skip,
+            ServiceError::Unauthorized(_) => ErrorAction::Abort,
+        }
+    }
+
+    /// Code of the failure in run issues and progress events
+    pub fn code(&self) -> &'static str {
+        match self {
+            ServiceError::RateLimited(_) => "rate_limited",
+            ServiceError::NotFound(_) => "not_found",
+            ServiceError::Unauthorized(_) => "unauthorized",
+            ServiceError::Timeout(_) => "timeout",
+            ServiceError::Malformed(_) => "malformed",
+            ServiceError::CircuitOpen(_) => "circuit_open",
+            ServiceError::Upstream { .. } => "upstream_error",
+            ServiceError::Network(_) => "network_error",
+        }
+    }
+}
+
+impl From<reqwest::Error> for ServiceError {
+    fn from(err: reqwest::Error) -> Self {
+        let detail = err.to_string();
+        match err.status() {
+            Some(status) => ServiceError::from_status(status, detail),
+            None if err.is_timeout() => ServiceError::Timeout(detail),
+            None if err.is_decode() => ServiceError::Malformed(detail),
+            None => ServiceError::Network(detail),
+        }
+    }
+}
+
+impl From<serde_json::Error> for ServiceError {
+    fn from(err: serde_json::Error) -> Self {
+        ServiceError::Malformed(err.to_string())
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, Path as UrlPath, RawQuery, State};
+use axum::http::{header, HeaderMap, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::any;
+use axum::Router;
+use chrono::Utc;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::net::TcpListener;
+use tracing::{error, info, warn};
+
+use crate::config::{Config, FIXTURE_MODE_RECORD};
+
+// Upstreams the fixture server stands in for; each is served under `/<name>/`
+const OSS_INSIGHT: &str = "oss_insight";
+const GITHUB_API: &str = "github";
+const GITHUB_RAW: &str = "github_raw";
+const LLM: &str = "llm";
+
+/// File names longer than this are shortened; the hash suffix keeps them unique
+const MAX_NAME_LEN: usize = 120;
+
+/// One recorded upstream response
+#[derive(Serialize, Deserialize)]
+struct Fixture {
+    status: u16,
+    content_type: Option<String>,
+    body: String,
+}
+
+/// Local HTTP server the upstream base URLs are pointed at. Recording forwards each request to
+/// the real upstream and saves the response under `<dir>/<snapshot>/<upstream>/`; replaying
+/// serves those files and never touches the network, so a recorded day can be collected again
+/// (locally, in CI, or as a backfill of that date) with identical results.
+struct FixtureServer {
+    client: Client,
+    record: bool,
+    dir: PathBuf,
+    /// Upstream name to its real base URL
+    upstreams: HashMap<String, String>,
+}
+
+/// Start the fixture server when `FIXTURE_MODE` is set and point every upstream URL of `config`
+/// (workspace overrides included) at it
+pub async fn install(config: &mut Config, client: Client) -> Result<()> {
+    let Some(fixtures) = config.fixtures.clone() else {
+        return Ok(());
+    };
+    let record = fixtures.mode == FIXTURE_MODE_RECORD;
+    let snapshot = match (&fixtures.snapshot, record) {
+        (Some(snapshot), _) => snapshot.clone(),
+        (None, true) => Utc::now().format("%Y-%m-%d").to_string(),
+        (None, false) => latest_snapshot(Path::new(&fixtures.dir))?,
+    };
+    let dir = Path::new(&fixtures.dir).join(&snapshot);
+    if !record && !dir.is_dir() {
+        anyhow::bail!("No fixture recording at {}", dir.display());
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.context("Failed to bind the fixture server")?;
+    let base = format!("http://{}", listener.local_addr()?);
+
+    let mut upstreams = HashMap::new();
+    for (name, url) in [
+        (OSS_INSIGHT, &mut config.oss_insight_base_url),
+        (GITHUB_API, &mut config.github_api_url),
+        (GITHUB_RAW, &mut config.github_raw_url),
+        (LLM, &mut config.deepseek_base_url),
+    ] {
+        upstreams.insert(name.to_string(), std::mem::replace(url, format!("{}/{}", base, name)));
+    }
+    for ws in &mut config.workspaces {
+        if let Some(url) = &mut ws.oss_insight_base_url {
+            let name = format!("{}_{}", OSS_INSIGHT, ws.name);
+            upstreams.insert(name.clone(), std::mem::replace(url, format!("{}/{}", base, name)));
+        }
+    }
+
+    let server = Arc::new(FixtureServer { client, record, dir, upstreams });
+    let app = Router::new()
+        .route("/:upstream/*rest", any(forward))
+        // LLM requests carry whole READMEs
+        .layer(DefaultBodyLimit::disable())
+        .with_state(server);
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            error!("Fixture server stopped: {}", e);
+        }
+    });
+
+    info!(
+        "Fixture mode: {} upstream responses {} {}",
+        fixtures.mode,
+        if record { "to" } else { "from" },
+        Path::new(&fixtures.dir).join(&snapshot).display()
+    );
+    Ok(())
+}
+
+/// Newest recording in `dir`; snapshots default to dates, so names sort chronologically
+fn latest_snapshot(dir: &Path) -> Result<String> {
+    let mut snapshots: Vec<String> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read fixture directory {}", dir.display()))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    snapshots.sort();
+    snapshots.pop().with_context(|| format!("No fixture recordings in {}", dir.display()))
+}
+
+async fn forward(
+    State(server): State<Arc<FixtureServer>>,
+    UrlPath((upstream, rest)): UrlPath<(String, String)>,
+    RawQuery(query): RawQuery,
+    method: Method,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let Some(base_url) = server.upstreams.get(&upstream) else {
+        return (StatusCode::NOT_FOUND, format!("Unknown upstream '{}'", upstream)).into_response();
+    };
+    let path = server.dir.join(&upstream).join(fixture_name(&method, &rest, query.as_deref(), &body));
+
+    let fixture = if server.record {
+        let mut url = format!("{}/{}", base_url, rest);
+        if let Some(query) = &query {
+            url = format!("{}?{}", url, query);
+        }
+        match record(&server.client, method, &url, headers, body, &path).await {
+            Ok(fixture) => fixture,
+            Err(e) => {
+                warn!("Failed to record {}: {:#}", url, e);
+                return (StatusCode::BAD_GATEWAY, e.to_string()).into_response();
+            }
+        }
+    } else {
+        match read_fixture(&path).await {
+            Ok(fixture) => fixture,
+            Err(e) => {
+                warn!("No fixture for {} /{} ({}): {:#}", upstream, rest, path.display(), e);
+                return (StatusCode::BAD_GATEWAY, format!("No fixture recorded at {}", path.display())).into_response();
+            }
+        }
+    };
+
+    let status = StatusCode::from_u16(fixture.status).unwrap_or(StatusCode::BAD_GATEWAY);
+    match fixture.content_type {
+        Some(content_type) => (status, [(header::CONTENT_TYPE, content_type)], fixture.body).into_response(),
+        None => (status, fixture.body).into_response(),
+    }
+}
+
+/// Call the real upstream and save its response, whatever the status, so failures replay too
+async fn record(client: &Client, method: Method, url: &str, mut headers: HeaderMap, body: Bytes, path: &Path) -> Result<Fixture> {
+    headers.remove(header::HOST);
+    headers.remove(header::CONTENT_LENGTH);
+    let response = client.request(method, url).headers(headers).body(body).send().await?;
+
+    let fixture = Fixture {
+        status: response.status().as_u16(),
+        content_type: response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string),
+        body: response.text().await?,
+    };
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(path, serde_json::to_vec_pretty(&fixture)?).await?;
+    Ok(fixture)
+}
+
+async fn read_fixture(path: &Path) -> Result<Fixture> {
+    let bytes = tokio::fs::read(path).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// File name of a request's fixture: readable from the method, path and query, with a hash
+/// when a body (LLM prompts) or a long name has to be told apart
+fn fixture_name(method: &Method, rest: &str, query: Option<&str>, body: &[u8]) -> String {
+    let request = match query {
+        Some(query) => format!("{} {}?{}", method, rest, query),
+        None => format!("{} {}", method, rest),
+    };
+    let mut name: String = request
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect();
+
+    if !body.is_empty() || name.len() > MAX_NAME_LEN {
+        let mut hasher = Sha256::new();
+        hasher.update(request.as_bytes());
+        hasher.update(body);
+        name.truncate(MAX_NAME_LEN);
+        name = format!("{}_{}", name, &format!("{:x}", hasher.finalize())[..16]);
+    }
+    format!("{}.json", name)
+}
@@ -0,0 +1,87 @@
+use chrono::{Duration, NaiveDate};
+
+use crate::models::{ForecastPoint, LanguageForecast, LanguageTrend};
+
+/// Days of stored history the model is fitted on
+pub const HISTORY_DAYS: i64 = 30;
+/// Window of the reported simple moving average
+const MOVING_AVERAGE_DAYS: usize = 7;
+/// Two-sided 95% interval under a normal approximation
+const Z_95: f64 = 1.96;
+/// Slopes smaller than this (percentage points per day) count as flat
+const FLAT_SLOPE: f64 = 0.05;
+
+/// Fit an ordinary least-squares line over daily shares and project `days` ahead with
+/// prediction intervals. Returns `None` when there are fewer than three usable points.
+pub fn forecast_language(language: &str, history: &[LanguageTrend], days: u32) -> Option<LanguageForecast> {
+    let mut points: Vec<(NaiveDate, f64)> = history
+        .iter()
+        .filter_map(|t| {
+            NaiveDate::parse_from_str(&t.date, "%Y-%m-%d")
+                .ok()
+                .map(|d| (d, t.normalized_percentage))
+        })
+        .collect();
+    points.sort_by_key(|(d, _)| *d);
+
+    if points.len() < 3 {
+        return None;
+    }
+
+    let origin = points[0].0;
+    let last = points[points.len() - 1].0;
+    let xs: Vec<f64> = points.iter().map(|(d, _)| (*d - origin).num_days() as f64).collect();
+    let ys: Vec<f64> = points.iter().map(|(_, y)| *y).collect();
+    let n = xs.len() as f64;
+
+    let x_mean = xs.iter().sum::<f64>() / n;
+    let y_mean = ys.iter().sum::<f64>() / n;
+    let sxx: f64 = xs.iter().map(|x| (x - x_mean).powi(2)).sum();
+    let sxy: f64 = xs.iter().zip(&ys).map(|(x, y)| (x - x_mean) * (y - y_mean)).sum();
+
+    let slope = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+    let intercept = y_mean - slope * x_mean;
+
+    let sse: f64 = xs
+        .iter()
+        .zip(&ys)
+        .map(|(x, y)| (y - (intercept + slope * x)).powi(2))
+        .sum();
+    let std_error = (sse / (n - 2.0)).sqrt();
+
+    let forecast = (1..=days as i64)
+        .map(|offset| {
+            let date = last + Duration::days(offset);
+            let x = (date - origin).num_days() as f64;
+            let predicted = intercept + slope * x;
+            let leverage = if sxx > 0.0 { (x - x_mean).powi(2) / sxx } else { 0.0 };
+            let margin = Z_95 * std_error * (1.0 + 1.0 / n + leverage).sqrt();
+            ForecastPoint {
+                date: date.format("%Y-%m-%d").to_string(),
+                predicted: predicted.clamp(0.0, 100.0),
+                lower: (predicted - margin).clamp(0.0, 100.0),
+                upper: (predicted + margin).clamp(0.0, 100.0),
+            }
+        })
+        .collect();
+
+    let recent = &ys[ys.len().saturating_sub(MOVING_AVERAGE_DAYS)..];
+    let moving_average = recent.iter().sum::<f64>() / recent.len() as f64;
+
+    let direction = if slope > FLAT_SLOPE {
+        "rising"
+    } else if slope < -FLAT_SLOPE {
+        "falling"
+    } else {
+        "flat"
+    };
+
+    Some(LanguageForecast {
+        language: language.to_string(),
+        history_days: points.len(),
+        moving_average,
+        slope_per_day: slope,
+        direction: direction.to_string(),
+        forecast,
+    })
+}
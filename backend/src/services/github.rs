@@ -1,47 +1,125 @@
-use anyhow::Result;
-use reqwest::Client;
+use metrics::counter;
+use reqwest::{Client, StatusCode};
+use std::sync::Arc;
 
 use tracing::{info, warn};
 
-use crate::models::{GitHubLanguages, GitHubRepoInfo, LanguageInfo};
+use crate::config::Config;
+use crate::services::breaker::CircuitBreakers;
+use crate::services::errors::{ServiceError, ServiceResult};
+use crate::services::github_app::GitHubAppAuth;
+use crate::services::github_tokens::TokenPool;
+use crate::services::usage::UsageMeter;
+use crate::telemetry;
+use crate::models::{GitHubLanguages, GitHubRepoInfo, GitHubUserInfo, LanguageInfo};
+
+/// Bucket collecting every language below the configured threshold
+pub const OTHER_LANGUAGE: &str = "Other";
+
+/// How requests to the GitHub API authenticate
+pub enum GitHubAuth {
+    Anonymous,
+    Tokens(TokenPool),
+    App(GitHubAppAuth),
+}
+
+impl GitHubAuth {
+    /// A configured GitHub App wins over personal access tokens
+    pub fn from_config(config: &Config, client: Client) -> Self {
+        match &config.github_app {
+            Some(app) => GitHubAuth::App(GitHubAppAuth::new(client, &config.github_api_url, app.clone())),
+            None if config.github_tokens.is_empty() => GitHubAuth::Anonymous,
+            None => GitHubAuth::Tokens(TokenPool::new(
+                config.github_tokens.clone(),
+                config.github_token_min_remaining,
+            )),
+        }
+    }
+}
 
 pub struct GitHubClient {
     client: Client,
     api_url: String,
-    token: Option<String>,
+    raw_url: String,
+    auth: GitHubAuth,
+    usage: Arc<UsageMeter>,
+    breakers: Arc<CircuitBreakers>,
 }
 
 impl GitHubClient {
-    pub fn new(api_url: &str, token: Option<String>) -> Self {
+    pub fn new(client: Client, api_url: &str, raw_url: &str, auth: GitHubAuth, usage: Arc<UsageMeter>) -> Self {
         GitHubClient {
-            client: Client::new(),
+            client,
             api_url: api_url.to_string(),
-            token,
+            raw_url: raw_url.to_string(),
+            auth,
+            usage,
+            breakers: Arc::new(CircuitBreakers::disabled()),
         }
     }
 
-    fn build_request(&self, url: &str) -> reqwest::RequestBuilder {
-        let mut req = self.client
+    pub fn with_breakers(mut self, breakers: Arc<CircuitBreakers>) -> Self {
+        self.breakers = breakers;
+        self
+    }
+
+    /// Every GitHub API call goes through here, so this is where requests are counted,
+    /// token quotas tracked and the host's circuit breaker consulted
+    async fn send(&self, url: &str) -> ServiceResult<reqwest::Response> {
+        self.breakers.check(url)?;
+        self.usage.record_github();
+        let req = self.client
             .get(url)
-            .header("Accept", "application/vnd.github+json")
-            .header("User-Agent", "Daily-Git-Brief");
-        
-        if let Some(ref token) = self.token {
-            req = req.header("Authorization", format!("Bearer {}", token));
-        }
-        
-        req
+            .header("Accept", "application/vnd.github+json");
+
+        let response = match &self.auth {
+            GitHubAuth::Anonymous => req.send().await,
+            GitHubAuth::Tokens(pool) => {
+                let (index, token) = pool.acquire();
+                let response = req.bearer_auth(token).send().await;
+                if let Ok(response) = &response {
+                    pool.record(index, response.headers());
+                }
+                response
+            }
+            GitHubAuth::App(app) => {
+                let token = app
+                    .token()
+                    .await
+                    .map_err(|e| ServiceError::Unauthorized(format!("No GitHub App installation token: {:#}", e)))?;
+                req.bearer_auth(token).send().await
+            }
+        };
+
+        let status = match &response {
+            Ok(response) => response.status().as_u16().to_string(),
+            Err(_) => "error".to_string(),
+        };
+        counter!(telemetry::GITHUB_RESPONSES, "status" => status).increment(1);
+        self.breakers.record(url, response.as_ref().is_ok_and(|r| !r.status().is_server_error()));
+
+        Ok(response?)
+    }
+
+    /// Raw file download, outside the API and its quotas
+    async fn send_raw(&self, url: &str) -> ServiceResult<reqwest::Response> {
+        self.breakers.check(url)?;
+        let response = self.client.get(url).send().await;
+        self.breakers.record(url, response.as_ref().is_ok_and(|r| !r.status().is_server_error()));
+        Ok(response?)
     }
 
-    pub async fn get_repo_languages(&self, repo_name: &str, threshold: f64) -> Result<Vec<LanguageInfo>> {
+    pub async fn get_repo_languages(&self, repo_name: &str, threshold: f64) -> ServiceResult<Vec<LanguageInfo>> {
         let url = format!("{}/repos/{}/languages", self.api_url, repo_name);
         
-        let response = self.build_request(&url).send().await?;
+        let response = self.send(&url).await?;
         
-        if !response.status().is_success() {
+        if response.status() == StatusCode::NOT_FOUND {
             warn!("Failed to fetch languages for {}: {}", repo_name, response.status());
             return Ok(vec![]);
         }
+        // Keep the status on other failures so callers can tell rate limits apart
+        let response = response.error_for_status()?;
 
         let languages: GitHubLanguages = response.json().await?;
         
@@ -52,50 +130,82 @@ impl GitHubClient {
             return Ok(vec![]);
         }
 
-        // Convert to percentages and filter by threshold
-        let mut lang_info: Vec<LanguageInfo> = languages
+        // Convert to percentages; languages below the threshold are folded into "Other"
+        let (mut lang_info, minor): (Vec<LanguageInfo>, Vec<LanguageInfo>) = languages
             .into_iter()
             .map(|(lang, bytes)| {
                 let percentage = (bytes as f64 / total as f64) * 100.0;
                 LanguageInfo { language: lang, percentage }
             })
-            .filter(|l| l.percentage >= (threshold * 100.0))
-            .collect();
+            .partition(|l| l.percentage >= (threshold * 100.0));
 
         // Sort by percentage descending
         lang_info.sort_by(|a, b| b.percentage.partial_cmp(&a.percentage).unwrap());
 
+        let other: f64 = minor.iter().map(|l| l.percentage).sum();
+        if other > 0.0 {
+            lang_info.push(LanguageInfo {
+                language: OTHER_LANGUAGE.to_string(),
+                percentage: other,
+            });
+        }
+
         info!("Found {} languages above {}% for {}", lang_info.len(), threshold * 100.0, repo_name);
         
         Ok(lang_info)
     }
 
-    pub async fn get_readme(&self, repo_name: &str) -> Result<Option<String>> {
+    pub async fn get_user_followers(&self, login: &str) -> ServiceResult<Option<i64>> {
+        let url = format!("{}/users/{}", self.api_url, login);
+
+        let response = self.send(&url).await?;
+
+        if !response.status().is_success() {
+            warn!("Failed to fetch user info for {}: {}", login, response.status());
+            return Ok(None);
+        }
+
+        let user: GitHubUserInfo = response.json().await?;
+        Ok(Some(user.followers))
+    }
+
+    /// Repo settings: all-time stargazer count, homepage, ...; `None` when the repo is gone
+    pub async fn get_repo_info(&self, repo_name: &str) -> ServiceResult<Option<GitHubRepoInfo>> {
+        let url = format!("{}/repos/{}", self.api_url, repo_name);
+        let response = self.send(&url).await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            warn!("Failed to fetch repo info for {}: {}", repo_name, response.status());
+            return Ok(None);
+        }
+        let response = response.error_for_status()?;
+
+        Ok(Some(response.json().await?))
+    }
+
+    pub async fn get_readme(&self, repo_name: &str) -> ServiceResult<Option<String>> {
         // First, get the default branch
         let repo_url = format!("{}/repos/{}", self.api_url, repo_name);
-        let repo_response = self.build_request(&repo_url).send().await?;
+        let repo_response = self.send(&repo_url).await?;
         
-        if !repo_response.status().is_success() {
+        if repo_response.status() == StatusCode::NOT_FOUND {
             warn!("Failed to fetch repo info for {}: {}", repo_name, repo_response.status());
             return Ok(None);
         }
+        let repo_response = repo_response.error_for_status()?;
 
         let repo_info: GitHubRepoInfo = repo_response.json().await?;
         let default_branch = repo_info.default_branch;
 
         // Fetch README from raw.githubusercontent.com
         let readme_urls = [
-            format!("https://raw.githubusercontent.com/{}/{}/README.md", repo_name, default_branch),
-            format!("https://raw.githubusercontent.com/{}/{}/readme.md", repo_name, default_branch),
-            format!("https://raw.githubusercontent.com/{}/{}/Readme.md", repo_name, default_branch),
+            format!("{}/{}/{}/README.md", self.raw_url, repo_name, default_branch),
+            format!("{}/{}/{}/readme.md", self.raw_url, repo_name, default_branch),
+            format!("{}/{}/{}/Readme.md", self.raw_url, repo_name, default_branch),
         ];
 
         for url in readme_urls {
-            let response = self.client
-                .get(&url)
-                .header("User-Agent", "Daily-Git-Brief")
-                .send()
-                .await?;
+            let response = self.send_raw(&url).await?;
 
             if response.status().is_success() {
                 let content = response.text().await?;
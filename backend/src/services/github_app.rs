@@ -0,0 +1,89 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::config::GitHubAppConfig;
+
+/// Installation tokens live for an hour; refresh a little early so in-flight requests don't expire
+const REFRESH_MARGIN_MINUTES: i64 = 5;
+
+#[derive(Serialize)]
+struct AppClaims {
+    iat: i64,
+    exp: i64,
+    iss: String,
+}
+
+#[derive(Deserialize)]
+struct InstallationToken {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+/// Authenticates as a GitHub App installation, caching the installation token until it nears expiry
+pub struct GitHubAppAuth {
+    client: Client,
+    api_url: String,
+    app: GitHubAppConfig,
+    token: Mutex<Option<InstallationToken>>,
+}
+
+impl GitHubAppAuth {
+    pub fn new(client: Client, api_url: &str, app: GitHubAppConfig) -> Self {
+        GitHubAppAuth {
+            client,
+            api_url: api_url.to_string(),
+            app,
+            token: Mutex::new(None),
+        }
+    }
+
+    pub async fn token(&self) -> Result<String> {
+        let mut cached = self.token.lock().await;
+
+        let fresh = cached
+            .as_ref()
+            .is_some_and(|t| t.expires_at - Duration::minutes(REFRESH_MARGIN_MINUTES) > Utc::now());
+        if !fresh {
+            *cached = Some(self.request_installation_token().await?);
+        }
+
+        Ok(cached.as_ref().map(|t| t.token.clone()).unwrap_or_default())
+    }
+
+    async fn request_installation_token(&self) -> Result<InstallationToken> {
+        let url = format!("{}/app/installations/{}/access_tokens", self.api_url, self.app.installation_id);
+
+        let token: InstallationToken = self.client
+            .post(&url)
+            .header("Accept", "application/vnd.github+json")
+            .bearer_auth(self.app_jwt()?)
+            .send()
+            .await?
+            .error_for_status()
+            .context("GitHub App installation token request failed")?
+            .json()
+            .await?;
+
+        info!("Refreshed GitHub App installation token (expires {})", token.expires_at);
+        Ok(token)
+    }
+
+    /// Short-lived JWT identifying the app; `iat` is backdated to tolerate clock drift
+    fn app_jwt(&self) -> Result<String> {
+        let now = Utc::now().timestamp();
+        let claims = AppClaims {
+            iat: now - 60,
+            exp: now + 9 * 60,
+            iss: self.app.app_id.clone(),
+        };
+        let key = EncodingKey::from_rsa_pem(self.app.private_key.as_bytes())
+            .context("Invalid GitHub App private key")?;
+
+        Ok(jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &key)?)
+    }
+}
@@ -0,0 +1,89 @@
+use chrono::Utc;
+use reqwest::header::HeaderMap;
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+/// Last rate limit state GitHub reported for a token
+#[derive(Debug, Clone, Copy, Default)]
+struct Quota {
+    /// `None` until the first response using the token
+    remaining: Option<i64>,
+    /// Unix timestamp when the quota resets
+    reset: Option<i64>,
+}
+
+impl Quota {
+    fn available(&self, min_remaining: i64, now: i64) -> bool {
+        self.remaining.is_none_or(|r| r > min_remaining) || self.reset.is_some_and(|reset| reset <= now)
+    }
+}
+
+/// Personal access tokens used in turn: the current one is kept until it nears its rate limit
+pub struct TokenPool {
+    tokens: Vec<String>,
+    min_remaining: i64,
+    state: Mutex<PoolState>,
+}
+
+struct PoolState {
+    current: usize,
+    quotas: Vec<Quota>,
+}
+
+impl TokenPool {
+    pub fn new(tokens: Vec<String>, min_remaining: i64) -> Self {
+        let quotas = vec![Quota::default(); tokens.len()];
+        TokenPool {
+            tokens,
+            min_remaining,
+            state: Mutex::new(PoolState { current: 0, quotas }),
+        }
+    }
+
+    /// Pick a token with quota left, or the one resetting soonest when all are exhausted
+    pub fn acquire(&self) -> (usize, &str) {
+        let mut state = self.state.lock().unwrap();
+        let now = Utc::now().timestamp();
+        let count = self.tokens.len();
+
+        let next = (0..count)
+            .map(|offset| (state.current + offset) % count)
+            .find(|&i| state.quotas[i].available(self.min_remaining, now));
+        let index = match next {
+            Some(i) => i,
+            None => {
+                let i = (0..count)
+                    .min_by_key(|&i| state.quotas[i].reset.unwrap_or(i64::MAX))
+                    .unwrap_or(0);
+                warn!("All {} GitHub tokens are near their rate limit; using token {}", count, i + 1);
+                i
+            }
+        };
+
+        if index != state.current {
+            info!("Rotating GitHub token {} -> {}", state.current + 1, index + 1);
+            state.current = index;
+        }
+
+        (index, &self.tokens[index])
+    }
+
+    /// Update a token's quota from GitHub's `x-ratelimit-*` response headers
+    pub fn record(&self, index: usize, headers: &HeaderMap) {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<i64>().ok())
+        };
+        let (Some(remaining), reset) = (header("x-ratelimit-remaining"), header("x-ratelimit-reset")) else {
+            return;
+        };
+
+        let mut state = self.state.lock().unwrap();
+        if let Some(quota) = state.quotas.get_mut(index) {
+            quota.remaining = Some(remaining);
+            quota.reset = reset;
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use anyhow::Result;
+use reqwest::{Client, NoProxy, Proxy};
+use std::time::Duration;
+use tracing::info;
+
+use crate::config::Config;
+
+/// Build the single outbound HTTP client shared by all services, so connection pooling,
+/// timeouts and proxy settings apply uniformly to OSS Insight, GitHub and the LLM API.
+pub fn build_client(config: &Config) -> Result<Client> {
+    let mut builder = Client::builder()
+        .user_agent(config.user_agent.as_str())
+        .timeout(Duration::from_secs(config.http_timeout_secs))
+        .connect_timeout(Duration::from_secs(config.http_connect_timeout_secs))
+        .pool_max_idle_per_host(config.http_pool_max_idle_per_host)
+        .pool_idle_timeout(Duration::from_secs(90));
+
+    // Explicit proxies replace reqwest's implicit environment lookup, so every
+    // supported variable is handled here.
+    if let Some(url) = &config.outbound_proxy {
+        builder = builder.proxy(configure_proxy(Proxy::all(url.as_str())?, config));
+        info!("Outbound proxy configured: {}", redact(url));
+    }
+    if let Some(url) = &config.http_proxy {
+        builder = builder.proxy(configure_proxy(Proxy::http(url.as_str())?, config));
+        info!("HTTP proxy configured: {}", redact(url));
+    }
+    if let Some(url) = &config.https_proxy {
+        builder = builder.proxy(configure_proxy(Proxy::https(url.as_str())?, config));
+        info!("HTTPS proxy configured: {}", redact(url));
+    }
+
+    Ok(builder.build()?)
+}
+
+fn configure_proxy(mut proxy: Proxy, config: &Config) -> Proxy {
+    if let (Some(username), Some(password)) = (&config.proxy_username, &config.proxy_password) {
+        proxy = proxy.basic_auth(username, password);
+    }
+    proxy.no_proxy(config.no_proxy.as_deref().and_then(NoProxy::from_string))
+}
+
+/// Strip credentials embedded in a proxy URL before logging it
+fn redact(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed) if !parsed.username().is_empty() || parsed.password().is_some() => {
+            // Only fails for URLs that cannot carry credentials, which these just did
+            let _ = parsed.set_username("***");
+            let _ = parsed.set_password(None);
+            parsed.to_string()
+        }
+        Ok(_) => url.to_string(),
+        // A password may itself contain '@', so the host starts after the last one
+        Err(_) => match url.rsplit_once('@') {
+            Some((_, host)) => format!("***@{}", host),
+            None => url.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::redact;
+
+    #[test]
+    fn redact_hides_the_whole_password() {
+        let redacted = redact("http://user:p@ss@proxy.internal:3128");
+        assert_eq!(redacted, "http://***@proxy.internal:3128/");
+        assert!(!redacted.contains("ss"));
+    }
+
+    #[test]
+    fn redact_keeps_urls_without_credentials() {
+        assert_eq!(redact("http://proxy.internal:3128"), "http://proxy.internal:3128");
+    }
+}
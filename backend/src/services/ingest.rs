@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use tracing::warn;
+
+use crate::models::{CollectionEntry, OssInsightCollectionRow, OssInsightRow, TrendCandidate};
+
+/// Values that failed to parse during a run, counted per field (`stars`, `collection.total`, ...)
+pub type ParseFailures = BTreeMap<String, usize>;
+
+/// OSS Insight's string-typed trending rows as typed candidates. A row without a usable repo id
+/// is dropped; any other unparsable value is left unset. Each failure is counted in `failures`.
+pub fn trend_candidates(rows: Vec<OssInsightRow>, failures: &mut ParseFailures) -> Vec<TrendCandidate> {
+    rows.into_iter()
+        .filter_map(|row| {
+            let mut fields = Fields { repo_name: &row.repo_name, prefix: "", failures: &mut *failures };
+            let repo_id = fields.repo_id(&row.repo_id)?;
+            let stars = fields.parse("stars", row.stars.as_deref());
+            let forks = fields.parse("forks", row.forks.as_deref());
+            let pull_requests = fields.parse("pull_requests", row.pull_requests.as_deref());
+            let pushes = fields.parse("pushes", row.pushes.as_deref());
+            let total_score = fields.parse("total_score", row.total_score.as_deref());
+            Some(TrendCandidate {
+                repo_id,
+                repo_name: row.repo_name,
+                primary_language: row.primary_language,
+                description: row.description,
+                stars,
+                forks,
+                pull_requests,
+                pushes,
+                total_score,
+                contributor_logins: row.contributor_logins,
+                collection_names: row.collection_names,
+            })
+        })
+        .collect()
+}
+
+/// A collection's string-typed rows as typed entries, under the same rules as `trend_candidates`;
+/// failures are counted as `collection.<field>`
+pub fn collection_entries(rows: Vec<OssInsightCollectionRow>, failures: &mut ParseFailures) -> Vec<CollectionEntry> {
+    rows.into_iter()
+        .filter_map(|row| {
+            let mut fields = Fields { repo_name: &row.repo_name, prefix: "collection.", failures: &mut *failures };
+            let repo_id = fields.repo_id(&row.repo_id)?;
+            let period_growth = fields.parse("current_period_growth", row.current_period_growth.as_deref());
+            let period_rank = fields.parse("current_period_rank", row.current_period_rank.as_deref());
+            let total_stars = fields.parse("total", row.total.as_deref());
+            Some(CollectionEntry {
+                repo_id,
+                repo_name: row.repo_name,
+                period_growth,
+                period_rank,
+                total_stars,
+            })
+        })
+        .collect()
+}
+
+/// Parses the fields of one row, counting failures under `prefix` + the field name
+struct Fields<'a> {
+    repo_name: &'a str,
+    prefix: &'static str,
+    failures: &'a mut ParseFailures,
+}
+
+impl Fields<'_> {
+    /// Repo ids must be positive; `None` drops the row
+    fn repo_id(&mut self, value: &str) -> Option<i64> {
+        let id = <i64 as Numeric>::parse(value.trim()).filter(|&id| id > 0);
+        if id.is_none() {
+            self.fail("repo_id", value);
+        }
+        id
+    }
+
+    /// Missing and empty values are `None` without counting as failures. Integers given in
+    /// float notation ("12.0") are accepted when whole.
+    fn parse<T: Numeric>(&mut self, field: &str, value: Option<&str>) -> Option<T> {
+        let value = value.map(str::trim).filter(|v| !v.is_empty())?;
+        let parsed = T::parse(value);
+        if parsed.is_none() {
+            self.fail(field, value);
+        }
+        parsed
+    }
+
+    fn fail(&mut self, field: &str, value: &str) {
+        let field = format!("{}{}", self.prefix, field);
+        warn!("Unparsable {} {:?} of {}", field, value, self.repo_name);
+        *self.failures.entry(field).or_default() += 1;
+    }
+}
+
+trait Numeric: Sized {
+    fn parse(value: &str) -> Option<Self>;
+}
+
+impl Numeric for f64 {
+    fn parse(value: &str) -> Option<Self> {
+        f64::from_str(value).ok().filter(|v| v.is_finite())
+    }
+}
+
+macro_rules! integer_numeric {
+    ($($t:ty),*) => {$(
+        impl Numeric for $t {
+            fn parse(value: &str) -> Option<Self> {
+                <$t>::from_str(value).ok().or_else(|| {
+                    let float = f64::from_str(value).ok().filter(|v| v.fract() == 0.0)?;
+                    (float >= <$t>::MIN as f64 && float <= <$t>::MAX as f64).then_some(float as $t)
+                })
+            }
+        }
+    )*};
+}
+
+integer_numeric!(i32, i64);
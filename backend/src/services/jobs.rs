@@ -0,0 +1,215 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDate, Utc};
+use tokio::sync::Notify;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::api::AppState;
+use crate::db::Database;
+use crate::models::Job;
+use crate::services::archive::Archiver;
+use crate::services::DataCollector;
+
+// Values of `Job::kind`
+pub const JOB_COLLECT: &str = "collect";
+/// Re-collect a past date from the current upstream snapshot
+pub const JOB_BACKFILL: &str = "backfill";
+/// Retry the summary stage for a date's repos without a summary
+pub const JOB_SUMMARIZE_MISSING: &str = "summarize_missing";
+/// Delete every collected day before `date`
+pub const JOB_PRUNE: &str = "prune";
+/// Publish a date to the object storage archive
+pub const JOB_EXPORT: &str = "export";
+/// Rebuild the language trends of every collected day from `date` on from stored repo languages
+pub const JOB_RECOMPUTE_TRENDS: &str = "recompute_trends";
+pub const JOB_KINDS: &[&str] = &[JOB_COLLECT, JOB_BACKFILL, JOB_SUMMARIZE_MISSING, JOB_PRUNE, JOB_EXPORT, JOB_RECOMPUTE_TRENDS];
+
+// Values of `Job::status`
+pub const JOB_QUEUED: &str = "queued";
+pub const JOB_RUNNING: &str = "running";
+pub const JOB_DONE: &str = "done";
+pub const JOB_FAILED: &str = "failed";
+pub const JOB_CANCELLED: &str = "cancelled";
+
+/// Outcome of `JobQueue::enqueue`
+pub enum Enqueued {
+    New(Job),
+    /// The same work was already queued or running
+    Existing(Job),
+}
+
+/// Persistent queue of background work for one workspace. Jobs live in the `jobs` table, so
+/// queued work survives restarts; a single worker runs them one at a time, highest priority first.
+#[derive(Clone)]
+pub struct JobQueue {
+    db: Database,
+    wake: Arc<Notify>,
+    busy: Arc<AtomicBool>,
+}
+
+impl JobQueue {
+    pub fn new(db: Database) -> Self {
+        JobQueue {
+            db,
+            wake: Arc::new(Notify::new()),
+            busy: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Queue a job unless the same kind is already queued or running for `date`
+    pub fn enqueue(&self, kind: &str, date: Option<&str>, priority: Option<i32>) -> Result<Enqueued> {
+        if let Some(active) = self.db.find_active_job(kind, date)? {
+            return Ok(Enqueued::Existing(active));
+        }
+
+        // Collector runs get their id up front so callers can follow them on the progress stream
+        let run_id = matches!(kind, JOB_COLLECT | JOB_BACKFILL | JOB_SUMMARIZE_MISSING).then(|| Uuid::new_v4().to_string());
+        let job = self.db.enqueue_job(kind, date, priority.unwrap_or_else(|| default_priority(kind)), run_id.as_deref())?;
+        info!("Queued {} job {}{}", job.kind, job.id, date.map(|d| format!(" for {}", d)).unwrap_or_default());
+        self.wake.notify_one();
+
+        Ok(Enqueued::New(job))
+    }
+
+    /// Whether a job is running right now
+    pub fn is_busy(&self) -> bool {
+        self.busy.load(Ordering::SeqCst)
+    }
+
+    /// Run queued jobs forever, one at a time. Jobs left running by a previous process are
+    /// queued again first.
+    pub fn spawn_worker(&self, state: Arc<AppState>) {
+        match self.db.requeue_interrupted_jobs() {
+            Ok(0) => {}
+            Ok(count) => warn!("Re-queued {} jobs interrupted by a restart ({})", count, state.config.workspace),
+            Err(e) => error!("Failed to re-queue interrupted jobs: {}", e),
+        }
+
+        let queue = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match queue.db.claim_next_job() {
+                    Ok(Some(job)) => queue.run(&state, job).await,
+                    // A job queued since the check left a permit, so this returns right away
+                    Ok(None) => queue.wake.notified().await,
+                    Err(e) => {
+                        error!("Failed to claim next job: {}", e);
+                        tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+                    }
+                }
+            }
+        });
+    }
+
+    async fn run(&self, state: &AppState, job: Job) {
+        info!("Running {} job {} ({})", job.kind, job.id, state.config.workspace);
+        self.busy.store(true, Ordering::SeqCst);
+        let result = execute(state, &job).await;
+        self.busy.store(false, Ordering::SeqCst);
+
+        let finished = match &result {
+            Ok(summary) => {
+                info!("{} job {} done: {}", job.kind, job.id, summary);
+                self.db.finish_job(job.id, Ok(summary))
+            }
+            Err(e) => {
+                error!("{} job {} failed: {:#}", job.kind, job.id, e);
+                self.db.finish_job(job.id, Err(&format!("{:#}", e)))
+            }
+        };
+        if let Err(e) = finished {
+            error!("Failed to record the outcome of job {}: {}", job.id, e);
+        }
+    }
+}
+
+/// Collections first; housekeeping yields to everything else
+pub fn default_priority(kind: &str) -> i32 {
+    match kind {
+        JOB_COLLECT => 10,
+        JOB_BACKFILL | JOB_SUMMARIZE_MISSING => 5,
+        JOB_EXPORT => 1,
+        _ => 0,
+    }
+}
+
+/// Check a job request's kind and date; collect takes no date, the others need one
+pub fn validate(kind: &str, date: Option<&str>) -> std::result::Result<(), String> {
+    if !JOB_KINDS.contains(&kind) {
+        return Err(format!("Unknown job kind '{}' (expected one of {})", kind, JOB_KINDS.join(", ")));
+    }
+    let today = Utc::now().date_naive();
+    let date = match (kind, date) {
+        (JOB_COLLECT, None) => return Ok(()),
+        (JOB_COLLECT, Some(_)) => return Err("collect jobs take no date; use a backfill job for past dates".to_string()),
+        (_, None) => return Err(format!("{} jobs need a date", kind)),
+        (_, Some(date)) => NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| format!("Invalid date '{}'", date))?,
+    };
+    if kind == JOB_BACKFILL && date >= today {
+        return Err("backfill jobs need a date before today".to_string());
+    }
+    if date > today {
+        return Err("date must not be in the future".to_string());
+    }
+    Ok(())
+}
+
+/// Run one job, returning a short summary of what it did
+async fn execute(state: &AppState, job: &Job) -> Result<String> {
+    let collector = || {
+        let collector = DataCollector::new(&state.config, state.db.clone(), &state.providers, state.changes.clone());
+        match &job.run_id {
+            Some(run_id) => collector.with_run_id(run_id.clone()),
+            None => collector,
+        }
+    };
+    let date = job.date.as_deref();
+    let progress_tx = Some(state.progress_tx.clone());
+
+    match job.kind.as_str() {
+        JOB_COLLECT | JOB_BACKFILL => {
+            let report = collector().collect(date, progress_tx).await?;
+            Ok(format!(
+                "Collected {} repos ({} partly), skipped {}, {} failed",
+                report.collected + report.partial, report.partial, report.skipped, report.failed
+            ))
+        }
+        JOB_SUMMARIZE_MISSING => {
+            let date = date.context("summarize_missing job without a date")?;
+            let report = collector().summarize_missing(date, progress_tx).await?;
+            Ok(format!("Summarized {} of {} repos", report.collected, report.repos.len()))
+        }
+        JOB_PRUNE => {
+            let before = NaiveDate::parse_from_str(date.context("prune job without a date")?, "%Y-%m-%d")?;
+            let last = (before - Duration::days(1)).format("%Y-%m-%d").to_string();
+            let days = state.db.get_trending_dates_between("0001-01-01", &last)?;
+            let mut deleted = 0;
+            for day in &days {
+                deleted += state.db.delete_day(day)?;
+            }
+            state.cache.clear();
+            Ok(format!("Deleted {} rows of {} days", deleted, days.len()))
+        }
+        JOB_RECOMPUTE_TRENDS => {
+            let from = date.context("recompute_trends job without a date")?;
+            let today = Utc::now().format("%Y-%m-%d").to_string();
+            let days = state.db.get_trending_dates_between(from, &today)?;
+            let collector = collector();
+            for day in &days {
+                collector.backfill_language_trends(day)?;
+            }
+            state.cache.clear();
+            Ok(format!("Recomputed language trends of {} days", days.len()))
+        }
+        JOB_EXPORT => {
+            let date = date.context("export job without a date")?;
+            let archiver = Archiver::new(&state.config, state.db.clone())?.context("No archive configured (ARCHIVE_BUCKET)")?;
+            let uploaded = archiver.publish(date).await?;
+            Ok(format!("Uploaded {} objects", uploaded))
+        }
+        other => anyhow::bail!("Unknown job kind '{}'", other),
+    }
+}
@@ -0,0 +1,65 @@
+/// Languages a description can be detected as; Latin script is reported as English, since
+/// that is what nearly every Latin-script description on GitHub is written in
+pub const LANG_KOREAN: &str = "ko";
+pub const LANG_JAPANESE: &str = "ja";
+pub const LANG_CHINESE: &str = "zh";
+pub const LANG_ENGLISH: &str = "en";
+pub const LANG_RUSSIAN: &str = "ru";
+pub const LANG_ARABIC: &str = "ar";
+pub const LANG_HINDI: &str = "hi";
+pub const LANG_THAI: &str = "th";
+
+/// One ideograph or syllable carries about as much as a short Latin word, so mixed texts like
+/// "A fast 数据库 client" are judged by what the reader has to understand
+const CJK_WEIGHT: usize = 3;
+
+/// Guess the language of a short text from the scripts of its letters; `None` when it has none.
+/// Kana marks Japanese even among Han characters, and Hangul outweighing Han marks Korean.
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let mut hangul = 0;
+    let mut kana = 0;
+    let mut han = 0;
+    let mut others: [(&'static str, usize); 5] = [
+        (LANG_ENGLISH, 0),
+        (LANG_RUSSIAN, 0),
+        (LANG_ARABIC, 0),
+        (LANG_HINDI, 0),
+        (LANG_THAI, 0),
+    ];
+
+    for c in text.chars().filter(|c| c.is_alphabetic()) {
+        match c as u32 {
+            0xAC00..=0xD7A3 | 0x1100..=0x11FF | 0x3130..=0x318F => hangul += 1,
+            0x3040..=0x30FF | 0x31F0..=0x31FF | 0xFF66..=0xFF9D => kana += 1,
+            0x4E00..=0x9FFF | 0x3400..=0x4DBF | 0xF900..=0xFAFF => han += 1,
+            0x0041..=0x024F => others[0].1 += 1,
+            0x0400..=0x04FF => others[1].1 += 1,
+            0x0600..=0x06FF => others[2].1 += 1,
+            0x0900..=0x097F => others[3].1 += 1,
+            0x0E00..=0x0E7F => others[4].1 += 1,
+            _ => {}
+        }
+    }
+
+    let cjk = if kana > 0 {
+        Some((LANG_JAPANESE, kana + han))
+    } else if hangul > 0 && hangul >= han {
+        Some((LANG_KOREAN, hangul))
+    } else if han > 0 {
+        Some((LANG_CHINESE, han))
+    } else {
+        None
+    };
+
+    let (language, count) = others
+        .into_iter()
+        .chain(cjk.map(|(language, count)| (language, count * CJK_WEIGHT)))
+        .max_by_key(|&(_, count)| count)?;
+    (count > 0).then_some(language)
+}
+
+/// Whether a description in `language` is worth translating for Korean readers: neither Korean
+/// nor English, which they are expected to read
+pub fn needs_translation(language: Option<&str>) -> bool {
+    language.is_some_and(|language| language != LANG_KOREAN && language != LANG_ENGLISH)
+}
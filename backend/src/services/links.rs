@@ -0,0 +1,96 @@
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use reqwest::Url;
+
+/// Most documentation links kept per repo
+pub const MAX_DOC_LINKS: usize = 5;
+
+/// Path segments of documentation pages
+const DOC_SEGMENTS: &[&str] = &[
+    "doc", "docs", "documentation", "wiki", "guide", "guides", "manual", "handbook", "reference",
+    "tutorial", "tutorials", "book",
+];
+/// Hosts serving nothing but documentation
+const DOC_HOST_SUFFIXES: &[&str] = &["readthedocs.io", "readthedocs.org", "gitbook.io", "docs.rs", "pkg.go.dev"];
+/// Words in a link's text marking it as documentation
+const DOC_WORDS: &[&str] = &["docs", "documentation", "guide", "manual", "reference", "tutorial", "문서"];
+
+/// The repo's homepage setting as an absolute http(s) URL; GitHub accepts bare domains, which
+/// get `https://`. Blank or unparsable values are `None`.
+pub fn homepage_url(homepage: Option<&str>) -> Option<String> {
+    let homepage = homepage.map(str::trim).filter(|h| !h.is_empty())?;
+    let url = if homepage.contains("://") {
+        Url::parse(homepage).ok()?
+    } else {
+        Url::parse(&format!("https://{}", homepage)).ok()?
+    };
+    is_web(&url).then(|| url.to_string())
+}
+
+/// Documentation links of a README, in document order, deduped and at most `MAX_DOC_LINKS`.
+/// A link counts when its host, path or text looks like documentation; relative links are
+/// resolved against the repo on GitHub.
+pub fn doc_links(readme: &str, repo_name: &str) -> Vec<String> {
+    let base = Url::parse(&format!("https://github.com/{}/blob/HEAD/", repo_name)).ok();
+    let mut links: Vec<String> = Vec::new();
+    // Destination and text of the link being read
+    let mut current: Option<(String, String)> = None;
+
+    for event in Parser::new(readme) {
+        match event {
+            Event::Start(Tag::Link { dest_url, .. }) => current = Some((dest_url.to_string(), String::new())),
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, link_text)) = &mut current {
+                    link_text.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Link) => {
+                let Some((dest, text)) = current.take() else { continue };
+                let Some(url) = resolve(&dest, base.as_ref()) else { continue };
+                // A relative link is judged by its own path, not the repo's
+                let (host, path) = match Url::parse(&dest) {
+                    Ok(_) => (url.host_str().unwrap_or_default(), url.path()),
+                    Err(_) => ("", dest.as_str()),
+                };
+                if is_doc_link(host, path, &text) && !links.contains(&url.to_string()) {
+                    links.push(url.to_string());
+                    if links.len() == MAX_DOC_LINKS {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    links
+}
+
+fn resolve(dest: &str, base: Option<&Url>) -> Option<Url> {
+    if dest.starts_with('#') {
+        return None;
+    }
+    let url = match Url::parse(dest) {
+        Ok(url) => url,
+        Err(_) => base?.join(dest.trim_start_matches("./").trim_start_matches('/')).ok()?,
+    };
+    is_web(&url).then_some(url)
+}
+
+fn is_web(url: &Url) -> bool {
+    matches!(url.scheme(), "http" | "https") && url.host_str().is_some()
+}
+
+fn is_doc_link(host: &str, path: &str, text: &str) -> bool {
+    let host = host.to_ascii_lowercase();
+    let doc_host = host.starts_with("docs.")
+        || host.starts_with("doc.")
+        || host.starts_with("wiki.")
+        || DOC_HOST_SUFFIXES.iter().any(|suffix| host == *suffix || host.ends_with(&format!(".{}", suffix)));
+    // "docs/", "GUIDE.md", ...
+    let doc_path = path.split('/').any(|segment| {
+        let stem = segment.split('.').next().unwrap_or_default().to_ascii_lowercase();
+        DOC_SEGMENTS.contains(&stem.as_str())
+    });
+    let text = text.to_lowercase();
+    let doc_text = DOC_WORDS.iter().any(|word| text.contains(word));
+    doc_host || doc_path || doc_text
+}
@@ -1,49 +1,326 @@
-use anyhow::Result;
+use metrics::histogram;
 use reqwest::Client;
+use std::sync::Arc;
+use std::time::Instant;
 use tracing::{info, warn};
 
-use crate::models::{ChatCompletionRequest, ChatCompletionResponse, ChatMessage};
+use crate::config::SummaryStyle;
+use crate::services::breaker::CircuitBreakers;
+use crate::services::errors::{ServiceError, ServiceResult};
+use crate::services::tags;
+use crate::services::usage::UsageMeter;
+use crate::telemetry;
+use crate::models::{
+    ChatCompletionChunk, ChatCompletionRequest, ChatCompletionResponse, ChatMessage, QualityVerdict, ResponseFormat,
+    StreamOptions, StructuredSummary,
+};
+
+const DEFAULT_SYSTEM_PROMPT: &str = r#"You are a technical documentation summarizer. 
+Your task is to summarize GitHub README content in Korean.
+Focus on:
+1. 프로젝트가 무엇인지 (What it does)
+2. 주요 기능 (Key features)
+
+Rules:
+- Use Korean language only
+- Do not include markdown formatting
+- Do not include links or code"#;
+
+/// Appended to the user message so custom system prompts still yield parseable output
+const SUMMARY_FORMAT_INSTRUCTIONS: &str = r#"Respond with a single JSON object only:
+{"one_liner": "요약", "features": ["주요 기능", ...], "tech_stack": ["Rust", ...],
+ "tags": ["web-framework", ...], "category": "ai-ml|web|mobile|devtools|infrastructure|data|security|systems|games|education|other",
+ "audience": "library|app|tool|course|research", "difficulty": "beginner|intermediate|advanced"}
+Use at most 5 features and 8 tech_stack entries; use empty arrays when unknown.
+"tags" are 1-5 lowercase English topics like GitHub topics (e.g. "llm", "database", "cli").
+"difficulty" rates how approachable the project is for someone new to its field."#;
+
+/// Characters a streamed one-liner grows by between partial progress events
+const PARTIAL_STEP_CHARS: usize = 20;
+
+/// The summary rules `style` sets, appended to the user message next to the format instructions
+fn style_instructions(style: &SummaryStyle) -> String {
+    let mut rules = Vec::new();
+    if let Some(max_chars) = style.max_chars {
+        rules.push(format!("- Keep the one-liner under {} characters", max_chars));
+    }
+    if let Some(max_sentences) = style.max_sentences {
+        rules.push(format!(
+            "- Write the one-liner in at most {} sentence{}",
+            max_sentences,
+            if max_sentences == 1 { "" } else { "s" }
+        ));
+    }
+    rules.push(format!("- Tone: {}", style.tone));
+    rules.push(if style.include_tech_stack {
+        "- List the tech stack in \"tech_stack\" when the README mentions it".to_string()
+    } else {
+        "- Leave \"tech_stack\" empty and do not describe the tech stack".to_string()
+    });
+    format!("Style:\n{}", rules.join("\n"))
+}
+
+/// Allowed values of the summary's `audience`
+pub const AUDIENCES: &[&str] = &["library", "app", "tool", "course", "research"];
+/// Allowed values of the summary's `difficulty`
+pub const DIFFICULTIES: &[&str] = &["beginner", "intermediate", "advanced"];
+/// Allowed values of the summary's `category`, the project's field
+pub const CATEGORIES: &[&str] = &[
+    "ai-ml", "web", "mobile", "devtools", "infrastructure", "data", "security", "systems", "games", "education", "other",
+];
+
+const README_CHANGE_PROMPT: &str = r#"You compare two versions of a GitHub README.
+Describe the most important change in one short Korean sentence (under 100 characters).
+Do not include markdown formatting, links or code."#;
+
+const DESCRIPTION_TRANSLATION_PROMPT: &str = r#"You translate GitHub repository descriptions into Korean.
+Reply with the translation only, in one line, keeping project and product names as written.
+Do not include markdown formatting, links or explanations."#;
+
+const QUALITY_JUDGE_PROMPT: &str = r#"You review GitHub repositories that reached a trending list.
+Decide whether the repository is low quality: spam, a bare list of links, a star-farmed copy of a tutorial,
+cheats, cracks or other content with no value to developers. Curated lists with real commentary are fine.
+Respond with a single JSON object only: {"low_quality": true|false, "reason": "one short English sentence"}"#;
 
 pub struct LlmClient {
     client: Client,
     base_url: String,
+    /// Host of `base_url`, labelling latency metrics
+    provider: String,
     api_key: String,
     model: String,
+    system_prompt: String,
+    usage: Arc<UsageMeter>,
+    /// Stream summary completions (`LLM_STREAM`)
+    stream: bool,
+    breakers: Arc<CircuitBreakers>,
 }
 
 impl LlmClient {
-    pub fn new(base_url: &str, api_key: &str, model: &str) -> Self {
+    pub fn new(
+        client: Client,
+        base_url: &str,
+        api_key: &str,
+        model: &str,
+        system_prompt: Option<String>,
+        usage: Arc<UsageMeter>,
+    ) -> Self {
         LlmClient {
-            client: Client::new(),
+            client,
             base_url: base_url.to_string(),
+            provider: reqwest::Url::parse(base_url)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_string))
+                .unwrap_or_else(|| base_url.to_string()),
             api_key: api_key.to_string(),
             model: model.to_string(),
+            system_prompt: system_prompt.unwrap_or_else(|| DEFAULT_SYSTEM_PROMPT.to_string()),
+            usage,
+            stream: false,
+            breakers: Arc::new(CircuitBreakers::disabled()),
         }
     }
 
-    pub async fn summarize_readme_korean(&self, readme_content: &str, repo_name: &str) -> Result<Option<String>> {
-        let url = format!("{}/chat/completions", self.base_url);
+    pub fn with_breakers(mut self, breakers: Arc<CircuitBreakers>) -> Self {
+        self.breakers = breakers;
+        self
+    }
 
-        let system_prompt = r#"You are a technical documentation summarizer. 
-Your task is to summarize GitHub README content in Korean.
-Focus on:
-1. 프로젝트가 무엇인지 (What it does)
-2. 주요 기능 (Key features)
-3. 기술 스택 (Tech stack if mentioned)
+    pub fn with_streaming(mut self, stream: bool) -> Self {
+        self.stream = stream;
+        self
+    }
 
-Rules:
-- Keep the summary under 200 characters
-- Use Korean language only
-- Be concise and informative
-- Do not include markdown formatting
-- Do not include links or code"#;
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    pub async fn summarize_readme_korean(
+        &self,
+        readme_content: &str,
+        repo_name: &str,
+        style: &SummaryStyle,
+    ) -> ServiceResult<Option<StructuredSummary>> {
+        let (user_content, max_tokens) = summary_request(readme_content, repo_name, style);
+
+        let Some(text) = self.complete(&self.system_prompt, user_content, max_tokens, true, repo_name).await? else {
+            return Ok(None);
+        };
+
+        finish_summary(&text, repo_name, style).map(Some)
+    }
+
+    /// `summarize_readme_korean`, streamed when `LLM_STREAM` is on: `on_partial` gets the
+    /// one-liner as it grows, and one running half again past `max_chars` is cut off as failed
+    pub async fn summarize_readme_korean_streaming(
+        &self,
+        readme_content: &str,
+        repo_name: &str,
+        style: &SummaryStyle,
+        on_partial: &(dyn Fn(&str) + Send + Sync),
+    ) -> ServiceResult<Option<StructuredSummary>> {
+        if !self.stream {
+            return self.summarize_readme_korean(readme_content, repo_name, style).await;
+        }
+        let (user_content, max_tokens) = summary_request(readme_content, repo_name, style);
+        let budget = style.max_chars.map(|chars| chars + chars / 2);
+
+        let Some(text) = self
+            .complete_streaming(&self.system_prompt, user_content, max_tokens, repo_name, budget, on_partial)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        finish_summary(&text, repo_name, style).map(Some)
+    }
+
+    /// One Korean sentence on what changed between two versions of a README
+    pub async fn describe_readme_change(&self, previous: &str, current: &str, repo_name: &str) -> ServiceResult<Option<String>> {
+        let user_content = format!(
+            "The README of '{}' changed since it last trended.\n\n--- Previous ---\n{}\n\n--- Current ---\n{}",
+            repo_name, previous, current
+        );
+
+        self.complete(README_CHANGE_PROMPT, user_content, 120, false, repo_name).await
+    }
+
+    /// A repo description translated into Korean
+    pub async fn translate_description_korean(&self, description: &str, repo_name: &str) -> ServiceResult<Option<String>> {
+        let user_content = format!("Description of '{}':\n{}", repo_name, description);
 
+        self.complete(DESCRIPTION_TRANSLATION_PROMPT, user_content, 200, false, repo_name).await
+    }
+
+    /// Second opinion on a repo the quality heuristics caught, given why they caught it
+    pub async fn judge_quality(&self, readme: &str, repo_name: &str, reasons: &[String]) -> ServiceResult<Option<QualityVerdict>> {
         let user_content = format!(
-            "Summarize this README for the repository '{}' in Korean:\n\n{}",
-            repo_name, readme_content
+            "Repository '{}' was caught by: {}.\n\nREADME:\n{}",
+            repo_name,
+            reasons.join("; "),
+            readme
         );
 
-        let request = ChatCompletionRequest {
+        let Some(text) = self.complete(QUALITY_JUDGE_PROMPT, user_content, 150, true, repo_name).await? else {
+            return Ok(None);
+        };
+        let verdict = text
+            .find('{')
+            .zip(text.rfind('}'))
+            .and_then(|(start, end)| text.get(start..=end))
+            .and_then(|json| serde_json::from_str(json).ok())
+            .ok_or_else(|| ServiceError::Malformed(format!("LLM returned an invalid quality verdict for {}", repo_name)))?;
+        Ok(Some(verdict))
+    }
+
+    /// Run one chat completion; `None` when the API returned no choices
+    async fn complete(
+        &self,
+        system_prompt: &str,
+        user_content: String,
+        max_tokens: u32,
+        json_output: bool,
+        repo_name: &str,
+    ) -> ServiceResult<Option<String>> {
+        let request = self.chat_request(system_prompt, user_content, max_tokens, json_output);
+        let response = self.send(&request, repo_name).await?;
+
+        let completion: ChatCompletionResponse = response.json().await?;
+        let (prompt_tokens, completion_tokens) = completion
+            .usage
+            .as_ref()
+            .map_or((0, 0), |u| (u.prompt_tokens, u.completion_tokens));
+        self.usage.record_llm(&self.model, prompt_tokens, completion_tokens);
+
+        if let Some(choice) = completion.choices.first() {
+            Ok(Some(choice.message.content.trim().to_string()))
+        } else {
+            warn!("No completion choices returned for {}", repo_name);
+            Ok(None)
+        }
+    }
+
+    /// `complete` for a JSON summary over a streamed response: the text is assembled from the
+    /// chunks, `on_partial` gets the one-liner every `PARTIAL_STEP_CHARS` characters, and the
+    /// stream is dropped with an error once the one-liner passes `budget` characters
+    async fn complete_streaming(
+        &self,
+        system_prompt: &str,
+        user_content: String,
+        max_tokens: u32,
+        repo_name: &str,
+        budget: Option<usize>,
+        on_partial: &(dyn Fn(&str) + Send + Sync),
+    ) -> ServiceResult<Option<String>> {
+        let mut request = self.chat_request(system_prompt, user_content, max_tokens, true);
+        request.stream = true;
+        request.stream_options = Some(StreamOptions { include_usage: true });
+        let mut response = self.send(&request, repo_name).await?;
+
+        let mut text = String::new();
+        let mut usage = None;
+        // Bytes after the last complete line; chunks may end mid-line or mid-character
+        let mut buffered: Vec<u8> = Vec::new();
+        let mut reported_chars = 0;
+        'stream: while let Some(bytes) = response.chunk().await? {
+            buffered.extend_from_slice(&bytes);
+            while let Some(end) = buffered.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffered.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                let Some(data) = line.trim().strip_prefix("data:").map(str::trim) else {
+                    continue;
+                };
+                if data == "[DONE]" {
+                    break 'stream;
+                }
+                let chunk: ChatCompletionChunk = match serde_json::from_str(data) {
+                    Ok(chunk) => chunk,
+                    Err(e) => {
+                        warn!("Skipping an unreadable completion chunk for {}: {}", repo_name, e);
+                        continue;
+                    }
+                };
+                if chunk.usage.is_some() {
+                    usage = chunk.usage;
+                }
+                for content in chunk.choices.into_iter().filter_map(|choice| choice.delta.content) {
+                    text.push_str(&content);
+                }
+
+                let Some(one_liner) = partial_one_liner(&text) else {
+                    continue;
+                };
+                let chars = one_liner.chars().count();
+                if let Some(budget) = budget.filter(|budget| chars > *budget) {
+                    // Dropping the response closes the stream, so the model stops generating
+                    self.usage.record_llm(&self.model, 0, 0);
+                    return Err(ServiceError::Malformed(format!(
+                        "Summary of {} ran past the length budget of {} characters",
+                        repo_name, budget
+                    )));
+                }
+                if chars >= reported_chars + PARTIAL_STEP_CHARS {
+                    reported_chars = chars;
+                    on_partial(&one_liner);
+                }
+            }
+        }
+
+        let (prompt_tokens, completion_tokens) = usage
+            .as_ref()
+            .map_or((0, 0), |u| (u.prompt_tokens, u.completion_tokens));
+        self.usage.record_llm(&self.model, prompt_tokens, completion_tokens);
+
+        let text = text.trim();
+        if text.is_empty() {
+            warn!("No completion streamed for {}", repo_name);
+            return Ok(None);
+        }
+        Ok(Some(text.to_string()))
+    }
+
+    fn chat_request(&self, system_prompt: &str, user_content: String, max_tokens: u32, json_output: bool) -> ChatCompletionRequest {
+        ChatCompletionRequest {
             model: self.model.clone(),
             messages: vec![
                 ChatMessage {
@@ -55,33 +332,127 @@ Rules:
                     content: user_content,
                 },
             ],
-            max_tokens: Some(300),
-        };
+            max_tokens: Some(max_tokens),
+            response_format: json_output.then(|| ResponseFormat {
+                format_type: "json_object".to_string(),
+            }),
+            stream: false,
+            stream_options: None,
+        }
+    }
+
+    /// Send a completion request, recording its latency up to the response headers; API
+    /// errors are logged and returned by status. Fails at once while the host's circuit is open.
+    async fn send(&self, request: &ChatCompletionRequest, repo_name: &str) -> ServiceResult<reqwest::Response> {
+        let url = format!("{}/chat/completions", self.base_url);
+        self.breakers.check(&url)?;
 
+        let started = Instant::now();
         let response = self.client
             .post(&url)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
-            .json(&request)
+            .json(request)
             .send()
-            .await?;
+            .await;
+        let outcome = match &response {
+            Ok(r) if r.status().is_success() => "success".to_string(),
+            Ok(r) => r.status().as_u16().to_string(),
+            Err(_) => "error".to_string(),
+        };
+        histogram!(
+            telemetry::LLM_REQUEST_DURATION,
+            "provider" => self.provider.clone(),
+            "model" => self.model.clone(),
+            "outcome" => outcome,
+        ).record(started.elapsed().as_secs_f64());
+        self.breakers.record(&url, response.as_ref().is_ok_and(|r| !r.status().is_server_error()));
+        let response = response?;
 
         if !response.status().is_success() {
             let status = response.status();
             let error_text = response.text().await.unwrap_or_default();
             warn!("LLM API error for {}: {} - {}", repo_name, status, error_text);
-            return Ok(None);
+            return Err(ServiceError::from_status(status, format!("LLM API for {}: {}", repo_name, error_text)));
         }
+        Ok(response)
+    }
+}
 
-        let completion: ChatCompletionResponse = response.json().await?;
-        
-        if let Some(choice) = completion.choices.first() {
-            let summary = choice.message.content.trim().to_string();
-            info!("Generated Korean summary for {} ({} chars)", repo_name, summary.len());
-            Ok(Some(summary))
-        } else {
-            warn!("No completion choices returned for {}", repo_name);
-            Ok(None)
+/// User message and token limit of a README summary request
+fn summary_request(readme_content: &str, repo_name: &str, style: &SummaryStyle) -> (String, u32) {
+    let user_content = format!(
+        "Summarize this README for the repository '{}' in Korean.\n{}\n{}\n\n{}",
+        repo_name, SUMMARY_FORMAT_INSTRUCTIONS, style_instructions(style), readme_content
+    );
+    // Room for the one-liner on top of the features and metadata; Korean runs about two
+    // tokens per character
+    let max_tokens = style.max_chars.map_or(1000, |chars| chars.saturating_mul(2).saturating_add(400).clamp(600, 4000)) as u32;
+    (user_content, max_tokens)
+}
+
+/// Parse a summary completion and apply what `style` says about its contents
+fn finish_summary(text: &str, repo_name: &str, style: &SummaryStyle) -> ServiceResult<StructuredSummary> {
+    let mut summary = parse_structured_summary(text)
+        .ok_or_else(|| ServiceError::Malformed(format!("LLM returned an invalid structured summary for {}", repo_name)))?;
+    if !style.include_tech_stack {
+        summary.tech_stack.clear();
+    }
+    info!(
+        "Generated Korean summary for {} ({} chars, {} features)",
+        repo_name, summary.one_liner.len(), summary.features.len()
+    );
+    Ok(summary)
+}
+
+/// The `one_liner` string of a JSON summary still being streamed, as far as it has arrived
+fn partial_one_liner(text: &str) -> Option<String> {
+    let rest = &text[text.find("\"one_liner\"")? + "\"one_liner\"".len()..];
+    let rest = rest.trim_start().strip_prefix(':')?.trim_start().strip_prefix('"')?;
+
+    let mut one_liner = String::new();
+    let mut chars = rest.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => break,
+            '\\' => match chars.next() {
+                Some('n') => one_liner.push('\n'),
+                Some('t') => one_liner.push('\t'),
+                Some('u') => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    if let Some(decoded) = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                        one_liner.push(decoded);
+                    }
+                }
+                Some(escaped) => one_liner.push(escaped),
+                None => break,
+            },
+            c => one_liner.push(c),
         }
     }
+    Some(one_liner)
+}
+
+fn normalize_choice(value: Option<String>, allowed: &[&str]) -> Option<String> {
+    let value = value?.trim().to_lowercase();
+    allowed.contains(&value.as_str()).then_some(value)
+}
+
+/// Parse the JSON summary, tolerating code fences around it. Blank entries are dropped and
+/// an empty one-liner counts as invalid.
+fn parse_structured_summary(text: &str) -> Option<StructuredSummary> {
+    let start = text.find('{')?;
+    let end = text.rfind('}')?;
+    let mut summary: StructuredSummary = serde_json::from_str(text.get(start..=end)?).ok()?;
+
+    summary.one_liner = summary.one_liner.trim().to_string();
+    summary.features.retain(|f| !f.trim().is_empty());
+    summary.tech_stack.retain(|t| !t.trim().is_empty());
+    summary.tags = tags::normalize_tags(&summary.tags);
+    // Ratings outside the allowed values are dropped rather than failing the summary
+    summary.audience = normalize_choice(summary.audience.take(), AUDIENCES);
+    summary.difficulty = normalize_choice(summary.difficulty.take(), DIFFICULTIES);
+    summary.category = normalize_choice(summary.category.take(), CATEGORIES);
+
+    (!summary.one_liner.is_empty()).then_some(summary)
 }
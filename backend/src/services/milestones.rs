@@ -0,0 +1,39 @@
+use anyhow::Result;
+use tracing::info;
+
+use crate::db::Database;
+use crate::models::StarMilestone;
+
+/// Flag repos whose total stars on `date` passed one of `milestones` since their previous
+/// appearance, store them and return the new milestones. Repos seen for the first time are
+/// not flagged, since there is nothing to have crossed from.
+pub fn detect_star_milestones(db: &Database, date: &str, milestones: &[i64]) -> Result<Vec<StarMilestone>> {
+    let crossed: Vec<StarMilestone> = db
+        .get_star_progress(date)?
+        .into_iter()
+        .flat_map(|(repo_id, repo_name, previous_date, previous_stars, total_stars)| {
+            milestones
+                .iter()
+                .filter(move |&&m| previous_stars < m && total_stars >= m)
+                .map(move |&milestone| StarMilestone {
+                    date: date.to_string(),
+                    repo_id,
+                    repo_name: repo_name.clone(),
+                    milestone,
+                    previous_stars,
+                    total_stars,
+                    previous_date: previous_date.clone(),
+                })
+        })
+        .collect();
+
+    for milestone in &crossed {
+        db.save_star_milestone(milestone)?;
+        info!(
+            "Star milestone on {}: {} passed {} stars ({} -> {})",
+            date, milestone.repo_name, milestone.milestone, milestone.previous_stars, milestone.total_stars
+        );
+    }
+
+    Ok(crossed)
+}
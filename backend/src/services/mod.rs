@@ -1,9 +1,49 @@
 pub mod oss_insight;
 pub mod github;
+pub mod github_app;
+pub mod github_tokens;
 pub mod llm;
 pub mod collector;
+pub mod http;
+pub mod jobs;
+pub mod langdetect;
+pub mod links;
+pub mod digest;
+pub mod fixtures;
+pub mod ingest;
+pub mod adhoc;
+pub mod anomaly;
+pub mod archive;
+pub mod breaker;
+pub mod calendar;
+pub mod changes;
+pub mod coalesce;
+pub mod errors;
+pub mod milestones;
+pub mod moderation;
+pub mod sources;
+pub mod tags;
+pub mod templates;
+pub mod tts;
+pub mod webhooks;
+pub mod forecast;
+pub mod trends;
+pub mod scoring;
+pub mod pipeline;
+pub mod profile;
+pub mod quality;
+pub mod providers;
+pub mod readme;
+pub mod routing;
+pub mod run_events;
+pub mod search;
+pub mod selfcheck;
+pub mod sitemap;
+pub mod usage;
 
 pub use oss_insight::OssInsightClient;
 pub use github::GitHubClient;
 pub use llm::LlmClient;
 pub use collector::DataCollector;
+pub use digest::DigestSender;
+pub use providers::Providers;
@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::config::{Config, ModerationApiConfig};
+use crate::services::providers::{ModerationProvider, Providers};
+
+/// Characters of README sent for moderation; the opening of a README says what the repo is
+const MAX_MODERATED_CHARS: usize = 4000;
+
+/// OpenAI-compatible `POST /moderations` API
+pub struct ModerationClient {
+    client: Client,
+    config: ModerationApiConfig,
+}
+
+#[derive(Deserialize)]
+struct ModerationResponse {
+    results: Vec<ModerationResult>,
+}
+
+#[derive(Deserialize)]
+struct ModerationResult {
+    #[serde(default)]
+    categories: std::collections::BTreeMap<String, bool>,
+}
+
+impl ModerationClient {
+    pub fn new(client: Client, config: &ModerationApiConfig) -> Self {
+        ModerationClient {
+            client,
+            config: config.clone(),
+        }
+    }
+
+    /// Categories the API flagged `text` for
+    pub async fn moderate(&self, text: &str) -> Result<Vec<String>> {
+        let response = self
+            .client
+            .post(format!("{}/moderations", self.config.base_url))
+            .bearer_auth(&self.config.api_key)
+            .json(&json!({ "model": self.config.model, "input": text }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Moderation API returned {}: {}", status, body.chars().take(200).collect::<String>());
+        }
+
+        let response: ModerationResponse = response.json().await?;
+        Ok(response
+            .results
+            .into_iter()
+            .flat_map(|result| result.categories)
+            .filter(|(_, flagged)| *flagged)
+            .map(|(category, _)| category)
+            .collect())
+    }
+}
+
+/// Screens a repo's description and README against the configured policy: keyword rules, plus
+/// the moderation API when `MODERATION=api`
+pub struct Moderator {
+    keywords: Vec<String>,
+    /// Provider categories that count; empty = all
+    categories: Vec<String>,
+    provider: Option<Arc<dyn ModerationProvider>>,
+}
+
+impl Moderator {
+    /// Returns `None` when no moderation is configured
+    pub fn new(config: &Config, providers: &Providers) -> Option<Self> {
+        let moderation = config.moderation.as_ref()?;
+        Some(Moderator {
+            keywords: moderation.keywords.clone(),
+            categories: moderation.api.as_ref().map(|api| api.categories.clone()).unwrap_or_default(),
+            provider: providers.moderation.clone(),
+        })
+    }
+
+    /// Policy violations found, e.g. `keyword:casino` or the API's `sexual`; empty when clean
+    pub async fn screen(&self, description: Option<&str>, readme: Option<&str>) -> Result<Vec<String>> {
+        let readme: String = readme.unwrap_or_default().chars().take(MAX_MODERATED_CHARS).collect();
+        let text = format!("{}\n\n{}", description.unwrap_or_default(), readme);
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let lowered = text.to_lowercase();
+        let mut violations: Vec<String> = self
+            .keywords
+            .iter()
+            .filter(|keyword| lowered.contains(keyword.as_str()))
+            .map(|keyword| format!("keyword:{}", keyword))
+            .collect();
+
+        if let Some(provider) = &self.provider {
+            let categories = provider.moderate(text).await?;
+            violations.extend(categories.into_iter().filter(|category| {
+                self.categories.is_empty() || self.categories.iter().any(|c| c.eq_ignore_ascii_case(category))
+            }));
+        }
+        Ok(violations)
+    }
+}
@@ -1,8 +1,11 @@
-use anyhow::Result;
 use reqwest::Client;
 use tracing::info;
 
-use crate::models::{OssInsightResponse, OssInsightRow};
+use crate::services::errors::ServiceResult;
+use crate::models::{
+    OssInsightCollectionInfo, OssInsightCollectionRow, OssInsightDeveloperResponse, OssInsightDeveloperRow,
+    OssInsightResponse, OssInsightRow, OssInsightRowsResponse,
+};
 
 pub struct OssInsightClient {
     client: Client,
@@ -10,14 +13,14 @@ pub struct OssInsightClient {
 }
 
 impl OssInsightClient {
-    pub fn new(base_url: &str) -> Self {
+    pub fn new(client: Client, base_url: &str) -> Self {
         OssInsightClient {
-            client: Client::new(),
+            client,
             base_url: base_url.to_string(),
         }
     }
 
-    pub async fn get_trending_repos(&self) -> Result<Vec<OssInsightRow>> {
+    pub async fn get_trending_repos(&self) -> ServiceResult<Vec<OssInsightRow>> {
         let url = format!("{}/v1/trends/repos/", self.base_url);
         
         info!("Fetching trending repos from OSS Insight API");
@@ -26,7 +29,8 @@ impl OssInsightClient {
             .get(&url)
             .header("Accept", "application/json")
             .send()
-            .await?;
+            .await?
+            .error_for_status()?;
 
         let oss_response: OssInsightResponse = response.json().await?;
         
@@ -34,4 +38,59 @@ impl OssInsightClient {
         
         Ok(oss_response.data.rows)
     }
+
+    pub async fn get_trending_developers(&self) -> ServiceResult<Vec<OssInsightDeveloperRow>> {
+        let url = format!("{}/v1/trends/developers/", self.base_url);
+
+        info!("Fetching trending developers from OSS Insight API");
+
+        let response = self.client
+            .get(&url)
+            .header("Accept", "application/json")
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let oss_response: OssInsightDeveloperResponse = response.json().await?;
+
+        info!("Fetched {} trending developers", oss_response.data.rows.len());
+
+        Ok(oss_response.data.rows)
+    }
+
+    pub async fn get_collection_name(&self, collection_id: i64) -> ServiceResult<Option<String>> {
+        let url = format!("{}/v1/collections/{}/", self.base_url, collection_id);
+
+        let response = self.client
+            .get(&url)
+            .header("Accept", "application/json")
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let oss_response: OssInsightRowsResponse<OssInsightCollectionInfo> = response.json().await?;
+
+        Ok(oss_response.data.rows.into_iter().next().map(|c| c.name))
+    }
+
+    /// Repos of a collection ranked by stars gained over the past 28 days
+    pub async fn get_collection_repos(&self, collection_id: i64) -> ServiceResult<Vec<OssInsightCollectionRow>> {
+        let url = format!("{}/v1/collections/{}/ranking_by_stars/", self.base_url, collection_id);
+
+        info!("Fetching collection {} ranking from OSS Insight API", collection_id);
+
+        let response = self.client
+            .get(&url)
+            .query(&[("period", "past_28_days")])
+            .header("Accept", "application/json")
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let oss_response: OssInsightRowsResponse<OssInsightCollectionRow> = response.json().await?;
+
+        info!("Fetched {} repos of collection {}", oss_response.data.rows.len(), collection_id);
+
+        Ok(oss_response.data.rows)
+    }
 }
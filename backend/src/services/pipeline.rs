@@ -0,0 +1,96 @@
+//! Building blocks for the collector's staged pipeline: each stage reads items from a
+//! channel, processes up to `concurrency` of them at once and forwards results downstream.
+
+use anyhow::Result;
+use futures::StreamExt;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::warn;
+
+use crate::config::StagePolicy;
+use crate::services::errors::{ErrorAction, ServiceError};
+
+/// Items buffered between two stages before the upstream one waits
+pub const CHANNEL_CAPACITY: usize = 32;
+
+/// Create the channel connecting two stages
+pub fn channel<T>() -> (mpsc::Sender<T>, mpsc::Receiver<T>) {
+    mpsc::channel(CHANNEL_CAPACITY)
+}
+
+/// Push `items` into the first stage, closing it once everything is queued
+pub async fn feed<T>(tx: mpsc::Sender<T>, items: impl IntoIterator<Item = T>) {
+    for item in items {
+        if tx.send(item).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Run `f` over every input with at most `concurrency` calls in flight. Results are
+/// forwarded in completion order; the stage ends when its input closes.
+pub async fn run_stage<I, O, F, Fut>(input: mpsc::Receiver<I>, output: mpsc::Sender<O>, concurrency: usize, f: F)
+where
+    F: Fn(I) -> Fut,
+    Fut: Future<Output = O>,
+{
+    let mut results = ReceiverStream::new(input).map(f).buffer_unordered(concurrency.max(1));
+    while let Some(result) = results.next().await {
+        if output.send(result).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Call `op` until it succeeds or the policy's retries are used up, backing off exponentially.
+/// Service errors that another attempt can't fix (see `ErrorAction`) are returned at once.
+pub async fn with_retry<T, F, Fut>(policy: &StagePolicy, what: &str, mut op: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) if e.downcast_ref::<ServiceError>().is_some_and(|e| e.action() != ErrorAction::Retry) => return Err(e),
+            Err(e) if attempt < policy.retries => {
+                let delay = Duration::from_millis(policy.retry_backoff_ms.saturating_mul(1 << attempt.min(16)));
+                attempt += 1;
+                warn!("{} failed (attempt {}/{}), retrying in {:?}: {}", what, attempt, policy.retries + 1, delay, e);
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Stops a run's calls to one upstream once a call failed in a way no other call gets past,
+/// such as a rejected API key; later calls fail with the same error without being made
+#[derive(Default)]
+pub struct Abort(Mutex<Option<ServiceError>>);
+
+impl Abort {
+    /// `call`'s result, or the error the upstream was aborted with
+    pub async fn guard<T>(&self, call: impl Future<Output = Result<T>>) -> Result<T> {
+        let aborted = self.0.lock().unwrap().clone();
+        if let Some(e) = aborted {
+            return Err(e.into());
+        }
+
+        let result = call.await;
+        if let Some(e) = result.as_ref().err().and_then(|e| e.downcast_ref::<ServiceError>()) {
+            if e.action() == ErrorAction::Abort {
+                let mut aborted = self.0.lock().unwrap();
+                if aborted.is_none() {
+                    warn!("Skipping further calls of this run to the same service: {}", e);
+                    *aborted = Some(e.clone());
+                }
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::models::{RepoProfile, SimilarRepo, StarPoint, StructuredSummary, TrendingRepo};
+
+/// Most similar repos listed in a profile
+pub const MAX_SIMILAR: usize = 5;
+
+/// Profile of a repo from its `appearances` (oldest first); `others` are the
+/// `(repo_id, repo_name, korean_summary, summary_tags)` of the other repos' latest tagged
+/// appearances. `None` when the repo never trended.
+pub fn repo_profile(appearances: &[TrendingRepo], others: &[(i64, String, Option<String>, String)]) -> Option<RepoProfile> {
+    let first = appearances.first()?;
+    let latest = appearances.last()?;
+
+    let mut previous_names: Vec<String> = Vec::new();
+    for repo in appearances.iter().rev() {
+        if !repo.repo_name.eq_ignore_ascii_case(&latest.repo_name) && !previous_names.contains(&repo.repo_name) {
+            previous_names.push(repo.repo_name.clone());
+        }
+    }
+
+    // Ties go to the latest day
+    let best = appearances
+        .iter()
+        .filter_map(|r| r.rank.map(|rank| (rank, r)))
+        .min_by(|(a, ra), (b, rb)| a.cmp(b).then_with(|| rb.date.cmp(&ra.date)));
+
+    let summarized = appearances.iter().rev().find(|r| r.korean_summary.is_some());
+    let latest_summary = summarized.and_then(|r| {
+        Some(StructuredSummary {
+            one_liner: r.korean_summary.clone()?,
+            features: json_list(r.summary_features.as_deref()),
+            tech_stack: json_list(r.summary_tech_stack.as_deref()),
+            tags: json_list(r.summary_tags.as_deref()),
+            audience: r.audience.clone(),
+            difficulty: r.difficulty.clone(),
+            category: r.category.clone(),
+        })
+    });
+
+    let topics = topics(appearances);
+    let similar = similar_repos(&topics, others);
+
+    Some(RepoProfile {
+        repo_id: latest.repo_id,
+        repo_name: latest.repo_name.clone(),
+        github_url: format!("https://github.com/{}", latest.repo_name),
+        previous_names,
+        trending_days: appearances.len() as i64,
+        first_seen: first.date.clone(),
+        last_seen: latest.date.clone(),
+        best_rank: best.map(|(rank, _)| rank),
+        best_rank_date: best.map(|(_, r)| r.date.clone()),
+        star_history: appearances
+            .iter()
+            .map(|r| StarPoint {
+                date: r.date.clone(),
+                stars: r.stars,
+                total_stars: r.total_stars,
+                rank: r.rank,
+            })
+            .collect(),
+        latest_summary,
+        summary_date: summarized.map(|r| r.date.clone()),
+        topics,
+        similar,
+    })
+}
+
+/// Tags of all the repo's summaries, most frequent first, then most recent
+fn topics(appearances: &[TrendingRepo]) -> Vec<String> {
+    let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+    for (i, repo) in appearances.iter().enumerate() {
+        for tag in json_list(repo.summary_tags.as_deref()) {
+            let (count, last) = counts.entry(tag).or_default();
+            *count += 1;
+            *last = i;
+        }
+    }
+    let mut topics: Vec<(String, (usize, usize))> = counts.into_iter().collect();
+    topics.sort_by(|(a, (ca, la)), (b, (cb, lb))| cb.cmp(ca).then_with(|| lb.cmp(la)).then_with(|| a.cmp(b)));
+    topics.into_iter().map(|(tag, _)| tag).collect()
+}
+
+/// Repos sharing the most topics, then by name; at most `MAX_SIMILAR`
+fn similar_repos(topics: &[String], others: &[(i64, String, Option<String>, String)]) -> Vec<SimilarRepo> {
+    let wanted: HashSet<&str> = topics.iter().map(String::as_str).collect();
+    let mut similar: Vec<SimilarRepo> = others
+        .iter()
+        .filter_map(|(repo_id, repo_name, korean_summary, tags)| {
+            let shared_topics: Vec<String> = json_list(Some(tags.as_str())).into_iter().filter(|t| wanted.contains(t.as_str())).collect();
+            (!shared_topics.is_empty()).then(|| SimilarRepo {
+                repo_id: *repo_id,
+                repo_name: repo_name.clone(),
+                korean_summary: korean_summary.clone(),
+                shared_topics,
+            })
+        })
+        .collect();
+    similar.sort_by(|a, b| b.shared_topics.len().cmp(&a.shared_topics.len()).then_with(|| a.repo_name.cmp(&b.repo_name)));
+    similar.truncate(MAX_SIMILAR);
+    similar
+}
+
+fn json_list(json: Option<&str>) -> Vec<String> {
+    json.and_then(|j| serde_json::from_str(j).ok()).unwrap_or_default()
+}
@@ -0,0 +1,300 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use futures::future::BoxFuture;
+use futures::TryFutureExt;
+use reqwest::Client;
+
+use crate::config::{Config, SummaryStyle};
+use crate::models::{GitHubRepoInfo, LanguageInfo, OssInsightCollectionRow, OssInsightDeveloperRow, OssInsightRow, QualityVerdict, StructuredSummary};
+use crate::services::breaker::CircuitBreakers;
+use crate::services::coalesce::{CoalescingMetadata, CoalescingSummaries};
+use crate::services::github::GitHubAuth;
+use crate::services::moderation::ModerationClient;
+use crate::services::tts::TtsClient;
+use crate::services::usage::UsageMeter;
+use crate::services::{GitHubClient, LlmClient, OssInsightClient};
+
+/// Where a collection's trending repos, developers and collections come from (OSS Insight)
+pub trait TrendSource: Send + Sync {
+    fn get_trending_repos(&self) -> BoxFuture<'_, Result<Vec<OssInsightRow>>>;
+    fn get_trending_developers(&self) -> BoxFuture<'_, Result<Vec<OssInsightDeveloperRow>>>;
+    fn get_collection_name(&self, collection_id: i64) -> BoxFuture<'_, Result<Option<String>>>;
+    fn get_collection_repos(&self, collection_id: i64) -> BoxFuture<'_, Result<Vec<OssInsightCollectionRow>>>;
+}
+
+/// Per-repo and per-user details (GitHub)
+pub trait RepoMetadataSource: Send + Sync {
+    /// Languages at or above `threshold`, the rest folded into "Other"
+    fn get_repo_languages<'a>(&'a self, repo_name: &'a str, threshold: f64) -> BoxFuture<'a, Result<Vec<LanguageInfo>>>;
+    /// `None` when the repo is gone
+    fn get_repo_info<'a>(&'a self, repo_name: &'a str) -> BoxFuture<'a, Result<Option<GitHubRepoInfo>>>;
+    /// `None` when the repo has no README
+    fn get_readme<'a>(&'a self, repo_name: &'a str) -> BoxFuture<'a, Result<Option<String>>>;
+    fn get_user_followers<'a>(&'a self, login: &'a str) -> BoxFuture<'a, Result<Option<i64>>>;
+}
+
+/// Writes the Korean summaries (the LLM); `None` means the model returned nothing usable
+pub trait SummaryProvider: Send + Sync {
+    /// Labels stored evaluation summaries
+    fn model(&self) -> &str;
+    fn summarize_readme_korean<'a>(
+        &'a self,
+        readme: &'a str,
+        repo_name: &'a str,
+        style: &'a SummaryStyle,
+    ) -> BoxFuture<'a, Result<Option<StructuredSummary>>>;
+    /// Like `summarize_readme_korean`, calling `on_partial` with the one-liner as it is written
+    /// when the provider streams; the default waits for the whole summary
+    fn summarize_readme_korean_streaming<'a>(
+        &'a self,
+        readme: &'a str,
+        repo_name: &'a str,
+        style: &'a SummaryStyle,
+        _on_partial: &'a (dyn Fn(&str) + Send + Sync),
+    ) -> BoxFuture<'a, Result<Option<StructuredSummary>>> {
+        self.summarize_readme_korean(readme, repo_name, style)
+    }
+    fn describe_readme_change<'a>(&'a self, previous: &'a str, current: &'a str, repo_name: &'a str) -> BoxFuture<'a, Result<Option<String>>>;
+    fn translate_description_korean<'a>(&'a self, description: &'a str, repo_name: &'a str) -> BoxFuture<'a, Result<Option<String>>>;
+    /// Whether a repo the quality heuristics caught really is low quality; `None` when the
+    /// provider cannot judge, which leaves the heuristics' decision standing
+    fn judge_quality<'a>(&'a self, _readme: &'a str, _repo_name: &'a str, _reasons: &'a [String]) -> BoxFuture<'a, Result<Option<QualityVerdict>>> {
+        Box::pin(async { Ok(None) })
+    }
+}
+
+/// Reads the daily brief aloud (a text-to-speech API); returns MP3 audio
+pub trait SpeechProvider: Send + Sync {
+    fn synthesize<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<u8>>>;
+}
+
+/// Screens text against content policy (a moderation API); returns the categories it flagged
+pub trait ModerationProvider: Send + Sync {
+    fn moderate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<String>>>;
+}
+
+impl TrendSource for OssInsightClient {
+    fn get_trending_repos(&self) -> BoxFuture<'_, Result<Vec<OssInsightRow>>> {
+        Box::pin(OssInsightClient::get_trending_repos(self).err_into())
+    }
+
+    fn get_trending_developers(&self) -> BoxFuture<'_, Result<Vec<OssInsightDeveloperRow>>> {
+        Box::pin(OssInsightClient::get_trending_developers(self).err_into())
+    }
+
+    fn get_collection_name(&self, collection_id: i64) -> BoxFuture<'_, Result<Option<String>>> {
+        Box::pin(OssInsightClient::get_collection_name(self, collection_id).err_into())
+    }
+
+    fn get_collection_repos(&self, collection_id: i64) -> BoxFuture<'_, Result<Vec<OssInsightCollectionRow>>> {
+        Box::pin(OssInsightClient::get_collection_repos(self, collection_id).err_into())
+    }
+}
+
+impl RepoMetadataSource for GitHubClient {
+    fn get_repo_languages<'a>(&'a self, repo_name: &'a str, threshold: f64) -> BoxFuture<'a, Result<Vec<LanguageInfo>>> {
+        Box::pin(GitHubClient::get_repo_languages(self, repo_name, threshold).err_into())
+    }
+
+    fn get_repo_info<'a>(&'a self, repo_name: &'a str) -> BoxFuture<'a, Result<Option<GitHubRepoInfo>>> {
+        Box::pin(GitHubClient::get_repo_info(self, repo_name).err_into())
+    }
+
+    fn get_readme<'a>(&'a self, repo_name: &'a str) -> BoxFuture<'a, Result<Option<String>>> {
+        Box::pin(GitHubClient::get_readme(self, repo_name).err_into())
+    }
+
+    fn get_user_followers<'a>(&'a self, login: &'a str) -> BoxFuture<'a, Result<Option<i64>>> {
+        Box::pin(GitHubClient::get_user_followers(self, login).err_into())
+    }
+}
+
+impl SummaryProvider for LlmClient {
+    fn model(&self) -> &str {
+        LlmClient::model(self)
+    }
+
+    fn summarize_readme_korean<'a>(
+        &'a self,
+        readme: &'a str,
+        repo_name: &'a str,
+        style: &'a SummaryStyle,
+    ) -> BoxFuture<'a, Result<Option<StructuredSummary>>> {
+        Box::pin(LlmClient::summarize_readme_korean(self, readme, repo_name, style).err_into())
+    }
+
+    fn summarize_readme_korean_streaming<'a>(
+        &'a self,
+        readme: &'a str,
+        repo_name: &'a str,
+        style: &'a SummaryStyle,
+        on_partial: &'a (dyn Fn(&str) + Send + Sync),
+    ) -> BoxFuture<'a, Result<Option<StructuredSummary>>> {
+        Box::pin(LlmClient::summarize_readme_korean_streaming(self, readme, repo_name, style, on_partial).err_into())
+    }
+
+    fn describe_readme_change<'a>(&'a self, previous: &'a str, current: &'a str, repo_name: &'a str) -> BoxFuture<'a, Result<Option<String>>> {
+        Box::pin(LlmClient::describe_readme_change(self, previous, current, repo_name).err_into())
+    }
+
+    fn translate_description_korean<'a>(&'a self, description: &'a str, repo_name: &'a str) -> BoxFuture<'a, Result<Option<String>>> {
+        Box::pin(LlmClient::translate_description_korean(self, description, repo_name).err_into())
+    }
+
+    fn judge_quality<'a>(&'a self, readme: &'a str, repo_name: &'a str, reasons: &'a [String]) -> BoxFuture<'a, Result<Option<QualityVerdict>>> {
+        Box::pin(LlmClient::judge_quality(self, readme, repo_name, reasons).err_into())
+    }
+}
+
+impl SpeechProvider for TtsClient {
+    fn synthesize<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<u8>>> {
+        Box::pin(TtsClient::synthesize(self, text))
+    }
+}
+
+impl ModerationProvider for ModerationClient {
+    fn moderate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<String>>> {
+        Box::pin(ModerationClient::moderate(self, text))
+    }
+}
+
+/// The upstream services collections of one workspace read from. Built once from the config in
+/// `main`; tests and alternative providers pass their own implementations to `Providers::new`.
+#[derive(Clone)]
+pub struct Providers {
+    pub trends: Arc<dyn TrendSource>,
+    pub metadata: Arc<dyn RepoMetadataSource>,
+    pub summaries: Arc<dyn SummaryProvider>,
+    /// Second model summarizing a sample of repos for A/B comparison
+    pub eval_summaries: Option<Arc<dyn SummaryProvider>>,
+    /// Cheaper model for small READMEs, when `SUMMARY_SMALL_MODEL` is set
+    pub small_summaries: Option<Arc<dyn SummaryProvider>>,
+    /// Stronger model for long READMEs, when `SUMMARY_LARGE_MODEL` is set
+    pub large_summaries: Option<Arc<dyn SummaryProvider>>,
+    /// Text-to-speech for the audio brief, when `TTS_PROVIDER` is set
+    pub speech: Option<Arc<dyn SpeechProvider>>,
+    /// Moderation API screening repos, when `MODERATION=api`
+    pub moderation: Option<Arc<dyn ModerationProvider>>,
+    /// API usage of the providers, flushed to `api_usage` after each run
+    pub usage: Arc<UsageMeter>,
+    /// Per-host circuit breakers of GitHub and the LLM; refused calls go into the run report
+    pub breakers: Arc<CircuitBreakers>,
+}
+
+impl Providers {
+    pub fn new(
+        trends: impl TrendSource + 'static,
+        metadata: impl RepoMetadataSource + 'static,
+        summaries: impl SummaryProvider + 'static,
+    ) -> Self {
+        Providers {
+            trends: Arc::new(trends),
+            metadata: Arc::new(metadata),
+            summaries: Arc::new(summaries),
+            eval_summaries: None,
+            small_summaries: None,
+            large_summaries: None,
+            speech: None,
+            moderation: None,
+            usage: Arc::new(UsageMeter::new()),
+            breakers: Arc::new(CircuitBreakers::disabled()),
+        }
+    }
+
+    pub fn with_eval_summaries(mut self, eval_summaries: impl SummaryProvider + 'static) -> Self {
+        self.eval_summaries = Some(Arc::new(eval_summaries));
+        self
+    }
+
+    pub fn with_small_summaries(mut self, small_summaries: impl SummaryProvider + 'static) -> Self {
+        self.small_summaries = Some(Arc::new(small_summaries));
+        self
+    }
+
+    pub fn with_large_summaries(mut self, large_summaries: impl SummaryProvider + 'static) -> Self {
+        self.large_summaries = Some(Arc::new(large_summaries));
+        self
+    }
+
+    pub fn with_speech(mut self, speech: impl SpeechProvider + 'static) -> Self {
+        self.speech = Some(Arc::new(speech));
+        self
+    }
+
+    pub fn with_moderation(mut self, moderation: impl ModerationProvider + 'static) -> Self {
+        self.moderation = Some(Arc::new(moderation));
+        self
+    }
+
+    /// Share concurrent identical GitHub lookups and summaries, so a repo fetched by a run and a
+    /// requeued summary at once is fetched and summarized only once
+    pub fn coalesced(mut self) -> Self {
+        let summaries = |provider: Arc<dyn SummaryProvider>| Arc::new(CoalescingSummaries::new(provider)) as Arc<dyn SummaryProvider>;
+        self.metadata = Arc::new(CoalescingMetadata::new(self.metadata));
+        self.summaries = summaries(self.summaries);
+        self.eval_summaries = self.eval_summaries.map(summaries);
+        self.small_summaries = self.small_summaries.map(summaries);
+        self.large_summaries = self.large_summaries.map(summaries);
+        self
+    }
+
+    /// OSS Insight, GitHub and the configured LLM, sharing one usage meter and one set of
+    /// circuit breakers, with identical calls in flight coalesced
+    pub fn from_config(config: &Config, http: Client) -> Self {
+        let usage = Arc::new(UsageMeter::new());
+        let breakers = Arc::new(CircuitBreakers::from_config(config));
+        let llm = |model: &str| {
+            LlmClient::new(
+                http.clone(),
+                &config.deepseek_base_url,
+                &config.deepseek_api_key,
+                model,
+                config.summary_prompt.clone(),
+                usage.clone(),
+            )
+            .with_streaming(config.llm_stream)
+            .with_breakers(breakers.clone())
+        };
+
+        Providers {
+            trends: Arc::new(OssInsightClient::new(http.clone(), &config.oss_insight_base_url)),
+            metadata: Arc::new(GitHubClient::new(
+                http.clone(),
+                &config.github_api_url,
+                &config.github_raw_url,
+                GitHubAuth::from_config(config, http.clone()),
+                usage.clone(),
+            )
+            .with_breakers(breakers.clone())),
+            summaries: Arc::new(llm(&config.deepseek_model)),
+            eval_summaries: config
+                .eval_model
+                .as_deref()
+                .filter(|_| config.eval_fraction > 0.0)
+                .map(|model| Arc::new(llm(model)) as Arc<dyn SummaryProvider>),
+            small_summaries: config
+                .summary_routing
+                .small_model
+                .as_deref()
+                .map(|model| Arc::new(llm(model)) as Arc<dyn SummaryProvider>),
+            large_summaries: config
+                .summary_routing
+                .large_model
+                .as_deref()
+                .map(|model| Arc::new(llm(model)) as Arc<dyn SummaryProvider>),
+            speech: config
+                .tts
+                .as_ref()
+                .map(|tts| Arc::new(TtsClient::new(http.clone(), tts)) as Arc<dyn SpeechProvider>),
+            moderation: config
+                .moderation
+                .as_ref()
+                .and_then(|moderation| moderation.api.as_ref())
+                .map(|api| Arc::new(ModerationClient::new(http.clone(), api)) as Arc<dyn ModerationProvider>),
+            usage,
+            breakers,
+        }
+        .coalesced()
+    }
+}
@@ -0,0 +1,54 @@
+use crate::config::{QualityRules, QUALITY_DROP};
+use crate::models::QualityVerdict;
+
+// Values of `QualityDecision::decision`
+/// Caught by the heuristics, then cleared by the LLM
+pub const DECISION_KEPT: &str = "kept";
+pub const DECISION_FLAGGED: &str = "flagged";
+pub const DECISION_DROPPED: &str = "dropped";
+
+/// Why the heuristics take a repo for low quality; empty when nothing was caught
+pub fn heuristic_reasons(rules: &QualityRules, repo_name: &str, description: Option<&str>, readme: Option<&str>) -> Vec<String> {
+    let mut reasons = Vec::new();
+
+    if let Some(readme) = readme {
+        let (links, density) = link_density(readme);
+        if links >= rules.min_links && density > rules.max_link_density {
+            reasons.push(format!("link density {:.2} ({} links)", density, links));
+        }
+    }
+
+    let text = format!("{} {}", repo_name, description.unwrap_or_default()).to_lowercase();
+    for pattern in &rules.patterns {
+        if text.contains(pattern.as_str()) {
+            reasons.push(format!("pattern \"{}\"", pattern));
+        }
+    }
+
+    reasons
+}
+
+/// The decision on a repo the heuristics caught: the configured mode's, unless the LLM judged
+/// the repo fine
+pub fn decide(rules: &QualityRules, verdict: Option<&QualityVerdict>) -> &'static str {
+    match verdict {
+        Some(verdict) if !verdict.low_quality => DECISION_KEPT,
+        _ if rules.mode == QUALITY_DROP => DECISION_DROPPED,
+        _ => DECISION_FLAGGED,
+    }
+}
+
+/// Links in a README and the share of its non-empty lines carrying one
+fn link_density(readme: &str) -> (usize, f64) {
+    let (mut lines, mut linked, mut links) = (0, 0, 0);
+    for line in readme.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        lines += 1;
+        let count = line.matches("](").count().max(usize::from(line.contains("http://") || line.contains("https://")));
+        if count > 0 {
+            linked += 1;
+            links += count;
+        }
+    }
+    let density = if lines == 0 { 0.0 } else { linked as f64 / lines as f64 };
+    (links, density)
+}
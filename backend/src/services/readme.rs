@@ -0,0 +1,21 @@
+use pulldown_cmark::{html, Options, Parser};
+use sha2::{Digest, Sha256};
+
+/// Hex SHA-256 of README text, used to spot changes between appearances
+pub fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// Render README markdown to HTML that is safe to embed: scripts, event handlers and
+/// other active content are stripped by ammonia's default allow-list.
+pub fn render_html(markdown: &str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+
+    let mut unsafe_html = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut unsafe_html, Parser::new_ext(markdown, options));
+
+    ammonia::clean(&unsafe_html)
+}
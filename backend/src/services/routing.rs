@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::services::providers::{Providers, SummaryProvider};
+
+/// Rough token count of `text`: about four ASCII characters per token, while other scripts
+/// (Korean, Chinese, Japanese, ...) take about a token per character
+pub fn estimate_tokens(text: &str) -> usize {
+    let quarters: usize = text.chars().map(|c| if c.is_ascii() { 1 } else { 4 }).sum();
+    quarters.div_ceil(4)
+}
+
+/// Picks the model a README is summarized with by its estimated size: `SUMMARY_SMALL_MODEL`
+/// up to `SUMMARY_SMALL_MAX_TOKENS`, `SUMMARY_LARGE_MODEL` from `SUMMARY_LARGE_MIN_TOKENS`,
+/// the default summary model otherwise
+pub struct SummaryRouter {
+    default: Arc<dyn SummaryProvider>,
+    small: Option<(usize, Arc<dyn SummaryProvider>)>,
+    large: Option<(usize, Arc<dyn SummaryProvider>)>,
+}
+
+impl SummaryRouter {
+    pub fn new(config: &Config, providers: &Providers) -> Self {
+        let routing = &config.summary_routing;
+        SummaryRouter {
+            default: providers.summaries.clone(),
+            small: providers.small_summaries.clone().map(|provider| (routing.small_max_tokens, provider)),
+            large: providers.large_summaries.clone().map(|provider| (routing.large_min_tokens, provider)),
+        }
+    }
+
+    pub fn route(&self, readme: &str) -> &Arc<dyn SummaryProvider> {
+        let tokens = estimate_tokens(readme);
+        match (&self.small, &self.large) {
+            (Some((max_tokens, small)), _) if tokens <= *max_tokens => small,
+            (_, Some((min_tokens, large))) if tokens >= *min_tokens => large,
+            _ => &self.default,
+        }
+    }
+}
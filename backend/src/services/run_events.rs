@@ -0,0 +1,40 @@
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::warn;
+
+use crate::db::Database;
+use crate::models::CollectionStatus;
+use crate::services::collector::SUMMARY_PARTIAL;
+
+/// Stored events older than this are dropped whenever a run finishes
+const RUN_EVENT_RETENTION_DAYS: i64 = 30;
+
+/// Store every progress event of the workspace's runs in `collection_events`, so a run can be
+/// inspected after the fact without an SSE client having been connected. Streamed partial
+/// summaries are left out; the finished summary is stored with the repo.
+pub fn spawn_recorder(progress_tx: &broadcast::Sender<CollectionStatus>, db: Database) {
+    let mut rx = progress_tx.subscribe();
+    tokio::spawn(async move {
+        loop {
+            let status = match rx.recv().await {
+                Ok(status) => status,
+                Err(RecvError::Lagged(missed)) => {
+                    warn!("Progress recorder fell behind, {} events not stored", missed);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            if status.repo.as_ref().is_some_and(|repo| repo.event == SUMMARY_PARTIAL) {
+                continue;
+            }
+
+            if let Err(e) = db.save_collection_event(&status) {
+                warn!("Failed to store progress event of run {}: {}", status.run_id, e);
+            }
+            if !status.is_running {
+                if let Err(e) = db.prune_collection_events(RUN_EVENT_RETENTION_DAYS) {
+                    warn!("Failed to prune progress events: {}", e);
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,78 @@
+use chrono::NaiveDate;
+
+use crate::config::ScoreWeights;
+
+/// Raw signals the computed score is built from
+#[derive(Debug, Clone, Default)]
+pub struct ScoreInputs {
+    pub stars: Option<i32>,
+    pub forks: Option<i32>,
+    pub pull_requests: Option<i32>,
+    pub pushes: Option<i32>,
+    /// Days since the repo first trended; `None` if this is its first appearance
+    pub days_since_first_seen: Option<i64>,
+    /// Consecutive trending days ending on the scored date (including it)
+    pub streak: i32,
+}
+
+/// Weighted sum of log-scaled activity plus recency and streak bonuses.
+/// Activity counts are log-scaled so a single viral repo doesn't flatten everyone else.
+pub fn compute_score(weights: &ScoreWeights, inputs: &ScoreInputs) -> f64 {
+    let log = |v: Option<i32>| (v.unwrap_or(0).max(0) as f64).ln_1p();
+
+    let recency = match inputs.days_since_first_seen {
+        None => 1.0,
+        Some(days) => 1.0 / (1.0 + days.max(0) as f64),
+    };
+
+    weights.star_delta * log(inputs.stars)
+        + weights.fork_delta * log(inputs.forks)
+        + weights.pull_requests * log(inputs.pull_requests)
+        + weights.pushes * log(inputs.pushes)
+        + weights.recency * recency
+        + weights.streak * (inputs.streak.max(0) as f64).ln_1p()
+}
+
+// Values of `trending_repos.lifecycle`; "dropped" is derived when serving (no row on that day)
+pub const LIFECYCLE_NEW: &str = "new";
+pub const LIFECYCLE_RECURRING: &str = "recurring";
+pub const LIFECYCLE_RESURFACED: &str = "resurfaced";
+pub const LIFECYCLE_DROPPED: &str = "dropped";
+
+/// Classify an appearance from its history signals: first time, continuing a daily streak,
+/// or back after at least one day away
+pub fn lifecycle(days_since_first_seen: Option<i64>, streak: i32) -> &'static str {
+    match days_since_first_seen {
+        None => LIFECYCLE_NEW,
+        Some(_) if streak > 1 => LIFECYCLE_RECURRING,
+        Some(_) => LIFECYCLE_RESURFACED,
+    }
+}
+
+/// Derive `(days_since_first_seen, streak)` for `date` from the repo's earlier appearance dates
+pub fn history_signals(date: &str, previous_dates: &[String]) -> (Option<i64>, i32) {
+    let Ok(today) = NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+        return (None, 1);
+    };
+
+    let mut dates: Vec<NaiveDate> = previous_dates
+        .iter()
+        .filter_map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .filter(|d| *d < today)
+        .collect();
+    dates.sort_unstable_by(|a, b| b.cmp(a));
+
+    let days_since_first_seen = dates.last().map(|first| (today - *first).num_days());
+
+    let mut streak = 1;
+    let mut expected = today.pred_opt();
+    for d in &dates {
+        if Some(*d) != expected {
+            break;
+        }
+        streak += 1;
+        expected = d.pred_opt();
+    }
+
+    (days_since_first_seen, streak)
+}
@@ -0,0 +1,74 @@
+/// First syllable of the Hangul Syllables block; every syllable is `(initial * 21 + medial) * 28 + final` past it
+const HANGUL_BASE: u32 = 0xAC00;
+const HANGUL_LAST: u32 = 0xD7A3;
+
+// Revised Romanization of each jamo position, per syllable (no sound changes across syllables)
+const INITIALS: [&str; 19] = [
+    "g", "kk", "n", "d", "tt", "r", "m", "b", "pp", "s", "ss", "", "j", "jj", "ch", "k", "t", "p", "h",
+];
+const MEDIALS: [&str; 21] = [
+    "a", "ae", "ya", "yae", "eo", "e", "yeo", "ye", "o", "wa", "wae", "oe", "yo", "u", "wo", "we", "wi", "yu", "eu",
+    "ui", "i",
+];
+const FINALS: [&str; 28] = [
+    "", "k", "k", "k", "n", "n", "n", "t", "l", "k", "m", "l", "l", "l", "p", "l", "m", "p", "p", "t", "t", "ng", "t",
+    "t", "k", "t", "p", "t",
+];
+
+/// Lowercase letters and digits only, so "데이터 베이스" and "데이터베이스", or "Deep-Seek" and
+/// "deepseek", compare equal
+pub fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Hangul syllables spelled out in Revised Romanization; everything else is kept as is
+pub fn romanize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c as u32 {
+            code @ HANGUL_BASE..=HANGUL_LAST => {
+                let index = (code - HANGUL_BASE) as usize;
+                out.push_str(INITIALS[index / (21 * 28)]);
+                out.push_str(MEDIALS[index % (21 * 28) / 28]);
+                out.push_str(FINALS[index % 28]);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+pub fn has_hangul(text: &str) -> bool {
+    text.chars().any(|c| (HANGUL_BASE..=HANGUL_LAST).contains(&(c as u32)))
+}
+
+/// What a repo is searched by: each field normalized, plus the romanized form of fields in
+/// Korean, separated by spaces so a query (which has none after normalizing) cannot match
+/// across two fields
+pub fn search_text(fields: &[Option<&str>]) -> String {
+    let fields: Vec<&str> = fields.iter().flatten().copied().collect();
+    let romanized = fields.iter().filter(|f| has_hangul(f)).map(|f| romanize(f));
+
+    fields
+        .iter()
+        .map(|f| normalize(f))
+        .chain(romanized.map(|f| normalize(&f)))
+        .filter(|f| !f.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Normalized forms of a query to look up in `search_text`: the query itself and, for Korean
+/// queries, its romanization; empty when nothing searchable is left
+pub fn query_terms(query: &str) -> Vec<String> {
+    let mut terms = vec![normalize(query)];
+    if has_hangul(query) {
+        terms.push(normalize(&romanize(query)));
+    }
+    terms.retain(|t| !t.is_empty());
+    terms.dedup();
+    terms
+}
@@ -0,0 +1,89 @@
+use std::time::Instant;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::db::Database;
+use crate::services::templates::NotificationTemplates;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    /// "database", "config" or "llm"
+    pub name: &'static str,
+    pub ok: bool,
+    /// What failed, or what was checked when it passed
+    pub detail: String,
+    pub duration_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfCheckReport {
+    pub ok: bool,
+    pub checks: Vec<CheckResult>,
+}
+
+/// Check what a collection run needs before trusting the scheduler with it: a writable database
+/// (readable on replicas), a usable configuration and an LLM key the provider accepts.
+pub async fn run(config: &Config, db: &Database, http: &reqwest::Client) -> SelfCheckReport {
+    let mut checks = Vec::new();
+
+    let started = Instant::now();
+    let database = if config.read_only {
+        db.get_latest_date().map(|_| "readable (read-only replica)".to_string())
+    } else {
+        db.check_writable().map(|()| "writable".to_string())
+    };
+    checks.push(result("database", database, started));
+
+    let started = Instant::now();
+    checks.push(result("config", check_config(config), started));
+
+    let started = Instant::now();
+    checks.push(result("llm", check_llm_key(config, http).await, started));
+
+    SelfCheckReport {
+        ok: checks.iter().all(|c| c.ok),
+        checks,
+    }
+}
+
+fn result(name: &'static str, outcome: Result<String>, started: Instant) -> CheckResult {
+    let duration_ms = started.elapsed().as_millis() as i64;
+    match outcome {
+        Ok(detail) => CheckResult { name, ok: true, detail, duration_ms },
+        Err(e) => CheckResult { name, ok: false, detail: format!("{:#}", e), duration_ms },
+    }
+}
+
+/// Settings `Config::from_env` accepts but a run would trip over
+fn check_config(config: &Config) -> Result<String> {
+    if config.deepseek_api_key.trim().is_empty() {
+        anyhow::bail!("DEEPSEEK_API_KEY is empty");
+    }
+    if !(0.0..=1.0).contains(&config.language_threshold) {
+        anyhow::bail!("LANGUAGE_THRESHOLD must be between 0 and 1, got {}", config.language_threshold);
+    }
+    NotificationTemplates::load(config.notification_templates_dir.as_deref())?;
+
+    Ok(format!("{} workspaces", config.workspaces.len()))
+}
+
+/// Listing models is free and fails on a rejected key just like a completion would
+async fn check_llm_key(config: &Config, http: &reqwest::Client) -> Result<String> {
+    let url = format!("{}/models", config.deepseek_base_url);
+    let response = http
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", config.deepseek_api_key))
+        .send()
+        .await?;
+
+    let status = response.status();
+    if status.is_success() {
+        Ok(format!("key accepted by {}", config.deepseek_base_url))
+    } else if matches!(status.as_u16(), 401 | 403) {
+        anyhow::bail!("{} rejected the API key ({})", config.deepseek_base_url, status)
+    } else {
+        anyhow::bail!("{} answered {} to the models list", url, status)
+    }
+}
@@ -0,0 +1,88 @@
+use std::sync::{Arc, RwLock};
+
+use anyhow::Result;
+
+use crate::config::Config;
+use crate::db::Database;
+
+/// `sitemap.xml` listing the frontend page of every collected date, so published briefs get
+/// indexed. Built on first request and rebuilt whenever a collection completes.
+#[derive(Clone)]
+pub struct Sitemap {
+    /// Public origin of the frontend; no sitemap without it
+    site_url: Option<String>,
+    db: Database,
+    xml: Arc<RwLock<Option<String>>>,
+}
+
+impl Sitemap {
+    pub fn new(config: &Config, db: Database) -> Self {
+        Sitemap {
+            site_url: config.site_url.clone(),
+            db,
+            xml: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// The current sitemap; `None` when no `SITE_URL` is configured
+    pub fn get(&self) -> Result<Option<String>> {
+        if self.site_url.is_none() {
+            return Ok(None);
+        }
+        if let Some(xml) = self.xml.read().unwrap().as_ref() {
+            return Ok(Some(xml.clone()));
+        }
+        self.regenerate()
+    }
+
+    /// Rebuild the sitemap from the collected dates
+    pub fn regenerate(&self) -> Result<Option<String>> {
+        let Some(site_url) = &self.site_url else {
+            return Ok(None);
+        };
+        let dates = self.db.get_trending_dates_between("0001-01-01", "9999-12-31")?;
+        let xml = render(site_url, &dates);
+        *self.xml.write().unwrap() = Some(xml.clone());
+        Ok(Some(xml))
+    }
+}
+
+/// The dashboard and language pages, then one canonical `/?date=` page per date, newest first
+fn render(site_url: &str, dates: &[String]) -> String {
+    let mut xml = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+        "\n",
+        r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#,
+        "\n",
+    ));
+    let latest = dates.last().map(String::as_str);
+
+    for (path, lastmod, changefreq) in [("/", latest, "daily"), ("/languages", latest, "daily")] {
+        push_url(&mut xml, &format!("{}{}", site_url, path), lastmod, changefreq);
+    }
+    for date in dates.iter().rev() {
+        push_url(&mut xml, &format!("{}/?date={}", site_url, date), Some(date), "never");
+    }
+
+    xml.push_str("</urlset>\n");
+    xml
+}
+
+fn push_url(xml: &mut String, loc: &str, lastmod: Option<&str>, changefreq: &str) {
+    xml.push_str("  <url>\n");
+    xml.push_str(&format!("    <loc>{}</loc>\n", escape(loc)));
+    if let Some(lastmod) = lastmod {
+        xml.push_str(&format!("    <lastmod>{}</lastmod>\n", lastmod));
+    }
+    xml.push_str(&format!("    <changefreq>{}</changefreq>\n", changefreq));
+    xml.push_str("  </url>\n");
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
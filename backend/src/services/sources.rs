@@ -0,0 +1,124 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::models::{RepoSource, SourceComparison, SourceOverlap, SourceSummary, TrendCandidate};
+
+/// Source name of the generic OSS Insight trending list
+pub const SOURCE_OSS_INSIGHT: &str = "oss_insight";
+/// Weight key shared by every `collection:<id>` source without a weight of its own
+const SOURCE_COLLECTION: &str = "collection";
+
+/// A repo found by one or more sources, with its merged score and where it came from
+pub struct MergedCandidate {
+    /// The first source's row; later sources only add to the score
+    pub row: TrendCandidate,
+    pub score: f64,
+    pub provenance: Vec<RepoSource>,
+}
+
+/// Weight of `source` in `weights` (`name=weight` pairs): its own entry, else the shared
+/// `collection` entry for collections, else 1.0
+pub fn source_weight(weights: &[(String, f64)], source: &str) -> f64 {
+    let lookup = |name: &str| weights.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, w)| *w);
+    lookup(source)
+        .or_else(|| source.starts_with("collection:").then(|| lookup(SOURCE_COLLECTION)).flatten())
+        .unwrap_or(1.0)
+}
+
+/// Merge the candidate lists of several sources into one, highest merged score first.
+///
+/// Repos are deduped by id, or by name when sources disagree on the id. Each source's scores are
+/// normalized against its top score (or, when it reports none, taken from the rank) so sources on
+/// different scales add up; a repo's merged score is the sum of its weighted, normalized scores.
+pub fn merge_candidates(lists: Vec<(String, Vec<TrendCandidate>)>, weights: &[(String, f64)]) -> Vec<MergedCandidate> {
+    let mut merged: Vec<MergedCandidate> = Vec::new();
+    let mut by_key: HashMap<String, usize> = HashMap::new();
+
+    for (source, rows) in lists {
+        let weight = source_weight(weights, &source);
+        let scores: Vec<Option<f64>> = rows.iter().map(|r| r.total_score).collect();
+        let top = scores.iter().flatten().fold(0.0_f64, |top, &s| top.max(s));
+        let count = rows.len();
+
+        for (i, (row, score)) in rows.into_iter().zip(scores).enumerate() {
+            let normalized = match score {
+                Some(score) if top > 0.0 => score.max(0.0) / top,
+                _ => (count - i) as f64 / count as f64,
+            };
+            let provenance = RepoSource {
+                source: source.clone(),
+                rank: i as i32 + 1,
+                score,
+                weight,
+                contribution: weight * normalized,
+            };
+
+            let id_key = format!("id:{}", row.repo_id);
+            let name_key = format!("name:{}", row.repo_name.to_lowercase());
+            let existing = by_key.get(&id_key).or_else(|| by_key.get(&name_key)).copied();
+
+            let index = match existing {
+                Some(index) => index,
+                None => {
+                    merged.push(MergedCandidate { row, score: 0.0, provenance: Vec::new() });
+                    merged.len() - 1
+                }
+            };
+            by_key.entry(id_key).or_insert(index);
+            by_key.entry(name_key).or_insert(index);
+
+            let candidate = &mut merged[index];
+            // A repo listed twice by one source counts once
+            if candidate.provenance.iter().any(|p| p.source == provenance.source) {
+                continue;
+            }
+            candidate.score += provenance.contribution;
+            candidate.provenance.push(provenance);
+        }
+    }
+
+    // Stable, so ties keep the order the sources listed them in
+    merged.sort_by(|a, b| b.score.total_cmp(&a.score));
+    merged
+}
+
+/// Overlap and exclusives of the repo sets each source produced on `date`, from
+/// `(source, repo_name)` rows
+pub fn compare_sources(date: &str, rows: Vec<(String, String)>) -> SourceComparison {
+    let mut sets: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for (source, repo_name) in rows {
+        sets.entry(source).or_default().insert(repo_name);
+    }
+
+    let sources = sets
+        .iter()
+        .map(|(source, repos)| SourceSummary {
+            source: source.clone(),
+            repo_count: repos.len(),
+            exclusive: repos
+                .iter()
+                .filter(|repo| !sets.iter().any(|(other, set)| other != source && set.contains(*repo)))
+                .cloned()
+                .collect(),
+        })
+        .collect();
+
+    let named: Vec<(&String, &BTreeSet<String>)> = sets.iter().collect();
+    let mut overlaps = Vec::new();
+    for (i, (a, a_repos)) in named.iter().enumerate() {
+        for (b, b_repos) in &named[i + 1..] {
+            let shared: Vec<String> = a_repos.intersection(b_repos).cloned().collect();
+            let union = a_repos.union(b_repos).count();
+            overlaps.push(SourceOverlap {
+                sources: [a.to_string(), b.to_string()],
+                jaccard: if union == 0 { 0.0 } else { shared.len() as f64 / union as f64 },
+                shared,
+            });
+        }
+    }
+
+    SourceComparison {
+        date: date.to_string(),
+        sources,
+        overlaps,
+    }
+}
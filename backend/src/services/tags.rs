@@ -0,0 +1,56 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::models::TagCount;
+
+/// Most tags kept per summary
+pub const MAX_TAGS: usize = 5;
+
+/// Tags in GitHub topic form: lowercase ASCII letters, digits, `+`/`#` (c++, c#) and single
+/// hyphens, so "Web Framework", "web_framework" and "web-framework" are one tag
+pub fn normalize_tag(tag: &str) -> String {
+    let mut normalized = String::with_capacity(tag.len());
+    for c in tag.trim().chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() || c == '+' || c == '#' {
+            normalized.push(c);
+        } else if (c.is_whitespace() || c == '-' || c == '_' || c == '/') && !normalized.ends_with('-') {
+            normalized.push('-');
+        }
+    }
+    normalized.trim_matches('-').to_string()
+}
+
+/// Normalized, deduplicated and at most `MAX_TAGS`, keeping the model's order
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    tags.iter()
+        .map(|tag| normalize_tag(tag))
+        .filter(|tag| !tag.is_empty() && seen.insert(tag.clone()))
+        .take(MAX_TAGS)
+        .collect()
+}
+
+/// Tag frequencies over `(repo_id, tags)` appearances, where `tags` is the stored JSON array;
+/// most frequent first, at most `limit`
+pub fn tag_cloud(rows: &[(i64, Option<String>)], limit: usize) -> Vec<TagCount> {
+    let mut counts: HashMap<String, (i64, HashSet<i64>)> = HashMap::new();
+    for (repo_id, tags) in rows {
+        let tags: Vec<String> = tags.as_deref().and_then(|t| serde_json::from_str(t).ok()).unwrap_or_default();
+        for tag in tags {
+            let (appearances, repos) = counts.entry(tag).or_default();
+            *appearances += 1;
+            repos.insert(*repo_id);
+        }
+    }
+
+    let mut cloud: Vec<TagCount> = counts
+        .into_iter()
+        .map(|(tag, (appearances, repos))| TagCount {
+            tag,
+            appearances,
+            repos: repos.len() as i64,
+        })
+        .collect();
+    cloud.sort_by(|a, b| b.appearances.cmp(&a.appearances).then_with(|| a.tag.cmp(&b.tag)));
+    cloud.truncate(limit);
+    cloud
+}
@@ -0,0 +1,60 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use minijinja::Environment;
+use serde::Serialize;
+use tracing::info;
+
+/// Built-in notification templates; a file of the same name in `NOTIFICATION_TEMPLATES_DIR`
+/// replaces one. `.html` templates are HTML-escaped and `.json` templates write every `{{ }}`
+/// value as JSON, so channel payloads stay well-formed whatever the data contains.
+const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    ("digest.txt", include_str!("../../templates/digest.txt")),
+    ("digest.html", include_str!("../../templates/digest.html")),
+    ("message.md", include_str!("../../templates/message.md")),
+    ("slack.json", include_str!("../../templates/slack.json")),
+    ("discord.json", include_str!("../../templates/discord.json")),
+    ("telegram.json", include_str!("../../templates/telegram.json")),
+    ("audio.txt", include_str!("../../templates/audio.txt")),
+];
+
+/// Shared templates notification content is rendered from, per channel
+pub struct NotificationTemplates {
+    env: Environment<'static>,
+}
+
+impl NotificationTemplates {
+    /// Built-in templates with overrides from `dir`; fails on unreadable or invalid overrides
+    pub fn load(dir: Option<&str>) -> Result<Self> {
+        let mut env = Environment::new();
+
+        for (name, builtin) in BUILTIN_TEMPLATES {
+            let override_path = dir.map(|d| Path::new(d).join(name)).filter(|p| p.exists());
+            let source = match &override_path {
+                Some(path) => {
+                    info!("Using notification template {}", path.display());
+                    std::fs::read_to_string(path)
+                        .with_context(|| format!("Failed to read template {}", path.display()))?
+                }
+                None => builtin.to_string(),
+            };
+            env.add_template_owned(name.to_string(), source)
+                .with_context(|| format!("Invalid notification template {}", name))?;
+        }
+
+        Ok(NotificationTemplates { env })
+    }
+
+    pub fn builtin() -> Self {
+        Self::load(None).expect("built-in notification templates are valid")
+    }
+
+    pub fn render(&self, name: &str, context: impl Serialize) -> Result<String> {
+        let rendered = self
+            .env
+            .get_template(name)?
+            .render(context)
+            .with_context(|| format!("Failed to render template {}", name))?;
+        Ok(rendered)
+    }
+}
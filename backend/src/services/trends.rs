@@ -0,0 +1,256 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::config::LanguageRules;
+use crate::db::Database;
+use crate::services::github::OTHER_LANGUAGE;
+use crate::models::{CategoryShare, LanguageChange, LanguageCooccurrence, LanguageMover, LanguagePair, LanguageTrend};
+
+/// Accumulates per-repo language percentages into daily shares under the trend rules.
+/// A repo counts once per trend language even when several raw languages map onto it.
+pub struct LanguageShares<'a> {
+    rules: &'a LanguageRules,
+    stats: HashMap<String, (f64, i32)>,
+}
+
+impl<'a> LanguageShares<'a> {
+    pub fn new(rules: &'a LanguageRules) -> Self {
+        LanguageShares {
+            rules,
+            stats: HashMap::new(),
+        }
+    }
+
+    /// Add one repo's `(language, percentage)` pairs, scaled by `weight`
+    pub fn add_repo<'l>(&mut self, languages: impl IntoIterator<Item = (&'l str, f64)>, weight: f64) {
+        let mut merged: HashMap<&str, f64> = HashMap::new();
+        for (language, percentage) in languages {
+            if let Some(language) = self.rules.trend_language(language) {
+                *merged.entry(language).or_insert(0.0) += percentage;
+            }
+        }
+
+        for (language, percentage) in merged {
+            let entry = self.stats.entry(language.to_string()).or_insert((0.0, 0));
+            entry.0 += percentage * weight;
+            entry.1 += 1;
+        }
+    }
+
+    /// Normalize to shares summing to 100, largest first; empty when nothing was weighted
+    pub fn into_trends(self, date: &str, run_id: Option<&str>) -> Vec<LanguageTrend> {
+        let total: f64 = self.stats.values().map(|(p, _)| p).sum();
+        if total <= 0.0 {
+            return vec![];
+        }
+
+        let mut trends: Vec<LanguageTrend> = self
+            .stats
+            .into_iter()
+            .map(|(language, (sum, repo_count))| LanguageTrend {
+                date: date.to_string(),
+                language,
+                normalized_percentage: sum / total * 100.0,
+                repo_count,
+                run_id: run_id.map(str::to_string),
+            })
+            .collect();
+        trends.sort_by(|a, b| b.normalized_percentage.total_cmp(&a.normalized_percentage));
+        trends
+    }
+}
+
+/// Daily shares recomputed from every repo language stored for `date`, with repos weighted by
+/// `weighting` ("unweighted", "score" or "stars"). Stored languages below `threshold` (as in
+/// `LANGUAGE_THRESHOLD`) count as "Other"; languages folded away when they were collected under
+/// a higher threshold cannot be recovered.
+pub fn stored_language_trends(
+    db: &Database,
+    rules: &LanguageRules,
+    date: &str,
+    weighting: &str,
+    threshold: f64,
+    run_id: Option<&str>,
+) -> anyhow::Result<Vec<LanguageTrend>> {
+    let rows = db.get_weighted_repo_languages(date, weighting)?;
+    let mut shares = LanguageShares::new(rules);
+    // Rows come ordered by repo, so each chunk is one repo's languages
+    for repo in rows.chunk_by(|a, b| a.0 == b.0) {
+        let languages = repo.iter().map(|(_, language, percentage, _)| {
+            let language = if *percentage < threshold * 100.0 { OTHER_LANGUAGE } else { language.as_str() };
+            (language, *percentage)
+        });
+        shares.add_repo(languages, repo[0].3);
+    }
+    Ok(shares.into_trends(date, run_id))
+}
+
+/// Average share per language over a set of daily trends. Days a language is missing from
+/// count as zero, so divide by the number of collected days rather than its appearances.
+pub fn average_shares(trends: &[LanguageTrend]) -> HashMap<String, f64> {
+    let days = trends.iter().map(|t| t.date.as_str()).collect::<HashSet<_>>().len();
+    let mut sums: HashMap<String, f64> = HashMap::new();
+
+    if days == 0 {
+        return sums;
+    }
+
+    for t in trends {
+        *sums.entry(t.language.clone()).or_insert(0.0) += t.normalized_percentage;
+    }
+    for share in sums.values_mut() {
+        *share /= days as f64;
+    }
+
+    sums
+}
+
+/// Rank languages by share, 1-based
+fn ranks(shares: &HashMap<String, f64>) -> HashMap<String, usize> {
+    let mut sorted: Vec<(&String, &f64)> = shares.iter().collect();
+    sorted.sort_by(|a, b| b.1.total_cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    sorted
+        .into_iter()
+        .enumerate()
+        .map(|(i, (lang, _))| (lang.clone(), i + 1))
+        .collect()
+}
+
+/// Compare two periods' language shares, ordered by the current period's rank
+pub fn compare_periods(current: &[LanguageTrend], previous: &[LanguageTrend]) -> Vec<LanguageChange> {
+    let current_shares = average_shares(current);
+    let previous_shares = average_shares(previous);
+    let current_ranks = ranks(&current_shares);
+    let previous_ranks = ranks(&previous_shares);
+
+    let mut changes: Vec<LanguageChange> = current_shares
+        .iter()
+        .map(|(language, this_week)| {
+            let last_week = previous_shares.get(language).copied().unwrap_or(0.0);
+            let rank = current_ranks[language];
+            let previous_rank = previous_ranks.get(language).copied();
+            LanguageChange {
+                language: language.clone(),
+                this_week: *this_week,
+                last_week,
+                absolute_change: this_week - last_week,
+                relative_change: (last_week > 0.0).then(|| (this_week - last_week) / last_week * 100.0),
+                rank,
+                previous_rank,
+                rank_change: previous_rank.map(|prev| prev as i64 - rank as i64),
+            }
+        })
+        .collect();
+
+    changes.sort_by_key(|c| c.rank);
+    changes
+}
+
+/// The `limit` languages whose share moved most between two days, largest move first. Unlike
+/// `compare_periods`, languages that dropped out of the current day are included (at zero).
+pub fn language_movers(current: &[LanguageTrend], previous: &[LanguageTrend], limit: usize) -> Vec<LanguageMover> {
+    let current_shares = average_shares(current);
+    let previous_shares = average_shares(previous);
+    let current_ranks = ranks(&current_shares);
+    let previous_ranks = ranks(&previous_shares);
+    let languages: HashSet<&String> = current_shares.keys().chain(previous_shares.keys()).collect();
+
+    let mut movers: Vec<LanguageMover> = languages
+        .into_iter()
+        .map(|language| {
+            let share = current_shares.get(language).copied().unwrap_or(0.0);
+            let previous_share = previous_shares.get(language).copied().unwrap_or(0.0);
+            LanguageMover {
+                language: language.clone(),
+                share,
+                previous_share,
+                change: share - previous_share,
+                rank: current_ranks.get(language).copied(),
+                previous_rank: previous_ranks.get(language).copied(),
+            }
+        })
+        .filter(|mover| mover.change != 0.0)
+        .collect();
+
+    movers.sort_by(|a, b| b.change.abs().total_cmp(&a.change.abs()).then_with(|| a.language.cmp(&b.language)));
+    movers.truncate(limit);
+    movers
+}
+
+/// Label of repos without a category in `category_shares`
+pub const UNCATEGORIZED: &str = "uncategorized";
+
+/// Each category's share of its day's trending repos, from `(date, category, repos)` counts
+/// ordered by date
+pub fn category_shares(counts: &[(String, Option<String>, i64)]) -> Vec<CategoryShare> {
+    counts
+        .chunk_by(|a, b| a.0 == b.0)
+        .flat_map(|day| {
+            let total: i64 = day.iter().map(|(_, _, repos)| repos).sum();
+            day.iter().map(move |(date, category, repos)| CategoryShare {
+                date: date.clone(),
+                category: category.clone().unwrap_or_else(|| UNCATEGORIZED.to_string()),
+                repo_count: *repos,
+                share: if total > 0 { *repos as f64 / total as f64 * 100.0 } else { 0.0 },
+            })
+        })
+        .collect()
+}
+
+/// How often trend languages share a repo. `rows` are `(date, repo_id, language)` ordered by
+/// date and repo, so every day a repo trended counts once. The matrix covers the `limit` most
+/// common languages; its diagonal holds each language's own repo count.
+pub fn language_cooccurrence(
+    rows: &[(String, i64, String)],
+    rules: &LanguageRules,
+    limit: usize,
+) -> LanguageCooccurrence {
+    let repos: Vec<HashSet<&str>> = rows
+        .chunk_by(|a, b| a.0 == b.0 && a.1 == b.1)
+        .map(|repo| {
+            repo.iter()
+                .filter_map(|(_, _, language)| rules.trend_language(language))
+                .collect()
+        })
+        .collect();
+
+    let mut counts: HashMap<&str, i64> = HashMap::new();
+    for languages in &repos {
+        for &language in languages {
+            *counts.entry(language).or_insert(0) += 1;
+        }
+    }
+    let mut languages: Vec<(&str, i64)> = counts.into_iter().collect();
+    languages.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    languages.truncate(limit);
+
+    let index: HashMap<&str, usize> = languages.iter().enumerate().map(|(i, (l, _))| (*l, i)).collect();
+    let mut matrix = vec![vec![0i64; languages.len()]; languages.len()];
+    for repo in &repos {
+        let present: Vec<usize> = repo.iter().filter_map(|l| index.get(l).copied()).collect();
+        for &i in &present {
+            for &j in &present {
+                matrix[i][j] += 1;
+            }
+        }
+    }
+
+    let mut pairs = Vec::new();
+    for i in 0..languages.len() {
+        for j in i + 1..languages.len() {
+            if matrix[i][j] > 0 {
+                pairs.push(LanguagePair {
+                    languages: [languages[i].0.to_string(), languages[j].0.to_string()],
+                    repo_count: matrix[i][j],
+                });
+            }
+        }
+    }
+    pairs.sort_by(|a, b| b.repo_count.cmp(&a.repo_count).then_with(|| a.languages.cmp(&b.languages)));
+
+    LanguageCooccurrence {
+        repos: repos.len(),
+        languages: languages.into_iter().map(|(l, _)| l.to_string()).collect(),
+        matrix,
+        pairs,
+    }
+}
@@ -0,0 +1,119 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde_json::json;
+use tracing::info;
+
+use crate::config::{Config, TtsConfig, TTS_PROVIDER_ELEVENLABS};
+use crate::db::Database;
+use crate::services::digest;
+use crate::services::providers::{Providers, SpeechProvider};
+use crate::services::templates::NotificationTemplates;
+
+/// Repos read out in the audio brief
+const AUDIO_REPO_LIMIT: usize = 10;
+
+/// OpenAI-compatible (`POST /audio/speech`) or ElevenLabs text-to-speech API returning MP3
+pub struct TtsClient {
+    client: Client,
+    config: TtsConfig,
+}
+
+impl TtsClient {
+    pub fn new(client: Client, config: &TtsConfig) -> Self {
+        TtsClient {
+            client,
+            config: config.clone(),
+        }
+    }
+
+    pub async fn synthesize(&self, text: &str) -> Result<Vec<u8>> {
+        let request = if self.config.provider == TTS_PROVIDER_ELEVENLABS {
+            self.client
+                .post(format!("{}/text-to-speech/{}", self.config.base_url, self.config.voice))
+                .header("xi-api-key", &self.config.api_key)
+                .header("Accept", "audio/mpeg")
+                .json(&json!({ "text": text, "model_id": self.config.model }))
+        } else {
+            self.client
+                .post(format!("{}/audio/speech", self.config.base_url))
+                .bearer_auth(&self.config.api_key)
+                .json(&json!({
+                    "model": self.config.model,
+                    "voice": self.config.voice,
+                    "input": text,
+                    "response_format": "mp3",
+                }))
+        };
+
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Speech API returned {}: {}", status, body.chars().take(200).collect::<String>());
+        }
+        Ok(response.bytes().await?.to_vec())
+    }
+}
+
+/// Reads each day's brief aloud: the `audio.txt` template rendered from the digest context,
+/// synthesized to `<TTS_AUDIO_DIR>/<date>.mp3`. The archive picks the file up as `brief.mp3`.
+pub struct BriefAudio {
+    speech: Arc<dyn SpeechProvider>,
+    db: Database,
+    templates: NotificationTemplates,
+    dir: PathBuf,
+    max_chars: usize,
+}
+
+impl BriefAudio {
+    /// Returns `None` when no speech API is configured
+    pub fn new(config: &Config, db: Database, providers: &Providers) -> Result<Option<Self>> {
+        let (Some(tts), Some(speech)) = (&config.tts, &providers.speech) else {
+            return Ok(None);
+        };
+
+        Ok(Some(BriefAudio {
+            speech: speech.clone(),
+            db,
+            templates: NotificationTemplates::load(config.notification_templates_dir.as_deref())?,
+            dir: PathBuf::from(&tts.audio_dir),
+            max_chars: tts.max_chars,
+        }))
+    }
+
+    /// Synthesize `date`'s brief, replacing an earlier recording; returns the file written
+    pub async fn generate(&self, date: &str) -> Result<PathBuf> {
+        let context = digest::digest_context(&self.db, date, false, AUDIO_REPO_LIMIT)?;
+        let script = self.templates.render("audio.txt", &context)?;
+        let script = truncate_script(&script, self.max_chars);
+
+        let audio = self.speech.synthesize(script).await.context("Speech synthesis failed")?;
+
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let path = audio_path(&self.dir, date);
+        // Written aside and renamed, so the endpoint never serves a half-written file
+        let partial = path.with_extension("mp3.partial");
+        tokio::fs::write(&partial, &audio).await?;
+        tokio::fs::rename(&partial, &path).await?;
+
+        info!("Recorded the brief of {} ({} bytes, {} characters)", date, audio.len(), script.chars().count());
+        Ok(path)
+    }
+}
+
+/// Where the recording of `date` is stored under `dir`
+pub fn audio_path(dir: impl AsRef<Path>, date: &str) -> PathBuf {
+    dir.as_ref().join(format!("{}.mp3", date))
+}
+
+/// At most `max_chars` characters, cut after the last full line that fits
+fn truncate_script(script: &str, max_chars: usize) -> &str {
+    let Some((end, _)) = script.char_indices().nth(max_chars) else {
+        return script;
+    };
+    let cut = &script[..end];
+    cut.rfind('\n').map_or(cut, |newline| &cut[..newline])
+}
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+// Values of `api_usage.provider`
+pub const PROVIDER_LLM: &str = "llm";
+pub const PROVIDER_GITHUB: &str = "github";
+
+/// Request and token counts of one provider/model pair
+#[derive(Debug, Clone, Default)]
+pub struct UsageCounts {
+    pub requests: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+}
+
+/// Counts external API usage of one collection run until it is flushed to the database
+#[derive(Debug, Default)]
+pub struct UsageMeter {
+    counts: Mutex<HashMap<(String, String), UsageCounts>>,
+}
+
+impl UsageMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_llm(&self, model: &str, prompt_tokens: i64, completion_tokens: i64) {
+        let mut counts = self.counts.lock().unwrap();
+        let entry = counts.entry((PROVIDER_LLM.to_string(), model.to_string())).or_default();
+        entry.requests += 1;
+        entry.prompt_tokens += prompt_tokens;
+        entry.completion_tokens += completion_tokens;
+    }
+
+    pub fn record_github(&self) {
+        let mut counts = self.counts.lock().unwrap();
+        counts.entry((PROVIDER_GITHUB.to_string(), String::new())).or_default().requests += 1;
+    }
+
+    /// Take the counts gathered so far as `(provider, model, counts)`, resetting the meter
+    pub fn take(&self) -> Vec<(String, String, UsageCounts)> {
+        let mut counts = self.counts.lock().unwrap();
+        counts
+            .drain()
+            .map(|((provider, model), usage)| (provider, model, usage))
+            .collect()
+    }
+}
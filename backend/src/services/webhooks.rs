@@ -0,0 +1,248 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::Sha256;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::db::Database;
+use crate::models::{DeliverySchedule, Webhook};
+use crate::services::digest;
+use crate::services::templates::NotificationTemplates;
+
+// Events a webhook can subscribe to
+pub const EVENT_COLLECTION_COMPLETED: &str = "collection.completed";
+pub const EVENT_LANGUAGE_ALERT: &str = "alert.language";
+pub const EVENT_STAR_MILESTONE: &str = "alert.star_milestone";
+pub const WEBHOOK_EVENTS: &[&str] = &[EVENT_COLLECTION_COMPLETED, EVENT_LANGUAGE_ALERT, EVENT_STAR_MILESTONE];
+/// Posted to scheduled hooks instead of the events above
+pub const EVENT_DIGEST: &str = "digest";
+
+/// Payload formats; all but "json" are rendered from the `<format>.json` template
+pub const FORMAT_JSON: &str = "json";
+pub const WEBHOOK_FORMATS: &[&str] = &[FORMAT_JSON, "slack", "discord", "telegram"];
+
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+pub const DELIVERY_HEADER: &str = "X-Webhook-Delivery";
+
+/// Posts events to registered webhooks. Each delivery runs in the background, retrying
+/// failures with exponential backoff, and every attempt is logged in `webhook_deliveries`.
+#[derive(Clone)]
+pub struct WebhookDispatcher {
+    db: Database,
+    client: reqwest::Client,
+    templates: Arc<NotificationTemplates>,
+    max_attempts: u32,
+    retry_backoff_ms: u64,
+}
+
+impl WebhookDispatcher {
+    pub fn new(config: &Config, db: Database, client: reqwest::Client) -> Self {
+        // A broken template override must not stop deliveries
+        let templates = NotificationTemplates::load(config.notification_templates_dir.as_deref()).unwrap_or_else(|e| {
+            warn!("Falling back to built-in notification templates: {:#}", e);
+            NotificationTemplates::builtin()
+        });
+        WebhookDispatcher {
+            db,
+            client,
+            templates: Arc::new(templates),
+            max_attempts: config.webhook_max_attempts,
+            retry_backoff_ms: config.webhook_retry_backoff_ms,
+        }
+    }
+
+    /// Queue `data` for every unscheduled webhook subscribed to `event`; returns without waiting for delivery
+    pub fn emit(&self, event: &'static str, data: serde_json::Value) {
+        let hooks = match self.db.get_webhooks() {
+            Ok(hooks) => hooks,
+            Err(e) => {
+                warn!("Failed to load webhooks for {}: {}", event, e);
+                return;
+            }
+        };
+
+        let envelope = envelope(event, data);
+
+        for hook in hooks
+            .into_iter()
+            .filter(|h| h.schedule.is_none())
+            .filter(|h| h.events.is_empty() || h.events.iter().any(|e| e == event))
+        {
+            let payload = match self.payload(&hook.format, &envelope) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    warn!("Failed to render {} payload of {} for webhook {}: {:#}", hook.format, event, hook.id, e);
+                    continue;
+                }
+            };
+            let dispatcher = self.clone();
+            tokio::spawn(async move {
+                dispatcher.deliver(&hook, event, &payload).await;
+            });
+        }
+    }
+
+    /// Queue a digest of the newest brief for every scheduled hook whose local delivery time has passed
+    pub fn send_due(&self) -> Result<usize> {
+        let Some(date) = self.db.get_latest_date()? else {
+            return Ok(0);
+        };
+        let now = Utc::now();
+        let mut queued = 0;
+
+        for hook in self.db.get_webhooks()? {
+            let Some(schedule) = &hook.schedule else {
+                continue;
+            };
+            let Some(local_date) = digest::schedule_due(schedule, now) else {
+                continue;
+            };
+
+            let weekly = schedule.frequency == "weekly";
+            let context = digest::digest_context(&self.db, &date, weekly, schedule.top_n.max(1) as usize)?;
+            let payload = match self.payload(&hook.format, &envelope(EVENT_DIGEST, context)) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    warn!("Failed to render {} digest for webhook {}: {:#}", hook.format, hook.id, e);
+                    continue;
+                }
+            };
+
+            // Marked before delivery so a slow retry loop can't post the same digest twice
+            self.db.mark_webhook_sent(hook.id, &local_date)?;
+            queued += 1;
+            let dispatcher = self.clone();
+            tokio::spawn(async move {
+                dispatcher.deliver(&hook, EVENT_DIGEST, &payload).await;
+            });
+        }
+
+        if queued > 0 {
+            info!("Queued {} scheduled webhook digests", queued);
+        }
+
+        Ok(queued)
+    }
+
+    /// The raw event envelope, or its rendering through the channel template of `format`
+    fn payload(&self, format: &str, envelope: &serde_json::Value) -> Result<String> {
+        if format == FORMAT_JSON {
+            return Ok(envelope.to_string());
+        }
+        let mut context = envelope.clone();
+        context["message"] = self.templates.render("message.md", envelope)?.into();
+        self.templates.render(&format!("{}.json", format), &context)
+    }
+
+    async fn deliver(&self, hook: &Webhook, event: &str, payload: &str) {
+        let delivery_id = Uuid::new_v4().to_string();
+        let signature = sign(hook.secret.as_deref().unwrap_or_default(), payload);
+
+        for attempt in 1..=self.max_attempts {
+            let started = Instant::now();
+            let result = self
+                .client
+                .post(&hook.url)
+                .header("Content-Type", "application/json")
+                .header(SIGNATURE_HEADER, &signature)
+                .header(EVENT_HEADER, event)
+                .header(DELIVERY_HEADER, &delivery_id)
+                .body(payload.to_string())
+                .send()
+                .await;
+            let duration_ms = started.elapsed().as_millis() as i64;
+
+            let (success, status_code, error) = match &result {
+                Ok(response) if response.status().is_success() => (true, Some(response.status().as_u16()), None),
+                Ok(response) => (false, Some(response.status().as_u16()), Some(format!("HTTP {}", response.status()))),
+                Err(e) => (false, None, Some(e.to_string())),
+            };
+
+            if let Err(e) = self.db.record_webhook_delivery(
+                hook.id,
+                &delivery_id,
+                event,
+                attempt,
+                success,
+                status_code,
+                error.as_deref(),
+                duration_ms,
+            ) {
+                warn!("Failed to record delivery of {} to webhook {}: {}", event, hook.id, e);
+            }
+
+            if success {
+                info!("Delivered {} to webhook {} (attempt {})", event, hook.id, attempt);
+                return;
+            }
+            if attempt < self.max_attempts {
+                let delay = Duration::from_millis(self.retry_backoff_ms.saturating_mul(1 << (attempt - 1).min(16)));
+                warn!(
+                    "Delivery of {} to webhook {} failed (attempt {}/{}), retrying in {:?}: {}",
+                    event, hook.id, attempt, self.max_attempts, delay, error.unwrap_or_default()
+                );
+                tokio::time::sleep(delay).await;
+            }
+        }
+
+        warn!("Giving up on {} for webhook {} after {} attempts", event, hook.id, self.max_attempts);
+    }
+}
+
+fn envelope(event: &str, data: serde_json::Value) -> serde_json::Value {
+    json!({
+        "event": event,
+        "sent_at": Utc::now().to_rfc3339(),
+        "data": data,
+    })
+}
+
+/// `sha256=<hex>` HMAC of the request body, as sent in `X-Webhook-Signature`
+pub fn sign(secret: &str, payload: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    format!("sha256={:x}", mac.finalize().into_bytes())
+}
+
+/// Reject hooks that could not be delivered to or name unknown events, formats or schedules
+pub fn validate(
+    url: &str,
+    events: &[String],
+    format: &str,
+    schedule: Option<&DeliverySchedule>,
+) -> Result<(), String> {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+        _ => return Err(format!("Invalid webhook URL: {}", url)),
+    }
+    if let Some(unknown) = events.iter().find(|e| !WEBHOOK_EVENTS.contains(&e.as_str())) {
+        return Err(format!("Unknown event '{}' (expected one of {})", unknown, WEBHOOK_EVENTS.join(", ")));
+    }
+    if !WEBHOOK_FORMATS.contains(&format) {
+        return Err(format!("Unknown format '{}' (expected one of {})", format, WEBHOOK_FORMATS.join(", ")));
+    }
+    if let Some(schedule) = schedule {
+        validate_schedule(schedule)?;
+    }
+    Ok(())
+}
+
+pub fn validate_schedule(schedule: &DeliverySchedule) -> Result<(), String> {
+    digest::validate_schedule(&schedule.frequency, &schedule.delivery_time, &schedule.timezone, schedule.weekday)?;
+    if !(1..=100).contains(&schedule.top_n) {
+        return Err("top_n must be between 1 and 100".to_string());
+    }
+    Ok(())
+}
+
+/// Random secret for hooks registered without one
+pub fn generate_secret() -> String {
+    Uuid::new_v4().simple().to_string()
+}
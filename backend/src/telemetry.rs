@@ -0,0 +1,185 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use metrics::{describe_counter, describe_gauge, describe_histogram, gauge, Unit};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+// Metric names; labels are listed with each description in `install`
+pub const HTTP_REQUESTS: &str = "http_requests_total";
+pub const HTTP_REQUEST_DURATION: &str = "http_request_duration_seconds";
+pub const LLM_REQUEST_DURATION: &str = "llm_request_duration_seconds";
+pub const GITHUB_RESPONSES: &str = "github_responses_total";
+pub const COLLECTOR_RUNS: &str = "collector_runs_total";
+pub const COLLECTOR_REPOS: &str = "collector_repos_total";
+pub const COLLECTOR_LAST_RUN_REPOS: &str = "collector_last_run_repos";
+pub const SSE_SUBSCRIBERS: &str = "sse_subscribers";
+pub const DB_SLOW_QUERIES: &str = "db_slow_queries_total";
+pub const CIRCUIT_BREAKER_OPEN: &str = "circuit_breaker_open";
+pub const CIRCUIT_BREAKER_REJECTED: &str = "circuit_breaker_rejected_total";
+pub const COALESCED_CALLS: &str = "coalesced_calls_total";
+
+/// Log lines buffered per log stream client before a slow client misses some
+const LOG_CHANNEL_CAPACITY: usize = 1024;
+/// Lines kept until `LOG_TAIL_LINES` is applied
+const DEFAULT_LOG_TAIL_LINES: usize = 500;
+
+/// Seconds; wide enough for both cached API reads and slow LLM completions
+const LATENCY_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+/// Install the process-wide recorder; until this runs (e.g. in `--mcp` or `--check` mode) every
+/// metric call is a no-op. The handle renders the `/metrics` response.
+pub fn install() -> Result<PrometheusHandle> {
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Suffix("_seconds".to_string()), LATENCY_BUCKETS)?
+        .install_recorder()?;
+
+    describe_counter!(HTTP_REQUESTS, "API requests by workspace, method, route and status");
+    describe_histogram!(HTTP_REQUEST_DURATION, Unit::Seconds, "API latency by workspace, method, route and status");
+    describe_histogram!(LLM_REQUEST_DURATION, Unit::Seconds, "LLM completion latency by provider, model and outcome");
+    describe_counter!(GITHUB_RESPONSES, "GitHub API responses by status code (\"error\" when no response arrived)");
+    describe_counter!(COLLECTOR_RUNS, "Finished collection runs by workspace and kind (collect or backfill)");
+    describe_counter!(COLLECTOR_REPOS, "Repos of finished runs by workspace and outcome (collected, skipped, failed)");
+    describe_gauge!(COLLECTOR_LAST_RUN_REPOS, "Repos of the latest run by workspace and outcome");
+    describe_gauge!(SSE_SUBSCRIBERS, "Open progress streams by workspace");
+    describe_counter!(DB_SLOW_QUERIES, "Database calls over SLOW_QUERY_MS by method");
+    describe_gauge!(CIRCUIT_BREAKER_OPEN, "1 while the circuit of an upstream host is open, by host");
+    describe_counter!(CIRCUIT_BREAKER_REJECTED, "Calls an open circuit refused, by host");
+    describe_counter!(COALESCED_CALLS, "Upstream calls answered by an identical call already in flight, by call");
+
+    Ok(handle)
+}
+
+/// Counts an open progress stream in `sse_subscribers` until dropped with the stream
+pub struct SseSubscriber {
+    workspace: String,
+}
+
+impl SseSubscriber {
+    pub fn new(workspace: &str) -> Self {
+        gauge!(SSE_SUBSCRIBERS, "workspace" => workspace.to_string()).increment(1.0);
+        SseSubscriber { workspace: workspace.to_string() }
+    }
+}
+
+impl Drop for SseSubscriber {
+    fn drop(&mut self) {
+        gauge!(SSE_SUBSCRIBERS, "workspace" => self.workspace.clone()).decrement(1.0);
+    }
+}
+
+/// One tracing event as sent by `/api/admin/logs/stream`
+#[derive(Debug, Clone, Serialize)]
+pub struct LogLine {
+    pub timestamp: DateTime<Utc>,
+    /// `ERROR`, `WARN`, `INFO`, `DEBUG` or `TRACE`
+    pub level: String,
+    /// Module the event came from, e.g. `daily_git_brief::services::collector`
+    pub target: String,
+    /// The message followed by the event's other fields as `key=value`
+    pub message: String,
+}
+
+impl LogLine {
+    /// Whether the line is at least as severe as `min`
+    pub fn at_least(&self, min: tracing::Level) -> bool {
+        self.level.parse::<tracing::Level>().is_ok_and(|level| level <= min)
+    }
+}
+
+/// The most recent log lines of the process plus a feed of new ones. Logs are process-wide, so
+/// every workspace sees the same tail.
+pub struct LogTail {
+    lines: Mutex<VecDeque<LogLine>>,
+    capacity: AtomicUsize,
+    tx: broadcast::Sender<LogLine>,
+}
+
+/// The process's log tail, filled by `LogTailLayer` once that is installed
+pub fn log_tail() -> &'static LogTail {
+    static LOG_TAIL: OnceLock<LogTail> = OnceLock::new();
+    LOG_TAIL.get_or_init(|| LogTail {
+        lines: Mutex::new(VecDeque::new()),
+        capacity: AtomicUsize::new(DEFAULT_LOG_TAIL_LINES),
+        tx: broadcast::channel(LOG_CHANNEL_CAPACITY).0,
+    })
+}
+
+impl LogTail {
+    /// Lines kept for new subscribers; tracing starts before the config is loaded, so this is
+    /// applied afterwards (0 keeps none, live lines still stream)
+    pub fn set_capacity(&self, capacity: usize) {
+        self.capacity.store(capacity, Ordering::Relaxed);
+        let mut lines = self.lines.lock().unwrap();
+        while lines.len() > capacity {
+            lines.pop_front();
+        }
+    }
+
+    /// The buffered lines, oldest first, and a receiver of every line after them
+    pub fn subscribe(&self) -> (Vec<LogLine>, broadcast::Receiver<LogLine>) {
+        // Subscribing under the lock means no line is both buffered and received, or neither
+        let lines = self.lines.lock().unwrap();
+        (lines.iter().cloned().collect(), self.tx.subscribe())
+    }
+
+    fn push(&self, line: LogLine) {
+        let capacity = self.capacity.load(Ordering::Relaxed);
+        let mut lines = self.lines.lock().unwrap();
+        if capacity > 0 {
+            if lines.len() >= capacity {
+                lines.pop_front();
+            }
+            lines.push_back(line.clone());
+        }
+        // No receivers is the normal case
+        let _ = self.tx.send(line);
+    }
+}
+
+/// Copies every event that passes `RUST_LOG` into `log_tail()`
+pub struct LogTailLayer;
+
+impl<S: Subscriber> Layer<S> for LogTailLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        log_tail().push(LogLine {
+            timestamp: Utc::now(),
+            level: metadata.level().to_string(),
+            target: metadata.target().to_string(),
+            message: visitor.message,
+        });
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.insert_str(0, value);
+        } else {
+            self.message.push_str(&format!(" {}={}", field.name(), value));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message.insert_str(0, &format!("{:?}", value));
+        } else {
+            self.message.push_str(&format!(" {}={:?}", field.name(), value));
+        }
+    }
+}
@@ -0,0 +1,385 @@
+mod common;
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::http::{Method, StatusCode};
+use common::{FakeRepo, TestApp};
+use daily_git_brief::config::{SummaryStyle, QUALITY_DROP};
+use daily_git_brief::models::StructuredSummary;
+use daily_git_brief::services::providers::SummaryProvider;
+use futures::future::BoxFuture;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+const REPOS: &[FakeRepo] = &[
+    FakeRepo { id: 101, name: "acme/rocket", language: "Rust", readme: Some("# Rocket\nA fast tool.\n\nRead the [guide](docs/guide.md) or the [changelog](CHANGELOG.md).") },
+    FakeRepo { id: 102, name: "acme/widget", language: "TypeScript", readme: Some("# Widget\nUI kit.") },
+    FakeRepo { id: 103, name: "acme/empty", language: "Go", readme: None },
+];
+
+#[tokio::test]
+async fn collect_then_query_serves_summarized_repos() {
+    let app = TestApp::spawn().await;
+    app.mount_trending(REPOS).await;
+    app.mount_llm_summary("빠른 로켓 도구").await;
+
+    let job = app.collect().await;
+    assert_eq!(job["status"], "done", "job: {}", job);
+
+    let (status, body) = app.request(Method::GET, "/api/trends").await;
+    assert_eq!(status, StatusCode::OK);
+    let repos = body["data"].as_array().expect("repos");
+    assert_eq!(repos.len(), 3);
+
+    let rocket = repos.iter().find(|r| r["repo_name"] == "acme/rocket").expect("rocket");
+    assert_eq!(rocket["rank"], 1);
+    assert_eq!(rocket["summary_status"], "done");
+    assert_eq!(rocket["korean_summary"], "빠른 로켓 도구");
+    assert_eq!(rocket["languages"][0]["language"], "Rust");
+    assert_eq!(rocket["homepage"], "https://rocket.example.com/");
+    assert_eq!(rocket["doc_links"][0], "https://github.com/acme/rocket/blob/HEAD/docs/guide.md");
+    assert_eq!(rocket["doc_links"].as_array().map(Vec::len), Some(1));
+
+    // No README to summarize
+    let empty = repos.iter().find(|r| r["repo_name"] == "acme/empty").expect("empty");
+    assert_eq!(empty["summary_status"], "unavailable");
+
+    let (status, body) = app.request(Method::GET, "/api/languages/daily").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!body["data"].as_array().expect("trends").is_empty());
+}
+
+#[tokio::test]
+async fn llm_outage_still_serves_metadata() {
+    let app = TestApp::spawn().await;
+    app.mount_trending(REPOS).await;
+    app.mount_llm_error(500).await;
+
+    let job = app.collect().await;
+    assert_eq!(job["status"], "done", "job: {}", job);
+
+    let (_, body) = app.request(Method::GET, "/api/trends").await;
+    let repos = body["data"].as_array().expect("repos");
+    assert_eq!(repos.len(), 3);
+    assert!(repos.iter().all(|r| r["korean_summary"].is_null()));
+    assert!(repos.iter().all(|r| r["summary_status"] != "done"));
+}
+
+#[tokio::test]
+async fn recollecting_skips_summarized_repos() {
+    let app = TestApp::spawn().await;
+    app.mount_trending(REPOS).await;
+    app.mount_llm_summary("요약").await;
+
+    app.collect().await;
+    let calls = app.llm.received_requests().await.expect("recorded requests").len();
+
+    // The stars have moved since the first run
+    app.oss_insight.reset().await;
+    app.mount_trending_with_stars(REPOS, 450).await;
+
+    app.collect().await;
+    let (_, body) = app.request(Method::GET, "/api/trends").await;
+    let repos = body["data"].as_array().expect("repos");
+    assert_eq!(repos.len(), 3);
+    // Only the repo without a README is retried, and it never reaches the LLM
+    assert_eq!(app.llm.received_requests().await.expect("recorded requests").len(), calls);
+    // Summarized repos keep their summaries but get fresh metrics
+    let rocket = repos.iter().find(|r| r["repo_name"] == "acme/rocket").expect("rocket");
+    assert_eq!(rocket["korean_summary"], "요약");
+    assert_eq!(rocket["stars"], 450);
+}
+
+#[tokio::test]
+async fn fresh_summaries_are_reused_when_repos_trend_again() {
+    let app = TestApp::spawn().await;
+    app.mount_trending(REPOS).await;
+    app.mount_llm_summary("요약").await;
+
+    // A backfill past SUMMARY_MAX_AGE_DAYS still writes its summaries today
+    let today = chrono::Utc::now().date_naive();
+    let backfilled = (today - chrono::Duration::days(30)).format("%Y-%m-%d").to_string();
+    app.collect_date(&backfilled).await;
+    let calls = app.llm.received_requests().await.expect("recorded requests").len();
+
+    // Same READMEs, summarized minutes ago: nothing to ask the LLM
+    let job = app.collect().await;
+    assert_eq!(app.llm.received_requests().await.expect("recorded requests").len(), calls);
+
+    let (_, body) = app.request(Method::GET, "/api/trends").await;
+    let repos = body["data"].as_array().expect("repos");
+    let rocket = repos.iter().find(|r| r["repo_name"] == "acme/rocket").expect("rocket");
+    assert_eq!(rocket["korean_summary"], "요약");
+    assert_eq!(rocket["summary_status"], "done");
+
+    let run_id = job["run_id"].as_str().expect("run_id");
+    let (_, body) = app.request(Method::GET, &format!("/api/collect/runs/{}", run_id)).await;
+    let rocket = body["data"]["repos"]
+        .as_array()
+        .and_then(|repos| repos.iter().find(|r| r["repo_name"] == "acme/rocket"))
+        .expect("rocket");
+    assert_eq!(rocket["outcome"], "collected");
+    assert_eq!(rocket["reasons"][0], format!("Reused the summary written on {}", today.format("%Y-%m-%d")));
+}
+
+#[tokio::test]
+async fn backfills_need_the_admin_token_and_keep_stored_metrics() {
+    let app = TestApp::spawn().await;
+    app.mount_trending_with_stars(&REPOS[..2], 120).await;
+    app.mount_llm_summary("요약").await;
+    let yesterday = (chrono::Utc::now().date_naive() - chrono::Duration::days(1)).format("%Y-%m-%d").to_string();
+
+    for uri in [format!("/api/collect?date={}", yesterday), "/api/collect?mode=languages".to_string()] {
+        let (status, _) = app.request_with_token(Method::POST, &uri, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", uri);
+    }
+
+    app.collect_date(&yesterday).await;
+    // Re-collecting the day later only fills in the repo it lacks
+    app.oss_insight.reset().await;
+    app.mount_trending_with_stars(REPOS, 999).await;
+    app.collect_date(&yesterday).await;
+
+    let (_, body) = app.request(Method::GET, &format!("/api/trends?date={}", yesterday)).await;
+    let repos = body["data"].as_array().expect("repos");
+    let stars = |name: &str| repos.iter().find(|r| r["repo_name"] == name).map(|r| r["stars"].clone());
+    assert_eq!(stars("acme/rocket"), Some(120.into()));
+    assert_eq!(stars("acme/widget"), Some(120.into()));
+    assert_eq!(stars("acme/empty"), Some(999.into()));
+}
+
+#[tokio::test]
+async fn filtered_lists_show_stored_ranks() {
+    let app = TestApp::spawn_configured(|config| {
+        config.quality_rules.mode = QUALITY_DROP.to_string();
+        config.quality_rules.patterns = vec!["widget".to_string()];
+    })
+    .await;
+    app.mount_trending(REPOS).await;
+    app.mount_llm_summary("요약").await;
+    app.collect().await;
+
+    // The dropped widget leaves a gap instead of moving the repos below it up
+    let (_, body) = app.request(Method::GET, "/api/trends").await;
+    let ranks: Vec<(String, i64)> = body["data"]
+        .as_array()
+        .expect("repos")
+        .iter()
+        .map(|r| (r["repo_name"].as_str().unwrap_or_default().to_string(), r["rank"].as_i64().unwrap_or_default()))
+        .collect();
+    assert_eq!(ranks, [("acme/rocket".to_string(), 1), ("acme/empty".to_string(), 3)]);
+}
+
+#[tokio::test]
+async fn progress_events_are_recorded_per_run() {
+    let app = TestApp::spawn().await;
+    app.mount_trending(REPOS).await;
+    app.mount_llm_summary("요약").await;
+
+    let job = app.collect().await;
+    let run_id = job["run_id"].as_str().expect("run_id");
+
+    let (status, body) = app.request(Method::GET, &format!("/api/collect/runs/{}/events", run_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let events = body["data"].as_array().expect("events");
+    assert!(events.iter().any(|e| e["repo"]["event"] == "summary_generated"));
+    assert_eq!(events.last().expect("last event")["phase"], "complete");
+}
+
+#[tokio::test]
+async fn run_report_gives_each_repo_outcome() {
+    let app = TestApp::spawn().await;
+    app.mount_trending(REPOS).await;
+    app.mount_llm_summary("요약").await;
+
+    let job = app.collect().await;
+    let run_id = job["run_id"].as_str().expect("run_id");
+
+    let (status, body) = app.request(Method::GET, &format!("/api/collect/runs/{}", run_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let report = &body["data"];
+    assert_eq!(report["kind"], "collect");
+    assert_eq!(report["collected"], 2);
+    assert_eq!(report["partial"], 1);
+    let empty = report["repos"]
+        .as_array()
+        .and_then(|repos| repos.iter().find(|r| r["repo_name"] == "acme/empty"))
+        .expect("empty");
+    assert_eq!(empty["outcome"], "partial");
+    assert_eq!(empty["reasons"][0], "No README");
+
+    // Recollecting only refreshes the metrics of summarized repos
+    let job = app.collect().await;
+    let run_id = job["run_id"].as_str().expect("run_id");
+    let (_, body) = app.request(Method::GET, &format!("/api/collect/runs/{}", run_id)).await;
+    assert_eq!(body["data"]["skipped"], 2);
+
+    let (status, _) = app.request(Method::GET, "/api/collect/runs/unknown").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+/// Summarizes without a model: the one-liner is the README's first line
+struct FirstLineSummaries;
+
+impl SummaryProvider for FirstLineSummaries {
+    fn model(&self) -> &str {
+        "first-line"
+    }
+
+    fn summarize_readme_korean<'a>(
+        &'a self,
+        readme: &'a str,
+        _repo_name: &'a str,
+        _style: &'a SummaryStyle,
+    ) -> BoxFuture<'a, Result<Option<StructuredSummary>>> {
+        let one_liner = readme.lines().next().unwrap_or_default().trim_start_matches("# ").to_string();
+        Box::pin(async move {
+            Ok(Some(StructuredSummary {
+                one_liner,
+                features: vec![],
+                tech_stack: vec![],
+                tags: vec![],
+                audience: None,
+                difficulty: None,
+                category: None,
+            }))
+        })
+    }
+
+    fn describe_readme_change<'a>(&'a self, _previous: &'a str, _current: &'a str, _repo_name: &'a str) -> BoxFuture<'a, Result<Option<String>>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    fn translate_description_korean<'a>(&'a self, _description: &'a str, _repo_name: &'a str) -> BoxFuture<'a, Result<Option<String>>> {
+        Box::pin(async { Ok(None) })
+    }
+}
+
+#[tokio::test]
+async fn injected_summary_provider_replaces_the_llm() {
+    let app = TestApp::spawn_with(|mut providers| {
+        providers.summaries = Arc::new(FirstLineSummaries);
+        providers
+    })
+    .await;
+    app.mount_trending(REPOS).await;
+
+    app.collect().await;
+    let (_, body) = app.request(Method::GET, "/api/trends").await;
+    let rocket = body["data"]
+        .as_array()
+        .and_then(|repos| repos.iter().find(|r| r["repo_name"] == "acme/rocket"))
+        .cloned()
+        .expect("rocket");
+    assert_eq!(rocket["korean_summary"], "Rocket");
+    assert_eq!(rocket["summary_model"], "first-line");
+    assert!(app.llm.received_requests().await.expect("recorded requests").is_empty());
+}
+
+#[tokio::test]
+async fn repos_in_several_merged_sources_are_fetched_once() {
+    let app = TestApp::spawn_configured(|config| {
+        config.merge_sources = true;
+        config.collections = vec![10010];
+    })
+    .await;
+    app.mount_trending(REPOS).await;
+    app.mount_collection(10010, "Rust Tools", &REPOS[..1]).await;
+    app.mount_llm_summary("요약").await;
+
+    let job = app.collect().await;
+    assert_eq!(job["status"], "done");
+
+    assert_eq!(github_calls(&app, "/repos/acme/rocket").await, 1);
+    assert_eq!(github_calls(&app, "/repos/acme/rocket/languages").await, 1);
+    assert_eq!(github_calls(&app, "/acme/rocket/main/README.md").await, 1);
+}
+
+#[tokio::test]
+async fn repo_profile_follows_renames() {
+    let app = TestApp::spawn().await;
+    app.mount_llm_summary("요약").await;
+
+    // Yesterday rocket still trended under its old name
+    let renamed = [FakeRepo { id: 101, name: "acme/old-rocket", language: "Rust", readme: Some("# Rocket\nA fast tool.") }];
+    app.mount_trending(&renamed).await;
+    let yesterday = (chrono::Utc::now().date_naive() - chrono::Duration::days(1)).format("%Y-%m-%d").to_string();
+    app.collect_date(&yesterday).await;
+    app.oss_insight.reset().await;
+    app.mount_trending(REPOS).await;
+    app.collect().await;
+
+    let (status, body) = app.request(Method::GET, "/api/repos/by-name/ACME/old-rocket").await;
+    assert_eq!(status, StatusCode::OK);
+    let profile = &body["data"];
+    assert_eq!(profile["repo_id"], 101);
+    assert_eq!(profile["repo_name"], "acme/rocket");
+    assert_eq!(profile["previous_names"][0], "acme/old-rocket");
+    assert_eq!(profile["trending_days"], 2);
+    assert_eq!(profile["first_seen"], yesterday);
+    assert_eq!(profile["best_rank"], 1);
+    assert_eq!(profile["star_history"].as_array().map(Vec::len), Some(2));
+    assert_eq!(profile["latest_summary"]["one_liner"], "요약");
+
+    let (status, _) = app.request(Method::GET, "/api/repos/by-name/acme/unknown").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn repo_profile_lookups_remember_misses_and_report_github_failures() {
+    let app = TestApp::spawn().await;
+
+    for _ in 0..3 {
+        let (status, _) = app.request(Method::GET, "/api/repos/by-name/acme/unknown").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+    assert_eq!(github_calls(&app, "/repos/acme/unknown").await, 1);
+
+    Mock::given(method("GET"))
+        .and(path("/repos/acme/limited"))
+        .respond_with(ResponseTemplate::new(429))
+        .mount(&app.github)
+        .await;
+    let (status, _) = app.request(Method::GET, "/api/repos/by-name/acme/limited").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    // Failures are not remembered as misses
+    app.request(Method::GET, "/api/repos/by-name/acme/limited").await;
+    assert_eq!(github_calls(&app, "/repos/acme/limited").await, 2);
+}
+
+#[tokio::test]
+async fn repo_profile_lookups_are_limited_per_client() {
+    let app = TestApp::spawn().await;
+
+    // Names GitHub can't have are answered without asking it
+    let (status, _) = app.request(Method::GET, "/api/repos/by-name/-acme/rocket").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(app.github.received_requests().await.expect("recorded requests").is_empty());
+
+    for i in 0..20 {
+        let (status, _) = app.request(Method::GET, &format!("/api/repos/by-name/acme/unknown-{}", i)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+    let (status, _) = app.request(Method::GET, "/api/repos/by-name/acme/unknown-20").await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(github_calls(&app, "/repos/acme/unknown-20").await, 0);
+}
+
+#[tokio::test]
+async fn admin_routes_require_the_admin_token() {
+    let app = TestApp::spawn().await;
+
+    let (status, _) = app.request_with_token(Method::GET, "/api/jobs", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = app.request_with_token(Method::GET, "/api/jobs", Some("test-admin-tokeN")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = app.request(Method::GET, "/api/jobs").await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+/// Requests the fake GitHub received for `route`
+async fn github_calls(app: &TestApp, route: &str) -> usize {
+    let requests = app.github.received_requests().await.expect("recorded requests");
+    requests.iter().filter(|r| r.url.path() == route).count()
+}
@@ -0,0 +1,295 @@
+//! Test support: the API of one workspace over an in-memory database, wired to wiremock
+//! stand-ins for OSS Insight, the GitHub API, raw.githubusercontent.com and the LLM.
+
+use std::sync::{Arc, Once};
+use std::time::Duration;
+
+use axum::body::{self, Body};
+use axum::http::{header, Method, Request, StatusCode};
+use axum::Router;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use daily_git_brief::api::{self, AppState, ResponseCache};
+use daily_git_brief::config::{Config, SummaryStyle, QUALITY_OFF};
+use daily_git_brief::db::Database;
+use daily_git_brief::services::{http, Providers};
+
+/// Admin token configured for the test app and sent by `request`
+pub const ADMIN_TOKEN: &str = "test-admin-token";
+
+/// A trending repo served by the fake upstreams
+pub struct FakeRepo {
+    pub id: i64,
+    pub name: &'static str,
+    pub language: &'static str,
+    pub readme: Option<&'static str>,
+}
+
+pub struct TestApp {
+    pub state: Arc<AppState>,
+    pub router: Router,
+    pub oss_insight: MockServer,
+    pub github: MockServer,
+    pub llm: MockServer,
+}
+
+impl TestApp {
+    /// Start the fake upstreams and the app with its job worker
+    pub async fn spawn() -> Self {
+        Self::spawn_with(|providers| providers).await
+    }
+
+    /// Like `spawn`, letting the test replace some of the providers built from the config
+    pub async fn spawn_with(customize: impl FnOnce(Providers) -> Providers) -> Self {
+        Self::start(|_| {}, customize).await
+    }
+
+    /// Like `spawn`, letting the test change the config first
+    pub async fn spawn_configured(configure: impl FnOnce(&mut Config)) -> Self {
+        Self::start(configure, |providers| providers).await
+    }
+
+    async fn start(configure: impl FnOnce(&mut Config), customize: impl FnOnce(Providers) -> Providers) -> Self {
+        let oss_insight = MockServer::start().await;
+        let github = MockServer::start().await;
+        let llm = MockServer::start().await;
+
+        let mut config = test_config(&oss_insight, &github, &llm);
+        configure(&mut config);
+        let db = Database::new(":memory:").expect("in-memory database");
+        let http_client = http::build_client(&config).expect("http client");
+        let cache = Arc::new(ResponseCache::new(Duration::from_secs(config.cache_ttl_secs)));
+        let providers = customize(Providers::from_config(&config, http_client.clone()));
+        let state = AppState::new(db, config, cache, http_client, providers);
+        state.jobs.spawn_worker(state.clone());
+
+        TestApp {
+            router: api::router(state.clone()),
+            state,
+            oss_insight,
+            github,
+            llm,
+        }
+    }
+
+    /// Serve `repos` as today's trending list, with their languages, repo info and READMEs
+    pub async fn mount_trending(&self, repos: &[FakeRepo]) {
+        self.mount_trending_with_stars(repos, 120).await;
+    }
+
+    /// Like `mount_trending`, with every repo at `stars`
+    pub async fn mount_trending_with_stars(&self, repos: &[FakeRepo], stars: i64) {
+        let rows: Vec<Value> = repos
+            .iter()
+            .map(|r| {
+                json!({
+                    "repo_id": r.id.to_string(),
+                    "repo_name": r.name,
+                    "primary_language": r.language,
+                    "description": format!("{} description", r.name),
+                    "stars": stars.to_string(),
+                    "forks": "10",
+                    "pull_requests": "3",
+                    "pushes": "7",
+                    "total_score": "1000.5",
+                    "contributor_logins": "alice,bob",
+                    "collection_names": null,
+                })
+            })
+            .collect();
+        Mock::given(method("GET"))
+            .and(path("/v1/trends/repos/"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "type": "sql_endpoint",
+                "data": { "columns": [], "rows": rows },
+            })))
+            .mount(&self.oss_insight)
+            .await;
+
+        for repo in repos {
+            Mock::given(method("GET"))
+                .and(path(format!("/repos/{}/languages", repo.name)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({ repo.language: 9000, "Shell": 1000 })))
+                .mount(&self.github)
+                .await;
+            Mock::given(method("GET"))
+                .and(path(format!("/repos/{}", repo.name)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "default_branch": "main",
+                    "stargazers_count": 5000,
+                    "homepage": format!("{}.example.com", repo.name.rsplit('/').next().unwrap_or_default()),
+                })))
+                .mount(&self.github)
+                .await;
+            if let Some(readme) = repo.readme {
+                Mock::given(method("GET"))
+                    .and(path(format!("/{}/main/README.md", repo.name)))
+                    .respond_with(ResponseTemplate::new(200).set_body_string(readme))
+                    .mount(&self.github)
+                    .await;
+            }
+        }
+    }
+
+    /// Serve collection `id` ranking `repos`, as OSS Insight does for `OSS_INSIGHT_COLLECTIONS`
+    pub async fn mount_collection(&self, id: i64, name: &str, repos: &[FakeRepo]) {
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/collections/{}/", id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "type": "sql_endpoint",
+                "data": { "columns": [], "rows": [{ "id": id.to_string(), "name": name }] },
+            })))
+            .mount(&self.oss_insight)
+            .await;
+
+        let rows: Vec<Value> = repos
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                json!({
+                    "repo_id": r.id.to_string(),
+                    "repo_name": r.name,
+                    "current_period_growth": "50",
+                    "current_period_rank": (i + 1).to_string(),
+                    "total": "5000",
+                })
+            })
+            .collect();
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/collections/{}/ranking_by_stars/", id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "type": "sql_endpoint",
+                "data": { "columns": [], "rows": rows },
+            })))
+            .mount(&self.oss_insight)
+            .await;
+    }
+
+    /// Answer every completion with `one_liner` as the summary
+    pub async fn mount_llm_summary(&self, one_liner: &str) {
+        let summary = json!({
+            "one_liner": one_liner,
+            "features": ["빠른 처리"],
+            "tech_stack": ["Rust"],
+            "audience": "tool",
+            "difficulty": "beginner",
+        });
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{ "message": { "role": "assistant", "content": summary.to_string() } }],
+                "usage": { "prompt_tokens": 100, "completion_tokens": 20 },
+            })))
+            .mount(&self.llm)
+            .await;
+    }
+
+    /// Fail every completion with `status`
+    pub async fn mount_llm_error(&self, status: u16) {
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(status).set_body_string("upstream error"))
+            .mount(&self.llm)
+            .await;
+    }
+
+    /// Send a request through the router as the admin; returns the status and the JSON body (`Null` if none)
+    pub async fn request(&self, method: Method, uri: &str) -> (StatusCode, Value) {
+        self.request_with_token(method, uri, Some(ADMIN_TOKEN)).await
+    }
+
+    /// Like `request`, with `token` (if any) as the bearer token
+    pub async fn request_with_token(&self, method: Method, uri: &str, token: Option<&str>) -> (StatusCode, Value) {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let request = request.body(Body::empty()).unwrap();
+        let response = self.router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+    }
+
+    /// Trigger a collection and wait for its job to finish; returns the finished job
+    pub async fn collect(&self) -> Value {
+        self.wait_for_collection("/api/collect").await
+    }
+
+    /// Like `collect`, backfilling a past `date`
+    pub async fn collect_date(&self, date: &str) -> Value {
+        self.wait_for_collection(&format!("/api/collect?date={}", date)).await
+    }
+
+    async fn wait_for_collection(&self, uri: &str) -> Value {
+        let (status, body) = self.request(Method::POST, uri).await;
+        assert_eq!(status, StatusCode::ACCEPTED, "collect response: {}", body);
+        let run_id = body["data"]["run_id"].as_str().expect("run_id").to_string();
+
+        for _ in 0..200 {
+            let (_, jobs) = self.request(Method::GET, "/api/jobs").await;
+            let job = jobs["data"]
+                .as_array()
+                .and_then(|jobs| jobs.iter().find(|j| j["run_id"] == run_id.as_str()))
+                .cloned();
+            if let Some(job) = job.filter(|j| j["status"] == "done" || j["status"] == "failed") {
+                return job;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("collection {} did not finish", run_id);
+    }
+}
+
+/// Configuration pointing every upstream at the mock servers, independent of any local `.env`
+fn test_config(oss_insight: &MockServer, github: &MockServer, llm: &MockServer) -> Config {
+    static ENV: Once = Once::new();
+    ENV.call_once(|| std::env::set_var("DEEPSEEK_API_KEY", "test-key"));
+
+    let mut config = Config::from_env().expect("config");
+    config.oss_insight_base_url = oss_insight.uri();
+    config.github_api_url = github.uri();
+    config.github_raw_url = github.uri();
+    config.deepseek_base_url = llm.uri();
+    config.deepseek_api_key = "test-key".to_string();
+    config.database_path = ":memory:".to_string();
+    config.github_tokens = vec![];
+    config.github_app = None;
+    config.workspaces = vec![];
+    config.language_filter = vec![];
+    config.collections = vec![];
+    config.star_milestones = vec![];
+    config.summary_prompt = None;
+    config.summary_style = SummaryStyle {
+        max_chars: Some(200),
+        max_sentences: None,
+        tone: "concise and informative".to_string(),
+        include_tech_stack: true,
+    };
+    config.eval_model = None;
+    config.summary_routing.small_model = None;
+    config.summary_routing.large_model = None;
+    config.readme_change_notes = false;
+    config.summary_max_age_days = 14;
+    config.quality_rules.mode = QUALITY_OFF.to_string();
+    config.admin_token = Some(ADMIN_TOKEN.to_string());
+    config.tls = None;
+    config.unix_socket_path = None;
+    // Failures should show up in the run, not be retried with backoff
+    config.summary_stage.retries = 0;
+    config.readme_stage.retries = 0;
+    config.metadata_stage.retries = 0;
+    config.archive = None;
+    config.read_only = false;
+    config.fixtures = None;
+    config.tts = None;
+    config.moderation = None;
+    config.site_url = None;
+    config.outbound_proxy = None;
+    config.http_proxy = None;
+    config.https_proxy = None;
+    config
+}